@@ -11,7 +11,7 @@ use std::{
 
 use hyper_ast_cvs_git::{
     allrefs::write_referencial_relations,
-    git::{fetch_github_repository, retrieve_commit},
+    git::{fetch_github_repository, retrieve_commit, Oid},
     preprocessed::PreProcessedRepository,
 };
 use hyper_ast_gen_ts_java::utils::memusage_linux;
@@ -101,12 +101,13 @@ fn multi_commit_ref_ana<const SEARCH_SKIP_SIZE: usize>(
 ) {
     let batch_id = format!("{}:({},{})", repo_name, before, after);
     let mut preprocessed = PreProcessedRepository::new(&repo_name);
-    let processing_ordered_commits = preprocessed.pre_process_with_limit(
-        &mut fetch_github_repository(&repo_name),
+    let processing_ordered_commits = preprocessed.pre_process_with_limit_and(
+        &mut fetch_github_repository(&repo_name).unwrap(),
         before,
         after,
         dir_path,
         1000,
+        |preprocessed, commit| write_store_stats(preprocessed, commit, out.as_ref()),
     );
     let mu = memusage_linux();
     log::warn!("total memory used {}", mu);
@@ -199,6 +200,40 @@ fn multi_commit_ref_ana<const SEARCH_SKIP_SIZE: usize>(
     log::warn!("hyperAST size: {}", mu - memusage_linux());
 }
 
+/// Logs a summary of the store statistics after processing `commit`,
+/// and writes them to `<commit>.stats.json` in `out`.
+///
+/// Failing to write them is only logged, to not abort long runs.
+fn write_store_stats(preprocessed: &PreProcessedRepository, commit: Oid, out: Option<&PathBuf>) {
+    let stats = preprocessed.processor.stats();
+    log::warn!(
+        "store after {}: {} nodes ({} bytes), {} labels ({} bytes), dedup hit ratio {:.3}",
+        commit,
+        stats.nodes.total.entities,
+        stats.nodes.total.bytes,
+        stats.labels.labels,
+        stats.labels.bytes,
+        stats.nodes.dedup_hit_ratio()
+    );
+    if let Some(out) = out {
+        let path = out.join(format!("{}.stats.json", commit));
+        let write = || -> Result<(), write_serializer::WriteJsonError> {
+            let file = File::create(&path)?;
+            let mut buf = BufWriter::with_capacity(BUFF_WRITER_CAPACITY, file);
+            stats.serialize(WriteJson::from(&mut buf))?;
+            buf.flush()?;
+            Ok(())
+        };
+        if let Err(err) = write() {
+            log::error!(
+                "could not write the store statistics to {:?}: {:?}",
+                path,
+                err
+            );
+        }
+    }
+}
+
 pub fn single_commit_ref_ana(
     repo_name: &String,
     after: &str,
@@ -206,13 +241,17 @@ pub fn single_commit_ref_ana(
     out: Option<PathBuf>,
 ) {
     let mut preprocessed = PreProcessedRepository::new(&repo_name);
-    preprocessed.pre_process_single(&mut fetch_github_repository(&repo_name), after, dir_path);
+    preprocessed.pre_process_single(
+        &mut fetch_github_repository(&repo_name).unwrap(),
+        after,
+        dir_path,
+    );
     let mu = memusage_linux();
     log::warn!("total memory used {}", mu);
     preprocessed.purge_caches();
     log::warn!("cache size: {}", mu - memusage_linux());
     log::warn!("search refs");
-    let repository = fetch_github_repository(preprocessed.name());
+    let repository = fetch_github_repository(preprocessed.name()).unwrap();
     // node identifier at after commit
     let root = preprocessed
         .commits
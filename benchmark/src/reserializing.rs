@@ -74,5 +74,6 @@ fn check_random_files_reserialization(
     // after: &str,
 ) -> (usize, usize) {
     let mut preprocessed = PreProcessedRepository::new(&repo_name);
-    preprocessed.check_random_files_reserialization(&mut fetch_github_repository(&repo_name))
+    preprocessed
+        .check_random_files_reserialization(&mut fetch_github_repository(&repo_name).unwrap())
 }
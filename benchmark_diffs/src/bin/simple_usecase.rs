@@ -93,7 +93,7 @@ fn inc(mut preprocessed: PreProcessedRepository, before: &str, after: &str, out:
             break;
         }
         let processing_ordered_commits = preprocessed.pre_process_with_limit(
-            &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name),
+            &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name).unwrap(),
             "",
             &curr,
             "",
@@ -163,7 +163,7 @@ fn whole(
     let batch_id = format!("{}:({},{})", &preprocessed.name, before, after);
     let mu = memusage_linux();
     let processing_ordered_commits = preprocessed.pre_process_with_limit(
-        &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name),
+        &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name).unwrap(),
         before,
         after,
         "",
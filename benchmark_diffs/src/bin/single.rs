@@ -44,12 +44,12 @@ fn bbb() {
 fn single(repo_name: &str, before: &str, after: &str) {
     let mut preprocessed = PreProcessedRepository::new(&repo_name);
     let oid_src = preprocessed.pre_process_single(
-        &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name),
+        &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name).unwrap(),
         before,
         "",
     );
     let oid_dst = preprocessed.pre_process_single(
-        &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name),
+        &mut hyper_ast_cvs_git::git::fetch_github_repository(&preprocessed.name).unwrap(),
         after,
         "",
     );
@@ -356,6 +356,7 @@ mod test {
             },
             // heuristic::gt::greedy_subtree_matcher::{GreedySubtreeMatcher, SubtreeMatcher},
             mapping_store::{DefaultMultiMappingStore, VecStore},
+            GumtreeParams,
         },
     };
 
@@ -1108,8 +1109,12 @@ mod test {
             // dst_arena.decompress_descendants(node_store, &dst_arena.root());
             // src_arena.go_through_descendants(node_store, &src_arena.root());
             // dst_arena.go_through_descendants(node_store, &dst_arena.root());
-            let mut matcher = LazyGreedySubtreeMatcher::<_, _, _, _, _, 1>::new(
-                &stores, src_arena, dst_arena, mappings,
+            let mut matcher = LazyGreedySubtreeMatcher::<_, _, _, _, _>::new(
+                &stores,
+                src_arena,
+                dst_arena,
+                mappings,
+                GumtreeParams::default(),
             );
             LazyGreedySubtreeMatcher::execute::<DefaultMultiMappingStore<_>>(&mut matcher);
             matcher
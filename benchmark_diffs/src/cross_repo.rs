@@ -48,7 +48,7 @@ pub fn windowed_commits_compare(
             (
                 preprocessed
                     .pre_process_with_limit(
-                        &mut x.configured_repo.clone().fetch(),
+                        &mut x.configured_repo.clone().fetch().unwrap(),
                         x.before,
                         x.before,
                         // x.dir_path,
@@ -138,7 +138,7 @@ pub fn windowed_commits_compare(
                         .get_caches()
                         .object_map
                         //.get::<Caches>().unwrap().object_map//object_map_maven
-                        .get(&oid)
+                        .get(&(oid, None))
                         .unwrap()
                         .clone(),
                 )
@@ -167,7 +167,7 @@ pub fn windowed_commits_compare(
                         .get_caches()
                         .object_map
                         // .get::<Caches>().unwrap().object_map//object_map_maven
-                        .get(&oid)
+                        .get(&(oid, None))
                         .unwrap()
                         .clone(),
                 )
@@ -27,7 +27,8 @@ pub mod window_combination;
 // #[cfg(test)]
 // pub mod bin::window_combination;
 pub mod cross_repo;
-pub mod diff_output;
+pub use hyper_diff::diff_output;
+pub mod merge_commits;
 pub mod other_tools;
 pub mod postprocess;
 pub mod preprocess;
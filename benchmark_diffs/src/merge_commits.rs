@@ -0,0 +1,137 @@
+//! Reproduction of merge commits with the three-way structural merge of [`hyper_diff::merge`].
+//!
+//! The trees of both parents of a merge commit are merged using the tree of their merge base,
+//! then the result is compared with the tree of the actual merge commit.
+use git2::{Oid, Repository};
+use hyper_ast_cvs_git::{no_space::as_nospaces, preprocessed::PreProcessedRepository};
+use hyper_diff::merge::{self, ConflictKind};
+
+#[derive(Debug)]
+pub struct MergeEvaluation {
+    pub commit: Oid,
+    pub base: Oid,
+    pub conflicts: Vec<ConflictKind>,
+    /// true if the merged tree is exactly the tree of the merge commit
+    pub reproduced: bool,
+}
+
+/// Evaluates the merge commits among `commits`,
+/// the ones whose parents or merge base were not preprocessed are skipped.
+pub fn evaluate(
+    preprocessed: &PreProcessedRepository,
+    repository: &Repository,
+    commits: &[Oid],
+) -> Vec<MergeEvaluation> {
+    commits
+        .iter()
+        .filter_map(|oid| evaluate_commit(preprocessed, repository, *oid))
+        .collect()
+}
+
+pub fn evaluate_commit(
+    preprocessed: &PreProcessedRepository,
+    repository: &Repository,
+    oid: Oid,
+) -> Option<MergeEvaluation> {
+    let commit = preprocessed.commits.get(&oid)?;
+    let [left, right] = commit.parents[..] else {
+        return None;
+    };
+    let base = repository.merge_base(left, right).ok()?;
+    let root = |oid: &Oid| preprocessed.commits.get(oid).map(|c| c.ast_root);
+    let (base_tr, left_tr, right_tr) = (root(&base)?, root(&left)?, root(&right)?);
+
+    let stores = &preprocessed.processor.main_stores;
+    let hyperast = as_nospaces(stores);
+    let result = merge::merge(&hyperast, &base_tr, &left_tr, &right_tr);
+    let reproduced = result.merged.same_as(&hyperast, &commit.ast_root);
+    log::info!(
+        "merge {oid}: {} conflicts, reproduced: {reproduced}",
+        result.conflicts.len()
+    );
+    Some(MergeEvaluation {
+        commit: oid,
+        base,
+        conflicts: result.conflicts.iter().map(|x| x.kind).collect(),
+        reproduced,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, time};
+
+    use git2::{Oid, Repository, Signature};
+    use hyper_ast_cvs_git::preprocessed::PreProcessedRepository;
+    use hyper_diff::merge::ConflictKind;
+
+    use super::{evaluate, MergeEvaluation};
+
+    /// Commits `content` as src/main/java/A.java, with the given parents
+    fn commit(repository: &Repository, content: &str, parents: &[Oid]) -> Oid {
+        let mut oid = repository.blob(content.as_bytes()).unwrap();
+        let mut mode = 0o100644;
+        for name in ["A.java", "java", "main", "src"] {
+            let mut builder = repository.treebuilder(None).unwrap();
+            builder.insert(name, oid, mode).unwrap();
+            oid = builder.write().unwrap();
+            mode = 0o040000;
+        }
+        let tree = repository.find_tree(oid).unwrap();
+        let parents: Vec<_> = (parents.iter())
+            .map(|x| repository.find_commit(*x).unwrap())
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        repository
+            .commit(None, &signature, &signature, "test", &tree, &parents)
+            .unwrap()
+    }
+
+    /// Evaluates a merge commit with the content `merged`, in a new repository
+    fn evaluate_merge(base: &str, left: &str, right: &str, merged: &str) -> MergeEvaluation {
+        let nanos = time::SystemTime::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let path = env::temp_dir().join(format!("merge_commits_{nanos}"));
+        let mut repository = Repository::init(&path).unwrap();
+        let base = commit(&repository, base, &[]);
+        let left = commit(&repository, left, &[base]);
+        let right = commit(&repository, right, &[base]);
+        let merge = commit(&repository, merged, &[left, right]);
+        let mut preprocessed = PreProcessedRepository::new("test");
+        let commits =
+            preprocessed.pre_process_no_maven(&mut repository, "", &merge.to_string(), "");
+        let mut evaluations = evaluate(&preprocessed, &repository, &commits);
+        fs::remove_dir_all(&path).unwrap();
+        assert_eq!(evaluations.len(), 1);
+        let evaluation = evaluations.pop().unwrap();
+        assert_eq!(evaluation.commit, merge);
+        assert_eq!(evaluation.base, base);
+        evaluation
+    }
+
+    const BASE: &str = r#"class A {
+    int f() { return 1; }
+    int g() { return 2; }
+}
+"#;
+
+    #[test]
+    fn test_evaluate_merge() {
+        let left = BASE.replace("return 1;", "return 3;");
+        let right = BASE.replace("return 2;", "return 4;");
+        let merged = left.replace("return 2;", "return 4;");
+        let evaluation = evaluate_merge(BASE, &left, &right, &merged);
+        assert!(evaluation.conflicts.is_empty());
+        assert!(evaluation.reproduced);
+    }
+
+    #[test]
+    fn test_evaluate_conflicting_merge() {
+        // resolved in favor of left like the structural merge
+        let left = BASE.replace("return 1;", "return 3;");
+        let right = BASE.replace("return 1;", "return 4;");
+        let evaluation = evaluate_merge(BASE, &left, &right, &left);
+        assert_eq!(evaluation.conflicts, vec![ConflictKind::UpdateUpdate]);
+        assert!(evaluation.reproduced);
+    }
+}
@@ -960,93 +960,88 @@ mod tests {
     use hyper_ast::store::{labels::LabelStore, nodes::legion::NodeStore, SimpleStores};
     // use hyper_ast_gen_ts_java::types::TStore;
     use hyper_ast_cvs_git::TStore;
-    use hyper_diff::algorithms::{self, DiffResult, MappingDurations};
+    use hyper_diff::algorithms::{self, DiffResult};
 
-    #[test]
-    fn test() {
-        let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
-        let data_root = root.parent().unwrap().join("gt_datasets/defects4j");
-        assert!(data_root.exists());
-        let data_root = data_root.as_path();
-        std::fs::read_dir(data_root).expect("should be a dir");
-        let root_buggy = data_root.join("buggy/Jsoup/92"); // /Jsoup/92
-        let root_fixed = data_root.join("fixed/Jsoup/92");
-        let src = root_buggy;
-        let dst = root_fixed;
+    /// Compares the results of `$diff` with the ones of the `$gt_matcher` matcher of upstream GumTree
+    macro_rules! check_against_gumtree {
+        ($gt_matcher:expr, $diff:path) => {{
+            let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+            let data_root = root.parent().unwrap().join("gt_datasets/defects4j");
+            assert!(data_root.exists());
+            let data_root = data_root.as_path();
+            std::fs::read_dir(data_root).expect("should be a dir");
+            let root_buggy = data_root.join("buggy/Jsoup/92"); // /Jsoup/92
+            let root_fixed = data_root.join("fixed/Jsoup/92");
+            let src = root_buggy;
+            let dst = root_fixed;
 
-        let stores = SimpleStores {
-            label_store: LabelStore::new(),
-            type_store: TStore::default(),
-            node_store: NodeStore::new(),
-        };
-        let md_cache = Default::default();
-        let mut java_gen = JavaPreprocessFileSys {
-            main_stores: stores,
-            java_md_cache: md_cache,
-        };
-        let now = Instant::now();
-        let (src_tr, dst_tr) = parse_dir_pair(&mut java_gen, &src, &dst);
-        let parse_t = now.elapsed().as_secs_f64();
+            let stores = SimpleStores {
+                label_store: LabelStore::new(),
+                type_store: TStore::default(),
+                node_store: NodeStore::new(),
+            };
+            let md_cache = Default::default();
+            let mut java_gen = JavaPreprocessFileSys {
+                main_stores: stores,
+                java_md_cache: md_cache,
+            };
+            let (src_tr, dst_tr) = parse_dir_pair(&mut java_gen, &src, &dst);
 
-        dbg!(&parse_t);
-        dbg!(&src_tr.metrics.size);
-        dbg!(&dst_tr.metrics.size);
+            let gt_out_format = "COMPRESSED"; // JSON
+            let gt_out = other_tools::gumtree::subprocess(
+                &java_gen.main_stores,
+                src_tr.compressed_node,
+                dst_tr.compressed_node,
+                $gt_matcher,
+                "Chawathe",
+                60 * 5,
+                gt_out_format,
+            )
+            .unwrap();
 
-        let gt_out_format = "COMPRESSED"; // JSON
-        let gt_out = other_tools::gumtree::subprocess(
-            &java_gen.main_stores,
-            src_tr.compressed_node,
-            dst_tr.compressed_node,
-            "gumtree",
-            "Chawathe",
-            60 * 5,
-            gt_out_format,
-        )
-        .unwrap();
+            let DiffResult {
+                mapper: mapping,
+                actions,
+                ..
+            } = $diff(
+                &java_gen.main_stores,
+                &src_tr.compressed_node,
+                &dst_tr.compressed_node,
+            );
+            let actions = actions.unwrap();
+            let pp = CompressedBfPostProcess::create(&gt_out);
+            let (pp, counts) = pp.counts();
+            let (pp, _) = pp.performances();
+            let valid = pp.validity_mappings(&mapping);
+            use hyper_diff::actions::Actions as _;
+            assert_eq!(
+                (valid.additional_mappings, valid.missing_mappings),
+                (0, 0),
+                "additional and missing mappings compared to gumtree"
+            );
+            assert!(counts.actions >= 0, "no actions computed");
+            assert_eq!(counts.actions as usize, actions.len());
+        }};
+    }
 
-        let DiffResult {
-            mapping_durations,
-            mapper: mapping,
-            actions,
-            prepare_gen_t,
-            gen_t,
-        } = algorithms::gumtree::diff(
-            &java_gen.main_stores,
-            &src_tr.compressed_node,
-            &dst_tr.compressed_node,
-        );
-        let actions = actions.unwrap();
-        // let Mapping {
-        //     src_arena,
-        //     dst_arena,
-        //     mappings,
-        // } = mapping;
-        let MappingDurations([subtree_matcher_t, bottomup_matcher_t]) = mapping_durations.into();
+    // NOTE the following tests need the defects4j dataset in `gt_datasets`
+    // next to the repository and the gumtree binary, run them with `--ignored`
+
+    #[test]
+    #[ignore]
+    fn test() {
+        check_against_gumtree!("gumtree", algorithms::gumtree::diff)
+    }
 
-        let hast_timings = vec![subtree_matcher_t, bottomup_matcher_t, prepare_gen_t + gen_t];
+    #[test]
+    #[ignore]
+    fn test_simple() {
+        check_against_gumtree!("gumtree-simple", algorithms::gumtree_simple::diff)
+    }
 
-        dbg!(&hast_timings);
-        let pp = CompressedBfPostProcess::create(&gt_out);
-        let (pp, counts) = pp.counts();
-        let (pp, gt_timings) = pp.performances();
-        let valid = pp.validity_mappings(&mapping);
-        use hyper_diff::actions::Actions as _;
-        if valid.additional_mappings > 0 || valid.missing_mappings > 0 {
-            dbg!(
-                valid.additional_mappings,
-                valid.missing_mappings,
-                actions.len(),
-                counts.actions
-            );
-            panic!()
-        } else if counts.actions < 0 {
-            panic!("no actions computed")
-        } else if counts.actions as usize != actions.len() {
-            dbg!(actions.len(), counts.actions);
-            panic!()
-        } else {
-            println!("gt_tt={:?} evos={}", &gt_timings, counts.actions);
-            println!("tt={:?} evos={}", &hast_timings, actions.len())
-        }
+    #[test]
+    #[ignore]
+    fn test_hybrid() {
+        check_against_gumtree!("gumtree-hybrid", algorithms::gumtree_hybrid::diff)
     }
 }
@@ -5,6 +5,7 @@ use hyper_ast::{
     filter::{Bloom, BloomSize, BF},
     full::FullNode,
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+    nodes::HashSize,
     store::{
         defaults::{LabelIdentifier, NodeIdentifier},
         nodes::legion::{compo, compo::CS, EntryRef, NodeStore, PendingInsert},
@@ -125,7 +126,7 @@ pub struct JavaAcc {
     pub(crate) name: String,
     pub(crate) children: Vec<NodeIdentifier>,
     pub(crate) children_names: Vec<LabelIdentifier>,
-    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     pub(crate) skiped_ana: bool,
     pub(crate) ana: PartialAnalysis,
 }
@@ -493,7 +494,7 @@ fn compress(
     size: u32,
     height: u32,
     size_no_spaces: u32,
-    hashs: SyntaxNodeHashs<u32>,
+    hashs: SyntaxNodeHashs<HashSize>,
     skiped_ana: bool,
     ana: &PartialAnalysis,
 ) -> NodeIdentifier {
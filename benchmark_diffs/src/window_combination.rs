@@ -24,7 +24,7 @@ pub fn windowed_commits_compare(
     let batch_id = format!("{}:({},{})", &preprocessed.name, before, after);
     let mu = memusage_linux();
     let processing_ordered_commits = preprocessed.pre_process_with_limit(
-        &mut fetch_github_repository(&preprocessed.name),
+        &mut fetch_github_repository(&preprocessed.name).unwrap(),
         before,
         after,
         dir_path,
@@ -355,7 +355,7 @@ mod test {
         assert!(window_size > 1);
 
         let processing_ordered_commits = preprocessed.pre_process_with_limit(
-            &mut fetch_github_repository(&preprocessed.name),
+            &mut fetch_github_repository(&preprocessed.name).unwrap(),
             before,
             after,
             "spoon-pom",
@@ -410,7 +410,7 @@ mod test {
         assert!(window_size > 1);
 
         let processing_ordered_commits = preprocessed.pre_process_with_limit(
-            &mut fetch_github_repository(&preprocessed.name),
+            &mut fetch_github_repository(&preprocessed.name).unwrap(),
             before,
             after,
             "spoon-pom",
@@ -497,7 +497,7 @@ mod test {
         assert!(window_size > 1);
 
         let processing_ordered_commits = preprocessed.pre_process_with_limit(
-            &mut fetch_github_repository(&preprocessed.name),
+            &mut fetch_github_repository(&preprocessed.name).unwrap(),
             before,
             after,
             "",
@@ -551,7 +551,7 @@ mod test {
         assert!(window_size > 1);
 
         preprocessed.pre_process_with_limit(
-            &mut fetch_github_repository(&preprocessed.name),
+            &mut fetch_github_repository(&preprocessed.name).unwrap(),
             before,
             after,
             "log4j-osgi",
@@ -575,7 +575,7 @@ mod test {
         assert!(window_size > 1);
 
         let processing_ordered_commits = preprocessed.pre_process_with_limit(
-            &mut fetch_github_repository(&preprocessed.name),
+            &mut fetch_github_repository(&preprocessed.name).unwrap(),
             before,
             after,
             "",
@@ -616,6 +616,10 @@ mod test {
                 dst_arena,
                 mappings,
             },
+            params: hyper_diff::matchers::GumtreeParams {
+                min_height: 10,
+                ..Default::default()
+            },
         };
         dbg!();
         use hyper_diff::decompressed_tree_store::ShallowDecompressedTreeStore;
@@ -630,18 +634,19 @@ mod test {
         >(&mut mapper);
         dbg!();
         use hyper_diff::matchers::heuristic::gt::lazy2_greedy_subtree_matcher::LazyGreedySubtreeMatcher;
-        LazyGreedySubtreeMatcher::<_, _, _, VecStore<_>, 10>::filter_mappings(&mut mapper, &mm);
+        LazyGreedySubtreeMatcher::<_, _, _, VecStore<_>>::filter_mappings(&mut mapper, &mm);
         // TODO do something with the multi mappings
         // modify filter_mappings to extract redundant mappings
         // the store it alongside other mappings
         dbg!();
         use hyper_diff::matchers::heuristic::gt::lazy2_greedy_bottom_up_matcher::GreedyBottomUpMatcher;
-        GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>, 1000, 1, 2>::execute(
+        GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>>::execute(
             &mut mapper,
             &stores.label_store,
         );
         // This one matches everingthing as it should but it is much slower
-        // GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>, 10_000, 1, 2>::execute(
+        // mapper.params.size_threshold = 10_000;
+        // GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>>::execute(
         //     &mut mapper,
         //     &stores.label_store,
         // );
@@ -11,7 +11,7 @@ use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
 
 use crate::{
-    commit, fetch, file,
+    commit, fetch, file, maven,
     scripting::{self, ScriptContent, ScriptContentDepth, ScriptingError, ScriptingParam},
     track, view, SharedState,
 };
@@ -60,11 +60,11 @@ pub fn scripting_app(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/script/github/:user/:name/:commit",
+            "/script/:forge/:user/:name/:commit",
             post(scripting).layer(scripting_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/script-depth/github/:user/:name/:commit",
+            "/script-depth/:forge/:user/:name/:commit",
             post(scripting_depth).layer(scripting_service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
@@ -94,7 +94,7 @@ pub fn fetch_git_file(_st: SharedState) -> Router<SharedState> {
         .timeout(Duration::from_secs(10))
         .layer(TraceLayer::new_for_http());
     Router::new().route(
-        "/file/github/:user/:name/:commit/*file",
+        "/file/:forge/:user/:name/:commit/*file",
         get(file).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
     )
 }
@@ -105,7 +105,7 @@ async fn file(
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> axum::response::Result<String> {
     dbg!(&path);
-    file::from_hyper_ast(state, path).map_err(|err| err.into())
+    file::from_hyper_ast(state, path).map_err(|err| (StatusCode::BAD_REQUEST, err).into())
 }
 
 pub fn track_code_route(_st: SharedState) -> Router<SharedState> {
@@ -122,15 +122,15 @@ pub fn track_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/track/github/:user/:name/:commit/*file",
+            "/track/:forge/:user/:name/:commit/*file",
             get(track_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/track_at_path/github/:user/:name/:commit/*path",
+            "/track_at_path/:forge/:user/:name/:commit/*path",
             get(track_code_at_path).layer(service_config.clone()),
         )
         .route(
-            "/track_at_path_with_changes/github/:user/:name/:commit/*path",
+            "/track_at_path_with_changes/:forge/:user/:name/:commit/*path",
             get(track_code_at_path_with_changes).layer(service_config.clone()),
         )
 }
@@ -189,6 +189,15 @@ pub fn view_code_route(_st: SharedState) -> Router<SharedState> {
             "/view/:id",
             get(view_code_with_node_id).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
+        .route(
+            // NOTE not under /view/ where the forge would conflict with the node id of the route above
+            "/view-forge/:forge/:user/:name/:commit/*path",
+            get(view_code).layer(service_config.clone()),
+        )
+        .route(
+            "/view-forge/:forge/:user/:name/:commit/",
+            get(view_code).layer(service_config.clone()),
+        )
 }
 
 // #[axum_macros::debug_handler]
@@ -197,7 +206,7 @@ async fn view_code(
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> axum::response::Result<Json<view::ViewRes>> {
     dbg!(&path);
-    view::view(state, path).map_err(|err| err.into())
+    view::view(state, path).map_err(|err| (StatusCode::BAD_REQUEST, err).into())
 }
 async fn view_code_with_node_id(
     axum::extract::Path(id): axum::extract::Path<u64>,
@@ -220,11 +229,11 @@ pub fn fetch_code_route(_st: SharedState) -> Router<SharedState> {
         .layer(TraceLayer::new_for_http());
     Router::new()
         .route(
-            "/fetch/github/:user/:name/:commit/*path",
+            "/fetch/:forge/:user/:name/:commit/*path",
             get(fetch_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
-            "/fetch/github/:user/:name/:commit/",
+            "/fetch/:forge/:user/:name/:commit/",
             get(fetch_code).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
         .route(
@@ -235,6 +244,18 @@ pub fn fetch_code_route(_st: SharedState) -> Router<SharedState> {
             "/fetch-labels/*ids",
             get(fetch_labels).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
         )
+        .route(
+            "/merkle-ids/*ids",
+            get(merkle_ids).layer(service_config.clone()),
+        )
+        .route(
+            "/fetch-merkle/*ids",
+            get(fetch_code_with_merkle_ids).layer(service_config.clone()),
+        )
+        .route(
+            "/fetch-merkle-in/:forge/:user/:name/:commit/*ids",
+            get(fetch_code_with_merkle_ids_in).layer(service_config.clone()),
+        )
 }
 // #[axum_macros::debug_handler]
 async fn fetch_code(
@@ -242,7 +263,7 @@ async fn fetch_code(
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> axum::response::Result<fetch::FetchedNodes> {
     dbg!(&path);
-    fetch::fetch(state, path).map_err(|err| err.into())
+    fetch::fetch(state, path).map_err(|err| (StatusCode::BAD_REQUEST, err).into())
 }
 async fn fetch_code_with_node_ids(
     axum::extract::Path(ids): axum::extract::Path<String>,
@@ -251,6 +272,41 @@ async fn fetch_code_with_node_ids(
     dbg!(&ids);
     fetch::fetch_with_node_ids(state, ids.split("/")).map_err(|err| err.into())
 }
+async fn merkle_ids(
+    axum::extract::Path(ids): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Timed<Json<fetch::MerkleIds>>> {
+    fetch::merkle_ids(state, ids.split("/")).map_err(|err| err.into())
+}
+async fn fetch_code_with_merkle_ids(
+    axum::extract::Path(ids): axum::extract::Path<String>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Timed<fetch::FetchedNodes>> {
+    Ok(fetch::fetch_with_merkle_ids(state, ids.split("/"))?)
+}
+async fn fetch_code_with_merkle_ids_in(
+    axum::extract::Path(path): axum::extract::Path<fetch::MerkleParameters>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Timed<fetch::FetchedNodes>> {
+    Ok(fetch::fetch_with_merkle_ids_in(state, path)?)
+}
+
+impl IntoResponse for fetch::FetchMerkleError {
+    fn into_response(self) -> Response {
+        match self {
+            fetch::FetchMerkleError::BadIdentifier(id) => (
+                StatusCode::BAD_REQUEST,
+                format!("bad merkle identifier {}", id),
+            ),
+            fetch::FetchMerkleError::Unknown(id) => (
+                StatusCode::NOT_FOUND,
+                format!("unknown merkle identifier {}", id),
+            ),
+            fetch::FetchMerkleError::Commit(err) => (StatusCode::BAD_REQUEST, err),
+        }
+        .into_response()
+    }
+}
 async fn fetch_labels(
     axum::extract::Path(ids): axum::extract::Path<String>,
     axum::extract::State(state): axum::extract::State<SharedState>,
@@ -293,7 +349,7 @@ pub fn commit_metadata_route(_st: SharedState) -> Router<SharedState> {
         .timeout(Duration::from_secs(10))
         .layer(TraceLayer::new_for_http());
     Router::new().route(
-        "/commit/github/:user/:name/:version",
+        "/commit/:forge/:user/:name/:version",
         get(commit_metadata).layer(service_config.clone()), // .with_state(Arc::clone(&shared_state)),
     )
 }
@@ -304,7 +360,55 @@ async fn commit_metadata(
     axum::extract::State(state): axum::extract::State<SharedState>,
 ) -> axum::response::Result<Json<commit::Metadata>> {
     dbg!(&path);
-    commit::commit_metadata(state, path).map_err(|err| err.into())
+    commit::commit_metadata(state, path).map_err(|err| (StatusCode::BAD_REQUEST, err).into())
+}
+
+pub fn maven_modules_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            log::error!("failed to list maven modules: {}", e);
+        }))
+        .load_shed()
+        .concurrency_limit(8)
+        .buffer(20)
+        .rate_limit(2, Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .layer(TraceLayer::new_for_http());
+    Router::new().route(
+        "/maven/:forge/:user/:name/:commit",
+        get(maven_modules).layer(service_config),
+    )
+}
+
+/// Maven modules of a commit, with their source directories and dependencies
+async fn maven_modules(
+    axum::extract::Path(path): axum::extract::Path<maven::Param>,
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Json<Vec<maven::Module>>> {
+    log::debug!("{:?}", path);
+    maven::modules(state, path).map_err(|err| (StatusCode::BAD_REQUEST, err).into())
+}
+
+pub fn stats_route(_st: SharedState) -> Router<SharedState> {
+    let service_config = ServiceBuilder::new()
+        .layer(HandleErrorLayer::new(|e: BoxError| async move {
+            log::error!("failed to compute store stats: {}", e);
+        }))
+        .load_shed()
+        .concurrency_limit(2)
+        .buffer(20)
+        .rate_limit(2, Duration::from_secs(5))
+        .timeout(Duration::from_secs(60))
+        .layer(TraceLayer::new_for_http());
+    Router::new().route("/stats", get(store_stats).layer(service_config))
+}
+
+/// Statistics about the stores shared by all repositories
+async fn store_stats(
+    axum::extract::State(state): axum::extract::State<SharedState>,
+) -> axum::response::Result<Json<hyper_ast::store::stats::StoreStats>> {
+    let repositories = state.repositories.read().unwrap();
+    Ok(Json(repositories.processor.stats()))
 }
 
 pub struct Timed<T> {
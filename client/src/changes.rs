@@ -7,7 +7,10 @@ use hyper_ast::{
     store::defaults::NodeIdentifier,
     types::{HyperAST, HyperType, IterableChildren, TypeStore, WithChildren, WithStats},
 };
-use hyper_diff::{decompressed_tree_store::ShallowDecompressedTreeStore, matchers::Mapper};
+use hyper_diff::{
+    decompressed_tree_store::ShallowDecompressedTreeStore,
+    matchers::{GumtreeParams, Mapper},
+};
 
 use crate::{matching, no_space, utils::get_pair_simp};
 
@@ -129,7 +132,7 @@ pub(crate) fn added_deleted(
         use hyper_diff::matchers::Mapping;
 
         dbg!();
-        match mappings_cache.entry((src_tr, dst_tr)) {
+        match mappings_cache.entry((src_tr, dst_tr, GumtreeParams::default())) {
             dashmap::mapref::entry::Entry::Occupied(entry) => entry.into_ref().downgrade(),
             dashmap::mapref::entry::Entry::Vacant(entry) => {
                 // std::collections::hash_map::Entry::Vacant(entry) => {
@@ -148,6 +151,7 @@ pub(crate) fn added_deleted(
                         dst_arena,
                         mappings,
                     },
+                    params: GumtreeParams::default(),
                 };
                 dbg!();
                 dbg!(mapper.mapping.src_arena.len());
@@ -17,6 +17,8 @@ pub(super) struct Options {
     ///
     /// use the following syntax: <forge>/<user>/<name>:<config>
     /// example: github.com/INRIA/spoon:Java
+    /// self-hosted forges and local mirrors listed in HYPERAST_ALLOWED_FORGES are also accepted:
+    /// https://git.example.com/team/project:Java or file:///srv/mirrors/team/project:Java
     #[clap(short, long)]
     pub repository: Vec<RepoConfig>,
 }
@@ -30,7 +32,7 @@ impl std::str::FromStr for RepoConfig {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (repo, config) = s.split_once(":").ok_or("")?;
+        let (repo, config) = s.rsplit_once(":").ok_or("")?;
         let repo = repo.parse()?;
         let config = config.parse()?;
 
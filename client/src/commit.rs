@@ -3,7 +3,7 @@ use std::{fmt::Display, rc::Rc};
 use axum::{body::HttpBody, Json};
 // use hyper_ast::types::LabelStore;
 use hyper_ast_cvs_git::{
-    git::{fetch_github_repository, retrieve_commit},
+    git::{retrieve_commit, Forge},
     preprocessed::child_at_path,
 };
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,8 @@ use crate::SharedState;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Param {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
     user: String,
     name: String,
     /// either a commit id or a tag
@@ -36,11 +38,16 @@ pub struct Metadata {
 
 pub fn commit_metadata(_state: SharedState, path: Param) -> Result<Json<Metadata>, String> {
     let Param {
+        forge,
         user,
         name,
         version,
     } = path.clone();
-    let repo = fetch_github_repository(&format!("{}/{}", user, name));
+    let repo = forge
+        .parse::<Forge>()?
+        .repo(&user, &name)
+        .fetch()
+        .map_err(|err| err.to_string())?;
     log::warn!("done cloning {user}/{name}");
     let commit = retrieve_commit(&repo, &version);
     let commit = commit.map_err(|err| err.to_string())?;
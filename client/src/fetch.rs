@@ -3,6 +3,7 @@ use hyper_ast::{
     store::{
         defaults,
         labels::label_id_from_usize, // ::fetched,
+        merkle::MerkleId,
         nodes::{
             self,
             fetched::{self, NodeIdentifier},
@@ -10,7 +11,7 @@ use hyper_ast::{
     },
     types::WithChildren,
 };
-use hyper_ast_cvs_git::TStore;
+use hyper_ast_cvs_git::{git::Forge, TStore};
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
 
@@ -18,6 +19,8 @@ use crate::{app::Timed, SharedState};
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Parameters {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
     user: String,
     name: String,
     commit: String,
@@ -54,32 +57,16 @@ pub struct FetchedNodes {
 pub fn fetch(mut state: SharedState, path: Parameters) -> Result<FetchedNodes, String> {
     let now = Instant::now();
     let Parameters {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
     dbg!(&path);
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
-    let repo = state
-        .repositories
-        .write()
-        .unwrap()
-        .get_config(repo_spec)
-        .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
-    log::warn!("done cloning {}", repo.spec);
-    let commits = state
-        .repositories
-        .write()
-        .unwrap()
-        .pre_process_with_limit(&mut repo, "", &commit, 2)
-        .map_err(|e| e.to_string())?;
-    log::warn!("done construction of {commits:?} in {}", repo.spec);
-    let repositories = state.repositories.read().unwrap();
-    let commit_src = repositories.get_commit(&repo.config, &commits[0]).unwrap();
-    let src_tr = commit_src.ast_root;
+    let src_tr = processed_root(&state, forge, user, name, &commit)?;
     dbg!(src_tr);
+    let repositories = state.repositories.read().unwrap();
     let node_store = &repositories.processor.main_stores.node_store;
 
     log::error!("searching for {path:?}");
@@ -130,6 +117,156 @@ pub fn fetch_with_node_ids<'a>(
     })
 }
 
+/// Content-addressed identifiers of nodes, in the same order
+#[derive(Serialize)]
+pub struct MerkleIds {
+    ids: Vec<MerkleId>,
+}
+
+fn parse_node_id(id: &str) -> Result<defaults::NodeIdentifier, String> {
+    let id: u64 = id
+        .parse()
+        .map_err(|_| format!("bad node identifier {}", id))?;
+    hyper_ast::store::snapshot::id_from_u64(id).ok_or_else(|| "null node identifier".to_string())
+}
+
+pub fn merkle_ids<'a>(
+    state: SharedState,
+    ids: impl Iterator<Item = &'a str>,
+) -> Result<Timed<Json<MerkleIds>>, String> {
+    let now = Instant::now();
+    let ids = ids.map(parse_node_id).collect::<Result<Vec<_>, _>>()?;
+    let repositories = state.repositories.read().unwrap();
+    let mut index = state.merkle.write().unwrap();
+    let ids = ids
+        .into_iter()
+        .map(|id| repositories.processor.merkle_id(&mut index, id))
+        .collect();
+    Ok(Timed {
+        time: now.elapsed().as_secs_f64(),
+        content: Json(MerkleIds { ids }),
+    })
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MerkleParameters {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
+    user: String,
+    name: String,
+    commit: String,
+    /// content-addressed identifiers separated by `/`
+    ids: String,
+}
+
+#[derive(Debug)]
+pub enum FetchMerkleError {
+    BadIdentifier(String),
+    /// not in the subtrees indexed so far, including the given commit if any
+    Unknown(MerkleId),
+    Commit(String),
+}
+
+fn parse_merkle_ids<'a>(
+    ids: impl Iterator<Item = &'a str>,
+) -> Result<Vec<MerkleId>, FetchMerkleError> {
+    ids.map(|id| {
+        id.parse::<MerkleId>()
+            .map_err(|_| FetchMerkleError::BadIdentifier(id.to_string()))
+    })
+    .collect()
+}
+
+fn fetch_indexed(state: &SharedState, ids: &[MerkleId]) -> Result<FetchedNodes, FetchMerkleError> {
+    let index = state.merkle.read().unwrap();
+    let ids = ids
+        .iter()
+        .map(|id| index.node(id).ok_or(FetchMerkleError::Unknown(*id)))
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(index);
+    let repositories = state.repositories.read().unwrap();
+    let node_store = extract_nodes(&ids, &repositories.processor.main_stores);
+    Ok(FetchedNodes {
+        node_store,
+        root: vec![],
+    })
+}
+
+/// Root of the given commit, processed if needed
+fn processed_root(
+    state: &SharedState,
+    forge: String,
+    user: String,
+    name: String,
+    commit: &str,
+) -> Result<defaults::NodeIdentifier, String> {
+    let repo_spec = forge.parse::<Forge>()?.repo(user, name);
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| "missing config for repository".to_string())?;
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
+    log::warn!("done cloning {}", repo.spec);
+    let commits = state
+        .repositories
+        .write()
+        .unwrap()
+        .pre_process_with_limit(&mut repo, "", commit, 2)
+        .map_err(|e| e.to_string())?;
+    log::warn!("done construction of {commits:?} in {}", repo.spec);
+    let repositories = state.repositories.read().unwrap();
+    let commit_src = repositories.get_commit(&repo.config, &commits[0]).unwrap();
+    Ok(commit_src.ast_root)
+}
+
+/// Same as [`fetch_with_node_ids`] with content-addressed identifiers.
+///
+/// Only the subtrees indexed so far are searched, eg. the ones given to [`merkle_ids`].
+/// The index is only kept in memory, see [`fetch_with_merkle_ids_in`] to search a given commit.
+pub fn fetch_with_merkle_ids<'a>(
+    state: SharedState,
+    ids: impl Iterator<Item = &'a str>,
+) -> Result<Timed<FetchedNodes>, FetchMerkleError> {
+    let now = Instant::now();
+    let ids = parse_merkle_ids(ids)?;
+    let content = fetch_indexed(&state, &ids)?;
+    Ok(Timed {
+        time: now.elapsed().as_secs_f64(),
+        content,
+    })
+}
+
+/// Same as [`fetch_with_merkle_ids`], but first indexes the subtree of the given commit,
+/// processing it if needed, eg. to find identifiers again after a restart of the server.
+pub fn fetch_with_merkle_ids_in(
+    state: SharedState,
+    path: MerkleParameters,
+) -> Result<Timed<FetchedNodes>, FetchMerkleError> {
+    let now = Instant::now();
+    let MerkleParameters {
+        forge,
+        user,
+        name,
+        commit,
+        ids,
+    } = path;
+    let ids = parse_merkle_ids(ids.split("/"))?;
+    let root =
+        processed_root(&state, forge, user, name, &commit).map_err(FetchMerkleError::Commit)?;
+    {
+        let repositories = state.repositories.read().unwrap();
+        let mut index = state.merkle.write().unwrap();
+        repositories.processor.merkle_id(&mut index, root);
+    }
+    let content = fetch_indexed(&state, &ids)?;
+    Ok(Timed {
+        time: now.elapsed().as_secs_f64(),
+        content,
+    })
+}
+
 pub fn fetch_labels<'a>(
     state: SharedState,
     ids: impl Iterator<Item = &'a str>,
@@ -1,5 +1,5 @@
 use hyper_ast::types::LabelStore;
-use hyper_ast_cvs_git::{git::fetch_github_repository, preprocessed::child_at_path};
+use hyper_ast_cvs_git::{git::Forge, preprocessed::child_at_path};
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
 
@@ -7,6 +7,8 @@ use crate::SharedState;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct FetchFileParam {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
     user: String,
     name: String,
     commit: String,
@@ -16,19 +18,20 @@ pub struct FetchFileParam {
 pub fn from_hyper_ast(state: SharedState, path: FetchFileParam) -> Result<String, String> {
     let now = Instant::now();
     let FetchFileParam {
+        forge,
         user,
         name,
         commit,
         file,
     } = path.clone();
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.parse::<Forge>()?.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
     log::warn!("done cloning {}", repo.spec);
     let commits = state
         .repositories
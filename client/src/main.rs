@@ -13,13 +13,16 @@ use dashmap::DashMap;
 use hyper_ast_cvs_git::{
     git::Forge, multi_preprocessed::PreProcessedRepositories, processing::ConfiguredRepoHandle,
 };
-use hyper_diff::{decompressed_tree_store::PersistedNode, matchers::mapping_store::VecStore};
+use hyper_diff::{
+    decompressed_tree_store::PersistedNode,
+    matchers::{mapping_store::VecStore, GumtreeParams},
+};
 use tower_http::cors::CorsLayer;
 
 use crate::{
     app::{
-        commit_metadata_route, fetch_code_route, fetch_git_file, scripting_app, track_code_route,
-        view_code_route,
+        commit_metadata_route, fetch_code_route, fetch_git_file, maven_modules_route,
+        scripting_app, stats_route, track_code_route, view_code_route,
     },
     examples::{example_app, kv_store_app},
 };
@@ -34,6 +37,7 @@ mod examples;
 mod fetch;
 mod file;
 mod matching;
+mod maven;
 mod scripting;
 mod track;
 mod utils;
@@ -48,6 +52,8 @@ pub struct AppState {
     mappings: MappingCache,
     mappings_alone: MappingAloneCache,
     partial_decomps: PartialDecompCache,
+    /// content-addressed identifiers of the nodes of `repositories`
+    merkle: RwLock<hyper_ast::store::merkle::MerkleIndex>,
     // Single shared doc
     doc: Arc<(
         RwLock<automerge::AutoCommit>,
@@ -69,6 +75,7 @@ impl Default for AppState {
             mappings: Default::default(),
             mappings_alone: Default::default(),
             partial_decomps: Default::default(),
+            merkle: Default::default(),
             doc: Arc::new((
                 RwLock::new(automerge::AutoCommit::new()),
                 tokio::sync::broadcast::channel(50),
@@ -93,10 +100,14 @@ impl Default for AppState {
 // }
 
 pub(crate) type PartialDecompCache = DashMap<NodeIdentifier, DS<PersistedNode<NodeIdentifier>>>;
+/// mappings are also keyed by the thresholds used to compute them
 pub(crate) type MappingAloneCache =
-    DashMap<(NodeIdentifier, NodeIdentifier), (MappingStage, VecStore<u32>)>;
-pub(crate) type MappingAloneCacheRef<'a> =
-    dashmap::mapref::one::Ref<'a, (NodeIdentifier, NodeIdentifier), (MappingStage, VecStore<u32>)>;
+    DashMap<(NodeIdentifier, NodeIdentifier, GumtreeParams), (MappingStage, VecStore<u32>)>;
+pub(crate) type MappingAloneCacheRef<'a> = dashmap::mapref::one::Ref<
+    'a,
+    (NodeIdentifier, NodeIdentifier, GumtreeParams),
+    (MappingStage, VecStore<u32>),
+>;
 
 pub(crate) enum MappingStage {
     Subtree,
@@ -138,6 +149,8 @@ async fn main() {
         .merge(view_code_route(Arc::clone(&shared_state)))
         .merge(fetch_code_route(Arc::clone(&shared_state)))
         .merge(commit_metadata_route(Arc::clone(&shared_state)))
+        .merge(maven_modules_route(Arc::clone(&shared_state)))
+        .merge(stats_route(Arc::clone(&shared_state)))
         .merge(example_app())
         .layer(CorsLayer::permissive()) // WARN unwanted for deployment
         .with_state(Arc::clone(&shared_state));
@@ -44,6 +44,7 @@ pub fn top_down<'store, HAST: HyperAST<'store>>(
     hyperast: &'store HAST,
     src_arena: &mut LazyPostOrder<HAST::T, u32>,
     dst_arena: &mut LazyPostOrder<HAST::T, u32>,
+    min_height: usize,
 ) -> DefaultMultiMappingStore<u32>
 where
     HAST::IdN: Clone + Debug + Eq,
@@ -53,8 +54,8 @@ where
 {
     let mut mm: DefaultMultiMappingStore<_> = Default::default();
     mm.topit(src_arena.len(), dst_arena.len());
-    Mapper::<_, _, _, VecStore<u32>>::compute_multimapping::<_, 1>(
-        hyperast, src_arena, dst_arena, &mut mm,
+    Mapper::<_, _, _, VecStore<u32>>::compute_multimapping(
+        hyperast, src_arena, dst_arena, &mut mm, min_height,
     );
     mm
 }
@@ -130,6 +131,7 @@ pub fn bottom_up_hiding<'store, 'a, 'b, HAST: HyperAST<'store>>(
                 dst_arena,
                 mappings,
             },
+            params: mapper.params,
         };
         GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>>::execute(
             &mut mapper,
@@ -156,11 +158,12 @@ pub fn full2<'store, 'a, 'b, HAST: HyperAST<'store>>(
     let mut mm: DefaultMultiMappingStore<_> = Default::default();
     mm.topit(mapper.src_arena.len(), mapper.dst_arena.len());
     let now = std::time::Instant::now();
-    Mapper::<HAST, _, _, VecStore<u32>>::compute_multimapping::<_, 1>(
+    Mapper::<HAST, _, _, VecStore<u32>>::compute_multimapping(
         mapper.hyperast,
         &mut mapper.mapping.src_arena,
         &mut mapper.mapping.dst_arena,
         &mut mm,
+        mapper.params.min_height,
     );
     let compute_multimapping_t = now.elapsed().as_secs_f64();
     dbg!(compute_multimapping_t);
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use axum::Json;
+use enumset::EnumSet;
+use hyper_ast::types::{IterableChildren, LabelStore, Labeled, WithChildren};
+use hyper_ast_cvs_git::{
+    git::Forge,
+    maven::{Project, SemFlags},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::SharedState;
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Param {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
+    user: String,
+    name: String,
+    commit: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Module {
+    /// path of the module from the root of the repository, empty for the root module
+    path: String,
+    /// the project completed with what it inherits from its parent module,
+    /// and with its properties interpolated
+    project: Project,
+}
+
+/// Lists the maven modules of a commit, in pre-order
+pub fn modules(state: SharedState, path: Param) -> Result<Json<Vec<Module>>, String> {
+    let Param {
+        forge,
+        user,
+        name,
+        commit,
+    } = path;
+    let repo_spec = forge.parse::<Forge>()?.repo(user, name);
+    let repo = state
+        .repositories
+        .write()
+        .unwrap()
+        .get_config(repo_spec)
+        .ok_or_else(|| "missing config for repository".to_string())?;
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
+    log::warn!("done cloning {}", repo.spec);
+    let commits = state
+        .repositories
+        .write()
+        .unwrap()
+        .pre_process_with_limit(&mut repo, "", &commit, 2)
+        .map_err(|e| e.to_string())?;
+    let repositories = state.repositories.read().unwrap();
+    let commit = commits
+        .first()
+        .ok_or_else(|| format!("commit {} was not found", commit))?;
+    let commit_src = repositories
+        .get_commit(&repo.config, commit)
+        .ok_or_else(|| "commit was not processed".to_string())?;
+    let stores = &repositories.processor.main_stores;
+
+    let mut modules = vec![];
+    let mut stack: Vec<(_, String, Option<Arc<Project>>)> =
+        vec![(commit_src.ast_root, String::new(), None)];
+    while let Some((id, path, inherited)) = stack.pop() {
+        let n = stores.node_store.resolve(id);
+        let inherited = match n.get_component::<Project>() {
+            Ok(project) => {
+                let mut project = project.clone();
+                project.inherit(inherited.as_deref());
+                modules.push(Module {
+                    path: path.clone(),
+                    project: project.interpolated(),
+                });
+                Some(Arc::new(project))
+            }
+            Err(_) => inherited,
+        };
+        let Some(cs) = n.children() else {
+            continue;
+        };
+        for c in cs.iter_children().rev() {
+            let child = stores.node_store.resolve(*c);
+            // only directories on the way to maven modules have flags
+            let leads_to_module = child
+                .get_component::<EnumSet<SemFlags>>()
+                .map_or(false, |x| {
+                    x.contains(SemFlags::IsMavenModule) || x.contains(SemFlags::HoldMavenSubModule)
+                });
+            if !leads_to_module {
+                continue;
+            }
+            let name = stores.label_store.resolve(child.get_label_unchecked());
+            let child_path = if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}/{}", path, name)
+            };
+            stack.push((*c, child_path, inherited.clone()));
+        }
+    }
+    Ok(Json(modules))
+}
@@ -16,6 +16,7 @@ use hyper_ast::{
     store::defaults::NodeIdentifier,
     types::{HyperType, LabelStore, Labeled, TypeStore, WithChildren, WithStats},
 };
+use hyper_ast_cvs_git::git::Forge;
 use num::ToPrimitive;
 use rhai::{
     packages::{BasicArrayPackage, CorePackage, Package},
@@ -26,6 +27,8 @@ use std::fmt::Display;
 
 #[derive(Deserialize, Clone)]
 pub struct ScriptingParam {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
     user: String,
     name: String,
     commit: String,
@@ -123,7 +126,12 @@ pub fn simple_depth(
         commits,
     } = script;
     let now = Instant::now();
-    let ScriptingParam { user, name, commit } = path.clone();
+    let ScriptingParam {
+        forge,
+        user,
+        name,
+        commit,
+    } = path.clone();
     let mut engine = Engine::new();
     engine.disable_symbol("/");
     add_utils(&mut engine);
@@ -136,7 +144,10 @@ pub fn simple_depth(
     let accumulate_script = engine.compile(script.accumulate.clone()).map_err(|x| {
         ScriptingError::AtCompilation(format!("Acc: {}, {}", x, script.accumulate.clone()))
     })?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge
+        .parse::<Forge>()
+        .map_err(ScriptingError::Other)?
+        .repo(user, name);
     let repo = state
         .repositories
         .write()
@@ -155,7 +166,9 @@ pub fn simple_depth(
         }
     };
     // .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let mut repo = repo.fetch();
+    let mut repo = repo
+        .fetch()
+        .map_err(|e| ScriptingError::Other(e.to_string()))?;
     log::warn!("done cloning {}", &repo.spec);
     let commits = state
         .repositories
@@ -208,7 +221,12 @@ fn simple_prepare(
     ),
     ScriptingError,
 > {
-    let ScriptingParam { user, name, commit } = path.clone();
+    let ScriptingParam {
+        forge,
+        user,
+        name,
+        commit,
+    } = path.clone();
     let mut engine = Engine::new();
     engine.disable_symbol("/");
     add_utils(&mut engine);
@@ -221,14 +239,19 @@ fn simple_prepare(
     let accumulate_script = engine.compile(script.accumulate.clone()).map_err(|x| {
         ScriptingError::AtCompilation(format!("Acc: {}, {}", x, script.accumulate.clone()))
     })?;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge
+        .parse::<Forge>()
+        .map_err(ScriptingError::Other)?
+        .repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| ScriptingError::Other("missing config for repository".to_string()))?;
-    let repo = repo.fetch();
+    let repo = repo
+        .fetch()
+        .map_err(|e| ScriptingError::Other(e.to_string()))?;
     log::warn!("done cloning {}", &repo.spec);
     Ok((
         commit,
@@ -16,8 +16,11 @@ use hyper_ast::{
     PrimInt,
 };
 use hyper_ast_cvs_git::{
-    git::Repo, multi_preprocessed, preprocessed::child_at_path_tracked,
-    processing::ConfiguredRepoTrait, TStore,
+    git::{Forge, Repo},
+    multi_preprocessed,
+    preprocessed::child_at_path_tracked,
+    processing::ConfiguredRepoTrait,
+    TStore,
 };
 use hyper_diff::{
     decompressed_tree_store::{
@@ -26,11 +29,11 @@ use hyper_diff::{
     },
     matchers::{
         mapping_store::{self, MonoMappingStore, MultiMappingStore},
-        Mapper,
+        GumtreeParams, Mapper,
     },
 };
 use serde::{Deserialize, Serialize};
-use serde_aux::prelude::deserialize_bool_from_anything;
+use serde_aux::prelude::{deserialize_bool_from_anything, deserialize_number_from_string};
 use tokio::time::Instant;
 
 use crate::{
@@ -40,6 +43,8 @@ use crate::{
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TrackingParam {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
     user: String,
     name: String,
     commit: String,
@@ -48,6 +53,8 @@ pub struct TrackingParam {
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct TrackingAtPathParam {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values, e.g. `github`
+    forge: String,
     user: String,
     name: String,
     commit: String,
@@ -61,6 +68,46 @@ pub struct TrackingQuery {
     before: Option<String>,
     #[serde(flatten)]
     flags: Flags,
+    #[serde(flatten)]
+    params: MatcherParams,
+}
+
+/// Thresholds of the matchers used when tracking, see [`GumtreeParams`]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(default)]
+pub(crate) struct MatcherParams {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub(crate) min_height: usize,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub(crate) size_threshold: usize,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub(crate) sim_threshold: f64,
+}
+
+impl Default for MatcherParams {
+    fn default() -> Self {
+        let GumtreeParams {
+            min_height,
+            size_threshold,
+            sim_threshold,
+        } = GumtreeParams::default();
+        Self {
+            min_height,
+            size_threshold,
+            sim_threshold: sim_threshold
+                .expect("the greedy matchers use a fixed similarity threshold"),
+        }
+    }
+}
+
+impl From<MatcherParams> for GumtreeParams {
+    fn from(value: MatcherParams) -> Self {
+        Self {
+            min_height: value.min_height,
+            size_threshold: value.size_threshold,
+            sim_threshold: Some(value.sim_threshold),
+        }
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Default, Clone, Debug)]
@@ -245,6 +292,24 @@ pub struct TrackingError {
     message: String,
 }
 
+fn parse_forge(forge: &str, now: Instant) -> Result<Forge, TrackingError> {
+    forge.parse().map_err(|message| TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message,
+    })
+}
+
+fn fetch_error(e: impl std::fmt::Display, now: Instant) -> TrackingError {
+    TrackingError {
+        compute_time: now.elapsed().as_secs_f64(),
+        commits_processed: 0,
+        node_processed: 0,
+        message: e.to_string(),
+    }
+}
+
 impl IntoResponse for TrackingError {
     fn into_response(self) -> axum::response::Response {
         let mut resp = Json(self).into_response();
@@ -260,6 +325,7 @@ pub fn track_code(
 ) -> Result<TrackingResult<IdN, Idx>, TrackingError> {
     let now = Instant::now();
     let TrackingParam {
+        forge,
         user,
         name,
         commit,
@@ -270,8 +336,10 @@ pub fn track_code(
         end,
         before,
         flags,
+        params,
     } = query;
-    let repo_specifier = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let params: GumtreeParams = params.into();
+    let repo_specifier = parse_forge(&forge, now)?.repo(user, name);
     let repo_handle = state
         .repositories
         .write()
@@ -283,7 +351,7 @@ pub fn track_code(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch().map_err(|e| fetch_error(e, now))?;
     log::warn!("done cloning {}", repository.spec);
     // let mut get_mut = state.write().unwrap();
     // let state = get_mut.deref_mut();
@@ -319,6 +387,7 @@ pub fn track_code(
             start,
             end,
             &flags,
+            params,
         ) {
             MappingResult::Direct { src: aaa, matches } => {
                 let aaa = aaa.globalize(repository.spec, commit);
@@ -401,14 +470,17 @@ pub(crate) fn track_code_at_path(
         end,
         before,
         flags,
+        params,
     } = query;
+    let params: GumtreeParams = params.into();
     let TrackingAtPathParam {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
-    let repo_specifier = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_specifier = parse_forge(&forge, now)?.repo(user, name);
     let repository = state
         .repositories
         .write()
@@ -420,7 +492,7 @@ pub(crate) fn track_code_at_path(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repository.fetch();
+    let mut repository = repository.fetch().map_err(|e| fetch_error(e, now))?;
     log::warn!("done cloning {}", repository.spec);
     // let mut get_mut = state.write().unwrap();
     // let state = get_mut.deref_mut();
@@ -460,7 +532,15 @@ pub(crate) fn track_code_at_path(
         } else {
             commits[1]
         };
-        match track_aux2(state.clone(), &repository, src_oid, dst_oid, &path, &flags) {
+        match track_aux2(
+            state.clone(),
+            &repository,
+            src_oid,
+            dst_oid,
+            &path,
+            &flags,
+            params,
+        ) {
             MappingResult::Direct { src: aaa, matches } => {
                 let aaa = aaa.globalize(repository.spec, commit);
                 let (src, intermediary) = if let Some(src) = source {
@@ -558,14 +638,17 @@ pub(crate) fn track_code_at_path_with_changes(
         end: _,
         before,
         flags,
+        params,
     } = query;
+    let params: GumtreeParams = params.into();
     let TrackingAtPathParam {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = parse_forge(&forge, now)?.repo(user, name);
     let configs = state.clone();
     let repo_handle = state
         .repositories
@@ -578,7 +661,7 @@ pub(crate) fn track_code_at_path_with_changes(
             node_processed: 0,
             message: "missing config for repository".to_string(),
         })?;
-    let mut repository = repo_handle.fetch();
+    let mut repository = repo_handle.fetch().map_err(|e| fetch_error(e, now))?;
     log::warn!("done cloning {}", repository.spec);
     let mut ori_oid = None;
     let mut commit = commit.clone();
@@ -615,7 +698,15 @@ pub(crate) fn track_code_at_path_with_changes(
                 message: "this commit has no parent".into(),
             });
         };
-        match track_aux2(state.clone(), &repository, src_oid, dst_oid, &path, &flags) {
+        match track_aux2(
+            state.clone(),
+            &repository,
+            src_oid,
+            dst_oid,
+            &path,
+            &flags,
+            params,
+        ) {
             MappingResult::Direct { src: aaa, matches } => {
                 let changes = changes::added_deleted(state, &repository, dst_oid, ori_oid.unwrap())
                     .map_err(|err| TrackingError {
@@ -940,6 +1031,7 @@ fn track_aux(
     start: Option<usize>,
     end: Option<usize>,
     flags: &Flags,
+    params: GumtreeParams,
 ) -> MappingResult<IdN, Idx> {
     let repositories = state.repositories.read().unwrap();
     let commit_src = repositories
@@ -1007,6 +1099,7 @@ fn track_aux(
         &state.partial_decomps,
         &state.mappings_alone,
         flags,
+        params,
         &target,
         dst_tr,
         &postprocess_matching,
@@ -1022,6 +1115,7 @@ fn track_aux2(
     dst_oid: hyper_ast_cvs_git::git::Oid,
     path: &[Idx],
     flags: &Flags,
+    params: GumtreeParams,
 ) -> MappingResult<IdN, Idx> {
     let repositories = state.repositories.read().unwrap();
     let commit_src = repositories
@@ -1078,6 +1172,7 @@ fn track_aux2(
         &state.partial_decomps,
         &state.mappings_alone,
         flags,
+        params,
         &target,
         dst_tr,
         &postprocess_matching,
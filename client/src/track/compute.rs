@@ -47,6 +47,7 @@ pub(super) fn do_tracking<'store, 'p, P, C>(
     partial_decomps: &PartialDecompCache,
     mappings_alone: &MappingAloneCache,
     flags: &Flags,
+    params: GumtreeParams,
     // no_spaces_path_to_target: Vec<super::Idx>,
     target: &'p P,
     other_tr: super::IdN,
@@ -112,6 +113,7 @@ where
             dst_arena: dst_tree,
             mappings: mapping_store::VecStore::default(),
         },
+        params,
     };
     let fuller_mappings = if flags.some() {
         // case where
@@ -121,6 +123,7 @@ where
                 hyperast,
                 &mut mapper.mapping.src_arena,
                 &mut mapper.mapping.dst_arena,
+                mapper.params.min_height,
             )
         };
         dbg!();
@@ -384,6 +387,7 @@ fn compute_mappings_full<'store, 'alone, 'trees, 'mapper, 'rest>(
     match mappings_cache.entry((
         mapper.src_arena.original(&mapper.src_arena.root()),
         mapper.dst_arena.original(&mapper.dst_arena.root()),
+        mapper.params,
     )) {
         dashmap::mapref::entry::Entry::Occupied(entry) => entry.into_ref().downgrade(),
         dashmap::mapref::entry::Entry::Vacant(entry) => {
@@ -232,6 +232,7 @@ fn lazy_subtree_mapping<'a, 'b>(
             dst_arena: decompress_dst.value_mut(),
             mappings,
         },
+        params: Default::default(),
     };
     mapper.mapping.mappings.topit(
         mapper.mapping.src_arena.len(),
@@ -9,7 +9,7 @@ use hyper_ast::{
         TypeStore, WithChildren,
     },
 };
-use hyper_ast_cvs_git::{git::fetch_github_repository, processing::ConfiguredRepoTrait};
+use hyper_ast_cvs_git::{git::Forge, processing::ConfiguredRepoTrait};
 use serde::{Deserialize, Serialize};
 use tokio::time::Instant;
 
@@ -17,12 +17,20 @@ use crate::SharedState;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct Parameters {
+    /// see [`hyper_ast_cvs_git::git::Forge`] for accepted values,
+    /// `github` when not given, as in the `/view/github/...` routes
+    #[serde(default = "github")]
+    forge: String,
     user: String,
     name: String,
     commit: String,
     path: Option<String>,
 }
 
+fn github() -> String {
+    "github".to_string()
+}
+
 type NodeId = u64;
 
 #[derive(Serialize, Clone, Debug)]
@@ -80,20 +88,21 @@ pub struct ViewBoth {
 pub fn view(state: SharedState, path: Parameters) -> Result<Json<ViewRes>, String> {
     let now = Instant::now();
     let Parameters {
+        forge,
         user,
         name,
         commit,
         path,
     } = path;
     dbg!(&path);
-    let repo_spec = hyper_ast_cvs_git::git::Forge::Github.repo(user, name);
+    let repo_spec = forge.parse::<Forge>()?.repo(user, name);
     let repo = state
         .repositories
         .write()
         .unwrap()
         .get_config(repo_spec)
         .ok_or_else(|| "missing config for repository".to_string())?;
-    let mut repo = repo.fetch();
+    let mut repo = repo.fetch().map_err(|e| e.to_string())?;
     log::warn!("done cloning {}", repo.spec);
     let commits = state
         .repositories
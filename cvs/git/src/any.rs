@@ -0,0 +1,336 @@
+use hyper_ast::{
+    filter::BloomSize,
+    hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::{compo, compo::CS, ComponentsRef, NodeInserter, PendingInsertion},
+    },
+    tree_gen::SubTreeMetrics,
+    types::LabelStore,
+};
+
+use crate::{
+    processing::ObjectName, Accumulator, DefaultMetrics, TStore, PROPAGATE_ERROR_ON_BAD_CST_NODE,
+};
+
+pub mod types;
+
+use types::Type;
+
+/// The languages that can be recognized in a file,
+/// only the ones whose generator is enabled are actually parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Java,
+    Cpp,
+    Xml,
+    Ts,
+    /// tree-sitter queries
+    Query,
+    Unknown,
+}
+
+impl FileKind {
+    /// Sniffs the kind of a file from its extension, then from its shebang.
+    pub fn sniff(name: &ObjectName, text: &[u8]) -> Self {
+        match Self::from_name(name) {
+            Self::Unknown => Self::from_shebang(text),
+            kind => kind,
+        }
+    }
+
+    pub fn from_name(name: &ObjectName) -> Self {
+        let name = name.as_bytes();
+        let ext = match name.iter().rposition(|x| *x == b'.') {
+            Some(i) if i > 0 => &name[i + 1..],
+            _ => return Self::Unknown,
+        };
+        match ext {
+            b"java" => Self::Java,
+            b"c" | b"h" | b"cc" | b"cpp" | b"cxx" | b"hh" | b"hpp" | b"hxx" => Self::Cpp,
+            b"xml" | b"xsd" | b"xsl" | b"xslt" | b"svg" => Self::Xml,
+            // not tsx nor jsx, see file_sys::Ts
+            b"ts" | b"js" | b"mjs" | b"cjs" => Self::Ts,
+            b"scm" => Self::Query,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// eg. `#!/usr/bin/env node` or `#!/usr/bin/node`
+    fn from_shebang(text: &[u8]) -> Self {
+        let Some(line) = text.strip_prefix(b"#!") else {
+            return Self::Unknown;
+        };
+        let line = line.split(|x| *x == b'\n').next().unwrap_or_default();
+        let Ok(line) = std::str::from_utf8(line) else {
+            return Self::Unknown;
+        };
+        let mut words = line.split_whitespace();
+        let Some(mut interpreter) = words.next().and_then(|x| x.rsplit('/').next()) else {
+            return Self::Unknown;
+        };
+        if interpreter == "env" {
+            match words.find(|x| !x.starts_with('-')) {
+                Some(x) => interpreter = x,
+                None => return Self::Unknown,
+            }
+        }
+        match interpreter {
+            "node" | "nodejs" | "deno" | "bun" | "ts-node" => Self::Ts,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "maven")]
+pub(crate) fn handle_xml_file<'stores, NS>(
+    tree_gen: &mut hyper_ast_gen_ts_xml::legion::XmlTreeGen<'stores, TStore, NS>,
+    name: &ObjectName,
+    text: &'stores [u8],
+) -> Result<hyper_ast_gen_ts_xml::legion::FNode<NS::IdN>, ()>
+where
+    TStore: hyper_ast_gen_ts_xml::types::XmlEnabledTypeStore<
+        NS::NodeRef<'stores, hyper_ast_gen_ts_xml::types::TIdN<NS::IdN>>,
+    >,
+    NS: NodeInserter,
+{
+    let tree = match hyper_ast_gen_ts_xml::legion::XmlTreeGen::<TStore>::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
+
+#[cfg(feature = "tsquery")]
+pub(crate) fn handle_query_file<'stores, 'cache, NS>(
+    tree_gen: &mut hyper_ast_gen_ts_tsquery::legion::TsQueryTreeGen<'stores, 'cache, TStore, NS>,
+    name: &ObjectName,
+    text: &'stores [u8],
+) -> Result<hyper_ast_gen_ts_tsquery::legion::FNode<NS::IdN>, ()>
+where
+    TStore: hyper_ast_gen_ts_tsquery::types::TsQueryEnabledTypeStore<
+        NS::NodeRef<'stores, hyper_ast_gen_ts_tsquery::types::TIdN<NS::IdN>>,
+    >,
+    NS: NodeInserter,
+{
+    let tree = match hyper_ast_gen_ts_tsquery::legion::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
+
+/// Above this size in bytes, the content of an opaque file is not interned in the label store.
+pub const MAX_OPAQUE_TEXT_LEN: usize = 1 << 12;
+
+/// Stores a file as a single leaf labeled by its content,
+/// or by the oid of its blob if it is not utf8 or larger than [`MAX_OPAQUE_TEXT_LEN`].
+pub(crate) fn handle_opaque_file<NS: NodeInserter>(
+    stores: &mut hyper_ast::store::SimpleStores<TStore, NS>,
+    oid: git2::Oid,
+    text: &[u8],
+) -> (NS::IdN, DefaultMetrics) {
+    let (kind, label) = match std::str::from_utf8(text) {
+        Ok(text) if text.len() <= MAX_OPAQUE_TEXT_LEN => (Type::Text, text.to_owned()),
+        Ok(_) => (Type::LargeText, oid.to_string()),
+        Err(_) => (Type::Binary, oid.to_string()),
+    };
+    let hbuilder = hashed::Builder::new(Default::default(), &kind, &label, 1);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(label);
+    let eq = eq_node::<NS>(&kind, Some(&label), &[]);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        insertion.insert((kind, label, hashs, BloomSize::None))
+    };
+    let metrics = SubTreeMetrics {
+        size: 1,
+        height: 1,
+        hashs,
+        size_no_spaces: 1,
+    };
+    (node_id, metrics)
+}
+
+pub struct AnyAcc<IdN = NodeIdentifier> {
+    pub(crate) name: String,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<IdN>,
+    pub(crate) metrics: DefaultMetrics,
+}
+
+impl<IdN> AnyAcc<IdN> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+}
+
+impl<IdN> From<String> for AnyAcc<IdN> {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl<IdN> AnyAcc<IdN> {
+    pub(crate) fn push(&mut self, name: LabelIdentifier, full_node: (IdN, DefaultMetrics)) {
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1);
+    }
+}
+
+impl<IdN> hyper_ast::tree_gen::Accumulator for AnyAcc<IdN> {
+    type Node = (LabelIdentifier, (IdN, DefaultMetrics));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        AnyAcc::push(self, name, full_node)
+    }
+}
+
+impl<IdN> Accumulator for AnyAcc<IdN> {
+    type Unlabeled = (IdN, DefaultMetrics);
+}
+
+pub(crate) fn make<NS: NodeInserter>(
+    acc: AnyAcc<NS::IdN>,
+    stores: &mut hyper_ast::store::SimpleStores<TStore, NS>,
+) -> (NS::IdN, DefaultMetrics) {
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    let eq = eq_node::<NS>(&Type::Directory, Some(&label), &acc.children);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make any {} {}", &acc.name, acc.children.len());
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        insertion.insert((
+            Type::Directory,
+            label,
+            hashs,
+            compo::Size(size),
+            compo::Height(height),
+            compo::SizeNoSpaces(size_no_spaces),
+            CS(acc.children_names.into_boxed_slice()),
+            CS(acc.children.into_boxed_slice()),
+            BloomSize::Much,
+        ))
+    };
+
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+    (node_id, metrics)
+}
+
+pub(crate) fn eq_node<'a, NS: NodeInserter>(
+    kind: &'a Type,
+    label_id: Option<&'a LabelIdentifier>,
+    children: &'a [NS::IdN],
+) -> impl Fn(NS::Entry<'_>) -> bool + 'a {
+    move |x: NS::Entry<'_>| {
+        if x.get::<Type>().as_deref() != Some(kind) {
+            return false;
+        }
+        if x.get::<LabelIdentifier>().as_deref() != label_id {
+            return false;
+        }
+        match x.get::<CS<NS::IdN>>() {
+            Some(cs) => cs.0.as_ref() == children,
+            None => children.is_empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_file_kind() {
+        let sniff =
+            |name: &str, text: &str| FileKind::sniff(&name.as_bytes().into(), text.as_bytes());
+        assert_eq!(sniff("A.java", ""), FileKind::Java);
+        assert_eq!(sniff("main.cc", ""), FileKind::Cpp);
+        assert_eq!(sniff("pom.xml", ""), FileKind::Xml);
+        assert_eq!(sniff("index.mjs", ""), FileKind::Ts);
+        assert_eq!(sniff("highlights.scm", ""), FileKind::Query);
+        assert_eq!(sniff("README.md", "# title"), FileKind::Unknown);
+        assert_eq!(sniff(".gitignore", ""), FileKind::Unknown);
+        assert_eq!(sniff("cli", "#!/usr/bin/env node\nmain()"), FileKind::Ts);
+        assert_eq!(sniff("cli", "#!/usr/bin/env -S deno run\n"), FileKind::Ts);
+        assert_eq!(sniff("cli", "#!/usr/local/bin/node"), FileKind::Ts);
+        assert_eq!(sniff("build", "#!/bin/sh\nmake"), FileKind::Unknown);
+    }
+    #[test]
+    fn test_opaque_file_label() {
+        use hyper_ast::types::{LabelStore, Labeled};
+        let mut stores = crate::SimpleStores::default();
+        let mut opaque = |text: &[u8]| {
+            let oid = git2::Oid::hash_object(git2::ObjectType::Blob, text).unwrap();
+            let (id, _) = handle_opaque_file(&mut stores, oid, text);
+            let node = stores.node_store.resolve(id);
+            let kind = *node.get_component::<Type>().unwrap();
+            let label = stores.label_store.resolve(node.get_label_unchecked());
+            (kind, label.to_owned(), oid.to_string())
+        };
+        let (kind, label, _) = opaque(b"# title");
+        assert_eq!((kind, label.as_str()), (Type::Text, "# title"));
+        let large = "a".repeat(MAX_OPAQUE_TEXT_LEN + 1);
+        let (kind, label, oid) = opaque(large.as_bytes());
+        assert_eq!((kind, label), (Type::LargeText, oid));
+        let (kind, label, oid) = opaque(&[0xff, 0xfe]);
+        assert_eq!((kind, label), (Type::Binary, oid));
+    }
+    #[test]
+    fn test_opaque_file_in_hecs_store() {
+        use hyper_ast::types::{LabelStore, Labeled};
+        let mut stores = hyper_ast::store::SimpleStores {
+            label_store: hyper_ast::store::labels::LabelStore::new(),
+            type_store: TStore::default(),
+            node_store: hyper_ast::store::nodes::hecs::NodeStore::new(),
+        };
+        let text = b"# title";
+        let oid = git2::Oid::hash_object(git2::ObjectType::Blob, text).unwrap();
+        let (id, _) = handle_opaque_file(&mut stores, oid, text);
+        let (again, _) = handle_opaque_file(&mut stores, oid, text);
+        assert_eq!(id, again);
+        let node = stores.node_store.resolve(id);
+        let kind = *node.get_component::<&Type>().unwrap();
+        let label = stores.label_store.resolve(node.get_label_unchecked());
+        assert_eq!((kind, label), (Type::Text, "# title"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Node types of the files and directories that are not handled by a language specific generator.
+
+use std::fmt::Display;
+
+use hyper_ast::types::{AnyType, HyperType, LangRef, TypeTrait};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[repr(u16)]
+pub enum Type {
+    Directory,
+    /// an utf8 file, labeled with its content
+    Text,
+    /// a non utf8 file, labeled with the oid of its blob
+    Binary,
+    /// an utf8 file too large to be kept in the label store, labeled with the oid of its blob
+    LargeText,
+}
+
+const COUNT: u16 = 4;
+
+static S_T_L: &[Type] = &[Type::Directory, Type::Text, Type::Binary, Type::LargeText];
+
+impl Type {
+    pub fn resolve(t: u16) -> Self {
+        assert!(t < COUNT);
+        S_T_L[t as usize]
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Type::Directory => "Directory",
+            Type::Text => "Text",
+            Type::Binary => "Binary",
+            Type::LargeText => "LargeText",
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+pub struct Any;
+pub use Any as Lang;
+
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <Any as hyper_ast::types::Lang<Type>>::to_u16(*t);
+    let t = <Any as hyper_ast::types::Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
+
+impl LangRef<AnyType> for Any {
+    fn make(&self, t: u16) -> &'static AnyType {
+        panic!("{}", t)
+    }
+    fn to_u16(&self, t: AnyType) -> u16 {
+        let t = t.as_any().downcast_ref::<Type>().unwrap();
+        *t as u16
+    }
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Any>()
+    }
+}
+
+impl LangRef<Type> for Any {
+    fn make(&self, t: u16) -> &'static Type {
+        &S_T_L[t as usize]
+    }
+    fn to_u16(&self, t: Type) -> u16 {
+        t as u16
+    }
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Any>()
+    }
+}
+
+impl hyper_ast::types::Lang<Type> for Any {
+    fn make(t: u16) -> &'static Type {
+        Any.make(t)
+    }
+    fn to_u16(t: Type) -> u16 {
+        Any.to_u16(t)
+    }
+}
+
+impl HyperType for Type {
+    fn generic_eq(&self, other: &dyn HyperType) -> bool
+    where
+        Self: 'static + PartialEq + Sized,
+    {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |a| self == a)
+    }
+
+    fn is_directory(&self) -> bool {
+        self == &Type::Directory
+    }
+
+    fn is_file(&self) -> bool {
+        !self.is_directory()
+    }
+
+    fn is_spaces(&self) -> bool {
+        false
+    }
+
+    fn is_syntax(&self) -> bool {
+        false
+    }
+
+    fn as_shared(&self) -> hyper_ast::types::Shared {
+        hyper_ast::types::Shared::Other
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_static(&self) -> &'static dyn HyperType {
+        let t = <Any as hyper_ast::types::Lang<Type>>::to_u16(*self);
+        let t = <Any as hyper_ast::types::Lang<Type>>::make(t);
+        t
+    }
+
+    fn get_lang(&self) -> hyper_ast::types::LangWrapper<Self>
+    where
+        Self: Sized,
+    {
+        From::<&'static (dyn LangRef<Self>)>::from(&Any)
+    }
+}
+
+/// Opaque nodes do not have any syntactic structure.
+impl TypeTrait for Type {
+    type Lang = Any;
+
+    fn is_fork(&self) -> bool {
+        false
+    }
+
+    fn is_literal(&self) -> bool {
+        false
+    }
+
+    fn is_primitive(&self) -> bool {
+        false
+    }
+
+    fn is_type_declaration(&self) -> bool {
+        false
+    }
+
+    fn is_identifier(&self) -> bool {
+        false
+    }
+
+    fn is_instance_ref(&self) -> bool {
+        false
+    }
+
+    fn is_type_body(&self) -> bool {
+        false
+    }
+
+    fn is_value_member(&self) -> bool {
+        false
+    }
+
+    fn is_executable_member(&self) -> bool {
+        false
+    }
+
+    fn is_statement(&self) -> bool {
+        false
+    }
+
+    fn is_declarative_statement(&self) -> bool {
+        false
+    }
+
+    fn is_structural_statement(&self) -> bool {
+        false
+    }
+
+    fn is_block_related(&self) -> bool {
+        false
+    }
+
+    fn is_simple_statement(&self) -> bool {
+        false
+    }
+
+    fn is_local_declare(&self) -> bool {
+        false
+    }
+
+    fn is_parameter(&self) -> bool {
+        false
+    }
+
+    fn is_parameter_list(&self) -> bool {
+        false
+    }
+
+    fn is_argument_list(&self) -> bool {
+        false
+    }
+
+    fn is_expression(&self) -> bool {
+        false
+    }
+
+    fn is_comment(&self) -> bool {
+        false
+    }
+}
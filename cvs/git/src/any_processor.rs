@@ -0,0 +1,440 @@
+use std::{
+    iter::Peekable,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{store::defaults::NodeIdentifier, types::LabelStore};
+
+use crate::{
+    any::{AnyAcc, FileKind},
+    git::BasicGitObject,
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, ObjectName},
+    DefaultMetrics, Processor,
+};
+
+/// Processes every file of a repository whatever the build system,
+/// each file is given to the generator of its language or kept as opaque text.
+pub struct AnyProcessor<'a, 'b, 'c, const RMS: bool, const FFWD: bool, Acc> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<AnyProc>,
+}
+
+impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool, Acc: From<String>>
+    AnyProcessor<'a, 'b, 'c, RMS, FFWD, Acc>
+{
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>();
+        let handle =
+            <AnyProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<AnyAcc>
+    for AnyProcessor<'a, 'b, 'c, RMS, FFWD, AnyAcc>
+{
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                if let Some(s) = self.dir_path.peek() {
+                    if name
+                        .as_bytes()
+                        .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+                    {
+                        self.dir_path.next();
+                        self.stack.last_mut().expect("never empty").1.clear();
+                        let tree = self.repository.find_tree(oid).unwrap();
+                        let prepared = prepare_dir_exploration(tree);
+                        self.stack
+                            .push((oid, prepared, AnyAcc::new(name.try_into().unwrap())));
+                    }
+                    return;
+                }
+                if let Some(already) = self
+                    .prepro
+                    .processing_systems
+                    .mut_or_default::<AnyProcessorHolder>()
+                    .get_caches_mut()
+                    .object_map
+                    .get(&oid)
+                {
+                    // reinit already computed node for post order
+                    let full_node = already.clone();
+
+                    let w = &mut self.stack.last_mut().unwrap().2;
+                    let name = self.prepro.intern_object_name(name);
+                    assert!(!w.children_names.contains(&name));
+                    w.push(name, full_node);
+                    return;
+                }
+                log::debug!("any tree {:?}", name.try_str());
+                let tree = self.repository.find_tree(oid).unwrap();
+                let prepared = prepare_dir_exploration(tree);
+                self.stack
+                    .push((oid, prepared, AnyAcc::new(name.try_into().unwrap())));
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                self.prepro.help_handle_any_file(
+                    oid,
+                    &mut self.stack.last_mut().unwrap().2,
+                    &name,
+                    self.repository,
+                    self.handle,
+                );
+            }
+        }
+    }
+
+    fn post(&mut self, oid: Oid, acc: AnyAcc) -> Option<(NodeIdentifier, DefaultMetrics)> {
+        let name = acc.name.clone();
+        let full_node = crate::any::make(acc, self.prepro.main_stores_mut());
+        self.prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .insert(oid, full_node.clone());
+
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.children_names.contains(&name),
+                "{:?} {:?}",
+                w.children_names,
+                name
+            );
+            w.push(name, full_node);
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, AnyAcc)> {
+        &mut self.stack
+    }
+}
+
+impl RepositoryProcessor {
+    fn help_handle_any_file(
+        &mut self,
+        oid: Oid,
+        parent: &mut AnyAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<AnyProc>,
+    ) {
+        let full_node = self.handle_any_blob(oid, name, repository, parameters);
+        let name = self.intern_object_name(name);
+        assert!(!parent.children_names.contains(&name));
+        parent.push(name, full_node);
+    }
+
+    /// Dispatches the file to the generator of its language if it is enabled,
+    /// otherwise or if the generator fails the file is stored as opaque text.
+    fn handle_any_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<AnyProc>,
+    ) -> (NodeIdentifier, DefaultMetrics) {
+        let key = (oid, name.clone());
+        if let Some(already) = self
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>()
+            .with_parameters_mut(parameters.0)
+            .get_caches_mut()
+            .file_map
+            .get(&key)
+        {
+            return already.clone();
+        }
+        let blob = repository.find_blob(oid).unwrap();
+        let text = blob.content();
+        let kind = match std::str::from_utf8(text) {
+            Ok(_) => FileKind::sniff(name, text),
+            Err(_) => FileKind::Unknown,
+        };
+        log::info!("blob {:?} {:?} {:?}", name.try_str(), oid, kind);
+        let line_break = if text.contains(&b'\r') {
+            "\r\n".as_bytes().to_vec()
+        } else {
+            "\n".as_bytes().to_vec()
+        };
+        let parsed = match kind {
+            #[cfg(feature = "java")]
+            FileKind::Java => {
+                use crate::java_processor::{JavaProc, JavaProcessorHolder};
+                let h = self
+                    .processing_systems
+                    .mut_or_default::<JavaProcessorHolder>();
+                let handle = <JavaProc as crate::processing::erased::CommitProcExt>::register_param(
+                    h,
+                    crate::java_processor::Parameter,
+                );
+                self.handle_java_blob(oid, name, repository, handle)
+                    .ok()
+                    .map(|(x, _)| (x.compressed_node, x.metrics))
+            }
+            #[cfg(feature = "cpp")]
+            FileKind::Cpp => {
+                use crate::cpp_processor::{CppProc, CppProcessorHolder};
+                let h = self
+                    .processing_systems
+                    .mut_or_default::<CppProcessorHolder>();
+                let handle = <CppProc as crate::processing::erased::CommitProcExt>::register_param(
+                    h,
+                    crate::cpp_processor::Parameter,
+                );
+                self.handle_cpp_blob(oid, name, repository, handle)
+                    .ok()
+                    .map(|(x, _)| (x.compressed_node, x.metrics))
+            }
+            #[cfg(feature = "ts")]
+            FileKind::Ts => {
+                use crate::ts_processor::{TsProc, TsProcessorHolder};
+                let h = self
+                    .processing_systems
+                    .mut_or_default::<TsProcessorHolder>();
+                let handle = <TsProc as crate::processing::erased::CommitProcExt>::register_param(
+                    h,
+                    crate::ts_processor::Parameter,
+                );
+                self.handle_ts_blob(oid, name, repository, handle)
+                    .ok()
+                    .map(|x| (x.compressed_node, x.metrics))
+            }
+            #[cfg(feature = "maven")]
+            FileKind::Xml => crate::any::handle_xml_file(
+                &mut hyper_ast_gen_ts_xml::legion::XmlTreeGen {
+                    line_break,
+                    stores: &mut self.main_stores,
+                },
+                name,
+                text,
+            )
+            .ok()
+            .map(|x| (x.local.compressed_node, x.local.metrics)),
+            #[cfg(feature = "tsquery")]
+            FileKind::Query => crate::any::handle_query_file(
+                &mut hyper_ast_gen_ts_tsquery::legion::TsQueryTreeGen {
+                    line_break,
+                    stores: &mut self.main_stores,
+                    md_cache: &mut self
+                        .processing_systems
+                        .mut_or_default::<AnyProcessorHolder>()
+                        .with_parameters_mut(parameters.0)
+                        .get_caches_mut()
+                        .query_md_cache,
+                },
+                name,
+                text,
+            )
+            .ok()
+            .map(|x| (x.local.compressed_node, x.local.metrics)),
+            _ => None,
+        };
+        let full_node = parsed
+            .unwrap_or_else(|| crate::any::handle_opaque_file(&mut self.main_stores, oid, text));
+        self.processing_systems
+            .mut_or_default::<AnyProcessorHolder>()
+            .with_parameters_mut(parameters.0)
+            .get_caches_mut()
+            .file_map
+            .insert(key, full_node.clone());
+        full_node
+    }
+}
+
+pub(crate) fn prepare_dir_exploration(tree: git2::Tree) -> Vec<BasicGitObject> {
+    tree.iter()
+        .rev()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect()
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+#[derive(Default)]
+pub(crate) struct AnyProcessorHolder(Option<AnyProc>);
+pub(crate) struct AnyProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Any,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for AnyProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(AnyProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+struct PreparedAnyCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedAnyCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedAnyCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = AnyProcessor::<true, false, AnyAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<AnyProcessorHolder>();
+        let handle =
+            <AnyProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for AnyProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("see reason in the java proc")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedAnyCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> Result<(), crate::Commit> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for AnyProc {
+    type Holder = AnyProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for AnyProcessorHolder {
+    type Proc = AnyProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+impl CacheHolding<crate::processing::caches::Any> for AnyProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Any {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Any {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Any> for AnyProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Any {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Any {
+        &self.0.as_ref().unwrap().cache
+    }
+}
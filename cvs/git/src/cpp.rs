@@ -5,17 +5,26 @@ use crate::{
 
 use hyper_ast::{
     hashed::SyntaxNodeHashs,
-    store::defaults::{LabelIdentifier, NodeIdentifier},
+    nodes::HashSize,
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::NodeInserter,
+    },
     tree_gen::SubTreeMetrics,
 };
+use hyper_ast_gen_ts_cpp::types::{CppEnabledTypeStore, TIdN};
 
 use hyper_ast_gen_ts_cpp::legion as cpp_tree_gen;
 
-pub(crate) fn handle_cpp_file<'stores, 'cache, 'b: 'stores>(
-    tree_gen: &mut cpp_tree_gen::CppTreeGen<'stores, 'cache, TStore>,
+pub(crate) fn handle_cpp_file<'stores, 'cache, 'b: 'stores, NS>(
+    tree_gen: &mut cpp_tree_gen::CppTreeGen<'stores, 'cache, TStore, NS>,
     name: &ObjectName,
     text: &'b [u8],
-) -> Result<cpp_tree_gen::FNode, ()> {
+) -> Result<cpp_tree_gen::FNode<NS::IdN>, ()>
+where
+    TStore: CppEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
+{
     let tree = match cpp_tree_gen::CppTreeGen::<TStore>::tree_sitter_parse(text) {
         Ok(tree) => tree,
         Err(tree) => {
@@ -31,14 +40,14 @@ pub(crate) fn handle_cpp_file<'stores, 'cache, 'b: 'stores>(
     Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
 }
 
-pub struct CppAcc {
+pub struct CppAcc<IdN = NodeIdentifier> {
     pub(crate) name: String,
-    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) children: Vec<IdN>,
     pub(crate) children_names: Vec<LabelIdentifier>,
-    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
 }
 
-impl CppAcc {
+impl<IdN> CppAcc<IdN> {
     pub(crate) fn new(name: String) -> Self {
         Self {
             name,
@@ -50,13 +59,13 @@ impl CppAcc {
     }
 }
 
-impl From<String> for CppAcc {
+impl<IdN> From<String> for CppAcc<IdN> {
     fn from(name: String) -> Self {
         Self::new(name)
     }
 }
 
-impl CppAcc {
+impl<IdN> CppAcc<IdN> {
     // pub(crate) fn push_file(
     //     &mut self,
     //     name: LabelIdentifier,
@@ -87,7 +96,7 @@ impl CppAcc {
     pub(crate) fn push(
         &mut self,
         name: LabelIdentifier,
-        full_node: cpp_tree_gen::Local,
+        full_node: cpp_tree_gen::Local<IdN>,
         skiped_ana: bool,
     ) {
         self.children.push(full_node.compressed_node);
@@ -96,8 +105,8 @@ impl CppAcc {
     }
 }
 
-impl hyper_ast::tree_gen::Accumulator for CppAcc {
-    type Node = (LabelIdentifier, (cpp_tree_gen::Local, IsSkippedAna));
+impl<IdN> hyper_ast::tree_gen::Accumulator for CppAcc<IdN> {
+    type Node = (LabelIdentifier, (cpp_tree_gen::Local<IdN>, IsSkippedAna));
     fn push(&mut self, (name, (full_node, skiped_ana)): Self::Node) {
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
@@ -105,6 +114,6 @@ impl hyper_ast::tree_gen::Accumulator for CppAcc {
     }
 }
 
-impl Accumulator for CppAcc {
-    type Unlabeled = (cpp_tree_gen::Local, IsSkippedAna);
+impl<IdN> Accumulator for CppAcc<IdN> {
+    type Unlabeled = (cpp_tree_gen::Local<IdN>, IsSkippedAna);
 }
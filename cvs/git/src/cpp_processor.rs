@@ -3,9 +3,11 @@ use std::{iter::Peekable, path::Components};
 use git2::{Oid, Repository};
 use hyper_ast::{
     hashed::SyntaxNodeHashs,
+    nodes::HashSize,
     store::{
-        defaults::{LabelIdentifier, NodeIdentifier},
-        nodes::legion::PendingInsert,
+        defaults::LabelIdentifier,
+        nodes::{NodeInserter, PendingInsertion},
+        SimpleStores,
     },
     types::LabelStore,
 };
@@ -21,7 +23,7 @@ use crate::{
     make::MakeModuleAcc,
     preprocessed::{IsSkippedAna, RepositoryProcessor},
     processing::{erased::CommitProcExt, CacheHolding, InFiles, ObjectName},
-    Processor, SimpleStores,
+    Processor, TStore,
 };
 
 pub(crate) fn prepare_dir_exploration(tree: git2::Tree) -> Vec<BasicGitObject> {
@@ -206,6 +208,15 @@ impl crate::processing::erased::CommitProc for CppProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         self.commits.get(&commit_oid)
     }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for CppProc {
@@ -229,6 +240,10 @@ impl crate::processing::erased::ParametrizedCommitProc2 for CppProcessorHolder {
         assert_eq!(0, parameters.0);
         self.0.as_ref().unwrap()
     }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
 }
 impl CacheHolding<crate::processing::caches::Cpp> for CppProc {
     fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Cpp {
@@ -249,7 +264,7 @@ impl CacheHolding<crate::processing::caches::Cpp> for CppProcessorHolder {
 
 #[cfg(feature = "cpp")]
 impl RepositoryProcessor {
-    fn handle_cpp_blob(
+    pub(crate) fn handle_cpp_blob(
         &mut self,
         oid: Oid,
         name: &ObjectName,
@@ -342,7 +357,10 @@ impl RepositoryProcessor {
     }
 }
 
-fn make(acc: CppAcc, stores: &mut SimpleStores) -> cpp_gen::Local {
+fn make<NS: NodeInserter>(
+    acc: CppAcc<NS::IdN>,
+    stores: &mut SimpleStores<TStore, NS>,
+) -> cpp_gen::Local<NS::IdN> {
     use hyper_ast::{
         hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
         tree_gen::SubTreeMetrics,
@@ -358,7 +376,7 @@ fn make(acc: CppAcc, stores: &mut SimpleStores) -> cpp_gen::Local {
     let hashable = &hbuilder.most_discriminating();
     let label_id = label_store.get_or_insert(acc.name.clone());
 
-    let eq = eq_node(&Type::Directory, Some(&label_id), &acc.children);
+    let eq = eq_node::<_, NS>(&Type::Directory, Some(&label_id), &acc.children);
 
     let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -407,30 +425,29 @@ fn make(acc: CppAcc, stores: &mut SimpleStores) -> cpp_gen::Local {
     full_node
 }
 
-fn compress(
-    insertion: PendingInsert,
+fn compress<P: PendingInsertion>(
+    insertion: P,
     label_id: LabelIdentifier,
-    children: Vec<NodeIdentifier>,
+    children: Vec<P::IdN>,
     children_names: Vec<LabelIdentifier>,
     size: u32,
     height: u32,
     size_no_spaces: u32,
-    hashs: SyntaxNodeHashs<u32>,
+    hashs: SyntaxNodeHashs<HashSize>,
     skiped_ana: bool,
     ana: &cpp_gen::PartialAnalysis,
-) -> NodeIdentifier {
+) -> P::IdN {
     use hyper_ast::{
         filter::BloomSize,
-        store::nodes::legion::{compo, compo::CS, NodeStore},
+        store::nodes::compo::{self, CS},
     };
-    let vacant = insertion.vacant();
     macro_rules! insert {
         ( $c0:expr, $($c:expr),* $(,)? ) => {{
             let c = $c0;
             $(
                 let c = c.concat($c);
             )*
-            NodeStore::insert_after_prepare(vacant, c)
+            insertion.insert(c)
         }};
     }
     match children.len() {
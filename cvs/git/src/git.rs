@@ -123,11 +123,21 @@ impl Display for Url {
     }
 }
 
-pub fn fetch_repository<'a, T: TryInto<Url>, U: Into<PathBuf>>(url: T, path: U) -> Repository
+pub fn fetch_repository<'a, T: TryInto<Url>, U: Into<PathBuf>>(
+    url: T,
+    path: U,
+) -> Result<Repository, git2::Error>
 where
     <T as TryInto<Url>>::Error: std::fmt::Debug,
 {
-    let url: Url = url.try_into().unwrap();
+    let url: Url = url
+        .try_into()
+        .map_err(|e| git2::Error::from_str(&format!("invalid url: {:?}", e)))?;
+    try_fetch_repository(url, path)
+}
+
+/// Clones or updates the repository at `url` in `path` joined with the path of `url`
+pub fn try_fetch_repository(url: Url, path: impl Into<PathBuf>) -> Result<Repository, git2::Error> {
     let mut path: PathBuf = path.into();
     path.push(url.path.clone());
     // let url = &format!("{}{}", "https://github.com/", repo_name);
@@ -143,33 +153,97 @@ where
 
     fo.remote_callbacks(callbacks);
 
-    let repository = up_to_date_repo(&path, fo, url);
-    repository
+    up_to_date_repo(&path, fo, url)
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub enum Forge {
     Github,
     Gitlab,
+    /// a self-hosted forge, given by the url prefix of its repositories,
+    /// e.g. `https://git.example.com/`
+    Url(String),
+    /// a directory of local repositories or mirrors,
+    /// repositories are found in `<dir>/<user>/<name>` and are not cloned
+    Local(PathBuf),
 }
 
+/// Only accepts github, gitlab and the forges given by [`allowed_forges`],
+/// any other url or directory would let users clone anything on the server
 impl std::str::FromStr for Forge {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "github.com" => Self::Github,
-            "gitlab.com" => Self::Gitlab,
-            x => return Err(format!("'{}' is not an authorize forge", x)),
+        Self::parse_allowed(s, &allowed_forges())
+    }
+}
+
+/// Self-hosted forges and directories of local repositories that can be parsed as a [`Forge`],
+/// given as a comma separated list in the `HYPERAST_ALLOWED_FORGES` environment variable,
+/// e.g. `https://git.example.com/,file:///srv/mirrors`
+pub fn allowed_forges() -> Vec<Forge> {
+    let Some(allowed) = std::env::var_os("HYPERAST_ALLOWED_FORGES") else {
+        return vec![];
+    };
+    let allowed = allowed.to_string_lossy();
+    allowed
+        .split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .filter_map(|x| match Forge::parse_any(x) {
+            Ok(forge) => Some(forge),
+            Err(e) => {
+                log::warn!("ignored allowed forge: {}", e);
+                None
+            }
         })
+        .collect()
+}
+
+/// inverse of the parsing of a [`Forge`]
+impl Display for Forge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Forge::Github => write!(f, "github.com"),
+            Forge::Gitlab => write!(f, "gitlab.com"),
+            Forge::Url(url) => write!(f, "{}", url.trim_end_matches('/')),
+            Forge::Local(dir) => write!(f, "file://{}", dir.display()),
+        }
     }
 }
 
 impl Forge {
-    fn url(&self) -> &str {
+    /// Parses a forge, accepting github, gitlab and the forges in `allowed`
+    pub fn parse_allowed(s: &str, allowed: &[Forge]) -> Result<Self, String> {
+        match Self::parse_any(s)? {
+            x @ (Forge::Github | Forge::Gitlab) => Ok(x),
+            x if allowed.contains(&x) => Ok(x),
+            _ => Err(format!("'{}' is not an authorize forge", s)),
+        }
+    }
+
+    fn parse_any(s: &str) -> Result<Self, String> {
+        Ok(match s {
+            "github.com" | "github" => Self::Github,
+            "gitlab.com" | "gitlab" => Self::Gitlab,
+            x if x.starts_with("file://") => {
+                let dir = x["file://".len()..].trim_end_matches('/');
+                Self::Local(dir.into())
+            }
+            x if x.contains("://") => {
+                let x = x.trim_end_matches('/');
+                Self::Url(format!("{}/", x))
+            }
+            x => return Err(format!("'{}' is not an authorize forge", x)),
+        })
+    }
+
+    fn url(&self) -> String {
         match self {
-            Forge::Github => "https://github.com/",
-            Forge::Gitlab => "https://gitlab.com/",
+            Forge::Github => "https://github.com/".to_string(),
+            Forge::Gitlab => "https://gitlab.com/".to_string(),
+            Forge::Url(url) => url.clone(),
+            Forge::Local(dir) => format!("file://{}/", dir.display()),
         }
     }
     pub fn repo(self, user: impl Into<String>, name: impl Into<String>) -> Repo {
@@ -183,6 +257,13 @@ impl Forge {
     }
 }
 
+/// Directory where remote repositories are cloned,
+/// can be changed with the `HYPERAST_REPOSITORIES_CACHE` environment variable
+pub fn cache_dir() -> PathBuf {
+    std::env::var_os("HYPERAST_REPOSITORIES_CACHE")
+        .map_or_else(|| "/tmp/hyperastgitresources/repo/".into(), PathBuf::from)
+}
+
 // TODO use `&'static str`s to derive with Copy
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub struct Repo {
@@ -195,10 +276,37 @@ impl Repo {
     pub fn url(&self) -> String {
         format!("{}{}/{}", self.forge.url(), self.user, self.name)
     }
-    pub fn fetch(&self) -> Repository {
+    fn parsed_url(&self) -> Result<Url, git2::Error> {
         let url = self.url();
-        let path = format!("{}", "/tmp/hyperastgitresources/repo/");
-        fetch_repository(url, path)
+        Url::try_from(url.clone())
+            .map_err(|_| git2::Error::from_str(&format!("'{}' is not a valid url", url)))
+    }
+    pub fn fetch(&self) -> Result<Repository, git2::Error> {
+        self.fetch_to(cache_dir())
+    }
+    /// Clones or updates the repository in `cache`,
+    /// repositories of a [`Forge::Local`] are opened in place
+    pub fn fetch_to(&self, cache: impl Into<PathBuf>) -> Result<Repository, git2::Error> {
+        // user and name are joined to paths
+        for x in [&self.user, &self.name] {
+            if x.is_empty() || x == "." || x == ".." || x.contains(['/', '\\']) {
+                return Err(git2::Error::from_str(&format!(
+                    "'{}' is not a valid user or repository name",
+                    x
+                )));
+            }
+        }
+        let mut cache: PathBuf = cache.into();
+        match &self.forge {
+            Forge::Local(dir) => Repository::open(dir.join(&self.user).join(&self.name)),
+            Forge::Github | Forge::Gitlab => try_fetch_repository(self.parsed_url()?, cache),
+            Forge::Url(_) => {
+                let url = self.parsed_url()?;
+                // avoid mixing repositories of different forges
+                cache.push(&url.domain);
+                try_fetch_repository(url, cache)
+            }
+        }
     }
 }
 
@@ -211,49 +319,46 @@ impl Display for Repo {
 impl std::str::FromStr for Repo {
     type Err = String;
 
+    /// parses `<forge>/<user>/<name>`, see the parsing of [`Forge`] for the accepted forges
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (forge, repo) = s
-            .split_once("/")
-            .ok_or("give a valid repository address without 'https://' and '.git'")?;
-        let (user, name) = repo
-            .split_once("/")
-            .ok_or("give a valid repository address without 'https://' and '.git'")?;
-        let forge = forge.parse()?;
-        if name.contains("/") {
-            return Err(format!(
-                "{} should not contain anymore '/' give a valid repository address",
-                name
-            ));
+        let mut it = s.rsplitn(3, "/");
+        let (Some(name), Some(user), Some(forge)) = (it.next(), it.next(), it.next()) else {
+            return Err("give a valid repository address without 'https://' and '.git'".into());
+        };
+        if forge.is_empty() || user.is_empty() || name.is_empty() {
+            return Err(format!("{} is not a valid repository address", s));
         }
+        let forge = forge.parse()?;
         let user = user.into();
         let name = name.into();
         Ok(Self { forge, user, name })
     }
 }
 
-pub fn fetch_github_repository(repo_name: &str) -> Repository {
+pub fn fetch_github_repository(repo_name: &str) -> Result<Repository, git2::Error> {
     let url = format!("{}{}", "https://github.com/", repo_name);
-    let path = format!("{}", "/tmp/hyperastgitresources/repo/");
-    fetch_repository(url, path)
+    fetch_repository(url, cache_dir())
 }
 
 /// avoid mixing providers
-pub fn up_to_date_repo(path: &Path, mut fo: git2::FetchOptions, url: Url) -> Repository {
+pub fn up_to_date_repo(
+    path: &Path,
+    mut fo: git2::FetchOptions,
+    url: Url,
+) -> Result<Repository, git2::Error> {
     if path.join(".git").exists() {
-        let repository = match Repository::open(path) {
-            Ok(repo) => repo,
-            Err(e) => panic!("failed to open: {}", e),
-        };
+        let repository = Repository::open(path)?;
         log::info!("fetch: {:?}", path);
+        // NOTE the configured refspecs, not all repositories have a main branch
         repository
-            .find_remote("origin")
-            .unwrap()
-            .fetch(&["main"], Some(&mut fo), None)
-            .unwrap_or_else(|e| log::error!("{}", e));
-
-        repository
+            .find_remote("origin")?
+            .fetch(&[] as &[&str], Some(&mut fo), None)?;
+        Ok(repository)
     } else if path.exists() && path.read_dir().map_or(true, |mut x| x.next().is_some()) {
-        todo!()
+        Err(git2::Error::from_str(&format!(
+            "{:?} is not empty and is not a repository",
+            path
+        )))
     } else {
         let mut builder = git2::build::RepoBuilder::new();
 
@@ -262,18 +367,12 @@ pub fn up_to_date_repo(path: &Path, mut fo: git2::FetchOptions, url: Url) -> Rep
         builder.fetch_options(fo);
 
         log::info!("clone {} in {:?}", url, path);
-        let repository = match builder.clone(&url.to_string(), path.join(".git").as_path()) {
-            Ok(repo) => repo,
-            Err(e) if e.code() == git2::ErrorCode::Locked => match builder
-                .clone(&url.to_string(), path.join(".git").as_path())
-            {
-                Ok(repo) => repo,
-                Err(e) if e.code() == git2::ErrorCode::Locked => panic!("failed to clone: {}", e),
-                Err(e) => panic!("failed to clone: {}", e),
-            },
-            Err(e) => panic!("failed to clone: {}", e),
-        };
-        repository
+        match builder.clone(&url.to_string(), path.join(".git").as_path()) {
+            Err(e) if e.code() == git2::ErrorCode::Locked => {
+                builder.clone(&url.to_string(), path.join(".git").as_path())
+            }
+            r => r,
+        }
     }
 }
 
@@ -521,3 +620,45 @@ fn own(r: &[u8]) -> Result<String, git2::Error> {
     let r = r.map_err(|x| git2::Error::from_str(&x.to_string()));
     r.map(|s| s.to_string())
 }
+
+#[test]
+fn test_parse_repo() {
+    let repo: Repo = "github.com/INRIA/spoon".parse().unwrap();
+    assert_eq!(repo, Forge::Github.repo("INRIA", "spoon"));
+    let allowed = [
+        Forge::Url("https://git.example.com/".into()),
+        Forge::Local("/srv/mirrors".into()),
+    ];
+    let url = Forge::parse_allowed("https://git.example.com", &allowed).unwrap();
+    assert_eq!(url, allowed[0]);
+    assert_eq!(
+        url.clone().repo("team", "project").url(),
+        "https://git.example.com/team/project"
+    );
+    let local = Forge::parse_allowed("file:///srv/mirrors/", &allowed).unwrap();
+    assert_eq!(local, allowed[1]);
+    for forge in [Forge::Github, url, local] {
+        assert_eq!(
+            Forge::parse_allowed(&forge.to_string(), &allowed),
+            Ok(forge)
+        );
+    }
+    // anything else could be used to make the server clone any repository
+    assert!(Forge::parse_allowed("https://other.example.com", &allowed).is_err());
+    assert!(Forge::parse_allowed("file:///", &allowed).is_err());
+    assert!(Forge::parse_allowed("file:///srv/mirrors/team", &allowed).is_err());
+    assert!("https://git.example.com/team/project"
+        .parse::<Repo>()
+        .is_err());
+}
+
+#[test]
+fn test_fetch_invalid_names() {
+    let cache = std::env::temp_dir();
+    for (user, name) in [("..", "x"), ("x", ".."), ("x/..", "x"), ("", "x")] {
+        let repo = Forge::Local("/srv/mirrors".into()).repo(user, name);
+        assert!(repo.fetch_to(&cache).is_err());
+        let repo = Forge::Github.repo(user, name);
+        assert!(repo.fetch_to(&cache).is_err());
+    }
+}
@@ -0,0 +1,646 @@
+use std::{
+    fmt::Debug,
+    path::{Component, Path, PathBuf},
+};
+
+use enumset::EnumSet;
+use hyper_ast::{
+    position::{TreePath, TreePathMut},
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::{legion::HashedNodeRef, NodeInserter},
+    },
+    tree_gen::SubTreeMetrics,
+    types::{IterableChildren, Tree, WithChildren},
+};
+use hyper_ast_gen_ts_java::{legion_with_refs as java_tree_gen, types::Type};
+
+use crate::{processing::ObjectName, Accumulator, DefaultMetrics, ParseErr, SimpleStores, TStore};
+
+const DEFAULT_SOURCE_DIRECTORY: &str = "src/main/java";
+const DEFAULT_TEST_SOURCE_DIRECTORY: &str = "src/test/java";
+
+/// There is no groovy nor kotlin generator, so build scripts are kept as opaque text in the HyperAST,
+/// their content is also scanned to extract the structure of the build.
+pub(crate) fn handle_gradle_file<NS: NodeInserter>(
+    stores: &mut hyper_ast::store::SimpleStores<TStore, NS>,
+    oid: git2::Oid,
+    name: &ObjectName,
+    text: &[u8],
+) -> Result<GradleFile<NS::IdN>, ParseErr> {
+    let script = Script::parse(name, &String::from_utf8_lossy(text));
+    let (compressed_node, metrics) = crate::any::handle_opaque_file(stores, oid, text);
+    Ok(GradleFile {
+        compressed_node,
+        metrics,
+        script,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct GradleFile<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
+    pub metrics: DefaultMetrics,
+    pub script: Script,
+}
+
+/// What a settings or build script tells about the structure of the build.
+///
+/// Scripts are not evaluated, only the usual declarations are recognized:
+/// `include`, `project(..).projectDir` and the `srcDir(s)` of java source sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    /// from a settings script, directories of the included projects relative to the root project
+    pub subprojects: Vec<String>,
+    /// from a build script, None if the main source set keeps its default directory
+    pub source_dirs: Option<Vec<String>>,
+    /// from a build script, None if the test source sets keep their default directory
+    pub test_source_dirs: Option<Vec<String>>,
+}
+
+impl Script {
+    pub fn parse(name: &ObjectName, text: &str) -> Self {
+        let name = name.as_bytes();
+        if name == b"settings.gradle" || name == b"settings.gradle.kts" {
+            Self::from_settings(text)
+        } else {
+            Self::from_build(text)
+        }
+    }
+
+    pub fn from_settings(text: &str) -> Self {
+        let mut included = vec![];
+        let mut project_dirs = vec![];
+        for stmt in statements(&tokenize(text)) {
+            match &stmt[..] {
+                [Token::Ident("include"), rest @ ..] => included.extend(strings(rest)),
+                // project(':a').projectDir = file('dir')
+                [Token::Ident("project"), Token::Punct('('), Token::Str(p), Token::Punct(')'), Token::Punct('.'), Token::Ident("projectDir"), Token::Punct('='), rest @ ..] => {
+                    if let Some(dir) = strings(rest).pop() {
+                        project_dirs.push((project_path(p), dir));
+                    }
+                }
+                _ => (),
+            }
+        }
+        let subprojects = included
+            .into_iter()
+            .map(|p| {
+                let p = project_path(&p);
+                match project_dirs.iter().rev().find(|(x, _)| x == &p) {
+                    Some((_, dir)) => dir.clone(),
+                    None => p,
+                }
+            })
+            .collect();
+        Self {
+            subprojects,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_build(text: &str) -> Self {
+        let mut r = Self::default();
+        let mut blocks: Vec<Vec<String>> = vec![];
+        let mut header = vec![];
+        for stmt in statements(&tokenize(text)) {
+            match &stmt[..] {
+                [Token::Punct('{')] => blocks.push(std::mem::take(&mut header)),
+                [Token::Punct('}')] => {
+                    blocks.pop();
+                    header.clear();
+                }
+                stmt => {
+                    let (path, rest) = access_path(stmt);
+                    if let Some(i) = path
+                        .iter()
+                        .position(|x| matches!(x.as_str(), "srcDir" | "srcDirs" | "setSrcDirs"))
+                    {
+                        let full: Vec<&str> = blocks
+                            .iter()
+                            .flatten()
+                            .chain(&path[..i])
+                            .map(|x| x.as_str())
+                            .collect();
+                        let replace =
+                            path[i] == "setSrcDirs" || rest.first() == Some(&Token::Punct('='));
+                        r.add_source_dirs(&full, replace, strings(rest));
+                    }
+                    header = path;
+                }
+            }
+        }
+        r
+    }
+
+    /// `path` leads to the `srcDir(s)` being configured, eg. `sourceSets.main.java`
+    fn add_source_dirs(&mut self, path: &[&str], replace: bool, dirs: Vec<String>) {
+        // configures other projects or other languages
+        if path
+            .iter()
+            .any(|x| matches!(*x, "subprojects" | "allprojects" | "project"))
+            || path.last() != Some(&"java")
+        {
+            return;
+        }
+        let source_set = match path.iter().position(|x| *x == "sourceSets") {
+            Some(i) if i + 2 < path.len() => path[i + 1],
+            _ if path.len() >= 2 => path[path.len() - 2],
+            _ => "main",
+        };
+        let (dirs_of_set, default) = if source_set == "test" || source_set.ends_with("Test") {
+            (&mut self.test_source_dirs, DEFAULT_TEST_SOURCE_DIRECTORY)
+        } else if source_set == "main" {
+            (&mut self.source_dirs, DEFAULT_SOURCE_DIRECTORY)
+        } else {
+            return;
+        };
+        if replace {
+            *dirs_of_set = Some(dirs);
+        } else {
+            dirs_of_set
+                .get_or_insert_with(|| vec![default.to_owned()])
+                .extend(dirs);
+        }
+    }
+}
+
+/// `:a:b` is in the directory `a/b` by default
+fn project_path(p: &str) -> String {
+    p.trim_start_matches(':').replace(':', "/")
+}
+
+/// The directories to explore from the directory of the project,
+/// the ones outside of it are not supported.
+pub(crate) fn module_paths(dirs: &[String]) -> Vec<PathBuf> {
+    dirs.iter().filter_map(|x| module_path(x)).collect()
+}
+
+fn module_path(path: &str) -> Option<PathBuf> {
+    let mut rest = path;
+    for prefix in ["$projectDir", "${projectDir}", "$rootDir", "${rootDir}"] {
+        rest = rest.strip_prefix(prefix).unwrap_or(rest);
+    }
+    let mut r = PathBuf::new();
+    for c in Path::new(rest.trim_start_matches('/')).components() {
+        match c {
+            Component::Normal(c) => r.push(c),
+            Component::CurDir => (),
+            _ => {
+                log::warn!("unsupported path outside of gradle project: {}", path);
+                return None;
+            }
+        }
+    }
+    if r.as_os_str().is_empty() {
+        None
+    } else {
+        Some(r)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Ident(&'a str),
+    /// the raw content of a string literal, without interpolation
+    Str(&'a str),
+    Punct(char),
+    NewLine,
+}
+
+/// Tokens common to groovy and kotlin, comments are skipped.
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut r = vec![];
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(x) = rest.strip_prefix("//") {
+            rest = x.find('\n').map_or("", |i| &x[i..]);
+        } else if let Some(x) = rest.strip_prefix("/*") {
+            rest = x.find("*/").map_or("", |i| &x[i + 2..]);
+        } else if c == '\n' {
+            r.push(Token::NewLine);
+            rest = &rest[1..];
+        } else if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c == '"' || c == '\'' {
+            let quote = if rest.starts_with("\"\"\"") {
+                "\"\"\""
+            } else if rest.starts_with("'''") {
+                "'''"
+            } else {
+                &rest[..1]
+            };
+            let x = &rest[quote.len()..];
+            let mut end = x.len();
+            let mut escaped = false;
+            for (i, c) in x.char_indices() {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if x[i..].starts_with(quote) {
+                    end = i;
+                    break;
+                }
+            }
+            r.push(Token::Str(&x[..end]));
+            rest = x.get(end + quote.len()..).unwrap_or_default();
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            r.push(Token::Ident(&rest[..end]));
+            rest = &rest[end..];
+        } else {
+            r.push(Token::Punct(c));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    r
+}
+
+/// Splits tokens at line breaks and semicolons, except inside parentheses, brackets or after a comma.
+/// Curly brackets are statements on their own, to follow the nesting of blocks.
+fn statements<'a, 'b>(tokens: &'b [Token<'a>]) -> Vec<&'b [Token<'a>]> {
+    let mut r = vec![];
+    let mut start = 0;
+    let mut depth = 0usize;
+    for (i, t) in tokens.iter().enumerate() {
+        match t {
+            Token::Punct('(' | '[') => depth += 1,
+            Token::Punct(')' | ']') => depth = depth.saturating_sub(1),
+            Token::Punct('{' | '}') => {
+                r.push(&tokens[start..i]);
+                r.push(&tokens[i..i + 1]);
+                start = i + 1;
+                depth = 0;
+            }
+            Token::NewLine | Token::Punct(';')
+                if depth == 0 && (i == 0 || tokens[i - 1] != Token::Punct(',')) =>
+            {
+                r.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    r.push(&tokens[start..]);
+    r.into_iter()
+        .map(|x| {
+            let start = x
+                .iter()
+                .position(|x| x != &Token::NewLine)
+                .unwrap_or(x.len());
+            &x[start..]
+        })
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+/// The dotted path at the start of a statement, eg. `sourceSets.main.java.srcDirs`,
+/// kotlin accessors such as `sourceSets["main"]` or `sourceSets.getByName("main")` are normalized.
+fn access_path<'a, 'b>(stmt: &'b [Token<'a>]) -> (Vec<String>, &'b [Token<'a>]) {
+    let mut path = vec![];
+    let mut i = 0;
+    loop {
+        match &stmt[i..] {
+            [Token::Ident("getByName" | "named" | "create" | "maybeCreate" | "getting"), Token::Punct('('), Token::Str(x), Token::Punct(')'), ..] =>
+            {
+                path.push(x.to_string());
+                i += 4;
+            }
+            [Token::Ident(x), ..] => {
+                path.push(x.to_string());
+                i += 1;
+            }
+            [Token::Punct('['), Token::Str(x), Token::Punct(']'), ..] if !path.is_empty() => {
+                path.push(x.to_string());
+                i += 3;
+            }
+            _ => break,
+        }
+        match &stmt[i..] {
+            [Token::Punct('.'), Token::Ident(_), ..] => i += 1,
+            [Token::Punct('['), ..] => (),
+            _ => break,
+        }
+    }
+    (path, &stmt[i..])
+}
+
+fn strings(tokens: &[Token]) -> Vec<String> {
+    tokens
+        .iter()
+        .filter_map(|x| match x {
+            Token::Str(x) => Some(x.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+#[derive(enumset::EnumSetType, Debug)]
+pub enum SemFlags {
+    IsGradleProject,
+    HoldMainFolder,
+    HoldTestFolder,
+    HoldGradleSubProject,
+}
+
+/// What the enclosing projects declare about a directory,
+/// ie. for subprojects and source sets, if they are this directory or the paths remaining inside it.
+///
+/// The same directory is processed differently depending on it,
+/// e.g. it is a subproject only if included by the settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ModuleContext {
+    pub(crate) submodules: (bool, Vec<PathBuf>),
+    pub(crate) source_directories: (bool, Vec<PathBuf>),
+    pub(crate) test_source_directories: (bool, Vec<PathBuf>),
+}
+
+pub struct GradleModuleAcc<IdN = NodeIdentifier> {
+    pub(crate) name: String,
+    /// what the enclosing projects declared about this directory
+    pub(crate) context: ModuleContext,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<IdN>,
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) sub_modules: Option<Vec<PathBuf>>,
+    pub(crate) main_dirs: Option<Vec<PathBuf>>,
+    pub(crate) test_dirs: Option<Vec<PathBuf>>,
+    pub(crate) status: EnumSet<SemFlags>,
+}
+
+impl<IdN> From<String> for GradleModuleAcc<IdN> {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl<IdN> GradleModuleAcc<IdN> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            context: Default::default(),
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+            sub_modules: None,
+            main_dirs: None,
+            test_dirs: None,
+            status: Default::default(),
+        }
+    }
+    pub(crate) fn with_content(
+        name: String,
+        sub_modules: Vec<PathBuf>,
+        main_dirs: Vec<PathBuf>,
+        test_dirs: Vec<PathBuf>,
+    ) -> Self {
+        let non_empty = |x: Vec<PathBuf>| if x.is_empty() { None } else { Some(x) };
+        Self {
+            sub_modules: non_empty(sub_modules),
+            main_dirs: non_empty(main_dirs),
+            test_dirs: non_empty(test_dirs),
+            ..Self::new(name)
+        }
+    }
+    /// A project included by the settings of an enclosing project,
+    /// its source sets are the default ones until its build script tells otherwise.
+    pub(crate) fn subproject(name: String, sub_modules: Vec<PathBuf>) -> Self {
+        let mut acc = Self::with_content(name, sub_modules, vec![], vec![]);
+        acc.set_default_dirs();
+        acc.status |= SemFlags::IsGradleProject;
+        acc
+    }
+    fn set_default_dirs(&mut self) {
+        if self.main_dirs.is_none() {
+            self.main_dirs = Some(vec![DEFAULT_SOURCE_DIRECTORY.into()]);
+        }
+        if self.test_dirs.is_none() {
+            self.test_dirs = Some(vec![DEFAULT_TEST_SOURCE_DIRECTORY.into()]);
+        }
+    }
+}
+
+impl<IdN> GradleModuleAcc<IdN> {
+    pub(crate) fn push_script(&mut self, name: LabelIdentifier, full_node: GradleFile<IdN>) {
+        self.status |= SemFlags::IsGradleProject;
+        assert!(!self.children_names.contains(&name));
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        let script = full_node.script;
+        if !script.subprojects.is_empty() {
+            self.sub_modules
+                .get_or_insert_with(Default::default)
+                .extend(module_paths(&script.subprojects));
+        }
+        if let Some(dirs) = &script.source_dirs {
+            self.main_dirs = Some(module_paths(dirs));
+        }
+        if let Some(dirs) = &script.test_source_dirs {
+            self.test_dirs = Some(module_paths(dirs));
+        }
+        self.set_default_dirs();
+        self.metrics.acc(full_node.metrics);
+    }
+    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (IdN, MD)) {
+        if full_node.1.status.contains(SemFlags::HoldGradleSubProject)
+            || full_node.1.status.contains(SemFlags::IsGradleProject)
+        {
+            self.status |= SemFlags::HoldGradleSubProject;
+        }
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+    pub(crate) fn push_source_directory(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: java_tree_gen::Local<IdN>,
+    ) {
+        self.status |= SemFlags::HoldMainFolder;
+        self.push_java_directory(name, full_node);
+    }
+    pub(crate) fn push_test_source_directory(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: java_tree_gen::Local<IdN>,
+    ) {
+        self.status |= SemFlags::HoldTestFolder;
+        self.push_java_directory(name, full_node);
+    }
+    fn push_java_directory(&mut self, name: LabelIdentifier, full_node: java_tree_gen::Local<IdN>) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(SubTreeMetrics {
+            hashs: full_node.metrics.hashs,
+            size: full_node.metrics.size,
+            height: full_node.metrics.height,
+            size_no_spaces: full_node.metrics.size_no_spaces,
+        });
+    }
+}
+
+impl<IdN> hyper_ast::tree_gen::Accumulator for GradleModuleAcc<IdN> {
+    type Node = (LabelIdentifier, (IdN, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        let s = full_node.1.status - SemFlags::IsGradleProject;
+        self.status |= s;
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+}
+
+impl<IdN> Accumulator for GradleModuleAcc<IdN> {
+    type Unlabeled = (IdN, MD);
+}
+
+/// Iterates over the paths to gradle projects, in pre-order
+pub struct IterGradleModules<'a, T: TreePath<NodeIdentifier>> {
+    stores: &'a SimpleStores,
+    path: T,
+    stack: Vec<(NodeIdentifier, u16, Option<Vec<NodeIdentifier>>)>,
+}
+
+impl<'a, T: TreePathMut<NodeIdentifier, u16> + Debug + Clone> Iterator
+    for IterGradleModules<'a, T>
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, offset, children) = self.stack.pop()?;
+            if let Some(children) = children {
+                if (offset as usize) < children.len() {
+                    let child = children[offset as usize];
+                    if offset == 0 {
+                        self.path.goto(child, offset);
+                    } else {
+                        self.path.inc(child);
+                    }
+                    self.stack.push((node, offset + 1, Some(children)));
+                    self.stack.push((child, 0, None));
+                } else {
+                    self.path.pop().expect("should not go higher than root");
+                }
+                continue;
+            }
+            let b = self.stores.node_store.resolve(node);
+            if Self::is_dead_end(&b) {
+                continue;
+            }
+            if b.has_children() {
+                let children = b.children().unwrap().iter_children().cloned().collect();
+                self.stack.push((node, 0, Some(children)));
+            }
+            if Self::is_matching(&b) {
+                return Some(self.path.clone());
+            }
+        }
+    }
+}
+
+impl<'a, T: TreePath<NodeIdentifier>> IterGradleModules<'a, T> {
+    pub fn new(stores: &'a SimpleStores, path: T, root: NodeIdentifier) -> Self {
+        let stack = vec![(root, 0, None)];
+        Self {
+            stores,
+            path,
+            stack,
+        }
+    }
+
+    fn flags(b: &HashedNodeRef<'a, NodeIdentifier>) -> EnumSet<SemFlags> {
+        b.get_component::<EnumSet<SemFlags>>()
+            .map_or(EnumSet::empty(), |x| *x)
+    }
+
+    fn is_dead_end(b: &HashedNodeRef<'a, NodeIdentifier>) -> bool {
+        b.get_component::<Type>() != Ok(&Type::GradleDirectory)
+            || !Self::flags(b).contains(SemFlags::IsGradleProject)
+                && !Self::flags(b).contains(SemFlags::HoldGradleSubProject)
+    }
+
+    fn is_matching(b: &HashedNodeRef<'a, NodeIdentifier>) -> bool {
+        Self::flags(b).contains(SemFlags::IsGradleProject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_script() {
+        let script = Script::from_settings(
+            r#"
+rootProject.name = 'services'
+// include ':commented'
+include ':api', ':core:model',
+    ':app'
+include("web")
+includeBuild 'build-logic'
+project(':app').projectDir = file('application')
+"#,
+        );
+        assert_eq!(
+            script.subprojects,
+            ["api", "core/model", "application", "web"]
+        );
+        assert_eq!(script.source_dirs, None);
+    }
+
+    #[test]
+    fn test_build_script() {
+        let script = Script::from_build(
+            r#"
+plugins { id 'java' }
+subprojects {
+    sourceSets.main.java.srcDirs = ['ignored']
+}
+sourceSets {
+    main {
+        java {
+            srcDirs = ['src/java', "$projectDir/gen"]
+        }
+        resources.srcDir 'res'
+    }
+    integrationTest { java.srcDir 'src/it/java' }
+}
+"#,
+        );
+        assert_eq!(
+            script.source_dirs.as_deref(),
+            Some(&["src/java".to_owned(), "$projectDir/gen".to_owned()][..])
+        );
+        assert_eq!(
+            script.test_source_dirs.as_deref(),
+            Some(&["src/test/java".to_owned(), "src/it/java".to_owned()][..])
+        );
+        assert_eq!(
+            module_paths(&script.source_dirs.unwrap()),
+            [PathBuf::from("src/java"), PathBuf::from("gen")]
+        );
+
+        let script = Script::from_build(
+            r#"sourceSets["test"].java.setSrcDirs(listOf("tests"))
+sourceSets.getByName("main") { java.srcDir("../shared") }"#,
+        );
+        assert_eq!(script.test_source_dirs, Some(vec!["tests".to_owned()]));
+        assert_eq!(
+            script.source_dirs,
+            Some(vec!["src/main/java".to_owned(), "../shared".to_owned()])
+        );
+        assert!(module_paths(&script.source_dirs.unwrap())
+            .iter()
+            .all(|x| !x.starts_with("..")));
+    }
+}
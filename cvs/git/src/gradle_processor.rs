@@ -0,0 +1,750 @@
+use std::{
+    iter::Peekable,
+    marker::PhantomData,
+    path::{Components, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    store::{
+        defaults::NodeIdentifier,
+        nodes::{ComponentsBuilder, NodeInserter, PendingInsertion},
+        SimpleStores,
+    },
+    tree_gen::Accumulator,
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_java::types::Type;
+
+use crate::{
+    git::BasicGitObject,
+    gradle::{GradleModuleAcc, ModuleContext, SemFlags, MD},
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    Processor, TStore,
+};
+
+/// RMS: Resursive Module Search
+/// FFWD: Fast ForWarD to java directories without looking at gradle stuff
+pub struct GradleProcessor<'a, 'b, 'c, const RMS: bool, const FFWD: bool, Acc> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleProc>,
+}
+
+impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool, Acc: From<String>>
+    GradleProcessor<'a, 'b, 'c, RMS, FFWD, Acc>
+{
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        mut dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>();
+        let handle =
+            <GradleProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, &mut dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<GradleModuleAcc>
+    for GradleProcessor<'a, 'b, 'c, RMS, FFWD, GradleModuleAcc>
+{
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                self.handle_tree_cached(name, oid);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if FFWD {
+                    return;
+                }
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                if crate::processing::file_sys::GradleScript::matches(&name) {
+                    if let Err(err) = self.prepro.handle_gradle_script(
+                        oid,
+                        &mut self.stack.last_mut().unwrap().2,
+                        name,
+                        &self.repository,
+                        self.handle.into(),
+                    ) {
+                        log::warn!("skipping gradle script {}: {:?}", oid, err);
+                    }
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: GradleModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        let name = acc.name.clone();
+        let context = acc.context.clone();
+        let full_node = make(acc, self.prepro.main_stores_mut());
+        self.prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .insert((oid, context), full_node.clone());
+        let name = self.prepro.intern_label(&name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.children_names.contains(&name),
+                "{:?} {:?}",
+                w.children_names,
+                name
+            );
+            if full_node.1.status.contains(SemFlags::IsGradleProject) {
+                w.push_submodule(name, full_node);
+            } else {
+                w.push((name, full_node));
+            }
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, GradleModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
+    GradleProcessor<'a, 'b, 'c, RMS, FFWD, GradleModuleAcc>
+{
+    fn handle_tree_cached(&mut self, name: ObjectName, oid: Oid) {
+        if let Some(s) = self.dir_path.peek() {
+            if name
+                .as_bytes()
+                .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+            {
+                self.dir_path.next();
+                self.stack.last_mut().expect("never empty").1.clear();
+                let tree = self.repository.find_tree(oid).unwrap();
+                let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
+                self.stack.push((
+                    oid,
+                    prepared,
+                    GradleModuleAcc::new(name.try_into().unwrap()),
+                ));
+            }
+            return;
+        }
+        let parent_acc = &mut self.stack.last_mut().unwrap().2;
+        let helper = GradleModuleHelper::from((parent_acc, &name));
+        if let Some(already) = self
+            .prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .get(&(oid, helper.context.clone()))
+        {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let w = &mut self.stack.last_mut().unwrap().2;
+            let name = self.prepro.intern_object_name(&name);
+            assert!(!w.children_names.contains(&name));
+            if full_node.1.status.contains(SemFlags::IsGradleProject) {
+                w.push_submodule(name, full_node);
+            } else {
+                w.push((name, full_node));
+            }
+            return;
+        }
+        log::debug!("gradle tree {:?}", name.try_str());
+        if FFWD {
+            let (name, (full_node, _)) = self.prepro.help_handle_java_folder(
+                &self.repository,
+                &mut self.dir_path,
+                oid,
+                &name,
+            );
+            let parent_acc = &mut self.stack.last_mut().unwrap().2;
+            assert!(!parent_acc.children_names.contains(&name));
+            parent_acc.push_source_directory(name, full_node);
+            return;
+        }
+        let context = &helper.context;
+        if context.source_directories.0 || context.test_source_directories.0 {
+            // handle as source dir
+            let (name, (full_node, _)) =
+                self.prepro
+                    .help_handle_java_folder(&self.repository, self.dir_path, oid, &name);
+            let parent_acc = &mut self.stack.last_mut().unwrap().2;
+            assert!(!parent_acc.children_names.contains(&name));
+            if context.source_directories.0 {
+                parent_acc.push_source_directory(name, full_node);
+            } else {
+                parent_acc.push_test_source_directory(name, full_node);
+            }
+        } else if context.submodules.0
+            || !context.submodules.1.is_empty()
+            || !context.source_directories.1.is_empty()
+            || !context.test_source_directories.1.is_empty()
+            || RMS
+        {
+            // a subproject included by the settings, a directory on the way to one,
+            // or a directory where to search for build scripts anyway
+            let tree = self.repository.find_tree(oid).unwrap();
+            let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
+            self.stack.push((oid, prepared, helper.into()));
+        }
+    }
+}
+
+pub(crate) fn make<NS: NodeInserter>(
+    acc: GradleModuleAcc<NS::IdN>,
+    stores: &mut SimpleStores<TStore, NS>,
+) -> (NS::IdN, MD) {
+    use hyper_ast::{
+        filter::BloomSize,
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        store::nodes::compo::{self, CS},
+        tree_gen::SubTreeMetrics,
+    };
+    use hyper_ast_gen_ts_java::legion_with_refs::eq_node;
+    let kind = Type::GradleDirectory;
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &kind, &acc.name, size_no_spaces);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    let eq = eq_node::<_, NS>(&kind, Some(&label), &acc.children);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make gradle {} {}", &acc.name, acc.children.len());
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        // NOTE use of dyn_builder, see maven_processor::make
+        let mut dyn_builder = NS::Builder::default();
+        dyn_builder.add(kind);
+        dyn_builder.add(hashs.clone());
+        dyn_builder.add(label);
+        dyn_builder.add(BloomSize::Much);
+        dyn_builder.add(compo::Size(size));
+        dyn_builder.add(compo::SizeNoSpaces(size_no_spaces));
+        dyn_builder.add(compo::Height(height));
+        dyn_builder.add(CS(acc.children_names.into_boxed_slice()));
+        dyn_builder.add(CS(acc.children.into_boxed_slice()));
+        if !acc.status.is_empty() {
+            dyn_builder.add(acc.status);
+        }
+        insertion.insert_built(dyn_builder)
+    };
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+    (
+        node_id,
+        MD {
+            metrics,
+            status: acc.status,
+        },
+    )
+}
+
+impl RepositoryProcessor {
+    fn handle_gradle_script(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut GradleModuleAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleFileProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::GradleScript>()
+            .handle2(oid, repository, &name, parameters, |_, n, t| {
+                crate::gradle::handle_gradle_file(&mut self.main_stores, oid, n, t)
+            })?;
+        let name = self.intern_object_name(&name);
+        assert!(!parent_acc.children_names.contains(&name));
+        parent_acc.push_script(name, x);
+        Ok(())
+    }
+}
+
+struct GradleModuleHelper {
+    name: String,
+    context: ModuleContext,
+}
+
+impl From<(&mut GradleModuleAcc, &ObjectName)> for GradleModuleHelper {
+    fn from((parent_acc, name): (&mut GradleModuleAcc, &ObjectName)) -> Self {
+        let process = |mut v: &mut Option<Vec<PathBuf>>| {
+            let mut v = drain_filter_strip(&mut v, name.as_bytes());
+            let c = v.extract_if(|x| x.components().next().is_none()).count();
+            (c > 0, v)
+        };
+        Self {
+            name: name.try_into().unwrap(),
+            context: ModuleContext {
+                submodules: process(&mut parent_acc.sub_modules),
+                source_directories: process(&mut parent_acc.main_dirs),
+                test_source_directories: process(&mut parent_acc.test_dirs),
+            },
+        }
+    }
+}
+
+impl From<GradleModuleHelper> for GradleModuleAcc {
+    fn from(helper: GradleModuleHelper) -> Self {
+        let context = helper.context.clone();
+        let mut acc = if context.submodules.0 {
+            // included by the settings, even without a build script
+            GradleModuleAcc::subproject(helper.name, context.submodules.1)
+        } else {
+            GradleModuleAcc::with_content(
+                helper.name,
+                context.submodules.1,
+                context.source_directories.1,
+                context.test_source_directories.1,
+            )
+        };
+        acc.context = helper.context;
+        acc
+    }
+}
+
+fn drain_filter_strip(v: &mut Option<Vec<PathBuf>>, name: &[u8]) -> Vec<PathBuf> {
+    let mut new_sub_modules = vec![];
+    let name = std::str::from_utf8(&name).unwrap();
+    if let Some(sub_modules) = v {
+        sub_modules
+            .extract_if(|x| x.starts_with(name))
+            .for_each(|x| {
+                let x = x.strip_prefix(name).unwrap().to_owned();
+                new_sub_modules.push(x);
+            });
+    }
+    new_sub_modules
+}
+
+/// build scripts are processed first,
+/// so that the source sets and subprojects they declare are known when exploring directories
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        // priority to the settings then to the build script
+        children_objects.sort_by_key(|x| match x {
+            BasicGitObject::Blob(_, n) if crate::processing::file_sys::GradleScript::matches(n) => {
+                if n.as_bytes().starts_with(b"settings.") {
+                    0
+                } else {
+                    1
+                }
+            }
+            _ => 2,
+        });
+        children_objects.reverse(); // we use it like a stack
+    }
+    children_objects
+}
+
+// # Gradle scripts
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleFileProc>
+{
+    fn from(
+        value: crate::processing::erased::ParametrizedCommitProcessor2Handle<GradleProc>,
+    ) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(value.0, PhantomData)
+    }
+}
+struct GradleFileProcessorHolder(Option<GradleFileProc>);
+impl Default for GradleFileProcessorHolder {
+    fn default() -> Self {
+        Self(Some(GradleFileProc {
+            parameter: Parameter,
+            cache: Default::default(),
+        }))
+    }
+}
+struct GradleFileProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::GradleFile,
+}
+impl crate::processing::erased::Parametrized for GradleFileProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(GradleFileProc {
+                    parameter: t,
+                    cache: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+impl crate::processing::erased::CommitProc for GradleFileProc {
+    fn process_root_tree(
+        &mut self,
+        repository: &git2::Repository,
+        tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        repository: &git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for GradleFileProc {
+    type Holder = GradleFileProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for GradleFileProcessorHolder {
+    type Proc = GradleFileProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+impl CacheHolding<crate::processing::caches::GradleFile> for GradleFileProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::GradleFile {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::GradleFile {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::GradleFile> for GradleFileProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::GradleFile {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::GradleFile {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+// # Gradle
+#[derive(Default)]
+pub struct GradleProcessorHolder(Option<GradleProc>);
+pub struct GradleProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Gradle,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for GradleProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(GradleProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+
+struct PreparedGradleCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedGradleCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedGradleCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = GradleProcessor::<true, false, GradleModuleAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<GradleProcessorHolder>();
+        let handle =
+            <GradleProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for GradleProc {
+    fn process_root_tree(
+        &mut self,
+        repository: &git2::Repository,
+        tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("cannot access retrieve RepositoryProcessor as a CommitProc is likely part of it, double mutable borrow RIP")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        oids: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedGradleCommitProc {
+            repository,
+            commit_builder: oids,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> Result<(), crate::Commit> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for GradleProc {
+    type Holder = GradleProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for GradleProcessorHolder {
+    type Proc = GradleProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+impl CacheHolding<crate::processing::caches::Gradle> for GradleProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Gradle {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Gradle {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Gradle> for GradleProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Gradle {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Gradle {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper_ast::position::{StructuralPosition, TreePath};
+
+    use super::*;
+    use crate::{
+        gradle::IterGradleModules,
+        preprocessed::CommitProcessor,
+        tests::repository::{Entry, TempRepository},
+    };
+
+    fn java_dir(file: &'static str) -> Entry {
+        use Entry::*;
+        let java = Dir(vec![(file, File("class A {}"))]);
+        Dir(vec![("main", Dir(vec![("java", java)]))])
+    }
+
+    fn build(repository: &TempRepository, settings: &'static str) -> Oid {
+        use Entry::*;
+        repository.tree(vec![
+            ("settings.gradle", File(settings)),
+            ("build.gradle", File("plugins { id 'java' }")),
+            ("api", Dir(vec![("src", java_dir("A.java"))])),
+            (
+                "core",
+                Dir(vec![("model", Dir(vec![("src", java_dir("M.java"))]))]),
+            ),
+            ("docs", Dir(vec![("src", java_dir("D.java"))])),
+        ])
+    }
+
+    fn process(
+        processor: &mut RepositoryProcessor,
+        repository: &TempRepository,
+        root: Oid,
+    ) -> NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let (root, _) = CommitProcessor::<crate::processing::file_sys::Gradle>::handle_module::<
+            false,
+        >(processor, &repository.repository, &mut dir_path, b"", root);
+        root
+    }
+
+    #[test]
+    fn test_process_included_subprojects() {
+        let repository = TempRepository::new("gradle_processor");
+        let all = build(&repository, "include ':api', ':core:model'");
+        let api_only = build(&repository, "include ':api'");
+        assert_eq!(
+            repository.oid(all, "core"),
+            repository.oid(api_only, "core")
+        );
+
+        let mut processor = RepositoryProcessor::default();
+        let all = process(&mut processor, &repository, all);
+        let api_only = process(&mut processor, &repository, api_only);
+
+        let get = |root, path: &str| {
+            path.split('/')
+                .try_fold(root, |d, name| processor.child_by_name(d, name))
+        };
+        assert!(get(all, "api/src/main/java/A.java").is_some());
+        assert!(get(all, "core/model/src/main/java/M.java").is_some());
+        // not included
+        assert!(get(all, "docs").is_none());
+        assert!(get(api_only, "api/src/main/java/A.java").is_some());
+        // not taken from the cache filled while processing the first root
+        assert!(get(api_only, "core").is_none());
+    }
+
+    #[test]
+    fn test_iter_gradle_modules() {
+        let repository = TempRepository::new("gradle_modules");
+        let root = build(&repository, "include ':api', ':core:model'");
+        let mut processor = RepositoryProcessor::default();
+        let root = process(&mut processor, &repository, root);
+
+        let stores = &processor.main_stores;
+        let modules: Vec<NodeIdentifier> =
+            IterGradleModules::new(stores, StructuralPosition::new(root), root)
+                .map(|x| *x.node().unwrap())
+                .collect();
+        let get = |path: &str| {
+            path.split('/')
+                .try_fold(root, |d, name| processor.child_by_name(d, name))
+                .unwrap()
+        };
+        // in pre-order, core only holds a subproject
+        assert_eq!(modules, [root, get("api"), get("core/model")]);
+    }
+}
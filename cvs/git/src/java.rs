@@ -3,20 +3,36 @@ use crate::{
     PROPAGATE_ERROR_ON_BAD_CST_NODE,
 };
 
+use std::sync::{Arc, Mutex};
+
 use hyper_ast::{
     hashed::SyntaxNodeHashs,
-    store::defaults::{LabelIdentifier, NodeIdentifier},
+    nodes::HashSize,
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        labels::{LabelInterner, LabelStore, SharedLabels},
+        nodes::NodeInserter,
+    },
     tree_gen::SubTreeMetrics,
 };
-use hyper_ast_gen_ts_java::{impact::partial_analysis::PartialAnalysis, types::Type};
+use hyper_ast_gen_ts_java::{
+    impact::partial_analysis::PartialAnalysis,
+    types::{JavaEnabledTypeStore, TIdN, Type},
+};
+use rayon::prelude::*;
 
 use hyper_ast_gen_ts_java::legion_with_refs as java_tree_gen;
 
-pub(crate) fn handle_java_file<'stores, 'cache, 'b: 'stores>(
-    tree_gen: &mut java_tree_gen::JavaTreeGen<'stores, 'cache, TStore>,
+pub(crate) fn handle_java_file<'stores, 'cache, 'b: 'stores, NS, LS>(
+    tree_gen: &mut java_tree_gen::JavaTreeGen<'stores, 'cache, TStore, NS, LS>,
     name: &ObjectName,
     text: &'b [u8],
-) -> Result<java_tree_gen::FNode, ()> {
+) -> Result<java_tree_gen::FNode<NS::IdN>, ()>
+where
+    TStore: JavaEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
+    LS: LabelInterner,
+{
     let tree = match java_tree_gen::JavaTreeGen::<TStore>::tree_sitter_parse(text) {
         Ok(tree) => tree,
         Err(tree) => {
@@ -32,16 +48,73 @@ pub(crate) fn handle_java_file<'stores, 'cache, 'b: 'stores>(
     Ok(tree_gen.generate_file(&name.as_bytes(), text, tree.walk()))
 }
 
-pub struct JavaAcc {
+/// Generates files on the rayon thread pool,
+/// their nodes are inserted through a [`hyper_ast::store::nodes::legion::concurrent::ConcurrentNodeStore`].
+///
+/// Files only depend on the stores and on `md_cache`, which are taken over for the time of the concurrent generation.
+/// Workers read `md_cache` and keep the metadata of new nodes in a local layer, merged into `md_cache` at the end.
+/// Results are in the order of `files`.
+pub(crate) fn handle_java_files(
+    stores: &mut crate::SimpleStores,
+    md_cache: &mut java_tree_gen::MDCache,
+    files: &[(ObjectName, Vec<u8>)],
+) -> Vec<Result<java_tree_gen::FNode, ()>> {
+    let node_store =
+        std::mem::take(&mut stores.node_store).into_concurrent(rayon::current_num_threads());
+    let label_store = Mutex::new(std::mem::replace(
+        &mut stores.label_store,
+        LabelStore::new(),
+    ));
+    let shared_md_cache = Arc::new(std::mem::take(md_cache));
+    let results: Vec<_> = files
+        .par_iter()
+        .map(|(name, text)| {
+            let worker = rayon::current_thread_index().unwrap_or(0);
+            let mut stores = hyper_ast::store::SimpleStores {
+                label_store: SharedLabels::new(&label_store),
+                type_store: TStore::default(),
+                node_store: node_store.worker(worker),
+            };
+            let line_break = if text.contains(&b'\r') {
+                "\r\n".as_bytes().to_vec()
+            } else {
+                "\n".as_bytes().to_vec()
+            };
+            let mut local_md_cache = java_tree_gen::MDCache::layered(shared_md_cache.clone());
+            let full_node = handle_java_file(
+                &mut java_tree_gen::JavaTreeGen {
+                    line_break,
+                    stores: &mut stores,
+                    md_cache: &mut local_md_cache,
+                },
+                name,
+                text,
+            );
+            (full_node, local_md_cache.into_local())
+        })
+        .collect();
+    stores.node_store = node_store.into_inner();
+    stores.label_store = label_store.into_inner().unwrap();
+    *md_cache = Arc::into_inner(shared_md_cache).expect("workers are done");
+    results
+        .into_iter()
+        .map(|(full_node, local_md_cache)| {
+            md_cache.extend(local_md_cache);
+            full_node
+        })
+        .collect()
+}
+
+pub struct JavaAcc<IdN = NodeIdentifier> {
     pub(crate) name: String,
-    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) children: Vec<IdN>,
     pub(crate) children_names: Vec<LabelIdentifier>,
-    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     pub(crate) skiped_ana: bool,
     pub(crate) ana: PartialAnalysis,
 }
 
-impl JavaAcc {
+impl<IdN> JavaAcc<IdN> {
     pub(crate) fn new(name: String) -> Self {
         Self {
             name,
@@ -55,13 +128,13 @@ impl JavaAcc {
     }
 }
 
-impl From<String> for JavaAcc {
+impl<IdN> From<String> for JavaAcc<IdN> {
     fn from(name: String) -> Self {
         Self::new(name)
     }
 }
 
-impl JavaAcc {
+impl<IdN> JavaAcc<IdN> {
     // pub(crate) fn push_file(
     //     &mut self,
     //     name: LabelIdentifier,
@@ -92,7 +165,7 @@ impl JavaAcc {
     pub(crate) fn push(
         &mut self,
         name: LabelIdentifier,
-        full_node: java_tree_gen::Local,
+        full_node: java_tree_gen::Local<IdN>,
         skiped_ana: bool,
     ) {
         self.children.push(full_node.compressed_node);
@@ -112,8 +185,8 @@ impl JavaAcc {
     }
 }
 
-impl hyper_ast::tree_gen::Accumulator for JavaAcc {
-    type Node = (LabelIdentifier, (java_tree_gen::Local, IsSkippedAna));
+impl<IdN> hyper_ast::tree_gen::Accumulator for JavaAcc<IdN> {
+    type Node = (LabelIdentifier, (java_tree_gen::Local<IdN>, IsSkippedAna));
     fn push(&mut self, (name, (full_node, skiped_ana)): Self::Node) {
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
@@ -132,6 +205,90 @@ impl hyper_ast::tree_gen::Accumulator for JavaAcc {
     }
 }
 
-impl Accumulator for JavaAcc {
-    type Unlabeled = (java_tree_gen::Local, IsSkippedAna);
+impl<IdN> Accumulator for JavaAcc<IdN> {
+    type Unlabeled = (java_tree_gen::Local<IdN>, IsSkippedAna);
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper_ast::store::nodes::DefaultNodeStore as NodeStore;
+
+    use super::*;
+
+    const BASE: &[u8] = br#"class Base {
+    int f(int x) { if (x > 0) { return x; } return -x; }
+}
+"#;
+
+    fn file(name: &str) -> (ObjectName, Vec<u8>) {
+        let text = format!(
+            r#"class {name} extends Base {{
+    int f(int x) {{ if (x > 0) {{ return x; }} return -x; }}
+    int g(int x) {{ if (x > 0) {{ return x; }} return -x; }}
+}}
+"#
+        );
+        (format!("{name}.java").as_bytes().into(), text.into_bytes())
+    }
+
+    fn stores() -> crate::SimpleStores {
+        hyper_ast::store::SimpleStores {
+            label_store: LabelStore::new(),
+            type_store: TStore::default(),
+            node_store: NodeStore::new(),
+        }
+    }
+
+    fn sequential(
+        stores: &mut crate::SimpleStores,
+        md_cache: &mut java_tree_gen::MDCache,
+        files: &[(ObjectName, Vec<u8>)],
+    ) -> Vec<java_tree_gen::FNode> {
+        files
+            .iter()
+            .map(|(name, text)| {
+                handle_java_file(
+                    &mut java_tree_gen::JavaTreeGen {
+                        line_break: "\n".as_bytes().to_vec(),
+                        stores: &mut *stores,
+                        md_cache: &mut *md_cache,
+                    },
+                    name,
+                    text,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// Files share subtrees between them and with a file generated beforehand,
+    /// the metadata of reused subtrees must not depend on the path taken.
+    #[test]
+    fn test_parallel_metadata_matches_sequential() {
+        let base = [(ObjectName::from("Base.java".as_bytes()), BASE.to_vec())];
+        let files = [file("A"), file("B"), file("C")];
+
+        let mut seq_stores = stores();
+        let mut seq_md_cache = Default::default();
+        sequential(&mut seq_stores, &mut seq_md_cache, &base);
+        let seq = sequential(&mut seq_stores, &mut seq_md_cache, &files);
+
+        let mut par_stores = stores();
+        let mut par_md_cache = Default::default();
+        sequential(&mut par_stores, &mut par_md_cache, &base);
+        let par = handle_java_files(&mut par_stores, &mut par_md_cache, &files);
+
+        assert_eq!(seq.len(), par.len());
+        for (seq, par) in seq.iter().zip(par) {
+            let (seq, par) = (&seq.local, par.unwrap().local);
+            assert_eq!(seq.metrics.size, par.metrics.size);
+            assert_eq!(seq.metrics.height, par.metrics.height);
+            assert_eq!(seq.metrics.size_no_spaces, par.metrics.size_no_spaces);
+            assert!(seq.metrics.hashs == par.metrics.hashs);
+            assert_eq!(format!("{:?}", seq.mcc), format!("{:?}", par.mcc));
+            assert_eq!(seq.ana.is_some(), par.ana.is_some());
+        }
+        // every generated node has its metadata, none went missing in a worker
+        assert_eq!(seq_md_cache.len(), par_md_cache.len());
+    }
 }
@@ -5,9 +5,11 @@ use hyper_ast::{
     cyclomatic::Mcc,
     filter::{Bloom, BloomSize, BF},
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+    nodes::HashSize,
     store::{
-        defaults::{LabelIdentifier, NodeIdentifier},
-        nodes::legion::{compo, compo::CS, NodeStore, PendingInsert},
+        defaults::LabelIdentifier,
+        nodes::{compo, compo::CS, NodeInserter, PendingInsertion},
+        SimpleStores,
     },
     tree_gen::SubTreeMetrics,
     types::LabelStore,
@@ -24,7 +26,7 @@ use crate::{
     java::JavaAcc,
     preprocessed::{IsSkippedAna, RepositoryProcessor},
     processing::{erased::CommitProcExt, CacheHolding, InFiles, ObjectName},
-    Processor, SimpleStores,
+    Processor, TStore,
 };
 
 pub(crate) fn prepare_dir_exploration(tree: git2::Tree) -> Vec<BasicGitObject> {
@@ -95,6 +97,7 @@ impl<'repo, 'b, 'd, 'c> Processor<JavaAcc> for JavaProcessor<'repo, 'b, 'd, 'c,
                 log::info!("tree {:?}", name.try_str());
                 let tree = self.repository.find_tree(oid).unwrap();
                 let prepared: Vec<BasicGitObject> = prepare_dir_exploration(tree);
+                self.prepro.handle_java_files(self.repository, &prepared);
                 self.stack
                     .push((oid, prepared, JavaAcc::new(name.try_into().unwrap())));
             }
@@ -147,7 +150,10 @@ impl<'repo, 'b, 'd, 'c> Processor<JavaAcc> for JavaProcessor<'repo, 'b, 'd, 'c,
     }
 }
 
-fn make(acc: JavaAcc, stores: &mut SimpleStores) -> hyper_ast_gen_ts_java::legion_with_refs::Local {
+fn make<NS: NodeInserter>(
+    acc: JavaAcc<NS::IdN>,
+    stores: &mut SimpleStores<TStore, NS>,
+) -> legion_with_refs::Local<NS::IdN> {
     let node_store = &mut stores.node_store;
     let label_store = &mut stores.label_store;
 
@@ -159,7 +165,7 @@ fn make(acc: JavaAcc, stores: &mut SimpleStores) -> hyper_ast_gen_ts_java::legio
     let hashable = &hbuilder.most_discriminating();
     let label_id = label_store.get_or_insert(acc.name.clone());
 
-    let eq = eq_node(&Type::Directory, Some(&label_id), &acc.children);
+    let eq = eq_node::<_, NS>(&Type::Directory, Some(&label_id), &acc.children);
 
     let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -251,26 +257,25 @@ fn make(acc: JavaAcc, stores: &mut SimpleStores) -> hyper_ast_gen_ts_java::legio
     full_node
 }
 
-fn compress(
-    insertion: PendingInsert,
+fn compress<P: PendingInsertion>(
+    insertion: P,
     label_id: LabelIdentifier,
-    children: Vec<NodeIdentifier>,
+    children: Vec<P::IdN>,
     children_names: Vec<LabelIdentifier>,
     size: u32,
     height: u32,
     size_no_spaces: u32,
-    hashs: SyntaxNodeHashs<u32>,
+    hashs: SyntaxNodeHashs<HashSize>,
     skiped_ana: bool,
     ana: &PartialAnalysis,
-) -> NodeIdentifier {
-    let vacant = insertion.vacant();
+) -> P::IdN {
     macro_rules! insert {
         ( $c0:expr, $($c:expr),* $(,)? ) => {{
             let c = $c0;
             $(
                 let c = c.concat($c);
             )*
-            NodeStore::insert_after_prepare(vacant, c)
+            insertion.insert(c)
         }};
     }
     // NOTE needed as macro because I only implemented BulkHasher and Bloom for u8 and u16
@@ -396,6 +401,15 @@ impl crate::processing::erased::CommitProc for JavaProc {
     ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
         todo!()
     }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for JavaProc {
@@ -419,6 +433,10 @@ impl crate::processing::erased::ParametrizedCommitProc2 for JavaProcessorHolder
         assert_eq!(0, parameters.0);
         self.0.as_ref().unwrap()
     }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
 }
 impl CacheHolding<crate::processing::caches::Java> for JavaProc {
     fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Java {
@@ -475,7 +493,7 @@ impl RepositoryProcessor {
         (name, full_node)
     }
 
-    fn handle_java_blob(
+    pub(crate) fn handle_java_blob(
         &mut self,
         oid: Oid,
         name: &ObjectName,
@@ -507,6 +525,49 @@ impl RepositoryProcessor {
             })
     }
 
+    /// Generates concurrently the java files of a directory that were not processed yet,
+    /// [`RepositoryProcessor::handle_java_blob`] then finds them in the cache.
+    fn handle_java_files(&mut self, repository: &Repository, objects: &[BasicGitObject]) {
+        let caches = self
+            .processing_systems
+            .mut_or_default::<JavaProcessorHolder>()
+            .get_caches_mut();
+        let todo: Vec<_> = objects
+            .iter()
+            .filter_map(|x| match x {
+                BasicGitObject::Blob(oid, name)
+                    if crate::processing::file_sys::Java::matches(name)
+                        && !caches.object_map.contains_key(&(*oid, name.clone())) =>
+                {
+                    Some((*oid, name))
+                }
+                _ => None,
+            })
+            .collect();
+        if todo.len() < 2 {
+            // not worth going through the thread pool, it is read and generated by handle_java_blob
+            return;
+        }
+        let (oids, files): (Vec<_>, Vec<_>) = todo
+            .into_iter()
+            .filter_map(|(oid, name)| {
+                log::info!("blob {:?} {:?}", name.try_str(), oid);
+                let blob = repository.find_blob(oid).ok()?;
+                std::str::from_utf8(blob.content()).ok()?;
+                Some((oid, (name.clone(), blob.content().to_vec())))
+            })
+            .unzip();
+        let full_nodes =
+            crate::java::handle_java_files(&mut self.main_stores, &mut caches.md_cache, &files);
+        for ((oid, (name, _)), full_node) in oids.into_iter().zip(files).zip(full_nodes) {
+            if let Ok(full_node) = full_node {
+                caches
+                    .object_map
+                    .insert((oid, name), (full_node.local, false));
+            }
+        }
+    }
+
     fn help_handle_java_file(
         &mut self,
         oid: Oid,
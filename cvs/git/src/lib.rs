@@ -1,25 +1,38 @@
 #![feature(test)]
 #![feature(extract_if)]
 pub mod allrefs;
+pub mod any;
 pub mod cpp;
 pub mod git;
+pub mod gradle;
 pub mod java;
 pub mod make;
 pub mod maven;
+pub mod npm;
+pub mod ts;
 
+#[cfg(feature = "any")]
+pub mod any_processor;
 #[cfg(feature = "cpp")]
 pub mod cpp_processor;
+#[cfg(feature = "gradle")]
+pub mod gradle_processor;
 #[cfg(feature = "java")]
 pub mod java_processor;
 #[cfg(feature = "make")]
 pub mod make_processor;
 #[cfg(feature = "maven")]
 pub mod maven_processor;
+#[cfg(feature = "npm")]
+pub mod npm_processor;
+#[cfg(feature = "ts")]
+pub mod ts_processor;
 pub mod multi_preprocessed;
 pub mod no_space;
 /// for now only tested on maven repositories with a pom in root.
 pub mod preprocessed;
 pub mod processing;
+pub mod snapshot;
 mod utils;
 
 #[cfg(test)]
@@ -27,7 +40,7 @@ pub mod tests;
 
 use git::BasicGitObject;
 use git2::Oid;
-use hyper_ast::{store::defaults::LabelIdentifier, utils::Bytes};
+use hyper_ast::{nodes::HashSize, store::defaults::LabelIdentifier, utils::Bytes};
 
 mod type_store;
 
@@ -42,7 +55,7 @@ pub(crate) const PROPAGATE_ERROR_ON_BAD_CST_NODE: bool = false;
 pub(crate) const MAX_REFS: u32 = 10000; //4096;
 
 pub(crate) type DefaultMetrics =
-    hyper_ast::tree_gen::SubTreeMetrics<hyper_ast::hashed::SyntaxNodeHashs<u32>>;
+    hyper_ast::tree_gen::SubTreeMetrics<hyper_ast::hashed::SyntaxNodeHashs<HashSize>>;
 
 pub struct Diffs();
 pub struct Impacts();
@@ -7,22 +7,32 @@ use std::{
 use hyper_ast::{
     hashed::SyntaxNodeHashs,
     position::{StructuralPosition, TreePath},
-    store::defaults::{LabelIdentifier, NodeIdentifier},
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::NodeInserter,
+    },
     tree_gen::SubTreeMetrics,
 };
 use hyper_ast_gen_ts_cpp::legion as cpp_tree_gen;
-use hyper_ast_gen_ts_xml::legion::XmlTreeGen;
+use hyper_ast_gen_ts_xml::{
+    legion::XmlTreeGen,
+    types::{TIdN, XmlEnabledTypeStore},
+};
 
 use crate::{
     processing::ObjectName, Accumulator, DefaultMetrics, SimpleStores, TStore,
     PROPAGATE_ERROR_ON_BAD_CST_NODE,
 };
 
-pub(crate) fn handle_makefile_file<'a>(
-    tree_gen: &mut XmlTreeGen<'a, TStore>,
+pub(crate) fn handle_makefile_file<'a, NS>(
+    tree_gen: &mut XmlTreeGen<'a, TStore, NS>,
     name: &ObjectName,
     text: &'a [u8],
-) -> Result<MakeFile, ()> {
+) -> Result<MakeFile<NS::IdN>, ()>
+where
+    TStore: XmlEnabledTypeStore<NS::NodeRef<'a, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
+{
     let tree = match XmlTreeGen::<TStore>::tree_sitter_parse(b"<proj></proj>") {
         Ok(tree) => tree,
         Err(tree) => {
@@ -51,8 +61,8 @@ pub(crate) fn handle_makefile_file<'a>(
 }
 
 #[derive(Debug, Clone)]
-pub struct MakeFile {
-    pub compressed_node: NodeIdentifier,
+pub struct MakeFile<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
     pub metrics: DefaultMetrics,
     submodules: Vec<String>,
     source_dirs: Vec<String>,
@@ -66,10 +76,10 @@ pub struct MD {
     pub(crate) ana: MakePartialAnalysis,
 }
 
-pub struct MakeModuleAcc {
+pub struct MakeModuleAcc<IdN = NodeIdentifier> {
     pub(crate) name: String,
     pub(crate) children_names: Vec<LabelIdentifier>,
-    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) children: Vec<IdN>,
     pub(crate) metrics: DefaultMetrics,
     pub(crate) ana: MakePartialAnalysis,
     pub(crate) sub_modules: Option<Vec<PathBuf>>,
@@ -77,7 +87,7 @@ pub struct MakeModuleAcc {
     pub(crate) test_dirs: Option<Vec<PathBuf>>,
 }
 
-impl From<String> for MakeModuleAcc {
+impl<IdN> From<String> for MakeModuleAcc<IdN> {
     fn from(name: String) -> Self {
         Self {
             name,
@@ -93,7 +103,7 @@ impl From<String> for MakeModuleAcc {
     }
 }
 
-impl MakeModuleAcc {
+impl<IdN> MakeModuleAcc<IdN> {
     pub(crate) fn new(name: String) -> Self {
         Self {
             name,
@@ -139,8 +149,8 @@ impl MakeModuleAcc {
     }
 }
 
-impl MakeModuleAcc {
-    pub(crate) fn push_makefile(&mut self, name: LabelIdentifier, full_node: MakeFile) {
+impl<IdN> MakeModuleAcc<IdN> {
+    pub(crate) fn push_makefile(&mut self, name: LabelIdentifier, full_node: MakeFile<IdN>) {
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
         self.main_dirs = Some(full_node.source_dirs.iter().map(|x| x.into()).collect());
@@ -156,7 +166,7 @@ impl MakeModuleAcc {
         // TODO
         // full_node.2.acc(&Type::Directory, &mut self.ana);
     }
-    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (IdN, MD)) {
         self.children.push(full_node.0);
         self.children_names.push(name);
         self.metrics.acc(full_node.1.metrics);
@@ -166,7 +176,7 @@ impl MakeModuleAcc {
     pub(crate) fn push_source_file(
         &mut self,
         name: LabelIdentifier,
-        full_node: cpp_tree_gen::Local,
+        full_node: cpp_tree_gen::Local<IdN>,
         skiped_ana: bool,
     ) {
         self.children.push(full_node.compressed_node);
@@ -181,7 +191,7 @@ impl MakeModuleAcc {
     pub(crate) fn push_source_directory(
         &mut self,
         name: LabelIdentifier,
-        full_node: cpp_tree_gen::Local,
+        full_node: cpp_tree_gen::Local<IdN>,
     ) {
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
@@ -197,7 +207,7 @@ impl MakeModuleAcc {
     pub(crate) fn push_test_source_directory(
         &mut self,
         name: LabelIdentifier,
-        full_node: cpp_tree_gen::Local,
+        full_node: cpp_tree_gen::Local<IdN>,
     ) {
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
@@ -371,8 +381,8 @@ pub struct IterMavenModules<'a, T: TreePath<NodeIdentifier>> {
 //     }
 // }
 
-impl hyper_ast::tree_gen::Accumulator for MakeModuleAcc {
-    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+impl<IdN> hyper_ast::tree_gen::Accumulator for MakeModuleAcc<IdN> {
+    type Node = (LabelIdentifier, (IdN, MD));
     fn push(&mut self, (name, full_node): Self::Node) {
         self.children.push(full_node.0);
         self.children_names.push(name);
@@ -389,8 +399,8 @@ impl hyper_ast::tree_gen::Accumulator for MakeModuleAcc {
     // }
 }
 
-impl Accumulator for MakeModuleAcc {
-    type Unlabeled = (NodeIdentifier, MD);
+impl<IdN> Accumulator for MakeModuleAcc<IdN> {
+    type Unlabeled = (IdN, MD);
     // fn push(
     //     &mut self,
     //     name: LabelIdentifier,
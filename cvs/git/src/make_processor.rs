@@ -9,7 +9,7 @@ use hyper_ast::{
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
     store::{
         defaults::NodeIdentifier,
-        nodes::legion::{compo, compo::CS, NodeStore},
+        nodes::{compo, compo::CS, NodeInserter, PendingInsertion},
     },
     tree_gen::SubTreeMetrics,
     types::LabelStore,
@@ -244,7 +244,10 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
     }
 }
 
-pub(crate) fn make(mut acc: MakeModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+pub(crate) fn make<NS: NodeInserter>(
+    mut acc: MakeModuleAcc<NS::IdN>,
+    stores: &mut hyper_ast::store::SimpleStores<crate::TStore, NS>,
+) -> (NS::IdN, MD) {
     let dir_hash: u32 = hash32(&Type::Directory); // FIXME should be MakeDirectory ?
     let hashs = acc.metrics.hashs;
     let size = acc.metrics.size + 1;
@@ -254,7 +257,7 @@ pub(crate) fn make(mut acc: MakeModuleAcc, stores: &mut SimpleStores) -> (NodeId
     let hashable = hbuilder.most_discriminating();
     let label = stores.label_store.get_or_insert(acc.name.clone());
 
-    let eq = eq_node(&Type::Directory, Some(&label), &acc.children);
+    let eq = eq_node::<_, NS>(&Type::Directory, Some(&label), &acc.children);
     let ana = {
         // let new_sub_modules = drain_filter_strip(&mut acc.sub_modules, b"..");
         // let new_main_dirs = drain_filter_strip(&mut acc.main_dirs, b"..");
@@ -283,22 +286,18 @@ pub(crate) fn make(mut acc: MakeModuleAcc, stores: &mut SimpleStores) -> (NodeId
         id
     } else {
         log::info!("make mm {} {}", &acc.name, acc.children.len());
-        let vacant = insertion.vacant();
         assert_eq!(acc.children_names.len(), acc.children.len());
-        NodeStore::insert_after_prepare(
-            vacant,
-            (
-                Type::Directory,
-                label,
-                hashs,
-                compo::Size(size),
-                compo::Height(height),
-                compo::SizeNoSpaces(size_no_spaces),
-                CS(acc.children_names.into_boxed_slice()), // TODO extract dir names
-                CS(acc.children.into_boxed_slice()),
-                BloomSize::Much,
-            ),
-        )
+        insertion.insert((
+            Type::Directory,
+            label,
+            hashs,
+            compo::Size(size),
+            compo::Height(height),
+            compo::SizeNoSpaces(size_no_spaces),
+            CS(acc.children_names.into_boxed_slice()), // TODO extract dir names
+            CS(acc.children.into_boxed_slice()),
+            BloomSize::Much,
+        ))
     };
 
     let metrics = SubTreeMetrics {
@@ -511,6 +510,14 @@ impl crate::processing::erased::CommitProc for MakefileProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         unimplemented!()
     }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.1.retain_nodes(alive);
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for MakefileProc {
@@ -534,6 +541,10 @@ impl crate::processing::erased::ParametrizedCommitProc2 for MakefileProcessorHol
         assert_eq!(0, parameters.0);
         self.0.as_ref().unwrap()
     }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
 }
 impl CacheHolding<crate::processing::caches::Makefile> for MakefileProc {
     fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Makefile {
@@ -643,6 +654,28 @@ impl crate::processing::erased::CommitProc for MakeProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         self.commits.get(&commit_oid)
     }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> Result<(), crate::Commit> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for MakeProc {
@@ -666,6 +699,10 @@ impl crate::processing::erased::ParametrizedCommitProc2 for MakeProcessorHolder
         assert_eq!(0, parameters.0);
         self.0.as_ref().unwrap()
     }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
 }
 impl CacheHolding<crate::processing::caches::Make> for MakeProc {
     fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Make {
@@ -1,7 +1,9 @@
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug},
     ops::AddAssign,
-    path::PathBuf,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
 };
 
 use enumset::EnumSet;
@@ -14,6 +16,7 @@ use hyper_ast::{
 use hyper_ast_gen_ts_java::legion_with_refs as java_tree_gen;
 use hyper_ast_gen_ts_xml::{legion::XmlTreeGen, types::Type};
 use num::ToPrimitive;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     processing::ObjectName, Accumulator, DefaultMetrics, ParseErr, SimpleStores, TStore,
@@ -40,24 +43,419 @@ pub(crate) fn handle_pom_file<'a>(
     let x = tree_gen
         .generate_file(name.as_bytes(), text, tree.walk())
         .local;
-    // TODO extract submodules, dependencies and directories. maybe even more ie. artefact id, ...
+    let project = Project::from_pom(tree_gen.stores, x.compressed_node);
     let x = POM {
         compressed_node: x.compressed_node,
         metrics: x.metrics,
-        submodules: vec![],
-        source_dirs: vec!["src/main/java".to_owned()],
-        test_source_dirs: vec!["src/test/java".to_owned()],
+        project: Arc::new(project),
     };
     Ok(x)
 }
 
 #[derive(Debug, Clone)]
-pub struct POM {
-    pub compressed_node: NodeIdentifier,
+pub struct POM<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
     pub metrics: DefaultMetrics,
-    submodules: Vec<String>,
-    source_dirs: Vec<String>,
-    test_source_dirs: Vec<String>,
+    /// as declared in the pom.xml, ie. without what is inherited from the parent project
+    pub project: Arc<Project>,
+}
+
+const DEFAULT_SOURCE_DIRECTORY: &str = "src/main/java";
+const DEFAULT_TEST_SOURCE_DIRECTORY: &str = "src/test/java";
+
+/// Identifies an artifact, missing parts are usually inherited from the parent project
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Coordinates {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Parent {
+    pub coordinates: Coordinates,
+    pub relative_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Dependency {
+    pub coordinates: Coordinates,
+    pub scope: Option<String>,
+    pub optional: bool,
+}
+
+/// Metadata of a maven project, as read from the XML HyperAST of its pom.xml
+///
+/// Only the default build is considered, ie. profiles are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Project {
+    pub coordinates: Coordinates,
+    pub packaging: Option<String>,
+    pub parent: Option<Parent>,
+    /// paths of the submodules, relative to the directory of the pom.xml
+    pub modules: Vec<String>,
+    pub source_directory: Option<String>,
+    pub test_source_directory: Option<String>,
+    pub dependencies: Vec<Dependency>,
+    /// from `<dependencyManagement>`, provides versions to the dependencies of the submodules
+    pub managed_dependencies: Vec<Dependency>,
+    pub properties: BTreeMap<String, String>,
+}
+
+impl Project {
+    pub fn from_pom(stores: &SimpleStores, root: NodeIdentifier) -> Self {
+        let pom = PomReader { stores };
+        let Some(project) = pom
+            .elements(root)
+            .into_iter()
+            .find(|x| pom.tag_name(*x) == Some("project"))
+        else {
+            return Self::default();
+        };
+        let mut r = Self {
+            coordinates: pom.coordinates(project),
+            packaging: pom.child_text(project, "packaging"),
+            ..Default::default()
+        };
+        for x in pom.elements(project) {
+            match pom.tag_name(x) {
+                Some("parent") => {
+                    r.parent = Some(Parent {
+                        coordinates: pom.coordinates(x),
+                        relative_path: pom.child_text(x, "relativePath"),
+                    })
+                }
+                Some("modules") => r.modules = pom.children_text(x, "module"),
+                Some("build") => {
+                    r.source_directory = pom.child_text(x, "sourceDirectory");
+                    r.test_source_directory = pom.child_text(x, "testSourceDirectory");
+                }
+                Some("dependencies") => r.dependencies = pom.dependencies(x),
+                Some("dependencyManagement") => {
+                    r.managed_dependencies = pom
+                        .child(x, "dependencies")
+                        .map(|x| pom.dependencies(x))
+                        .unwrap_or_default()
+                }
+                Some("properties") => {
+                    r.properties = pom
+                        .elements(x)
+                        .into_iter()
+                        .filter_map(|x| Some((pom.tag_name(x)?.to_owned(), pom.text(x))))
+                        .collect()
+                }
+                _ => (),
+            }
+        }
+        r
+    }
+
+    /// Tells if `parent` is the project referenced in the `<parent>` of this project.
+    pub fn is_child_of(&self, parent: &Project) -> bool {
+        let Some(p) = &self.parent else {
+            return false;
+        };
+        let c = &parent.coordinates;
+        p.coordinates.artifact_id.is_some()
+            && p.coordinates.artifact_id == c.artifact_id
+            && (p.coordinates.group_id.is_none()
+                || c.group_id.is_none()
+                || p.coordinates.group_id == c.group_id)
+    }
+
+    /// Completes this project with what it inherits from its parent.
+    ///
+    /// The group id and version default to the ones of the `<parent>`,
+    /// the rest is only inherited if `parent` is the referenced parent project.
+    pub fn inherit(&mut self, parent: Option<&Project>) {
+        if let Some(p) = &self.parent {
+            let c = &mut self.coordinates;
+            if c.group_id.is_none() {
+                c.group_id = p.coordinates.group_id.clone();
+            }
+            if c.version.is_none() {
+                c.version = p.coordinates.version.clone();
+            }
+        }
+        let Some(parent) = parent.filter(|x| self.is_child_of(x)) else {
+            return;
+        };
+        if self.source_directory.is_none() {
+            self.source_directory = parent.source_directory.clone();
+        }
+        if self.test_source_directory.is_none() {
+            self.test_source_directory = parent.test_source_directory.clone();
+        }
+        for (k, v) in &parent.properties {
+            if !self.properties.contains_key(k) {
+                self.properties.insert(k.clone(), v.clone());
+            }
+        }
+        let inherit_deps = |deps: &mut Vec<Dependency>, parent_deps: &[Dependency]| {
+            for d in parent_deps {
+                if !deps.iter().any(|x| x.is_same_artifact(d)) {
+                    deps.push(d.clone());
+                }
+            }
+        };
+        inherit_deps(&mut self.dependencies, &parent.dependencies);
+        inherit_deps(&mut self.managed_dependencies, &parent.managed_dependencies);
+    }
+
+    /// Replaces the `${...}` references to properties,
+    /// and gives their managed version to dependencies without one.
+    pub fn interpolated(&self) -> Self {
+        let coordinates = |c: &Coordinates| Coordinates {
+            group_id: c.group_id.as_deref().map(|x| self.interpolate(x)),
+            artifact_id: c.artifact_id.as_deref().map(|x| self.interpolate(x)),
+            version: c.version.as_deref().map(|x| self.interpolate(x)),
+        };
+        let dependency = |d: &Dependency| Dependency {
+            coordinates: coordinates(&d.coordinates),
+            scope: d.scope.as_deref().map(|x| self.interpolate(x)),
+            optional: d.optional,
+        };
+        let managed_dependencies: Vec<_> =
+            self.managed_dependencies.iter().map(dependency).collect();
+        let dependencies = self
+            .dependencies
+            .iter()
+            .map(dependency)
+            .map(|mut d| {
+                if d.coordinates.version.is_none() {
+                    d.coordinates.version = managed_dependencies
+                        .iter()
+                        .find(|x| x.is_same_artifact(&d))
+                        .and_then(|x| x.coordinates.version.clone());
+                }
+                d
+            })
+            .collect();
+        Self {
+            coordinates: coordinates(&self.coordinates),
+            packaging: self.packaging.clone(),
+            parent: self.parent.clone(),
+            modules: self.modules.iter().map(|x| self.interpolate(x)).collect(),
+            source_directory: Some(self.interpolate(self.source_directory())),
+            test_source_directory: Some(self.interpolate(self.test_source_directory())),
+            dependencies,
+            managed_dependencies,
+            properties: self.properties.clone(),
+        }
+    }
+
+    pub fn source_directory(&self) -> &str {
+        self.source_directory
+            .as_deref()
+            .unwrap_or(DEFAULT_SOURCE_DIRECTORY)
+    }
+
+    pub fn test_source_directory(&self) -> &str {
+        self.test_source_directory
+            .as_deref()
+            .unwrap_or(DEFAULT_TEST_SOURCE_DIRECTORY)
+    }
+
+    /// The directories to explore from the directory of the pom.xml,
+    /// the ones outside of it are not supported.
+    pub(crate) fn module_paths(&self) -> (Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>) {
+        let paths = |x: &mut dyn Iterator<Item = &str>| -> Vec<PathBuf> {
+            x.filter_map(|x| self.module_path(x)).collect()
+        };
+        (
+            paths(&mut self.modules.iter().map(|x| x.as_str())),
+            paths(&mut std::iter::once(self.source_directory())),
+            paths(&mut std::iter::once(self.test_source_directory())),
+        )
+    }
+
+    fn module_path(&self, path: &str) -> Option<PathBuf> {
+        let path = self.interpolate(path);
+        let mut r = PathBuf::new();
+        for c in Path::new(&path).components() {
+            match c {
+                Component::Normal(c) => r.push(c),
+                Component::CurDir => (),
+                _ => {
+                    log::warn!("unsupported path outside of maven module: {}", path);
+                    return None;
+                }
+            }
+        }
+        if r.as_os_str().is_empty() {
+            None
+        } else {
+            Some(r)
+        }
+    }
+
+    /// The base directory is interpolated as an empty path,
+    /// unknown properties are left as is.
+    fn interpolate(&self, s: &str) -> String {
+        let mut s = s.to_owned();
+        // properties can reference other properties
+        for _ in 0..8 {
+            let mut r = String::with_capacity(s.len());
+            let mut rest = s.as_str();
+            while let Some(i) = rest.find("${") {
+                let Some(j) = rest[i..].find('}') else {
+                    break;
+                };
+                r.push_str(&rest[..i]);
+                let key = &rest[i + 2..i + j];
+                let after = &rest[i + j + 1..];
+                if matches!(key, "basedir" | "project.basedir" | "pom.basedir") {
+                    // also remove the separator following the base directory
+                    rest = after.strip_prefix('/').unwrap_or(after);
+                    continue;
+                }
+                match self.property(key) {
+                    Some(v) => r.push_str(v),
+                    None => r.push_str(&rest[i..i + j + 1]),
+                }
+                rest = after;
+            }
+            r.push_str(rest);
+            if r == s {
+                break;
+            }
+            s = r;
+        }
+        s
+    }
+
+    fn property(&self, key: &str) -> Option<&str> {
+        let c = &self.coordinates;
+        // the pom. prefix is deprecated but still found in old projects
+        match key {
+            "project.groupId" | "pom.groupId" => c.group_id.as_deref(),
+            "project.artifactId" | "pom.artifactId" => c.artifact_id.as_deref(),
+            "project.version" | "pom.version" => c.version.as_deref(),
+            "project.parent.version" => self.parent.as_ref()?.coordinates.version.as_deref(),
+            key => self.properties.get(key).map(|x| x.as_str()),
+        }
+    }
+}
+
+impl Dependency {
+    fn is_same_artifact(&self, other: &Dependency) -> bool {
+        self.coordinates.group_id == other.coordinates.group_id
+            && self.coordinates.artifact_id == other.coordinates.artifact_id
+    }
+}
+
+/// Navigates the elements of a pom.xml in its XML HyperAST
+struct PomReader<'a> {
+    stores: &'a SimpleStores,
+}
+
+impl<'a> PomReader<'a> {
+    fn node(&self, id: NodeIdentifier) -> XmlNode<'a> {
+        self.stores
+            .node_store
+            .try_resolve_typed::<XmlIdN>(&id)
+            .unwrap()
+            .0
+    }
+
+    fn children(&self, id: NodeIdentifier) -> Vec<NodeIdentifier> {
+        let n = self.node(id);
+        n.children()
+            .map(|cs| cs.iter_children().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn children_of_type(&self, id: NodeIdentifier, t: Type) -> Vec<NodeIdentifier> {
+        self.children(id)
+            .into_iter()
+            .filter(|x| self.node(*x).get_type() == t)
+            .collect()
+    }
+
+    fn label(&self, id: NodeIdentifier) -> Option<&'a str> {
+        let n = self.node(id);
+        if n.has_label() {
+            Some(self.stores.label_store.resolve(n.get_label_unchecked()))
+        } else {
+            None
+        }
+    }
+
+    /// eg. `project` for `<project>...</project>`
+    fn tag_name(&self, element: NodeIdentifier) -> Option<&'a str> {
+        self.children(element)
+            .into_iter()
+            .find(|x| matches!(self.node(*x).get_type(), Type::STag | Type::EmptyElemTag))
+            .and_then(|x| self.children_of_type(x, Type::Name).into_iter().next())
+            .and_then(|x| self.label(x))
+    }
+
+    /// the elements nested in a document or in an element
+    fn elements(&self, id: NodeIdentifier) -> Vec<NodeIdentifier> {
+        let mut r = vec![];
+        for x in self.children(id) {
+            match self.node(x).get_type() {
+                Type::Element => r.push(x),
+                Type::Content => r.extend(self.children_of_type(x, Type::Element)),
+                _ => (),
+            }
+        }
+        r
+    }
+
+    fn child(&self, element: NodeIdentifier, name: &str) -> Option<NodeIdentifier> {
+        self.elements(element)
+            .into_iter()
+            .find(|x| self.tag_name(*x) == Some(name))
+    }
+
+    /// the trimmed text content of an element
+    fn text(&self, element: NodeIdentifier) -> String {
+        let mut r = String::new();
+        for x in self.children_of_type(element, Type::Content) {
+            for x in self.children(x) {
+                if matches!(self.node(x).get_type(), Type::CharData) {
+                    r.push_str(self.label(x).unwrap_or_default());
+                }
+            }
+        }
+        r.trim().to_owned()
+    }
+
+    fn child_text(&self, element: NodeIdentifier, name: &str) -> Option<String> {
+        self.child(element, name)
+            .map(|x| self.text(x))
+            .filter(|x| !x.is_empty())
+    }
+
+    fn children_text(&self, element: NodeIdentifier, name: &str) -> Vec<String> {
+        self.elements(element)
+            .into_iter()
+            .filter(|x| self.tag_name(*x) == Some(name))
+            .map(|x| self.text(x))
+            .filter(|x| !x.is_empty())
+            .collect()
+    }
+
+    fn coordinates(&self, element: NodeIdentifier) -> Coordinates {
+        Coordinates {
+            group_id: self.child_text(element, "groupId"),
+            artifact_id: self.child_text(element, "artifactId"),
+            version: self.child_text(element, "version"),
+        }
+    }
+
+    fn dependencies(&self, element: NodeIdentifier) -> Vec<Dependency> {
+        self.elements(element)
+            .into_iter()
+            .filter(|x| self.tag_name(*x) == Some("dependency"))
+            .map(|x| Dependency {
+                coordinates: self.coordinates(x),
+                scope: self.child_text(x, "scope"),
+                optional: self.child_text(x, "optional").as_deref() == Some("true"),
+            })
+            .collect()
+    }
 }
 
 pub struct IterMavenModules2<'a> {
@@ -198,21 +596,27 @@ pub struct MD {
     #[allow(unused)] // TODO needed for scalable module level reference analysis
     pub(crate) ana: MavenPartialAnalysis,
     pub(crate) status: EnumSet<SemFlags>,
+    /// None if the directory does not contain a pom.xml
+    pub project: Option<Arc<Project>>,
 }
 
-pub struct MavenModuleAcc {
+pub struct MavenModuleAcc<IdN = NodeIdentifier> {
     pub(crate) name: String,
     pub(crate) children_names: Vec<LabelIdentifier>,
-    pub(crate) children: Vec<NodeIdentifier>,
+    pub(crate) children: Vec<IdN>,
     pub(crate) metrics: DefaultMetrics, //java_tree_gen::SubTreeMetrics<SyntaxNodeHashs<u32>>,
     pub(crate) ana: MavenPartialAnalysis,
     pub(crate) sub_modules: Option<Vec<PathBuf>>,
     pub(crate) main_dirs: Option<Vec<PathBuf>>,
     pub(crate) test_dirs: Option<Vec<PathBuf>>,
     pub(crate) status: EnumSet<SemFlags>,
+    /// the project declared by the pom.xml of this directory
+    pub(crate) project: Option<Arc<Project>>,
+    /// the project of the closest enclosing module, completed with what it inherits itself
+    pub(crate) inherited: Option<Arc<Project>>,
 }
 
-impl From<String> for MavenModuleAcc {
+impl<IdN> From<String> for MavenModuleAcc<IdN> {
     fn from(name: String) -> Self {
         Self {
             name,
@@ -225,11 +629,13 @@ impl From<String> for MavenModuleAcc {
             main_dirs: None,
             test_dirs: None,
             status: Default::default(),
+            project: None,
+            inherited: None,
         }
     }
 }
 
-impl MavenModuleAcc {
+impl<IdN> MavenModuleAcc<IdN> {
     pub(crate) fn new(name: String) -> Self {
         Self {
             name,
@@ -242,10 +648,13 @@ impl MavenModuleAcc {
             main_dirs: None,
             test_dirs: None,
             status: Default::default(),
+            project: None,
+            inherited: None,
         }
     }
     pub(crate) fn with_content(
         name: String,
+        inherited: Option<Arc<Project>>,
         sub_modules: Vec<PathBuf>,
         main_dirs: Vec<PathBuf>,
         test_dirs: Vec<PathBuf>,
@@ -273,6 +682,8 @@ impl MavenModuleAcc {
                 Some(test_dirs)
             },
             status: Default::default(),
+            project: None,
+            inherited,
         }
     }
 }
@@ -285,26 +696,25 @@ pub enum SemFlags {
     HoldMavenSubModule,
 }
 
-impl MavenModuleAcc {
-    pub(crate) fn push_pom(&mut self, name: LabelIdentifier, full_node: POM) {
+impl<IdN> MavenModuleAcc<IdN> {
+    pub(crate) fn push_pom(&mut self, name: LabelIdentifier, full_node: POM<IdN>) {
         self.status |= SemFlags::IsMavenModule;
         assert!(!self.children_names.contains(&name));
         self.children.push(full_node.compressed_node);
         self.children_names.push(name);
-        self.main_dirs = Some(full_node.source_dirs.iter().map(|x| x.into()).collect());
-        self.test_dirs = Some(
-            full_node
-                .test_source_dirs
-                .iter()
-                .map(|x| x.into())
-                .collect(),
-        );
-        self.sub_modules = Some(full_node.submodules.iter().map(|x| x.into()).collect());
+        let mut project = full_node.project.as_ref().clone();
+        project.inherit(self.inherited.as_deref());
+        let (sub_modules, main_dirs, test_dirs) = project.module_paths();
+        self.sub_modules = Some(sub_modules);
+        self.main_dirs = Some(main_dirs);
+        self.test_dirs = Some(test_dirs);
+        self.project = Some(full_node.project);
+        self.inherited = Some(Arc::new(project));
         self.metrics.acc(full_node.metrics);
         // TODO
         // full_node.2.acc(&Type::Directory, &mut self.ana);
     }
-    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (NodeIdentifier, MD)) {
+    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (IdN, MD)) {
         if full_node.1.status.contains(SemFlags::HoldMavenSubModule)
             || full_node.1.status.contains(SemFlags::IsMavenModule)
         {
@@ -319,7 +729,7 @@ impl MavenModuleAcc {
     pub(crate) fn push_source_directory(
         &mut self,
         name: LabelIdentifier,
-        full_node: java_tree_gen::Local,
+        full_node: java_tree_gen::Local<IdN>,
     ) {
         self.status |= SemFlags::HoldMainFolder;
         self.children.push(full_node.compressed_node);
@@ -336,7 +746,7 @@ impl MavenModuleAcc {
     pub(crate) fn push_test_source_directory(
         &mut self,
         name: LabelIdentifier,
-        full_node: java_tree_gen::Local,
+        full_node: java_tree_gen::Local<IdN>,
     ) {
         self.status |= SemFlags::HoldTestFolder;
         self.children.push(full_node.compressed_node);
@@ -531,8 +941,8 @@ impl<'a, T: TreePath<NodeIdentifier>> IterMavenModules<'a, T> {
     }
 }
 
-impl hyper_ast::tree_gen::Accumulator for MavenModuleAcc {
-    type Node = (LabelIdentifier, (NodeIdentifier, MD));
+impl<IdN> hyper_ast::tree_gen::Accumulator for MavenModuleAcc<IdN> {
+    type Node = (LabelIdentifier, (IdN, MD));
     fn push(&mut self, (name, full_node): Self::Node) {
         let s = full_node.1.status - SemFlags::IsMavenModule;
         assert!(!s.contains(SemFlags::IsMavenModule));
@@ -552,8 +962,8 @@ impl hyper_ast::tree_gen::Accumulator for MavenModuleAcc {
     // }
 }
 
-impl Accumulator for MavenModuleAcc {
-    type Unlabeled = (NodeIdentifier, MD);
+impl<IdN> Accumulator for MavenModuleAcc<IdN> {
+    type Unlabeled = (IdN, MD);
     // fn push(
     //     &mut self,
     //     name: LabelIdentifier,
@@ -566,3 +976,96 @@ impl Accumulator for MavenModuleAcc {
     //     // full_node.2.acc(&Type::Directory, &mut self.ana);
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Project {
+        let mut stores = SimpleStores::default();
+        let mut tree_gen = XmlTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+        };
+        let pom =
+            handle_pom_file(&mut tree_gen, &"pom.xml".as_bytes().into(), text.as_bytes()).unwrap();
+        pom.project.as_ref().clone()
+    }
+
+    #[test]
+    fn test_project_from_pom() {
+        let parent = parse(
+            r#"<?xml version="1.0"?>
+<project>
+  <groupId>org.example</groupId>
+  <artifactId>parent</artifactId>
+  <version>1.0</version>
+  <packaging>pom</packaging>
+  <modules>
+    <module>core</module>
+    <module>./plugins/a/</module>
+    <module>../outside</module>
+  </modules>
+  <properties>
+    <junit.version>5.9.0</junit.version>
+    <!-- a comment -->
+    <src>${project.basedir}/src/java</src>
+  </properties>
+  <build>
+    <sourceDirectory>${src}</sourceDirectory>
+  </build>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>org.junit</groupId>
+        <artifactId>junit</artifactId>
+        <version>${junit.version}</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+</project>
+"#,
+        );
+        assert_eq!(parent.coordinates.artifact_id.as_deref(), Some("parent"));
+        assert_eq!(parent.packaging.as_deref(), Some("pom"));
+        assert_eq!(parent.modules, ["core", "./plugins/a/", "../outside"]);
+        assert_eq!(parent.source_directory.as_deref(), Some("${src}"));
+        assert_eq!(parent.test_source_directory, None);
+        assert_eq!(parent.managed_dependencies.len(), 1);
+        let (sub_modules, main_dirs, test_dirs) = parent.module_paths();
+        assert_eq!(
+            sub_modules,
+            [PathBuf::from("core"), PathBuf::from("plugins/a")]
+        );
+        assert_eq!(main_dirs, [PathBuf::from("src/java")]);
+        assert_eq!(test_dirs, [PathBuf::from("src/test/java")]);
+
+        let mut child = parse(
+            r#"<project>
+  <parent>
+    <groupId>org.example</groupId>
+    <artifactId>parent</artifactId>
+    <version>1.0</version>
+  </parent>
+  <artifactId>core</artifactId>
+  <dependencies>
+    <dependency>
+      <groupId>org.junit</groupId>
+      <artifactId>junit</artifactId>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+</project>
+"#,
+        );
+        assert!(child.is_child_of(&parent));
+        child.inherit(Some(&parent));
+        let child = child.interpolated();
+        assert_eq!(child.coordinates.group_id.as_deref(), Some("org.example"));
+        assert_eq!(child.coordinates.version.as_deref(), Some("1.0"));
+        assert_eq!(child.source_directory.as_deref(), Some("src/java"));
+        let junit = &child.dependencies[0];
+        assert_eq!(junit.coordinates.version.as_deref(), Some("5.9.0"));
+        assert_eq!(junit.scope.as_deref(), Some("test"));
+    }
+}
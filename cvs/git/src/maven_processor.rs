@@ -2,18 +2,27 @@ use std::{
     iter::Peekable,
     marker::PhantomData,
     path::{Components, PathBuf},
+    sync::Arc,
 };
 
 use git2::{Oid, Repository};
-use hyper_ast::{store::defaults::NodeIdentifier, tree_gen::Accumulator, types::LabelStore};
+use hyper_ast::{
+    store::{
+        defaults::NodeIdentifier,
+        nodes::{ComponentsBuilder, NodeInserter, PendingInsertion},
+        SimpleStores,
+    },
+    tree_gen::Accumulator,
+    types::LabelStore,
+};
 use hyper_ast_gen_ts_xml::types::Type;
 
 use crate::{
     git::{BasicGitObject, NamedObject, ObjectType, TypedObject},
-    maven::{MavenModuleAcc, MD},
+    maven::{MavenModuleAcc, Project, MD},
     preprocessed::RepositoryProcessor,
     processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
-    Processor, SimpleStores,
+    Processor, TStore,
 };
 
 /// RMS: Resursive Module Search
@@ -89,12 +98,13 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<MavenModuleAcc>
     fn post(&mut self, oid: Oid, acc: MavenModuleAcc) -> Option<(NodeIdentifier, MD)> {
         let name = acc.name.clone();
         let full_node = Self::make(acc, self.prepro.main_stores_mut());
+        let inherited = self.stack.last().and_then(|x| x.2.inherited.clone());
         self.prepro
             .processing_systems
             .mut_or_default::<MavenProcessorHolder>()
             .get_caches_mut()
             .object_map
-            .insert(oid, full_node.clone());
+            .insert((oid, inherited), full_node.clone());
         if name == "pac4j-kerberos" {
             dbg!(full_node.1.status);
         }
@@ -130,7 +140,7 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool> Processor<MavenModuleAcc>
 impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
     MavenProcessor<'a, 'b, 'c, RMS, FFWD, MavenModuleAcc>
 {
-    fn make(acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+    fn make(acc: MavenModuleAcc, stores: &mut crate::SimpleStores) -> (NodeIdentifier, MD) {
         make(acc, stores)
     }
 
@@ -151,13 +161,14 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
                 return;
             }
         }
+        let inherited = self.stack.last().unwrap().2.inherited.clone();
         if let Some(already) = self
             .prepro
             .processing_systems
             .mut_or_default::<MavenProcessorHolder>()
             .get_caches_mut()
             .object_map
-            .get(&oid)
+            .get(&(oid, inherited))
         {
             // reinit already computed node for post order
             let full_node = already.clone();
@@ -206,8 +217,7 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
                 parent_acc.push_test_source_directory(name, full_node);
             }
         }
-        // check if module or src/main/java or src/test/java
-        // TODO use maven pom.xml to find source_dir  and tests_dir ie. ignore resources, maybe also tests
+        // check if module or source directory declared in the pom.xml (by default src/main/java or src/test/java)
         // TODO maybe at some point try to handle maven modules and source dirs that reference parent directory in their path
 
         // TODO check it we can use more info from context and prepare analysis more specifically
@@ -234,11 +244,14 @@ impl<'a, 'b, 'c, const RMS: bool, const FFWD: bool>
     }
 }
 
-pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeIdentifier, MD) {
+pub(crate) fn make<NS: NodeInserter>(
+    mut acc: MavenModuleAcc<NS::IdN>,
+    stores: &mut SimpleStores<TStore, NS>,
+) -> (NS::IdN, MD) {
     use hyper_ast::{
         filter::BloomSize,
         hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
-        store::nodes::legion::{compo, compo::CS, NodeStore},
+        store::nodes::compo::{self, CS},
         tree_gen::SubTreeMetrics,
     };
     use hyper_ast_gen_ts_java::legion_with_refs::{eq_node, hash32};
@@ -251,7 +264,7 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
     let hashable = hbuilder.most_discriminating();
     let label = stores.label_store.get_or_insert(acc.name.clone());
 
-    let eq = eq_node(&Type::MavenDirectory, Some(&label), &acc.children);
+    let eq = eq_node::<_, NS>(&Type::MavenDirectory, Some(&label), &acc.children);
     let ana = {
         let new_sub_modules = drain_filter_strip(&mut acc.sub_modules, b"..");
         let new_main_dirs = drain_filter_strip(&mut acc.main_dirs, b"..");
@@ -274,23 +287,19 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
         id
     } else {
         log::info!("make mm {} {}", &acc.name, acc.children.len());
-        let vacant = insertion.vacant();
         assert_eq!(acc.children_names.len(), acc.children.len());
         if false {
-            NodeStore::insert_after_prepare(
-                vacant,
-                (
-                    Type::MavenDirectory,
-                    label,
-                    hashs,
-                    compo::Size(size),
-                    compo::Height(height),
-                    compo::SizeNoSpaces(size_no_spaces),
-                    CS(acc.children_names.into_boxed_slice()), // TODO extract dir names
-                    CS(acc.children.into_boxed_slice()),
-                    BloomSize::Much,
-                ),
-            )
+            insertion.insert((
+                Type::MavenDirectory,
+                label,
+                hashs,
+                compo::Size(size),
+                compo::Height(height),
+                compo::SizeNoSpaces(size_no_spaces),
+                CS(acc.children_names.into_boxed_slice()), // TODO extract dir names
+                CS(acc.children.into_boxed_slice()),
+                BloomSize::Much,
+            ))
         } else {
             // NOTE use of dyn_builder
             // TODO make it available through cargo feature or runtime config
@@ -301,8 +310,7 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
             // - checking some invariants when adding metadata,
             // - checking some invariants for indentifying data on debug builds
             // - tying up parts of accumulator (hyper_ast::tree_genBasicAccumulator) and builder (EntityBuilder).
-            let mut dyn_builder =
-                hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+            let mut dyn_builder = NS::Builder::default();
             dyn_builder.add(Type::MavenDirectory);
             dyn_builder.add(hashs.clone());
             dyn_builder.add(label);
@@ -315,7 +323,10 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
             if !acc.status.is_empty() {
                 dyn_builder.add(acc.status);
             }
-            NodeStore::insert_built_after_prepare(vacant, dyn_builder.build())
+            if let Some(project) = &acc.project {
+                dyn_builder.add(project.as_ref().clone());
+            }
+            insertion.insert_built(dyn_builder)
         }
     };
     let status = acc.status;
@@ -332,6 +343,7 @@ pub(crate) fn make(mut acc: MavenModuleAcc, stores: &mut SimpleStores) -> (NodeI
             metrics,
             ana,
             status,
+            project: acc.project,
         },
     );
     full_node
@@ -410,6 +422,7 @@ impl RepositoryProcessor {
 
 struct MavenModuleHelper {
     name: String,
+    inherited: Option<Arc<Project>>,
     submodules: (bool, Vec<PathBuf>),
     source_directories: (bool, Vec<PathBuf>),
     test_source_directories: (bool, Vec<PathBuf>),
@@ -424,6 +437,7 @@ impl From<(&mut MavenModuleAcc, &ObjectName)> for MavenModuleHelper {
         };
         Self {
             name: name.try_into().unwrap(),
+            inherited: parent_acc.inherited.clone(),
             submodules: process(&mut parent_acc.sub_modules),
             source_directories: process(&mut parent_acc.main_dirs),
             test_source_directories: process(&mut parent_acc.test_dirs),
@@ -435,6 +449,7 @@ impl From<MavenModuleHelper> for MavenModuleAcc {
     fn from(helper: MavenModuleHelper) -> Self {
         MavenModuleAcc::with_content(
             helper.name,
+            helper.inherited,
             helper.submodules.1,
             helper.source_directories.1,
             helper.test_source_directories.1,
@@ -573,6 +588,14 @@ impl crate::processing::erased::CommitProc for PomProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         unimplemented!()
     }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for PomProc {
@@ -596,6 +619,10 @@ impl crate::processing::erased::ParametrizedCommitProc2 for PomProcessorHolder {
         assert_eq!(0, parameters.0);
         self.0.as_ref().unwrap()
     }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
 }
 impl CacheHolding<crate::processing::caches::Pom> for PomProc {
     fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Pom {
@@ -721,6 +748,28 @@ impl crate::processing::erased::CommitProc for MavenProc {
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
         self.commits.get(&commit_oid)
     }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> Result<(), crate::Commit> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
 }
 
 impl crate::processing::erased::CommitProcExt for MavenProc {
@@ -744,6 +793,10 @@ impl crate::processing::erased::ParametrizedCommitProc2 for MavenProcessorHolder
         assert_eq!(0, parameters.0);
         self.0.as_ref().unwrap()
     }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
 }
 impl CacheHolding<crate::processing::caches::Maven> for MavenProc {
     fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Maven {
@@ -761,3 +814,93 @@ impl CacheHolding<crate::processing::caches::Maven> for MavenProcessorHolder {
         &self.0.as_ref().unwrap().cache
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        preprocessed::CommitProcessor,
+        tests::repository::{Entry, TempRepository},
+    };
+
+    const MODULE_POM: &str = r#"<project>
+  <parent><artifactId>root</artifactId></parent>
+  <artifactId>a</artifactId>
+</project>"#;
+
+    /// The same module, once inheriting its source directory, once using the default one.
+    #[test]
+    fn test_process_inherited_source_directory() {
+        use Entry::*;
+        let repository = TempRepository::new("maven_processor");
+        let module = || {
+            Dir(vec![
+                ("pom.xml", File(MODULE_POM)),
+                ("java", Dir(vec![("A.java", File("class A {}"))])),
+                (
+                    "src",
+                    Dir(vec![(
+                        "main",
+                        Dir(vec![("java", Dir(vec![("B.java", File("class B {}"))]))]),
+                    )]),
+                ),
+            ])
+        };
+        let inheriting = repository.tree(vec![
+            (
+                "pom.xml",
+                File(
+                    r#"<project>
+  <artifactId>root</artifactId>
+  <modules><module>a</module></modules>
+  <build><sourceDirectory>java</sourceDirectory></build>
+</project>"#,
+                ),
+            ),
+            ("a", module()),
+        ]);
+        let default = repository.tree(vec![
+            (
+                "pom.xml",
+                File(
+                    r#"<project>
+  <artifactId>root</artifactId>
+  <modules><module>a</module></modules>
+</project>"#,
+                ),
+            ),
+            ("a", module()),
+        ]);
+        assert_eq!(
+            repository.oid(inheriting, "a"),
+            repository.oid(default, "a")
+        );
+
+        let mut processor = RepositoryProcessor::default();
+        let mut process = |root| {
+            let dir_path = PathBuf::from("");
+            let mut dir_path = dir_path.components().peekable();
+            let (root, _) =
+                CommitProcessor::<crate::processing::file_sys::Maven>::handle_module::<false>(
+                    &mut processor,
+                    &repository.repository,
+                    &mut dir_path,
+                    b"",
+                    root,
+                );
+            root
+        };
+        let inheriting = process(inheriting);
+        let default = process(default);
+
+        let get = |root, path: &str| {
+            path.split('/')
+                .try_fold(root, |d, name| processor.child_by_name(d, name))
+        };
+        assert!(get(inheriting, "a/java").is_some());
+        assert!(get(inheriting, "a/src/main").is_none());
+        // not taken from the cache filled while processing the first root
+        assert!(get(default, "a/java").is_none());
+        assert!(get(default, "a/src/main/java").is_some());
+    }
+}
@@ -9,8 +9,8 @@ use crate::{
     maven_processor::make,
     preprocessed::{CommitProcessor, RepositoryProcessor},
     processing::{
-        erased::ParametrizedCommitProcessorHandle, ConfiguredRepo, ConfiguredRepo2,
-        ConfiguredRepoHandle2, RepoConfig,
+        erased::{ParametrizedCommitProc, ParametrizedCommitProcessorHandle},
+        ConfiguredRepo, ConfiguredRepo2, ConfiguredRepoHandle2, RepoConfig,
     },
     Commit, SimpleStores,
 };
@@ -22,12 +22,15 @@ pub struct PreProcessedRepositories {
     // pub commits: HashMap<RepoConfig, HashMap<git2::Oid, Commit>>,
     pub processor: RepositoryProcessor,
     // pub processing_ordered_commits: HashMap<String,Vec<git2::Oid>>,
-    configs: HashMap<Repo, ParametrizedCommitProcessorHandle>,
+    pub(crate) configs: HashMap<Repo, ParametrizedCommitProcessorHandle>,
+    /// needed to register again the configs when reloading a snapshot
+    pub(crate) repo_configs: HashMap<Repo, RepoConfig>,
 }
 
 #[derive(Default)]
 pub struct CommitsPerSys {
     pub maven: HashMap<git2::Oid, Commit>,
+    pub gradle: HashMap<git2::Oid, Commit>,
     pub make: HashMap<git2::Oid, Commit>,
     pub npm: HashMap<git2::Oid, Commit>,
     pub any: HashMap<git2::Oid, Commit>,
@@ -45,6 +48,7 @@ impl CommitsPerSys {
     pub fn accessCommits<'a>(&'a self, sys: &RepoConfig) -> &'a HashMap<git2::Oid, Commit> {
         match sys {
             RepoConfig::JavaMaven => &self.maven,
+            RepoConfig::JavaGradle => &self.gradle,
             RepoConfig::CppMake => &self.make,
             RepoConfig::TsNpm => &self.npm,
             RepoConfig::Any => &self.any,
@@ -118,6 +122,42 @@ impl PreProcessedRepositories {
         self.processor.purge_caches()
     }
 
+    /// Only keeps in the stores the subtrees of commits selected by `keep`,
+    /// other commits are forgotten along with the cached nodes that were collected.
+    pub fn collect_garbage(
+        &mut self,
+        keep: impl Fn(&Repo, &git2::Oid) -> bool,
+    ) -> hyper_ast::store::gc::Collected {
+        let roots: Vec<_> = self
+            .commits()
+            .filter(|(repo, oid, _)| keep(repo, oid))
+            .map(|(_, _, c)| c.ast_root)
+            .collect();
+        let collected = self
+            .processor
+            .main_stores
+            .collect_garbage(roots, std::iter::empty());
+        // caches and commits reference nodes that might have been collected
+        let node_store = &self.processor.main_stores.node_store;
+        self.processor
+            .processing_systems
+            .retain_nodes(&|id| node_store.try_resolve(*id).is_some());
+        collected
+    }
+
+    /// Iterates over the processed commits of all configured repositories.
+    pub fn commits(&self) -> impl Iterator<Item = (&Repo, &git2::Oid, &Commit)> {
+        self.configs.iter().flat_map(|(repo, handle)| {
+            let proc = self
+                .processor
+                .processing_systems
+                .by_id(&handle.0)
+                .unwrap()
+                .get(handle.1);
+            proc.commits().map(move |(oid, c)| (repo, oid, c))
+        })
+    }
+
     pub fn get_commit(
         &self,
         config: &ParametrizedCommitProcessorHandle,
@@ -145,6 +185,16 @@ impl PreProcessedRepositories {
                     config: h.register_param(crate::maven_processor::Parameter),
                 }
             }
+            RepoConfig::JavaGradle => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::gradle_processor::GradleProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::gradle_processor::Parameter),
+                }
+            }
             RepoConfig::CppMake => {
                 let h = self
                     .processor
@@ -155,10 +205,30 @@ impl PreProcessedRepositories {
                     config: h.register_param(crate::make_processor::Parameter),
                 }
             }
-            _ => todo!(),
+            RepoConfig::TsNpm => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::npm_processor::NpmProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::npm_processor::Parameter),
+                }
+            }
+            RepoConfig::Any => {
+                let h = self
+                    .processor
+                    .processing_systems
+                    .mut_or_default::<crate::any_processor::AnyProcessorHolder>();
+                ConfiguredRepoHandle2 {
+                    spec: repo,
+                    config: h.register_param(crate::any_processor::Parameter),
+                }
+            }
         };
 
         self.configs.insert(r.spec.clone(), r.config);
+        self.repo_configs.insert(r.spec.clone(), config);
         r
     }
 
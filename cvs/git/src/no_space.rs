@@ -430,6 +430,23 @@ impl<'a> types::Typed for NoSpaceWrapper<'a, MIdN<NodeIdentifier>> {
             let t = *t as u16;
             let t = <hyper_ast_gen_ts_xml::types::Xml as hyper_ast::types::Lang<_>>::make(t);
             MultiType::Xml(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_ts::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_ts::types::Ts as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Ts(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_tsquery::types::Type>()
+        {
+            let t = *t as u16;
+            let t =
+                <hyper_ast_gen_ts_tsquery::types::TsQuery as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Query(*t)
+        } else if let Ok(t) = self.inner.get_component::<crate::any::types::Type>() {
+            MultiType::Any(*t)
         } else {
             panic!()
         }
@@ -461,6 +478,23 @@ impl<'a> types::Typed for NoSpaceWrapper<'a, NodeIdentifier> {
             let t = *t as u16;
             let t = <hyper_ast_gen_ts_xml::types::Xml as hyper_ast::types::Lang<_>>::make(t);
             MultiType::Xml(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_ts::types::Type>()
+        {
+            let t = *t as u16;
+            let t = <hyper_ast_gen_ts_ts::types::Ts as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Ts(*t)
+        } else if let Ok(t) = self
+            .inner
+            .get_component::<hyper_ast_gen_ts_tsquery::types::Type>()
+        {
+            let t = *t as u16;
+            let t =
+                <hyper_ast_gen_ts_tsquery::types::TsQuery as hyper_ast::types::Lang<_>>::make(t);
+            MultiType::Query(*t)
+        } else if let Ok(t) = self.inner.get_component::<crate::any::types::Type>() {
+            MultiType::Any(*t)
         } else {
             panic!()
         }
@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+
+use hyper_ast::{
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::NodeInserter,
+        SimpleStores,
+    },
+    tree_gen::SubTreeMetrics,
+};
+use hyper_ast_gen_ts_ts::legion as ts_tree_gen;
+
+use crate::{processing::ObjectName, Accumulator, DefaultMetrics, ParseErr, TStore};
+
+/// There is no json generator and a bare json object is not a valid ts program,
+/// so the package.json is kept as opaque text in the HyperAST,
+/// its content is also read as json to extract the metadata of the package.
+pub(crate) fn handle_package_json<NS: NodeInserter>(
+    stores: &mut SimpleStores<TStore, NS>,
+    oid: git2::Oid,
+    name: &ObjectName,
+    text: &[u8],
+) -> Result<PackageJson<NS::IdN>, ParseErr> {
+    let package = match serde_json::from_slice(text) {
+        Ok(json) => Package::from_json(&json),
+        Err(err) => {
+            log::warn!("bad package.json {:?}: {}", name.try_str(), err);
+            Package::default()
+        }
+    };
+    let (compressed_node, metrics) = crate::any::handle_opaque_file(stores, oid, text);
+    Ok(PackageJson {
+        compressed_node,
+        metrics,
+        package,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageJson<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
+    pub metrics: DefaultMetrics,
+    pub package: Package,
+}
+
+/// Metadata of an npm package, as declared in its package.json
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Package {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    /// glob patterns of the directories containing the packages of the workspace
+    pub workspaces: Vec<String>,
+    pub dependencies: Vec<String>,
+    pub dev_dependencies: Vec<String>,
+}
+
+impl Package {
+    pub fn from_json(json: &serde_json::Value) -> Self {
+        let string = |k: &str| json.get(k).and_then(|x| x.as_str()).map(|x| x.to_owned());
+        let keys = |k: &str| -> Vec<String> {
+            json.get(k)
+                .and_then(|x| x.as_object())
+                .map(|x| x.keys().cloned().collect())
+                .unwrap_or_default()
+        };
+        let strings = |x: &serde_json::Value| -> Vec<String> {
+            x.as_array()
+                .map(|x| {
+                    x.iter()
+                        .filter_map(|x| x.as_str())
+                        .map(|x| x.to_owned())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        // npm uses a list of patterns, yarn also accepts an object with a list of packages
+        let workspaces = match json.get("workspaces") {
+            Some(x) if x.is_array() => strings(x),
+            Some(x) => x.get("packages").map(strings).unwrap_or_default(),
+            None => vec![],
+        };
+        Self {
+            name: string("name"),
+            version: string("version"),
+            workspaces,
+            dependencies: keys("dependencies"),
+            dev_dependencies: keys("devDependencies"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MD {
+    pub(crate) metrics: DefaultMetrics,
+    /// None if the directory does not contain a package.json, eg. the packages/ of a monorepo
+    pub package: Option<Package>,
+}
+
+/// Where a directory stands relatively to the workspace patterns of its parent.
+///
+/// The same directory is processed differently depending on it,
+/// e.g. it is a package only if the workspace declares it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WorkspaceContext {
+    /// the directory is a package of the workspace
+    pub(crate) package: bool,
+    /// remaining patterns to match inside the directory
+    pub(crate) workspaces: Vec<PathBuf>,
+}
+
+pub struct NpmModuleAcc<IdN = NodeIdentifier> {
+    pub(crate) name: String,
+    /// what the enclosing workspace declared about this directory
+    pub(crate) context: WorkspaceContext,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) children: Vec<IdN>,
+    pub(crate) metrics: DefaultMetrics,
+    pub(crate) package: Option<Package>,
+    /// workspace patterns relative to this directory
+    pub(crate) workspaces: Option<Vec<PathBuf>>,
+}
+
+impl<IdN> From<String> for NpmModuleAcc<IdN> {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl<IdN> NpmModuleAcc<IdN> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            context: Default::default(),
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+            package: None,
+            workspaces: None,
+        }
+    }
+    pub(crate) fn with_content(name: String, workspaces: Vec<PathBuf>) -> Self {
+        Self {
+            workspaces: if workspaces.is_empty() {
+                None
+            } else {
+                Some(workspaces)
+            },
+            ..Self::new(name)
+        }
+    }
+}
+
+impl<IdN> NpmModuleAcc<IdN> {
+    pub(crate) fn push_package_json(&mut self, name: LabelIdentifier, full_node: PackageJson<IdN>) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.metrics);
+        let workspaces = full_node
+            .package
+            .workspaces
+            .iter()
+            // excluded packages are still explored as source directories
+            .filter(|x| !x.starts_with('!'))
+            .map(|x| x.into());
+        self.workspaces
+            .get_or_insert_with(Default::default)
+            .extend(workspaces);
+        self.package = Some(full_node.package);
+    }
+    pub fn push_submodule(&mut self, name: LabelIdentifier, full_node: (IdN, MD)) {
+        self.children.push(full_node.0);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.1.metrics);
+    }
+    pub(crate) fn push_source_file(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: ts_tree_gen::Local<IdN>,
+    ) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(SubTreeMetrics {
+            hashs: full_node.metrics.hashs,
+            size: full_node.metrics.size,
+            height: full_node.metrics.height,
+            size_no_spaces: full_node.metrics.size_no_spaces,
+        });
+    }
+    pub(crate) fn push_source_directory(
+        &mut self,
+        name: LabelIdentifier,
+        full_node: ts_tree_gen::Local<IdN>,
+    ) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.metrics);
+    }
+}
+
+impl<IdN> hyper_ast::tree_gen::Accumulator for NpmModuleAcc<IdN> {
+    type Node = (LabelIdentifier, (IdN, MD));
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.push_submodule(name, full_node);
+    }
+}
+
+impl<IdN> Accumulator for NpmModuleAcc<IdN> {
+    type Unlabeled = (IdN, MD);
+}
+
+/// Matches a directory name against a component of a workspace pattern,
+/// only `*` wildcards are supported.
+///
+/// A `**` component matches any number of directories,
+/// so it must be handled by the caller before calling this function.
+pub(crate) fn matches_workspace_component(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // no wildcard
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_from_json() {
+        let json = serde_json::json!({
+            "name": "@scope/app",
+            "version": "1.2.3",
+            "workspaces": { "packages": ["packages/*", "tools/cli"] },
+            "dependencies": { "react": "^18.0.0" },
+            "devDependencies": { "typescript": "^5.0.0", "jest": "^29.0.0" },
+        });
+        let package = Package::from_json(&json);
+        assert_eq!(package.name.as_deref(), Some("@scope/app"));
+        assert_eq!(package.version.as_deref(), Some("1.2.3"));
+        assert_eq!(package.workspaces, ["packages/*", "tools/cli"]);
+        assert_eq!(package.dependencies, ["react"]);
+        assert_eq!(package.dev_dependencies.len(), 2);
+
+        let json = serde_json::json!({ "workspaces": ["packages/*"] });
+        let package = Package::from_json(&json);
+        assert_eq!(package.name, None);
+        assert_eq!(package.workspaces, ["packages/*"]);
+    }
+
+    #[test]
+    fn test_matches_workspace_component() {
+        assert!(matches_workspace_component("*", "core"));
+        assert!(matches_workspace_component("plugin-*", "plugin-a"));
+        assert!(!matches_workspace_component("plugin-*", "core"));
+        assert!(matches_workspace_component("packages", "packages"));
+        assert!(!matches_workspace_component("packages", "packages2"));
+        assert!(matches_workspace_component("a*b*c", "aXbYc"));
+        assert!(!matches_workspace_component("a*b*c", "aXcYb"));
+    }
+}
@@ -0,0 +1,729 @@
+use std::{
+    iter::Peekable,
+    path::{Component, Components, Path, PathBuf},
+};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    filter::BloomSize,
+    hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+    store::{
+        defaults::NodeIdentifier,
+        nodes::{compo, compo::CS, NodeInserter, PendingInsertion},
+        SimpleStores,
+    },
+    tree_gen::SubTreeMetrics,
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_ts::{legion::eq_node, types::Type};
+
+use crate::{
+    git::BasicGitObject,
+    npm::{matches_workspace_component, NpmModuleAcc, WorkspaceContext, MD},
+    preprocessed::RepositoryProcessor,
+    processing::{erased::ParametrizedCommitProc2, CacheHolding, InFiles, ObjectName},
+    ts_processor::{is_ignored_dir, TsProc, TsProcessorHolder},
+    Processor, TStore,
+};
+
+pub struct NpmProcessor<'a, 'b, 'c, Acc> {
+    prepro: &'b mut RepositoryProcessor,
+    repository: &'a Repository,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    dir_path: &'c mut Peekable<Components<'c>>,
+    handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>,
+    ts_handle: crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+}
+
+impl<'a, 'b, 'c, Acc: From<String>> NpmProcessor<'a, 'b, 'c, Acc> {
+    pub fn new(
+        repository: &'a Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'c mut Peekable<Components<'c>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self {
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let handle =
+            <NpmProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<TsProcessorHolder>();
+        let ts_handle = <TsProc as crate::processing::erased::CommitProcExt>::register_param(
+            h,
+            crate::ts_processor::Parameter,
+        );
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree, dir_path);
+        let name = std::str::from_utf8(&name).unwrap().to_string();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            handle,
+            ts_handle,
+        }
+    }
+}
+
+impl<'a, 'b, 'c> Processor<NpmModuleAcc> for NpmProcessor<'a, 'b, 'c, NpmModuleAcc> {
+    fn pre(&mut self, current_dir: BasicGitObject) {
+        match current_dir {
+            BasicGitObject::Tree(oid, name) => {
+                if let Some(s) = self.dir_path.peek() {
+                    if name
+                        .as_bytes()
+                        .eq(std::ffi::OsStr::as_encoded_bytes(s.as_os_str()))
+                    {
+                        self.dir_path.next();
+                        self.stack.last_mut().expect("never empty").1.clear();
+                        let tree = self.repository.find_tree(oid).unwrap();
+                        let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
+                        self.stack.push((
+                            oid,
+                            prepared,
+                            NpmModuleAcc::new(name.try_into().unwrap()),
+                        ));
+                    }
+                    return;
+                }
+                if is_ignored_dir(&name) {
+                    log::debug!("ignored directory {:?}", name.try_str());
+                    return;
+                }
+                let parent_acc = &self.stack.last().unwrap().2;
+                let helper = NpmModuleHelper::from((parent_acc, &name));
+                if let Some(already) = self
+                    .prepro
+                    .processing_systems
+                    .mut_or_default::<NpmProcessorHolder>()
+                    .get_caches_mut()
+                    .object_map
+                    .get(&(oid, helper.context.clone()))
+                {
+                    // reinit already computed node for post order
+                    let full_node = already.clone();
+
+                    let w = &mut self.stack.last_mut().unwrap().2;
+                    let name = self.prepro.intern_object_name(name);
+                    assert!(!w.children_names.contains(&name));
+                    w.push_submodule(name, full_node);
+                    return;
+                }
+                log::debug!("npm tree {:?}", name.try_str());
+
+                if helper.context.package || !helper.context.workspaces.is_empty() {
+                    // handle as a package of the workspace, or search further for them
+                    let tree = self.repository.find_tree(oid).unwrap();
+                    let prepared = prepare_dir_exploration(tree, &mut self.dir_path);
+                    self.stack.push((oid, prepared, helper.into()));
+                } else {
+                    // handle as source dir
+                    let (name, full_node) = self.prepro.help_handle_ts_folder(
+                        &self.repository,
+                        self.dir_path,
+                        oid,
+                        &name,
+                    );
+                    let parent_acc = &mut self.stack.last_mut().unwrap().2;
+                    assert!(!parent_acc.children_names.contains(&name));
+                    parent_acc.push_source_directory(name, full_node);
+                }
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if self.dir_path.peek().is_some() {
+                    return;
+                }
+                if crate::processing::file_sys::PackageJson::matches(&name) {
+                    self.prepro
+                        .help_handle_package_json(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            name,
+                            &self.repository,
+                            self.handle.into(),
+                        )
+                        .unwrap();
+                } else if crate::processing::file_sys::Ts::matches(&name) {
+                    self.prepro
+                        .help_handle_ts_file2(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            &name,
+                            self.repository,
+                            self.ts_handle,
+                        )
+                        .unwrap();
+                } else {
+                    log::debug!("not ts source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+
+    fn post(&mut self, oid: Oid, acc: NpmModuleAcc) -> Option<(NodeIdentifier, MD)> {
+        let name = acc.name.clone();
+        let context = acc.context.clone();
+        let full_node = make(acc, self.prepro.main_stores_mut());
+        self.prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .insert((oid, context), full_node.clone());
+
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.children_names.contains(&name),
+                "{:?} {:?}",
+                w.children_names,
+                name
+            );
+            w.push_submodule(name, full_node);
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, NpmModuleAcc)> {
+        &mut self.stack
+    }
+}
+
+pub(crate) fn make<NS: NodeInserter>(
+    acc: NpmModuleAcc<NS::IdN>,
+    stores: &mut SimpleStores<TStore, NS>,
+) -> (NS::IdN, MD) {
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let hashable = hbuilder.most_discriminating();
+    let label = stores.label_store.get_or_insert(acc.name.clone());
+
+    let eq = eq_node::<_, NS>(&Type::Directory, Some(&label), &acc.children);
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let node_id = if let Some(id) = insertion.occupied_id() {
+        id
+    } else {
+        log::info!("make npm {} {}", &acc.name, acc.children.len());
+        assert_eq!(acc.children_names.len(), acc.children.len());
+        insertion.insert((
+            Type::Directory,
+            label,
+            hashs,
+            compo::Size(size),
+            compo::Height(height),
+            compo::SizeNoSpaces(size_no_spaces),
+            CS(acc.children_names.into_boxed_slice()),
+            CS(acc.children.into_boxed_slice()),
+            BloomSize::Much,
+        ))
+    };
+
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        hashs,
+        size_no_spaces,
+    };
+
+    let md = MD {
+        metrics,
+        package: acc.package,
+    };
+    (node_id, md)
+}
+
+impl RepositoryProcessor {
+    fn help_handle_package_json(
+        &mut self,
+        oid: Oid,
+        parent_acc: &mut NpmModuleAcc,
+        name: ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<PackageJsonProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let x = self
+            .processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::PackageJson>()
+            .handle(oid, repository, &name, parameters, |_, n, t| {
+                crate::npm::handle_package_json(&mut self.main_stores, oid, n, t)
+            })?;
+        let name = self.intern_object_name(&name);
+        assert!(!parent_acc.children_names.contains(&name));
+        parent_acc.push_package_json(name, x);
+        Ok(())
+    }
+}
+
+/// Computes the [`WorkspaceContext`] of a directory from the patterns of its parent.
+///
+/// Contrary to maven modules, a pattern can match multiple directories,
+/// so patterns are not consumed when they match.
+struct NpmModuleHelper {
+    name: String,
+    context: WorkspaceContext,
+}
+
+impl From<(&NpmModuleAcc, &ObjectName)> for NpmModuleHelper {
+    fn from((parent_acc, name): (&NpmModuleAcc, &ObjectName)) -> Self {
+        let name: String = name.try_into().unwrap();
+        let mut helper = Self {
+            name,
+            context: Default::default(),
+        };
+        for pattern in parent_acc.workspaces.iter().flatten() {
+            helper.match_pattern(pattern);
+        }
+        helper
+    }
+}
+
+impl NpmModuleHelper {
+    fn match_pattern(&mut self, pattern: &Path) {
+        let mut components = pattern
+            .components()
+            .skip_while(|x| matches!(x, Component::CurDir));
+        let Some(first) = components.next() else {
+            return;
+        };
+        let Some(first) = first.as_os_str().to_str() else {
+            return;
+        };
+        let rest: PathBuf = components.collect();
+        if first == "**" {
+            // matches this directory then possibly its descendants
+            self.context.workspaces.push(pattern.to_path_buf());
+            if rest.components().next().is_none() {
+                self.context.package = true;
+            } else {
+                // or matches no directory at all
+                self.match_pattern(&rest);
+            }
+            return;
+        }
+        if !matches_workspace_component(first, &self.name) {
+            return;
+        }
+        if rest.components().next().is_none() {
+            self.context.package = true;
+        } else {
+            self.context.workspaces.push(rest);
+        }
+    }
+}
+
+impl From<NpmModuleHelper> for NpmModuleAcc {
+    fn from(helper: NpmModuleHelper) -> Self {
+        let mut acc = NpmModuleAcc::with_content(helper.name, helper.context.workspaces.clone());
+        acc.context = helper.context;
+        acc
+    }
+}
+
+/// The package.json is explored first, as it tells where are the packages of the workspace.
+pub(crate) fn prepare_dir_exploration(
+    tree: git2::Tree,
+    dir_path: &mut Peekable<Components>,
+) -> Vec<BasicGitObject> {
+    let mut children_objects: Vec<BasicGitObject> = tree
+        .iter()
+        .rev()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect();
+    if dir_path.peek().is_none() {
+        let p = children_objects.iter().position(|x| match x {
+            BasicGitObject::Blob(_, n) => crate::processing::file_sys::PackageJson::matches(n),
+            _ => false,
+        });
+        if let Some(p) = p {
+            // we use it like a stack
+            let package_json = children_objects.remove(p);
+            children_objects.push(package_json);
+        }
+    }
+    children_objects
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+impl From<crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>>
+    for crate::processing::erased::ParametrizedCommitProcessor2Handle<PackageJsonProc>
+{
+    fn from(value: crate::processing::erased::ParametrizedCommitProcessor2Handle<NpmProc>) -> Self {
+        crate::processing::erased::ParametrizedCommitProcessor2Handle(
+            value.0,
+            std::marker::PhantomData,
+        )
+    }
+}
+
+// # package.json
+
+struct PackageJsonProcessorHolder(Option<PackageJsonProc>);
+impl Default for PackageJsonProcessorHolder {
+    fn default() -> Self {
+        Self(Some(PackageJsonProc(Parameter, Default::default())))
+    }
+}
+struct PackageJsonProc(Parameter, crate::processing::caches::PackageJson);
+impl crate::processing::erased::Parametrized for PackageJsonProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self.0.iter().position(|x| &x.0 == &t).unwrap_or_else(|| {
+            let l = 0;
+            self.0 = Some(PackageJsonProc(t, Default::default()));
+            l
+        });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+// TODO should not have to impl this trait
+impl crate::processing::erased::CommitProc for PackageJsonProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!()
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!()
+    }
+
+    fn get_commit(&self, _commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        unimplemented!()
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.1.retain_nodes(alive);
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for PackageJsonProc {
+    type Holder = PackageJsonProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for PackageJsonProcessorHolder {
+    type Proc = PackageJsonProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+impl CacheHolding<crate::processing::caches::PackageJson> for PackageJsonProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::PackageJson {
+        &mut self.1
+    }
+    fn get_caches(&self) -> &crate::processing::caches::PackageJson {
+        &self.1
+    }
+}
+
+// # Npm
+
+#[derive(Default)]
+pub(crate) struct NpmProcessorHolder(Option<NpmProc>);
+pub(crate) struct NpmProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Npm,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for NpmProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(NpmProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+struct PreparedNpmCommitProc<'repo> {
+    repository: &'repo git2::Repository,
+    commit_builder: crate::preprocessed::CommitBuilder,
+}
+impl<'repo> crate::processing::erased::PreparedCommitProc for PreparedNpmCommitProc<'repo> {
+    fn process(
+        self: Box<PreparedNpmCommitProc<'repo>>,
+        prepro: &mut RepositoryProcessor,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let name = b"";
+        let root_full_node = NpmProcessor::<NpmModuleAcc>::new(
+            self.repository,
+            prepro,
+            &mut dir_path,
+            name,
+            self.commit_builder.tree_oid(),
+        )
+        .process();
+        let h = prepro
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>();
+        let handle =
+            <NpmProc as crate::processing::erased::CommitProcExt>::register_param(h, Parameter);
+        let commit_oid = self.commit_builder.commit_oid();
+        let commit = self.commit_builder.finish(root_full_node.0);
+        h.with_parameters_mut(handle.0)
+            .commits
+            .insert(commit_oid, commit);
+        root_full_node.0
+    }
+}
+impl crate::processing::erased::CommitProc for NpmProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("see reason in the java proc")
+    }
+
+    fn prepare_processing<'repo>(
+        &self,
+        repository: &'repo git2::Repository,
+        commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc + 'repo> {
+        Box::new(PreparedNpmCommitProc {
+            repository,
+            commit_builder,
+        })
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(self.commits.iter())
+    }
+
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> Result<(), crate::Commit> {
+        self.commits.insert(commit_oid, commit);
+        Ok(())
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for NpmProc {
+    type Holder = NpmProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for NpmProcessorHolder {
+    type Proc = NpmProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+impl CacheHolding<crate::processing::caches::Npm> for NpmProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Npm {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Npm {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Npm> for NpmProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Npm {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Npm {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper_ast::types::Tree;
+
+    use super::*;
+    use crate::{
+        preprocessed::CommitProcessor,
+        tests::repository::{Entry, TempRepository},
+    };
+
+    #[test]
+    fn test_process_workspace() {
+        use Entry::*;
+        let repository = TempRepository::new("npm_processor");
+        let root = repository.tree(vec![
+            (
+                "package.json",
+                File(r#"{ "name": "root", "workspaces": ["packages/*", "tools/**/plugin"] }"#),
+            ),
+            (
+                "packages",
+                Dir(vec![
+                    (
+                        "a",
+                        Dir(vec![
+                            ("package.json", File(r#"{ "name": "a" }"#)),
+                            ("index.ts", File("export const a = 1;\n")),
+                        ]),
+                    ),
+                    ("b", Dir(vec![("package.json", File(r#"{ "name": "b" }"#))])),
+                ]),
+            ),
+            (
+                "tools",
+                Dir(vec![
+                    (
+                        "x",
+                        Dir(vec![(
+                            "plugin",
+                            Dir(vec![("package.json", File(r#"{ "name": "nested" }"#))]),
+                        )]),
+                    ),
+                    (
+                        "plugin",
+                        Dir(vec![("package.json", File(r#"{ "name": "shallow" }"#))]),
+                    ),
+                ]),
+            ),
+            (
+                "src",
+                Dir(vec![("index.ts", File("export const b = 2;\n"))]),
+            ),
+        ]);
+        let oid = |path: &str| repository.oid(root, path);
+
+        let mut processor = RepositoryProcessor::default();
+        let dir_path = PathBuf::from("");
+        let mut dir_path = dir_path.components().peekable();
+        let (root_node, md) =
+            CommitProcessor::<crate::processing::file_sys::Npm>::handle_module::<true>(
+                &mut processor,
+                &repository.repository,
+                &mut dir_path,
+                b"",
+                root,
+            );
+        let package = md.package.unwrap();
+        assert_eq!(package.name.as_deref(), Some("root"));
+        assert_eq!(package.workspaces, ["packages/*", "tools/**/plugin"]);
+
+        // the package.json is kept as an opaque leaf, not as an erroneous ts tree
+        let package_json = processor.child_by_name(root_node, "package.json").unwrap();
+        let package_json = processor.main_stores.node_store.resolve(package_json);
+        let kind = package_json.get_component::<crate::any::types::Type>();
+        assert_eq!(kind.ok(), Some(&crate::any::types::Type::Text));
+        assert!(!package_json.has_children());
+
+        let cache = &processor
+            .processing_systems
+            .mut_or_default::<NpmProcessorHolder>()
+            .get_caches()
+            .object_map;
+        let lookup = |path: &str| {
+            let oid = oid(path);
+            cache.iter().find(|((x, _), _)| *x == oid)
+        };
+        let package_name = |path: &str| {
+            let (_, (_, md)) = lookup(path).expect(path);
+            md.package.as_ref().and_then(|x| x.name.clone())
+        };
+        assert_eq!(package_name("packages/a").as_deref(), Some("a"));
+        assert_eq!(package_name("packages/b").as_deref(), Some("b"));
+        assert_eq!(package_name("tools/x/plugin").as_deref(), Some("nested"));
+        // `**` also matches no directory at all
+        assert_eq!(package_name("tools/plugin").as_deref(), Some("shallow"));
+        // explored only to find the packages of the workspace
+        assert_eq!(package_name("packages"), None);
+        assert_eq!(package_name("tools/x"), None);
+        // handled as a plain ts directory
+        assert!(lookup("src").is_none());
+
+        // cached with what the workspace declared about them
+        let ((_, context), _) = lookup("packages/a").unwrap();
+        assert!(context.package);
+        let ((_, context), _) = lookup("tools").unwrap();
+        assert!(!context.package);
+        assert_eq!(context.workspaces, [PathBuf::from("**/plugin")]);
+    }
+}
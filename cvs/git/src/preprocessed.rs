@@ -85,6 +85,23 @@ impl RepositoryProcessor {
     pub fn purge_caches(&mut self) {
         self.processing_systems.clear();
     }
+
+    /// Statistics about the stores, nodes are grouped by type.
+    pub fn stats(&self) -> hyper_ast::store::stats::StoreStats {
+        use hyper_ast::types::TypeStore;
+        self.main_stores.stats(|type_store, n| type_store.resolve_type(n))
+    }
+
+    /// Content-addressed identifier of `root`, see [`hyper_ast::store::merkle`].
+    pub fn merkle_id(
+        &self,
+        index: &mut hyper_ast::store::merkle::MerkleIndex,
+        root: NodeIdentifier,
+    ) -> hyper_ast::store::merkle::MerkleId {
+        use hyper_ast::types::TypeStore;
+        self.main_stores
+            .merkle_id(index, |type_store, n| type_store.resolve_type(n), root)
+    }
 }
 
 impl PreProcessedRepository {
@@ -335,6 +352,20 @@ impl PreProcessedRepository {
         after: &str,
         dir_path: &str,
         limit: usize,
+    ) -> Vec<git2::Oid> {
+        self.pre_process_with_limit_and(repository, before, after, dir_path, limit, |_, _| ())
+    }
+
+    /// Same as [`Self::pre_process_with_limit`],
+    /// but calls `on_processed` after each processed commit, eg. to track the growth of the stores.
+    pub fn pre_process_with_limit_and(
+        &mut self,
+        repository: &mut Repository,
+        before: &str,
+        after: &str,
+        dir_path: &str,
+        limit: usize,
+        mut on_processed: impl FnMut(&Self, git2::Oid),
     ) -> Vec<git2::Oid> {
         let count = all_commits_between(&repository, before, after).map(|x| x.count());
         log::info!("commits to process: {:?}", count);
@@ -354,6 +385,7 @@ impl PreProcessedRepository {
             );
             processing_ordered_commits.push(oid.clone());
             self.commits.insert(oid.clone(), c);
+            on_processed(self, oid);
         });
         processing_ordered_commits
     }
@@ -586,23 +618,6 @@ impl CommitBuilder {
     }
 }
 
-#[cfg(feature = "any")]
-impl CommitProcessor<file_sys::Any> for RepositoryProcessor {
-    fn handle_module<'a, 'b, const RMS: bool>(
-        &mut self,
-        repository: &'a Repository,
-        dir_path: &'b mut Peekable<Components<'b>>,
-        name: &[u8],
-        oid: git2::Oid,
-    ) -> NodeIdentifier {
-        let root_full_node = MavenProcessor::<RMS, false, MavenModuleAcc>::new(
-            repository, self, dir_path, name, oid,
-        )
-        .process();
-        root_full_node.0
-    }
-}
-
 impl<H: IdHolder, T> IdHolder for (H, T) {
     type Id = H::Id;
     fn id(&self) -> Self::Id {
@@ -653,6 +668,23 @@ impl CommitProcessor<file_sys::Java> for RepositoryProcessor {
     }
 }
 
+#[cfg(feature = "gradle")]
+impl CommitProcessor<file_sys::Gradle> for RepositoryProcessor {
+    type Module = (NodeIdentifier, crate::gradle::MD);
+    fn handle_module<'a, 'b, const RMS: bool>(
+        &mut self,
+        repository: &'a Repository,
+        dir_path: &'b mut Peekable<Components<'b>>,
+        name: &[u8],
+        oid: git2::Oid,
+    ) -> Self::Module {
+        crate::gradle_processor::GradleProcessor::<RMS, false, crate::gradle::GradleModuleAcc>::new(
+            repository, self, dir_path, name, oid,
+        )
+        .process()
+    }
+}
+
 #[cfg(feature = "make")]
 impl CommitProcessor<file_sys::Make> for RepositoryProcessor {
     type Module = (NodeIdentifier, crate::make::MD);
@@ -672,22 +704,26 @@ impl CommitProcessor<file_sys::Make> for RepositoryProcessor {
     }
 }
 
+#[cfg(feature = "any")]
 impl CommitProcessor<file_sys::Any> for RepositoryProcessor {
     type Module = (NodeIdentifier, DefaultMetrics);
     fn handle_module<'a, 'b, const RMS: bool>(
         &mut self,
-        _repository: &'a Repository,
-        _dir_path: &'b mut Peekable<Components<'b>>,
-        _name: &[u8],
-        _oid: git2::Oid,
+        repository: &'a Repository,
+        dir_path: &'b mut Peekable<Components<'b>>,
+        name: &[u8],
+        oid: git2::Oid,
     ) -> Self::Module {
-        todo!("still not sure how to dispatch")
+        crate::any_processor::AnyProcessor::<RMS, false, crate::any::AnyAcc>::new(
+            repository, self, dir_path, name, oid,
+        )
+        .process()
     }
 }
 
 /// plan to work on all languges of the family of typesript ie. ts, js, tsx, jsx
-/// - [ ] ts
-/// - [ ] js
+/// - [x] ts
+/// - [x] js
 /// - [ ] tsx
 /// - [ ] jsx
 /// - [ ] d.ts
@@ -696,20 +732,18 @@ impl CommitProcessor<file_sys::Any> for RepositoryProcessor {
 ///   - [ ] ts
 #[cfg(feature = "npm")]
 impl CommitProcessor<file_sys::Npm> for RepositoryProcessor {
-    type Module = (NodeIdentifier, DefaultMetrics);
+    type Module = (NodeIdentifier, crate::npm::MD);
     fn handle_module<'a, 'b, const RMS: bool>(
         &mut self,
-        _repository: &'a Repository,
-        _dir_path: &'b mut Peekable<Components<'b>>,
-        _name: &[u8],
-        _oid: git2::Oid,
+        repository: &'a Repository,
+        dir_path: &'b mut Peekable<Components<'b>>,
+        name: &[u8],
+        oid: git2::Oid,
     ) -> Self::Module {
-        todo!("need to implement NpmProcessor")
-        // let root_full_node = NpmProcessor::<RMS, FFWD, NpmModuleAcc>::new(repository, self, dir_path, name, oid)
-        //     .process();
-        // // self.object_map_make
-        // //     .insert(commit_oid, root_full_node.clone());
-        // root_full_node.0
+        crate::npm_processor::NpmProcessor::<crate::npm::NpmModuleAcc>::new(
+            repository, self, dir_path, name, oid,
+        )
+        .process()
     }
 }
 
@@ -6,6 +6,7 @@ pub use erased::ParametrizedCommitProcessorHandle;
 
 pub enum BuildSystem {
     Maven,
+    Gradle,
     Make,
     Npm,
     None,
@@ -20,6 +21,7 @@ enum Language {
 
 pub enum ProcessingConfig<P> {
     JavaMaven { limit: usize, dir_path: P },
+    JavaGradle { limit: usize, dir_path: P },
     CppMake { limit: usize, dir_path: P },
     TsNpm { limit: usize, dir_path: P },
     Any { limit: usize, dir_path: P },
@@ -33,6 +35,7 @@ pub enum RepoConfig {
     JavaMaven,
     TsNpm,
     Any,
+    JavaGradle,
 }
 
 impl std::str::FromStr for RepoConfig {
@@ -44,6 +47,9 @@ impl std::str::FromStr for RepoConfig {
             "cpp" => Self::CppMake,
             "Java" => Self::JavaMaven,
             "java" => Self::JavaMaven,
+            "JavaGradle" => Self::JavaGradle,
+            "Gradle" => Self::JavaGradle,
+            "gradle" => Self::JavaGradle,
             "typescript" => Self::TsNpm,
             "javascript" => Self::TsNpm,
             "Ts" => Self::TsNpm,
@@ -65,8 +71,18 @@ impl From<&RepoConfig> for ProcessingConfig<&'static str> {
                 limit: 3,
                 dir_path: "",
             },
-            RepoConfig::TsNpm => todo!(),
-            RepoConfig::Any => todo!(),
+            RepoConfig::TsNpm => Self::TsNpm {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::Any => Self::Any {
+                limit: 3,
+                dir_path: "",
+            },
+            RepoConfig::JavaGradle => Self::JavaGradle {
+                limit: 3,
+                dir_path: "",
+            },
         }
     }
 }
@@ -98,12 +114,12 @@ impl ConfiguredRepoTrait for ConfiguredRepoHandle {
 }
 
 impl ConfiguredRepoHandle {
-    pub fn fetch(self) -> ConfiguredRepo {
-        ConfiguredRepo {
-            repo: self.spec.fetch(),
+    pub fn fetch(self) -> Result<ConfiguredRepo, git2::Error> {
+        Ok(ConfiguredRepo {
+            repo: self.spec.fetch()?,
             spec: self.spec,
             config: self.config,
-        }
+        })
     }
 }
 
@@ -125,12 +141,12 @@ impl ConfiguredRepoTrait for ConfiguredRepoHandle2 {
     }
 }
 impl ConfiguredRepoHandle2 {
-    pub fn fetch(self) -> ConfiguredRepo2 {
-        ConfiguredRepo2 {
-            repo: self.spec.fetch(),
+    pub fn fetch(self) -> Result<ConfiguredRepo2, git2::Error> {
+        Ok(ConfiguredRepo2 {
+            repo: self.spec.fetch()?,
             spec: self.spec,
             config: self.config,
-        }
+        })
     }
 }
 
@@ -253,12 +269,26 @@ pub(crate) mod caches {
     pub(crate) type OidMap<T> = std::collections::BTreeMap<git2::Oid, T>;
     pub(crate) type NamedMap<T> = std::collections::BTreeMap<(git2::Oid, ObjectName), T>;
 
+    /// Caches hold identifiers of nodes, the ones of collected nodes must be forgotten,
+    /// see [`crate::multi_preprocessed::PreProcessedRepositories::collect_garbage`].
+    pub trait RetainNodes {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool);
+    }
+
     #[derive(Default)]
     pub struct Java {
         pub(crate) md_cache: hyper_ast_gen_ts_java::legion_with_refs::MDCache,
         pub object_map: NamedMap<(hyper_ast_gen_ts_java::legion_with_refs::Local, IsSkippedAna)>,
     }
 
+    impl RetainNodes for Java {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.md_cache.retain(|id, _| alive(id));
+            self.object_map
+                .retain(|_, (x, _)| alive(&x.compressed_node));
+        }
+    }
+
     impl super::ObjectMapper for Java {
         type K = (git2::Oid, ObjectName);
 
@@ -279,6 +309,14 @@ pub(crate) mod caches {
         pub object_map: NamedMap<(hyper_ast_gen_ts_cpp::legion::Local, IsSkippedAna)>,
     }
 
+    impl RetainNodes for Cpp {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.md_cache.retain(|id, _| alive(id));
+            self.object_map
+                .retain(|_, (x, _)| alive(&x.compressed_node));
+        }
+    }
+
     impl super::ObjectMapper for Cpp {
         type K = (git2::Oid, ObjectName);
 
@@ -295,7 +333,18 @@ pub(crate) mod caches {
 
     #[derive(Default)]
     pub struct Maven {
-        pub object_map: OidMap<(NodeIdentifier, crate::maven::MD)>,
+        /// a module is also keyed by the project it inherits from its enclosing modules,
+        /// as its source directories and dependencies can come from there
+        pub object_map: std::collections::HashMap<
+            (git2::Oid, Option<std::sync::Arc<crate::maven::Project>>),
+            (NodeIdentifier, crate::maven::MD),
+        >,
+    }
+
+    impl RetainNodes for Maven {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, (id, _)| alive(id));
+        }
     }
 
     #[derive(Default)]
@@ -303,6 +352,12 @@ pub(crate) mod caches {
         pub object_map: OidMap<crate::maven::POM>,
     }
 
+    impl RetainNodes for Pom {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, x| alive(&x.compressed_node));
+        }
+    }
+
     impl super::ObjectMapper for Pom {
         type K = git2::Oid;
 
@@ -317,16 +372,68 @@ pub(crate) mod caches {
         }
     }
 
+    #[derive(Default)]
+    pub struct Gradle {
+        /// a directory is also keyed by what the enclosing projects declare about it
+        pub object_map: std::collections::HashMap<
+            (git2::Oid, crate::gradle::ModuleContext),
+            (NodeIdentifier, crate::gradle::MD),
+        >,
+    }
+
+    impl RetainNodes for Gradle {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, (id, _)| alive(id));
+        }
+    }
+
+    #[derive(Default)]
+    pub struct GradleFile {
+        pub object_map: NamedMap<crate::gradle::GradleFile>,
+    }
+
+    impl RetainNodes for GradleFile {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, x| alive(&x.compressed_node));
+        }
+    }
+
+    impl super::ObjectMapper for GradleFile {
+        type K = (git2::Oid, ObjectName);
+
+        type V = crate::gradle::GradleFile;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
     #[derive(Default)]
     pub struct Make {
         pub object_map: OidMap<(NodeIdentifier, crate::make::MD)>,
     }
 
+    impl RetainNodes for Make {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, (id, _)| alive(id));
+        }
+    }
+
     #[derive(Default)]
     pub struct Makefile {
         pub object_map: OidMap<crate::make::MakeFile>,
     }
 
+    impl RetainNodes for Makefile {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, x| alive(&x.compressed_node));
+        }
+    }
+
     impl super::ObjectMapper for Makefile {
         type K = git2::Oid;
 
@@ -341,6 +448,91 @@ pub(crate) mod caches {
         }
     }
 
+    #[derive(Default)]
+    pub struct Ts {
+        pub(crate) md_cache: hyper_ast_gen_ts_ts::legion::MDCache,
+        pub object_map: NamedMap<hyper_ast_gen_ts_ts::legion::Local>,
+    }
+
+    impl RetainNodes for Ts {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.md_cache.retain(|id, _| alive(id));
+            self.object_map.retain(|_, x| alive(&x.compressed_node));
+        }
+    }
+
+    impl super::ObjectMapper for Ts {
+        type K = (git2::Oid, ObjectName);
+
+        type V = hyper_ast_gen_ts_ts::legion::Local;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Npm {
+        /// a directory is also keyed by what the enclosing workspace declares about it
+        pub object_map: std::collections::HashMap<
+            (git2::Oid, crate::npm::WorkspaceContext),
+            (NodeIdentifier, crate::npm::MD),
+        >,
+    }
+
+    impl RetainNodes for Npm {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, (id, _)| alive(id));
+        }
+    }
+
+    #[derive(Default)]
+    pub struct PackageJson {
+        pub object_map: OidMap<crate::npm::PackageJson>,
+    }
+
+    impl RetainNodes for PackageJson {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, x| alive(&x.compressed_node));
+        }
+    }
+
+    impl super::ObjectMapper for PackageJson {
+        type K = git2::Oid;
+
+        type V = crate::npm::PackageJson;
+
+        fn get(&self, key: &Self::K) -> Option<&Self::V> {
+            self.object_map.get(key)
+        }
+
+        fn insert(&mut self, key: Self::K, value: Self::V) -> Option<Self::V> {
+            self.object_map.insert(key, value)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Any {
+        pub object_map: OidMap<(NodeIdentifier, crate::DefaultMetrics)>,
+        /// files whatever their language, parsed or kept opaque
+        pub file_map: NamedMap<(NodeIdentifier, crate::DefaultMetrics)>,
+        #[cfg(feature = "tsquery")]
+        pub(crate) query_md_cache: hyper_ast_gen_ts_tsquery::legion::MDCache,
+    }
+
+    impl RetainNodes for Any {
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.object_map.retain(|_, (id, _)| alive(id));
+            self.file_map.retain(|_, (id, _)| alive(id));
+            #[cfg(feature = "tsquery")]
+            self.query_md_cache.retain(|id, _| alive(id));
+        }
+    }
+
     // // any
     // pub object_map_any: OidMap<(NodeIdentifier, DefaultMetrics)>,
     // // maven
@@ -382,6 +574,10 @@ pub mod file_sys {
     /// The default file system, directories and files
     pub struct Any;
 
+    impl CachesHolding for Any {
+        type Caches = super::caches::Any;
+    }
+
     /// The maven scheme https://maven.apache.org/guides/introduction/introduction-to-the-standard-directory-layout.html ,
     /// made of nested maven modules.
     /// Each maven module has a config file (often a pom.xml),
@@ -410,6 +606,37 @@ pub mod file_sys {
         }
     }
 
+    /// The gradle scheme https://docs.gradle.org/current/userguide/multi_project_builds.html ,
+    /// made of a root project whose settings script includes subprojects.
+    /// Each project can have a build script that configures its source sets,
+    /// by default a src/main/java/ directory for production code and a src/test/java/ directory for tests, like maven.
+    #[cfg(feature = "gradle")]
+    pub struct Gradle;
+
+    #[cfg(feature = "gradle")]
+    impl CachesHolding for Gradle {
+        type Caches = super::caches::Gradle;
+    }
+
+    /// The settings and build scripts, either in groovy or kotlin
+    #[cfg(feature = "gradle")]
+    pub struct GradleScript;
+
+    #[cfg(feature = "gradle")]
+    impl CachesHolding for GradleScript {
+        type Caches = super::caches::GradleFile;
+    }
+
+    #[cfg(feature = "gradle")]
+    impl super::InFiles for GradleScript {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"build.gradle")
+                || name.0.eq(b"build.gradle.kts")
+                || name.0.eq(b"settings.gradle")
+                || name.0.eq(b"settings.gradle.kts")
+        }
+    }
+
     /// The java scheme,
     /// made of packages and modules https://docs.oracle.com/javase/specs/jls/se11/html/jls-7.html
     #[cfg(feature = "maven")]
@@ -471,6 +698,46 @@ pub mod file_sys {
     /// or is a collection of packages that contains a packages/ directory where each package is located
     #[cfg(feature = "npm")]
     pub struct Npm;
+
+    #[cfg(feature = "npm")]
+    impl CachesHolding for Npm {
+        type Caches = super::caches::Npm;
+    }
+
+    #[cfg(feature = "npm")]
+    pub struct PackageJson;
+
+    #[cfg(feature = "npm")]
+    impl CachesHolding for PackageJson {
+        type Caches = super::caches::PackageJson;
+    }
+
+    #[cfg(feature = "npm")]
+    impl super::InFiles for PackageJson {
+        fn matches(name: &ObjectName) -> bool {
+            name.0.eq(b"package.json")
+        }
+    }
+
+    /// The typescript family of languages, ie. ts, tsx, js, jsx and their module variants
+    #[cfg(feature = "ts")]
+    pub struct Ts;
+
+    #[cfg(feature = "ts")]
+    impl CachesHolding for Ts {
+        type Caches = super::caches::Ts;
+    }
+
+    #[cfg(feature = "ts")]
+    impl super::InFiles for Ts {
+        fn matches(name: &ObjectName) -> bool {
+            // tsx and jsx need the tsx grammar, that the ts generator does not handle yet
+            name.0.ends_with(b".ts")
+                || name.0.ends_with(b".js")
+                || name.0.ends_with(b".mjs")
+                || name.0.ends_with(b".cjs")
+        }
+    }
 }
 
 impl crate::preprocessed::RepositoryProcessor {
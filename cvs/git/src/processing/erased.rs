@@ -69,6 +69,26 @@ pub trait CommitProc {
     ) -> hyper_ast::store::defaults::NodeIdentifier;
 
     fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit>;
+
+    /// Already processed commits, used to persist them along the stores.
+    fn commits(&self) -> Box<dyn Iterator<Item = (&git2::Oid, &crate::Commit)> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    /// Forgets the cached nodes and the commits whose nodes were collected,
+    /// see [`crate::processing::caches::RetainNodes`].
+    fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool);
+
+    /// Registers a commit processed by a previous run, eg. reloaded from a snapshot.
+    /// The commit is given back by processors that do not keep commits, eg. the ones of files.
+    fn restore_commit(
+        &mut self,
+        commit_oid: git2::Oid,
+        commit: crate::Commit,
+    ) -> Result<(), crate::Commit> {
+        let _ = commit_oid;
+        Err(commit)
+    }
 }
 pub trait PreparedCommitProc {
     fn process(
@@ -98,12 +118,16 @@ pub trait ParametrizedCommitProc: std::any::Any {
 
     fn get_mut(&mut self, parameters: ConfigParametersHandle) -> &mut dyn CommitProc;
     fn get(&self, parameters: ConfigParametersHandle) -> &dyn CommitProc;
+    /// see [`CommitProc::retain_nodes`], for the processors of all registered parameters
+    fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool);
 }
 
 pub trait ParametrizedCommitProc2: ParametrizedCommitProc {
     type Proc: CommitProcExt;
     fn with_parameters(&self, parameters: ConfigParametersHandle) -> &Self::Proc;
     fn with_parameters_mut(&mut self, parameters: ConfigParametersHandle) -> &mut Self::Proc;
+    /// The processors of all registered parameters
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_>;
 }
 
 impl<T: ParametrizedCommitProc2> ParametrizedCommitProc for T {
@@ -114,6 +138,12 @@ impl<T: ParametrizedCommitProc2> ParametrizedCommitProc for T {
     fn get(&self, parameters: ConfigParametersHandle) -> &dyn CommitProc {
         ParametrizedCommitProc2::with_parameters(self, parameters)
     }
+
+    fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+        for proc in self.procs_mut() {
+            proc.retain_nodes(alive)
+        }
+    }
 }
 
 #[test]
@@ -156,6 +186,8 @@ fn t() {
         fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
             todo!()
         }
+
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {}
     }
     impl ParametrizedCommitProc for P0 {
         fn get_mut(&mut self, parameters: ConfigParametersHandle) -> &mut dyn CommitProc {
@@ -164,6 +196,9 @@ fn t() {
         fn get(&self, parameters: ConfigParametersHandle) -> &dyn CommitProc {
             &self.0[parameters.0]
         }
+        fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            self.0.iter_mut().for_each(|x| x.retain_nodes(alive))
+        }
     }
 
     pub struct ProcessorMap<V>(std::collections::HashMap<std::any::TypeId, V>);
@@ -293,6 +328,12 @@ mod spreaded {
             let r = <dyn Any>::downcast_mut(r.as_mut_any());
             r.unwrap()
         }
+        /// see [`CommitProc::retain_nodes`]
+        pub(crate) fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {
+            for proc in self.0.values_mut() {
+                proc.retain_nodes(alive)
+            }
+        }
         pub fn get<T: 'static + ToErasedProc + Default + Send + Sync>(&self) -> Option<&T> {
             let r = self.0.get(&std::any::TypeId::of::<T>())?;
             <dyn Any>::downcast_ref(r.as_any())
@@ -350,6 +391,8 @@ mod spreaded {
             fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
                 todo!()
             }
+
+            fn retain_nodes(&mut self, alive: &dyn Fn(&NodeIdentifier) -> bool) {}
         }
         impl CommitProcExt for P {
             type Holder = P0;
@@ -371,6 +414,9 @@ mod spreaded {
             ) -> &mut Self::Proc {
                 &mut self.0[parameters.0]
             }
+            fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+                Box::new(self.0.iter_mut())
+            }
         }
 
         let mut h = ProcessorMap::<Box<dyn ErasableProcessor>>::default();
@@ -0,0 +1,201 @@
+//! Warm-start of [`PreProcessedRepositories`] from on-disk snapshots.
+//!
+//! A snapshot is made of the stores (see [`hyper_ast::store::snapshot`])
+//! followed by the registered repositories, each with its config and already processed commits.
+//! Processing caches are not persisted,
+//! git objects are processed again on demand but their subtrees are found in the reloaded stores.
+
+use std::io::{Read, Write};
+
+use hyper_ast::store::snapshot::{id_from_u64, id_to_u64, Codecs, SnapshotError};
+
+use crate::{
+    git::Repo,
+    multi_preprocessed::PreProcessedRepositories,
+    processing::{erased::ParametrizedCommitProc, RepoConfig},
+    Commit, SimpleStores,
+};
+
+/// Codecs for all components that can be found in the stores of a [`crate::preprocessed::RepositoryProcessor`].
+pub fn codecs() -> Codecs {
+    let mut codecs = Codecs::default();
+    #[cfg(feature = "java")]
+    codecs.register_type::<hyper_ast_gen_ts_java::types::Type>("java_type");
+    #[cfg(feature = "cpp")]
+    codecs.register_type::<hyper_ast_gen_ts_cpp::types::Type>("cpp_type");
+    #[cfg(feature = "maven")]
+    codecs.register_type::<hyper_ast_gen_ts_xml::types::Type>("xml_type");
+    #[cfg(feature = "ts")]
+    codecs.register_type::<hyper_ast_gen_ts_ts::types::Type>("ts_type");
+    #[cfg(feature = "tsquery")]
+    codecs.register_type::<hyper_ast_gen_ts_tsquery::types::Type>("tsquery_type");
+    codecs.register_type::<crate::any::types::Type>("any_type");
+    #[cfg(feature = "maven")]
+    codecs.register::<enumset::EnumSet<crate::maven::SemFlags>>(
+        "maven_sem_flags",
+        |x, out| out.extend(x.as_u32().to_le_bytes()),
+        |input| {
+            enumset::EnumSet::try_from_u32(input.u32()?)
+                .ok_or(SnapshotError::Corrupted("bad maven flags"))
+        },
+    );
+    #[cfg(feature = "gradle")]
+    codecs.register::<enumset::EnumSet<crate::gradle::SemFlags>>(
+        "gradle_sem_flags",
+        |x, out| out.extend(x.as_u32().to_le_bytes()),
+        |input| {
+            enumset::EnumSet::try_from_u32(input.u32()?)
+                .ok_or(SnapshotError::Corrupted("bad gradle flags"))
+        },
+    );
+    #[cfg(feature = "maven")]
+    codecs.register::<crate::maven::Project>(
+        "maven_project",
+        |x, out| out.extend(serde_json::to_vec(x).unwrap()),
+        |input| {
+            serde_json::from_slice(input.rest())
+                .map_err(|_| SnapshotError::Corrupted("bad maven project"))
+        },
+    );
+    codecs
+}
+
+fn config_to_u8(config: &RepoConfig) -> u8 {
+    match config {
+        RepoConfig::CppMake => 0,
+        RepoConfig::JavaMaven => 1,
+        RepoConfig::TsNpm => 2,
+        RepoConfig::Any => 3,
+        RepoConfig::JavaGradle => 4,
+    }
+}
+
+fn config_from_u8(x: u8) -> Result<RepoConfig, SnapshotError> {
+    Ok(match x {
+        0 => RepoConfig::CppMake,
+        1 => RepoConfig::JavaMaven,
+        2 => RepoConfig::TsNpm,
+        3 => RepoConfig::Any,
+        4 => RepoConfig::JavaGradle,
+        _ => return Err(SnapshotError::Corrupted("bad repository config")),
+    })
+}
+
+fn write_str(w: &mut impl Write, s: &str) -> std::io::Result<()> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_bytes<const N: usize>(r: &mut impl Read) -> std::io::Result<[u8; N]> {
+    let mut b = [0; N];
+    r.read_exact(&mut b)?;
+    Ok(b)
+}
+
+fn read_str(r: &mut impl Read) -> Result<String, SnapshotError> {
+    let len = u32::from_le_bytes(read_bytes(r)?) as usize;
+    let mut b = vec![0; len];
+    r.read_exact(&mut b)?;
+    String::from_utf8(b).map_err(|_| SnapshotError::Corrupted("string is not utf8"))
+}
+
+fn read_oid(r: &mut impl Read) -> Result<git2::Oid, SnapshotError> {
+    let b: [u8; 20] = read_bytes(r)?;
+    git2::Oid::from_bytes(&b).map_err(|_| SnapshotError::Corrupted("bad git oid"))
+}
+
+fn write_commit(w: &mut impl Write, oid: &git2::Oid, commit: &Commit) -> std::io::Result<()> {
+    w.write_all(oid.as_bytes())?;
+    w.write_all(commit.tree_oid.as_bytes())?;
+    w.write_all(&id_to_u64(commit.ast_root).to_le_bytes())?;
+    w.write_all(&commit.processing_time().to_le_bytes())?;
+    w.write_all(&(commit.memory_used().bytes() as i64).to_le_bytes())?;
+    w.write_all(&(commit.parents.len() as u32).to_le_bytes())?;
+    for p in &commit.parents {
+        w.write_all(p.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_commit(r: &mut impl Read) -> Result<(git2::Oid, Commit), SnapshotError> {
+    let oid = read_oid(r)?;
+    let tree_oid = read_oid(r)?;
+    let ast_root = id_from_u64(u64::from_le_bytes(read_bytes(r)?))
+        .ok_or(SnapshotError::Corrupted("null node identifier"))?;
+    let processing_time = u128::from_le_bytes(read_bytes(r)?);
+    let memory_used = (i64::from_le_bytes(read_bytes(r)?) as isize).into();
+    let len = u32::from_le_bytes(read_bytes(r)?);
+    let parents = (0..len)
+        .map(|_| read_oid(r))
+        .collect::<Result<Vec<_>, _>>()?;
+    let commit = Commit {
+        parents,
+        processing_time,
+        memory_used,
+        ast_root,
+        tree_oid,
+    };
+    Ok((oid, commit))
+}
+
+impl PreProcessedRepositories {
+    /// Persists the stores, the registered repositories and their processed commits.
+    pub fn save_snapshot(&self, w: &mut impl Write) -> Result<(), SnapshotError> {
+        self.processor.main_stores.save_snapshot(w, &codecs())?;
+        w.write_all(&(self.repo_configs.len() as u32).to_le_bytes())?;
+        for (repo, config) in &self.repo_configs {
+            write_str(w, &repo.forge.to_string())?;
+            write_str(w, &repo.user)?;
+            write_str(w, &repo.name)?;
+            w.write_all(&[config_to_u8(config)])?;
+            let handle = self.configs[repo];
+            let proc = self
+                .processor
+                .processing_systems
+                .by_id(&handle.0)
+                .unwrap()
+                .get(handle.1);
+            let commits: Vec<_> = proc.commits().collect();
+            w.write_all(&(commits.len() as u64).to_le_bytes())?;
+            for (oid, commit) in commits {
+                write_commit(w, oid, commit)?;
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Reloads repositories persisted with [`PreProcessedRepositories::save_snapshot`],
+    /// commits already processed are not processed again.
+    ///
+    /// Must be called before building any other node in this process.
+    pub fn load_snapshot(r: &mut impl Read) -> Result<Self, SnapshotError> {
+        let main_stores = SimpleStores::load_snapshot(r, &codecs())?;
+        let mut repositories = Self::default();
+        repositories.processor.main_stores = main_stores;
+        let len = u32::from_le_bytes(read_bytes(r)?);
+        for _ in 0..len {
+            let forge = read_str(r)?
+                .parse()
+                .map_err(|_| SnapshotError::Corrupted("bad forge"))?;
+            let user = read_str(r)?;
+            let name = read_str(r)?;
+            let config = config_from_u8(read_bytes::<1>(r)?[0])?;
+            let repo = Repo { forge, user, name };
+            let handle = repositories.register_config(repo, config).config;
+            let proc = repositories
+                .processor
+                .processing_systems
+                .by_id_mut(&handle.0)
+                .unwrap()
+                .get_mut(handle.1);
+            let count = u64::from_le_bytes(read_bytes(r)?);
+            for _ in 0..count {
+                let (oid, commit) = read_commit(r)?;
+                proc.restore_commit(oid, commit)
+                    .map_err(|_| SnapshotError::Corrupted("config cannot hold commits"))?;
+            }
+        }
+        Ok(repositories)
+    }
+}
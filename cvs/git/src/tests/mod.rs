@@ -2,6 +2,7 @@ pub mod direct_type_ref;
 #[cfg(test)]
 pub mod extends_package_local;
 pub mod obj_creation;
+pub(crate) mod repository;
 
 use crate::{git::fetch_github_repository, preprocessed::PreProcessedRepository};
 use hyper_ast_gen_ts_java::impact::element::RefsEnum;
@@ -26,7 +27,7 @@ fn example_main() {
 
     let mut preprocessed = PreProcessedRepository::new(&repo_name);
     preprocessed.pre_process(
-        &mut fetch_github_repository(&repo_name),
+        &mut fetch_github_repository(&repo_name).unwrap(),
         before,
         after,
         dir_path,
@@ -115,7 +116,7 @@ fn example_process_make_cpp_project() {
     let name = &"official-stockfish/Stockfish";
     let mut preprocessed = PreProcessedRepository::new(name);
     let a = preprocessed.pre_process_make_project_with_limit(
-        &mut fetch_github_repository(name),
+        &mut fetch_github_repository(name).unwrap(),
         "",
         // "587bc647d7d14b53d8625c4446006e23a4acd82a",
         "f97c5b6909d22277f28e3dea2f146e9314d634dc", // issue with operator[]('K') = KB;
@@ -0,0 +1,51 @@
+//! Small git repositories written on the fly, to test the processors without fetching anything.
+
+use std::{env, fs, path::PathBuf, time};
+
+use git2::{Oid, Repository};
+
+pub(crate) enum Entry {
+    File(&'static str),
+    Dir(Vec<(&'static str, Entry)>),
+}
+
+/// A repository in the temporary directory, removed when dropped.
+pub(crate) struct TempRepository {
+    path: PathBuf,
+    pub(crate) repository: Repository,
+}
+
+impl TempRepository {
+    pub(crate) fn new(name: &str) -> Self {
+        let nanos = time::SystemTime::UNIX_EPOCH.elapsed().unwrap().as_nanos();
+        let path = env::temp_dir().join(format!("{name}_{nanos}"));
+        let repository = Repository::init(&path).unwrap();
+        Self { path, repository }
+    }
+
+    /// Writes the tree made of `entries`, returns its oid.
+    pub(crate) fn tree(&self, entries: Vec<(&'static str, Entry)>) -> Oid {
+        let mut builder = self.repository.treebuilder(None).unwrap();
+        for (name, entry) in entries {
+            let (oid, mode) = match entry {
+                Entry::File(text) => (self.repository.blob(text.as_bytes()).unwrap(), 0o100644),
+                Entry::Dir(entries) => (self.tree(entries), 0o040000),
+            };
+            builder.insert(name, oid, mode).unwrap();
+        }
+        builder.write().unwrap()
+    }
+
+    /// The oid of the object at `path` in the tree `root`.
+    pub(crate) fn oid(&self, root: Oid, path: &str) -> Oid {
+        let tree = self.repository.find_tree(root).unwrap();
+        let oid = tree.get_path(std::path::Path::new(path)).unwrap().id();
+        oid
+    }
+}
+
+impl Drop for TempRepository {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
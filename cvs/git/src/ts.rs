@@ -0,0 +1,83 @@
+use crate::{processing::ObjectName, Accumulator, TStore, PROPAGATE_ERROR_ON_BAD_CST_NODE};
+
+use hyper_ast::{
+    hashed::SyntaxNodeHashs,
+    nodes::HashSize,
+    store::{
+        defaults::{LabelIdentifier, NodeIdentifier},
+        nodes::NodeInserter,
+    },
+    tree_gen::SubTreeMetrics,
+};
+use hyper_ast_gen_ts_ts::types::{TIdN, TsEnabledTypeStore};
+
+use hyper_ast_gen_ts_ts::legion as ts_tree_gen;
+
+pub(crate) fn handle_ts_file<'stores, 'cache, 'b: 'stores, NS>(
+    tree_gen: &mut ts_tree_gen::TsTreeGen<'stores, 'cache, TStore, NS>,
+    name: &ObjectName,
+    text: &'b [u8],
+) -> Result<ts_tree_gen::FNode<NS::IdN>, ()>
+where
+    TStore: TsEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
+{
+    let tree = match ts_tree_gen::TsTreeGen::<TStore>::tree_sitter_parse(text) {
+        Ok(tree) => tree,
+        Err(tree) => {
+            log::warn!("bad CST: {:?}", name.try_str());
+            log::debug!("{}", tree.root_node().to_sexp());
+            if PROPAGATE_ERROR_ON_BAD_CST_NODE {
+                return Err(());
+            } else {
+                tree
+            }
+        }
+    };
+    Ok(tree_gen.generate_file(name.as_bytes(), text, tree.walk()))
+}
+
+pub struct TsAcc<IdN = NodeIdentifier> {
+    pub(crate) name: String,
+    pub(crate) children: Vec<IdN>,
+    pub(crate) children_names: Vec<LabelIdentifier>,
+    pub(crate) metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
+}
+
+impl<IdN> TsAcc<IdN> {
+    pub(crate) fn new(name: String) -> Self {
+        Self {
+            name,
+            children_names: Default::default(),
+            children: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+}
+
+impl<IdN> From<String> for TsAcc<IdN> {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl<IdN> TsAcc<IdN> {
+    pub(crate) fn push(&mut self, name: LabelIdentifier, full_node: ts_tree_gen::Local<IdN>) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.metrics);
+    }
+}
+
+impl<IdN> hyper_ast::tree_gen::Accumulator for TsAcc<IdN> {
+    type Node = (LabelIdentifier, ts_tree_gen::Local<IdN>);
+    fn push(&mut self, (name, full_node): Self::Node) {
+        self.children.push(full_node.compressed_node);
+        self.children_names.push(name);
+        self.metrics.acc(full_node.metrics);
+    }
+}
+
+impl<IdN> Accumulator for TsAcc<IdN> {
+    type Unlabeled = ts_tree_gen::Local<IdN>;
+}
@@ -0,0 +1,451 @@
+use std::{iter::Peekable, path::Components};
+
+use git2::{Oid, Repository};
+use hyper_ast::{
+    hashed::SyntaxNodeHashs,
+    nodes::HashSize,
+    store::{
+        defaults::LabelIdentifier,
+        nodes::{NodeInserter, PendingInsertion},
+        SimpleStores,
+    },
+    types::LabelStore,
+};
+use hyper_ast_gen_ts_ts::{
+    legion::{self as ts_gen, eq_node},
+    types::Type,
+};
+use tuples::CombinConcat;
+
+use crate::{
+    git::BasicGitObject,
+    npm::NpmModuleAcc,
+    preprocessed::RepositoryProcessor,
+    processing::{erased::CommitProcExt, CacheHolding, InFiles, ObjectName},
+    ts::TsAcc,
+    Processor, TStore,
+};
+
+pub(crate) fn prepare_dir_exploration(tree: git2::Tree) -> Vec<BasicGitObject> {
+    tree.iter()
+        .rev()
+        .map(TryInto::try_into)
+        .filter_map(|x| x.ok())
+        .collect()
+}
+
+/// Installed dependencies are not part of the sources of a package, even when they are committed
+pub(crate) fn is_ignored_dir(name: &ObjectName) -> bool {
+    name.as_bytes().eq(b"node_modules")
+}
+
+pub struct TsProcessor<'repo, 'prepro, 'd, 'c, Acc> {
+    repository: &'repo Repository,
+    prepro: &'prepro mut RepositoryProcessor,
+    stack: Vec<(Oid, Vec<BasicGitObject>, Acc)>,
+    pub dir_path: &'d mut Peekable<Components<'c>>,
+    parameters: &'d crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+}
+
+impl<'repo, 'b, 'd, 'c, Acc: From<String>> TsProcessor<'repo, 'b, 'd, 'c, Acc> {
+    pub(crate) fn new(
+        repository: &'repo Repository,
+        prepro: &'b mut RepositoryProcessor,
+        dir_path: &'d mut Peekable<Components<'c>>,
+        name: &ObjectName,
+        oid: git2::Oid,
+        parameters: &'d crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+    ) -> Self {
+        let tree = repository.find_tree(oid).unwrap();
+        let prepared = prepare_dir_exploration(tree);
+        let name = name.try_into().unwrap();
+        let stack = vec![(oid, prepared, Acc::from(name))];
+        Self {
+            stack,
+            repository,
+            prepro,
+            dir_path,
+            parameters,
+        }
+    }
+}
+
+impl<'repo, 'b, 'd, 'c> Processor<TsAcc> for TsProcessor<'repo, 'b, 'd, 'c, TsAcc> {
+    fn pre(&mut self, current_object: BasicGitObject) {
+        match current_object {
+            BasicGitObject::Tree(oid, name) => {
+                if is_ignored_dir(&name) {
+                    log::debug!("ignored directory {:?}", name.try_str());
+                    return;
+                }
+                self.handle_tree_cached(oid, name);
+            }
+            BasicGitObject::Blob(oid, name) => {
+                if crate::processing::file_sys::Ts::matches(&name) {
+                    self.prepro
+                        .help_handle_ts_file(
+                            oid,
+                            &mut self.stack.last_mut().unwrap().2,
+                            &name,
+                            self.repository,
+                            *self.parameters,
+                        )
+                        .unwrap();
+                } else {
+                    log::debug!("not ts source file {:?}", name.try_str());
+                }
+            }
+        }
+    }
+    fn post(&mut self, oid: Oid, acc: TsAcc) -> Option<ts_gen::Local> {
+        let name = acc.name.clone();
+        let key = (oid, name.as_bytes().into());
+        let full_node = make(acc, self.prepro.main_stores_mut());
+        self.prepro
+            .processing_systems
+            .mut_or_default::<TsProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .insert(key, full_node.clone());
+        let name = self.prepro.main_stores.label_store.get_or_insert(name);
+        if self.stack.is_empty() {
+            Some(full_node)
+        } else {
+            let w = &mut self.stack.last_mut().unwrap().2;
+            assert!(
+                !w.children_names.contains(&name),
+                "{:?} {:?}",
+                w.children_names,
+                name
+            );
+            w.push(name, full_node);
+            None
+        }
+    }
+
+    fn stack(&mut self) -> &mut Vec<(Oid, Vec<BasicGitObject>, TsAcc)> {
+        &mut self.stack
+    }
+}
+
+impl<'repo, 'prepro, 'd, 'c> TsProcessor<'repo, 'prepro, 'd, 'c, TsAcc> {
+    fn handle_tree_cached(&mut self, oid: Oid, name: ObjectName) {
+        if let Some(already) = self
+            .prepro
+            .processing_systems
+            .mut_or_default::<TsProcessorHolder>()
+            .get_caches_mut()
+            .object_map
+            .get(&(oid, name.clone()))
+        {
+            // reinit already computed node for post order
+            let full_node = already.clone();
+            let w = &mut self.stack.last_mut().unwrap().2;
+            let name = self.prepro.intern_object_name(&name);
+            assert!(!w.children_names.contains(&name));
+            w.push(name, full_node);
+        } else {
+            log::info!("tree {:?}", name.try_str());
+            let tree = self.repository.find_tree(oid).unwrap();
+            let prepared: Vec<BasicGitObject> = prepare_dir_exploration(tree);
+            self.stack
+                .push((oid, prepared, TsAcc::new(name.try_into().unwrap())));
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Parameter;
+#[derive(Default)]
+pub(crate) struct TsProcessorHolder(Option<TsProc>);
+pub(crate) struct TsProc {
+    parameter: Parameter,
+    cache: crate::processing::caches::Ts,
+    commits: std::collections::HashMap<git2::Oid, crate::Commit>,
+}
+impl crate::processing::erased::Parametrized for TsProcessorHolder {
+    type T = Parameter;
+    fn register_param(
+        &mut self,
+        t: Self::T,
+    ) -> crate::processing::erased::ParametrizedCommitProcessorHandle {
+        let l = self
+            .0
+            .iter()
+            .position(|x| &x.parameter == &t)
+            .unwrap_or_else(|| {
+                let l = 0;
+                self.0 = Some(TsProc {
+                    parameter: t,
+                    cache: Default::default(),
+                    commits: Default::default(),
+                });
+                l
+            });
+        use crate::processing::erased::ConfigParametersHandle;
+        use crate::processing::erased::ParametrizedCommitProc;
+        use crate::processing::erased::ParametrizedCommitProcessorHandle;
+        ParametrizedCommitProcessorHandle(self.erased_handle(), ConfigParametersHandle(l))
+    }
+}
+impl crate::processing::erased::CommitProc for TsProc {
+    fn process_root_tree(
+        &mut self,
+        _repository: &git2::Repository,
+        _tree_oid: &git2::Oid,
+    ) -> hyper_ast::store::defaults::NodeIdentifier {
+        unimplemented!("ts files are processed as part of npm packages")
+    }
+
+    fn prepare_processing(
+        &self,
+        _repository: &git2::Repository,
+        _commit_builder: crate::preprocessed::CommitBuilder,
+    ) -> Box<dyn crate::processing::erased::PreparedCommitProc> {
+        unimplemented!("ts files are processed as part of npm packages")
+    }
+
+    fn get_commit(&self, commit_oid: git2::Oid) -> Option<&crate::Commit> {
+        self.commits.get(&commit_oid)
+    }
+
+    fn retain_nodes(
+        &mut self,
+        alive: &dyn Fn(&hyper_ast::store::defaults::NodeIdentifier) -> bool,
+    ) {
+        use crate::processing::caches::RetainNodes;
+        self.cache.retain_nodes(alive);
+        self.commits.retain(|_, c| alive(&c.ast_root));
+    }
+}
+
+impl crate::processing::erased::CommitProcExt for TsProc {
+    type Holder = TsProcessorHolder;
+}
+impl crate::processing::erased::ParametrizedCommitProc2 for TsProcessorHolder {
+    type Proc = TsProc;
+
+    fn with_parameters_mut(
+        &mut self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &mut Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_mut().unwrap()
+    }
+
+    fn with_parameters(
+        &self,
+        parameters: crate::processing::erased::ConfigParametersHandle,
+    ) -> &Self::Proc {
+        assert_eq!(0, parameters.0);
+        self.0.as_ref().unwrap()
+    }
+
+    fn procs_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Proc> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+impl CacheHolding<crate::processing::caches::Ts> for TsProc {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Ts {
+        &mut self.cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Ts {
+        &self.cache
+    }
+}
+impl CacheHolding<crate::processing::caches::Ts> for TsProcessorHolder {
+    fn get_caches_mut(&mut self) -> &mut crate::processing::caches::Ts {
+        &mut self.0.as_mut().unwrap().cache
+    }
+    fn get_caches(&self) -> &crate::processing::caches::Ts {
+        &self.0.as_ref().unwrap().cache
+    }
+}
+
+#[cfg(feature = "ts")]
+impl RepositoryProcessor {
+    pub(crate) fn handle_ts_blob(
+        &mut self,
+        oid: Oid,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+    ) -> Result<ts_gen::Local, crate::ParseErr> {
+        self.processing_systems
+            .caching_blob_handler::<crate::processing::file_sys::Ts>()
+            .handle2(oid, repository, &name, parameters, |c, n, t| {
+                let line_break = if t.contains(&b'\r') {
+                    "\r\n".as_bytes().to_vec()
+                } else {
+                    "\n".as_bytes().to_vec()
+                };
+                crate::ts::handle_ts_file(
+                    &mut ts_gen::TsTreeGen {
+                        line_break,
+                        stores: &mut self.main_stores,
+                        md_cache: &mut c
+                            .mut_or_default::<TsProcessorHolder>()
+                            .get_caches_mut()
+                            .md_cache,
+                    },
+                    n,
+                    t,
+                )
+                .map_err(|_| crate::ParseErr::IllFormed)
+                .map(|x| x.local.clone())
+            })
+    }
+
+    pub(crate) fn help_handle_ts_file(
+        &mut self,
+        oid: Oid,
+        parent: &mut TsAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let full_node = self.handle_ts_blob(oid, name, repository, parameters)?;
+        let name = self.intern_object_name(name);
+        assert!(!parent.children_names.contains(&name));
+        parent.push(name, full_node);
+        Ok(())
+    }
+
+    pub(crate) fn help_handle_ts_file2(
+        &mut self,
+        oid: Oid,
+        parent: &mut NpmModuleAcc,
+        name: &ObjectName,
+        repository: &Repository,
+        parameters: crate::processing::erased::ParametrizedCommitProcessor2Handle<TsProc>,
+    ) -> Result<(), crate::ParseErr> {
+        let full_node = self.handle_ts_blob(oid, name, repository, parameters)?;
+        let name = self.intern_object_name(name);
+        assert!(!parent.children_names.contains(&name));
+        parent.push_source_file(name, full_node);
+        Ok(())
+    }
+
+    pub(crate) fn handle_ts_directory<'b, 'd: 'b>(
+        &mut self,
+        repository: &Repository,
+        dir_path: &'b mut Peekable<Components<'d>>,
+        name: &ObjectName,
+        oid: git2::Oid,
+    ) -> ts_gen::Local {
+        let h = self
+            .processing_systems
+            .mut_or_default::<TsProcessorHolder>();
+        let handle = TsProc::register_param(h, Parameter);
+        TsProcessor::<TsAcc>::new(repository, self, dir_path, name, oid, &handle).process()
+    }
+
+    pub(crate) fn help_handle_ts_folder<'a, 'b, 'c, 'd: 'c>(
+        &'a mut self,
+        repository: &'b Repository,
+        dir_path: &'c mut Peekable<Components<'d>>,
+        oid: Oid,
+        name: &ObjectName,
+    ) -> <TsAcc as hyper_ast::tree_gen::Accumulator>::Node {
+        let full_node = self.handle_ts_directory(repository, dir_path, name, oid);
+        let name = self.intern_object_name(name);
+        (name, full_node)
+    }
+}
+
+fn make<NS: NodeInserter>(
+    acc: TsAcc<NS::IdN>,
+    stores: &mut SimpleStores<TStore, NS>,
+) -> ts_gen::Local<NS::IdN> {
+    use hyper_ast::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        tree_gen::SubTreeMetrics,
+    };
+    let node_store = &mut stores.node_store;
+    let label_store = &mut stores.label_store;
+
+    let hashs = acc.metrics.hashs;
+    let size = acc.metrics.size + 1;
+    let height = acc.metrics.height + 1;
+    let size_no_spaces = acc.metrics.size_no_spaces + 1;
+    let hbuilder = hashed::Builder::new(hashs, &Type::Directory, &acc.name, size_no_spaces);
+    let hashable = &hbuilder.most_discriminating();
+    let label_id = label_store.get_or_insert(acc.name.clone());
+
+    let eq = eq_node::<_, NS>(&Type::Directory, Some(&label_id), &acc.children);
+
+    let insertion = node_store.prepare_insertion(&hashable, eq);
+
+    let hashs = hbuilder.build();
+    let metrics = SubTreeMetrics {
+        size,
+        height,
+        size_no_spaces,
+        hashs,
+    };
+
+    if let Some(id) = insertion.occupied_id() {
+        return ts_gen::Local {
+            compressed_node: id,
+            metrics,
+        };
+    }
+
+    let node_id = compress(
+        insertion,
+        label_id,
+        acc.children,
+        acc.children_names,
+        size,
+        height,
+        size_no_spaces,
+        hashs,
+    );
+
+    ts_gen::Local {
+        compressed_node: node_id,
+        metrics,
+    }
+}
+
+fn compress<P: PendingInsertion>(
+    insertion: P,
+    label_id: LabelIdentifier,
+    children: Vec<P::IdN>,
+    children_names: Vec<LabelIdentifier>,
+    size: u32,
+    height: u32,
+    size_no_spaces: u32,
+    hashs: SyntaxNodeHashs<HashSize>,
+) -> P::IdN {
+    use hyper_ast::{
+        filter::BloomSize,
+        store::nodes::compo::{self, CS},
+    };
+    macro_rules! insert {
+        ( $c0:expr, $($c:expr),* $(,)? ) => {{
+            let c = $c0;
+            $(
+                let c = c.concat($c);
+            )*
+            insertion.insert(c)
+        }};
+    }
+    match children.len() {
+        0 => insert!((Type::Directory, label_id, hashs, BloomSize::None),),
+        _ => {
+            assert_eq!(children_names.len(), children.len());
+            let c = (
+                Type::Directory,
+                label_id,
+                compo::Size(size),
+                compo::Height(height),
+                compo::SizeNoSpaces(size_no_spaces),
+                hashs,
+                CS(children_names.into_boxed_slice()),
+                CS(children.into_boxed_slice()),
+            );
+            insert!(c, (BloomSize::Much,))
+        }
+    }
+}
@@ -9,10 +9,17 @@ use hyper_ast::{
 use hyper_ast_gen_ts_cpp::types::CppEnabledTypeStore;
 #[cfg(feature = "java")]
 use hyper_ast_gen_ts_java::types::JavaEnabledTypeStore;
+#[cfg(feature = "ts")]
+use hyper_ast_gen_ts_ts::types::TsEnabledTypeStore;
+#[cfg(feature = "tsquery")]
+use hyper_ast_gen_ts_tsquery::types::TsQueryEnabledTypeStore;
 #[cfg(feature = "maven")]
 use hyper_ast_gen_ts_xml::types::XmlEnabledTypeStore;
 
-use crate::no_space::{MIdN, NoSpaceWrapper};
+use crate::{
+    any,
+    no_space::{MIdN, NoSpaceWrapper},
+};
 
 #[repr(u8)]
 pub enum TStore {
@@ -51,7 +58,10 @@ impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
         on_multi!(n, [
                 hyper_ast_gen_ts_java,
                 hyper_ast_gen_ts_cpp,
-                hyper_ast_gen_ts_xml
+                hyper_ast_gen_ts_xml,
+                hyper_ast_gen_ts_ts,
+                hyper_ast_gen_ts_tsquery,
+                any
             ],
             (t, u) => u::types::as_any(t),
             {
@@ -68,7 +78,10 @@ impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
         on_multi!(n, [
                 hyper_ast_gen_ts_java,
                 hyper_ast_gen_ts_cpp,
-                hyper_ast_gen_ts_xml
+                hyper_ast_gen_ts_xml,
+                hyper_ast_gen_ts_ts,
+                hyper_ast_gen_ts_tsquery,
+                any
             ],
             (_t, u) => From::<&'static (dyn LangRef<AnyType>)>::from(&u::types::Lang),
             {
@@ -84,7 +97,10 @@ impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
         on_multi!(n, [
                 hyper_ast_gen_ts_java,
                 hyper_ast_gen_ts_cpp,
-                hyper_ast_gen_ts_xml
+                hyper_ast_gen_ts_xml,
+                hyper_ast_gen_ts_ts,
+                hyper_ast_gen_ts_tsquery,
+                any
             ],
             (t, u) => {
                 let ty = <u::types::Lang as hyper_ast::types::Lang<_>>::to_u16(*t);
@@ -108,7 +124,10 @@ impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
         on_multi!(n, [
                 hyper_ast_gen_ts_java,
                 hyper_ast_gen_ts_cpp,
-                hyper_ast_gen_ts_xml
+                hyper_ast_gen_ts_xml,
+                hyper_ast_gen_ts_ts,
+                hyper_ast_gen_ts_tsquery,
+                any
             ],
             (t, u) =>{
                 if let Ok(tt) = m.get_component::<u::types::Type>() {
@@ -330,7 +349,10 @@ impl<'a> TypeStore<NoSpaceWrapper<'a, NodeIdentifier>> for &TStore {
         on_multi!(n.as_ref(), [
                 hyper_ast_gen_ts_java,
                 hyper_ast_gen_ts_cpp,
-                hyper_ast_gen_ts_xml
+                hyper_ast_gen_ts_xml,
+                hyper_ast_gen_ts_ts,
+                hyper_ast_gen_ts_tsquery,
+                any
             ],
             (t, u) =>{
                 if let Ok(tt) = m.as_ref().get_component::<u::types::Type>() {
@@ -493,11 +515,309 @@ impl<'a>
     }
 }
 
+impl<'a> TypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>>
+    for TStore
+{
+    type Ty = hyper_ast_gen_ts_ts::types::Type;
+    const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+    fn resolve_type(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+    ) -> Self::Ty {
+        *n.get_component::<hyper_ast_gen_ts_ts::types::Type>().unwrap()
+    }
+
+    fn resolve_lang(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+    ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+        todo!("{:?}", n)
+    }
+
+    type Marshaled = TypeIndex;
+
+    fn marshal_type(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+    ) -> Self::Marshaled {
+        todo!("{:?}", n)
+    }
+
+    fn type_eq(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+        m: &HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>,
+    ) -> bool {
+        todo!("{:?} {:?}", n, m)
+    }
+}
+impl<'a>
+    TsEnabledTypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_ts::types::TIdN<NodeIdentifier>>>
+    for TStore
+{
+    const LANG: u16 = 0;
+
+    fn _intern(l: u16, t: u16) -> Self::Ty {
+        unimplemented!("remove _intern {} {}", l, t)
+    }
+
+    // the ts generator interns the types of the nodes it builds
+    fn intern(&self, t: hyper_ast_gen_ts_ts::types::Type) -> Self::Ty {
+        t
+    }
+
+    fn resolve(&self, t: Self::Ty) -> hyper_ast_gen_ts_ts::types::Type {
+        t
+    }
+}
+
+impl<'a> TypeStore<HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>>
+    for TStore
+{
+    type Ty = hyper_ast_gen_ts_tsquery::types::Type;
+    const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+    fn resolve_type(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>,
+    ) -> Self::Ty {
+        *n.get_component::<hyper_ast_gen_ts_tsquery::types::Type>().unwrap()
+    }
+
+    fn resolve_lang(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>,
+    ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+        todo!("{:?}", n)
+    }
+
+    type Marshaled = TypeIndex;
+
+    fn marshal_type(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>,
+    ) -> Self::Marshaled {
+        todo!("{:?}", n)
+    }
+
+    fn type_eq(
+        &self,
+        n: &HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>,
+        m: &HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>,
+    ) -> bool {
+        todo!("{:?} {:?}", n, m)
+    }
+}
+impl<'a>
+    TsQueryEnabledTypeStore<
+        HashedNodeRef<'a, hyper_ast_gen_ts_tsquery::types::TIdN<NodeIdentifier>>,
+    > for TStore
+{
+    const LANG: u16 = 0;
+
+    fn _intern(l: u16, t: u16) -> Self::Ty {
+        unimplemented!("remove _intern {} {}", l, t)
+    }
+
+    // the query generator interns the types of the nodes it builds
+    fn intern(&self, t: hyper_ast_gen_ts_tsquery::types::Type) -> Self::Ty {
+        t
+    }
+
+    fn resolve(&self, t: Self::Ty) -> hyper_ast_gen_ts_tsquery::types::Type {
+        t
+    }
+}
+
+/// Same type store for nodes of a [`hyper_ast::store::nodes::hecs::NodeStore`].
+mod hecs_impls {
+    use super::*;
+
+    use hyper_ast::store::nodes::hecs::{HashedNodeRef, NodeIdentifier};
+
+    macro_rules! on_multi {
+        ($n:expr, [$on0:ident $(, $on:ident)*], ($with:ident, $with1:ident) => $body:expr, $default:expr) => {
+            if let Some($with) = $n.get_component::<&$on0::types::Type>() {
+                use $on0 as $with1;
+                $body
+            } $( else if let Some($with) = $n.get_component::<&$on::types::Type>() {
+                use $on as $with1;
+                $body
+            })* else {
+                $default
+            }
+        };
+    }
+
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            on_multi!(n, [
+                    hyper_ast_gen_ts_java,
+                    hyper_ast_gen_ts_cpp,
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_tsquery,
+                    any
+                ],
+                (t, u) => u::types::as_any(&t),
+                panic!("{:?}", n)
+            )
+        }
+
+        fn resolve_lang(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            on_multi!(n, [
+                    hyper_ast_gen_ts_java,
+                    hyper_ast_gen_ts_cpp,
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_tsquery,
+                    any
+                ],
+                (_t, u) => From::<&'static (dyn LangRef<AnyType>)>::from(&u::types::Lang),
+                panic!("{:?}", n)
+            )
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            on_multi!(n, [
+                    hyper_ast_gen_ts_java,
+                    hyper_ast_gen_ts_cpp,
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_tsquery,
+                    any
+                ],
+                (t, u) => {
+                    let ty = <u::types::Lang as hyper_ast::types::Lang<_>>::to_u16(*t);
+                    let lang = hyper_ast::types::LangRef::<u::types::Type>::name(
+                        &u::types::Lang,
+                    );
+                    TypeIndex { lang, ty }
+                },
+                panic!("{:?}", n)
+            )
+        }
+
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+            m: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> bool {
+            on_multi!(n, [
+                    hyper_ast_gen_ts_java,
+                    hyper_ast_gen_ts_cpp,
+                    hyper_ast_gen_ts_xml,
+                    hyper_ast_gen_ts_ts,
+                    hyper_ast_gen_ts_tsquery,
+                    any
+                ],
+                (t, u) => {
+                    if let Some(tt) = m.get_component::<&u::types::Type>() {
+                        *t == *tt
+                    } else {
+                        false
+                    }
+                },
+                panic!("{:?}", n)
+            )
+        }
+    }
+
+    /// Nodes of the generator of `$lang`, which interns types as they are.
+    macro_rules! typed_store {
+        ($lang:ident) => {
+            impl<'a> TypeStore<HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>> for TStore {
+                type Ty = $lang::types::Type;
+                const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+                fn resolve_type(
+                    &self,
+                    n: &HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>,
+                ) -> Self::Ty {
+                    *n.get_component::<&$lang::types::Type>().unwrap()
+                }
+
+                fn resolve_lang(
+                    &self,
+                    _n: &HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>,
+                ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+                    From::<&'static (dyn LangRef<Self::Ty>)>::from(&$lang::types::Lang)
+                }
+
+                type Marshaled = TypeIndex;
+
+                fn marshal_type(
+                    &self,
+                    n: &HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>,
+                ) -> Self::Marshaled {
+                    let t = *n.get_component::<&$lang::types::Type>().unwrap();
+                    TypeIndex {
+                        lang: LangRef::<Self::Ty>::name(&$lang::types::Lang),
+                        ty: <$lang::types::Lang as hyper_ast::types::Lang<_>>::to_u16(t),
+                    }
+                }
+
+                fn type_eq(
+                    &self,
+                    n: &HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>,
+                    m: &HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>,
+                ) -> bool {
+                    self.resolve_type(n) == self.resolve_type(m)
+                }
+            }
+        };
+        ($lang:ident, $enabled:ident) => {
+            typed_store!($lang);
+            impl<'a> $enabled<HashedNodeRef<'a, $lang::types::TIdN<NodeIdentifier>>> for TStore {
+                const LANG: u16 = 0;
+
+                fn _intern(l: u16, t: u16) -> Self::Ty {
+                    unimplemented!("remove _intern {} {}", l, t)
+                }
+
+                fn intern(&self, t: $lang::types::Type) -> Self::Ty {
+                    t
+                }
+
+                fn resolve(&self, t: Self::Ty) -> $lang::types::Type {
+                    t
+                }
+            }
+        };
+    }
+
+    use hyper_ast_gen_ts_cpp as cpp;
+    use hyper_ast_gen_ts_java as java;
+    use hyper_ast_gen_ts_ts as ts;
+    use hyper_ast_gen_ts_tsquery as tsquery;
+    use hyper_ast_gen_ts_xml as xml;
+
+    typed_store!(java);
+    impl<'a> JavaEnabledTypeStore<HashedNodeRef<'a, java::types::TIdN<NodeIdentifier>>> for TStore {}
+    typed_store!(xml, XmlEnabledTypeStore);
+    typed_store!(cpp, CppEnabledTypeStore);
+    typed_store!(ts, TsEnabledTypeStore);
+    typed_store!(tsquery, TsQueryEnabledTypeStore);
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum MultiType {
     Java(hyper_ast_gen_ts_java::types::Type),
     Cpp(hyper_ast_gen_ts_cpp::types::Type),
     Xml(hyper_ast_gen_ts_xml::types::Type),
+    Ts(hyper_ast_gen_ts_ts::types::Type),
+    Query(hyper_ast_gen_ts_tsquery::types::Type),
+    /// opaque files and directories, see [`crate::any`]
+    Any(crate::any::types::Type),
 }
 
 macro_rules! on_multi {
@@ -506,6 +826,9 @@ macro_rules! on_multi {
             MultiType::Java($with) => $body,
             MultiType::Cpp($with) => $body,
             MultiType::Xml($with) => $body,
+            MultiType::Ts($with) => $body,
+            MultiType::Query($with) => $body,
+            MultiType::Any($with) => $body,
         }
     };
     ($on1:ident, $on2:ident, ($with1:ident,$with2:ident) => $body:expr, _ => $default:expr) => {
@@ -513,6 +836,9 @@ macro_rules! on_multi {
             (MultiType::Java($with1), MultiType::Java($with2)) => $body,
             (MultiType::Cpp($with1), MultiType::Cpp($with2)) => $body,
             (MultiType::Xml($with1), MultiType::Xml($with2)) => $body,
+            (MultiType::Ts($with1), MultiType::Ts($with2)) => $body,
+            (MultiType::Query($with1), MultiType::Query($with2)) => $body,
+            (MultiType::Any($with1), MultiType::Any($with2)) => $body,
             _ => $default,
         }
     };
@@ -2,21 +2,18 @@
 use std::{collections::HashMap, fmt::Debug, vec};
 
 use crate::{types::TIdN, TNode};
-use legion::world::EntryRef;
 use tuples::CombinConcat;
 
 use hyper_ast::{
     filter::BloomSize,
     full::FullNode,
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
-    nodes::Space,
+    nodes::{HashSize, Space},
     store::{
         nodes::{
-            legion::{
-                compo::{self, NoSpacesCS, CS},
-                HashedNodeRef, NodeIdentifier, PendingInsert,
-            },
-            DefaultNodeStore as NodeStore,
+            compo::{self, NoSpacesCS, CS},
+            ComponentsBuilder, ComponentsRef, DefaultNodeIdentifier as NodeIdentifier,
+            DefaultNodeStore as NodeStore, NodeInserter, PendingInsertion,
         },
         SimpleStores,
     },
@@ -34,25 +31,25 @@ use crate::types::{CppEnabledTypeStore, Type};
 
 pub type LabelIdentifier = hyper_ast::store::labels::DefaultLabelIdentifier;
 
-pub struct CppTreeGen<'store, 'cache, TS> {
+pub struct CppTreeGen<'store, 'cache, TS, NS: NodeInserter = NodeStore> {
     pub line_break: Vec<u8>,
-    pub stores: &'store mut SimpleStores<TS>,
-    pub md_cache: &'cache mut MDCache,
+    pub stores: &'store mut SimpleStores<TS, NS>,
+    pub md_cache: &'cache mut MDCache<NS::IdN>,
 }
 
-pub type MDCache = HashMap<NodeIdentifier, MD>;
+pub type MDCache<IdN = NodeIdentifier> = HashMap<IdN, MD>;
 
 // NOTE only keep compute intensive metadata (where space/time tradeoff is worth storing)
 // eg. decls refs, maybe hashes but not size and height
 // * metadata: computation results from concrete code of node and its children
 // they can be qualitative metadata .eg a hash or they can be quantitative .eg lines of code
 pub struct MD {
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     ana: Option<PartialAnalysis>,
 }
 
-impl From<Local> for MD {
-    fn from(x: Local) -> Self {
+impl<IdN> From<Local<IdN>> for MD {
+    fn from(x: Local<IdN>) -> Self {
         MD {
             metrics: x.metrics,
             ana: x.ana,
@@ -67,14 +64,14 @@ pub type Global<'a> = SpacedGlobalData<'a>;
 pub struct PartialAnalysis {}
 
 #[derive(Debug, Clone)]
-pub struct Local {
-    pub compressed_node: NodeIdentifier,
-    pub metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+pub struct Local<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
+    pub metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     pub ana: Option<PartialAnalysis>,
 }
 
-impl Local {
-    fn acc(self, acc: &mut Acc) {
+impl<IdN: Copy> Local<IdN> {
+    fn acc(self, acc: &mut Acc<IdN>) {
         if self.metrics.size_no_spaces > 0 {
             acc.no_space.push(self.compressed_node)
         }
@@ -85,19 +82,19 @@ impl Local {
     }
 }
 
-pub struct Acc {
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+pub struct Acc<IdN = NodeIdentifier> {
+    simple: BasicAccumulator<Type, IdN>,
+    no_space: Vec<IdN>,
     labeled: bool,
     start_byte: usize,
     end_byte: usize,
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     ana: Option<PartialAnalysis>,
     padding_start: usize,
     indentation: Spaces,
 }
 
-impl Debug for Acc {
+impl<IdN: Debug> Debug for Acc<IdN> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Acc")
             .field("simple", &self.simple)
@@ -113,20 +110,20 @@ impl Debug for Acc {
     }
 }
 
-pub type FNode = FullNode<BasicGlobalData, Local>;
-impl Accumulator for Acc {
-    type Node = FNode;
+pub type FNode<IdN = NodeIdentifier> = FullNode<BasicGlobalData, Local<IdN>>;
+impl<IdN: Copy> Accumulator for Acc<IdN> {
+    type Node = FNode<IdN>;
     fn push(&mut self, full_node: Self::Node) {
         full_node.local.acc(self);
     }
 }
 
-impl AccIndentation for Acc {
+impl<IdN: Copy> AccIndentation for Acc<IdN> {
     fn indentation<'a>(&'a self) -> &'a Spaces {
         &self.indentation
     }
 }
-impl WithByteRange for Acc {
+impl<IdN> WithByteRange for Acc<IdN> {
     fn begin_byte(&self) -> usize {
         self.start_byte
     }
@@ -240,10 +237,12 @@ impl<'a> hyper_ast::tree_gen::parser::TreeCursor<'a, TNode<'a>> for TTreeCursor<
     }
 }
 
-impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdentifier>>>>
-    ZippedTreeGen for CppTreeGen<'store, 'cache, TS>
+impl<'store, 'cache, TS, NS> ZippedTreeGen for CppTreeGen<'store, 'cache, TS, NS>
+where
+    TS: CppEnabledTypeStore<NS::NodeRef<'store, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Stores = SimpleStores<TS>;
+    type Stores = SimpleStores<TS, NS>;
     type Text = [u8];
     type Node<'b> = TNode<'b>;
     type TreeCursor<'b> = TTreeCursor<'b>;
@@ -376,28 +375,30 @@ impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIden
     }
 }
 
-impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdentifier>>>>
-    CppTreeGen<'store, 'cache, TS>
+impl<'store, 'cache, TS, NS> CppTreeGen<'store, 'cache, TS, NS>
+where
+    TS: CppEnabledTypeStore<NS::NodeRef<'store, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
     fn make_spacing(
         &mut self,
         spacing: Vec<u8>, //Space>,
-    ) -> Local {
+    ) -> Local<NS::IdN> {
         let bytes_len = spacing.len();
         let spacing = std::str::from_utf8(&spacing).unwrap().to_string();
         let spacing_id = self.stores.label_store.get_or_insert(spacing.clone());
-        let hbuilder: hashed::Builder<SyntaxNodeHashs<u32>> =
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
             hashed::Builder::new(Default::default(), &Type::Spaces, &spacing, 1);
         let hsyntax = hbuilder.most_discriminating();
         let hashable = &hsyntax;
 
-        let eq = |x: EntryRef| {
-            let t = x.get_component::<Type>();
-            if t != Ok(&Type::Spaces) {
+        let eq = |x: NS::Entry<'_>| {
+            let t = x.get::<Type>();
+            if t.as_deref() != Some(&Type::Spaces) {
                 return false;
             }
-            let l = x.get_component::<LabelIdentifier>();
-            if l != Ok(&spacing_id) {
+            let l = x.get::<LabelIdentifier>();
+            if l.as_deref() != Some(&spacing_id) {
                 return false;
             }
             true
@@ -412,12 +413,8 @@ impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIden
         let compressed_node = if let Some(id) = insertion.occupied_id() {
             id
         } else {
-            let vacant = insertion.vacant();
             let bytes_len = compo::BytesLen(bytes_len.try_into().unwrap());
-            NodeStore::insert_after_prepare(
-                vacant,
-                (Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None),
-            )
+            insertion.insert((Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None))
         };
         Local {
             compressed_node,
@@ -433,9 +430,9 @@ impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIden
 
     pub fn new(
         stores: &'store mut <Self as ZippedTreeGen>::Stores,
-        md_cache: &'cache mut MDCache,
-    ) -> CppTreeGen<'store, 'cache, TS> {
-        CppTreeGen::<'store, 'cache, TS> {
+        md_cache: &'cache mut MDCache<NS::IdN>,
+    ) -> CppTreeGen<'store, 'cache, TS, NS> {
+        CppTreeGen::<'store, 'cache, TS, NS> {
             line_break: "\n".as_bytes().to_vec(),
             stores,
             md_cache,
@@ -459,7 +456,7 @@ impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIden
         name: &[u8],
         text: &'store [u8],
         cursor: tree_sitter::TreeCursor,
-    ) -> FullNode<BasicGlobalData, Local> {
+    ) -> FNode<NS::IdN> {
         let mut global = Global::from(TextedGlobalData::new(Default::default(), text));
         let mut init = self.init_val(text, &TNode(cursor.node()));
         let mut xx = TTreeCursor(cursor);
@@ -514,27 +511,27 @@ impl<'store, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIden
     }
 }
 
-pub fn eq_node<'a, K>(
+pub fn eq_node<'a, K, NS: NodeInserter>(
     kind: &'a K,
     label_id: Option<&'a LabelIdentifier>,
-    children: &'a [NodeIdentifier],
-) -> impl Fn(EntryRef) -> bool + 'a
+    children: &'a [NS::IdN],
+) -> impl Fn(NS::Entry<'_>) -> bool + 'a
 where
     K: 'static + Eq + std::hash::Hash + Copy + std::marker::Send + std::marker::Sync,
 {
-    move |x: EntryRef| {
-        let t = x.get_component::<K>();
-        if t != Ok(kind) {
+    move |x: NS::Entry<'_>| {
+        let t = x.get::<K>();
+        if t.as_deref() != Some(kind) {
             return false;
         }
-        let l = x.get_component::<LabelIdentifier>().ok();
-        if l != label_id {
+        let l = x.get::<LabelIdentifier>();
+        if l.as_deref() != label_id {
             return false;
         } else {
-            let cs = x.get_component::<CS<legion::Entity>>();
+            let cs = x.get::<CS<NS::IdN>>();
             let r = match cs {
-                Ok(CS(cs)) => cs.as_ref() == children,
-                Err(_) => children.is_empty(),
+                Some(cs) => cs.0.as_ref() == children,
+                None => children.is_empty(),
             };
             if !r {
                 return false;
@@ -544,10 +541,12 @@ where
     }
 }
 
-impl<'stores, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>> TreeGen
-    for CppTreeGen<'stores, 'cache, TS>
+impl<'stores, 'cache, TS, NS> TreeGen for CppTreeGen<'stores, 'cache, TS, NS>
+where
+    TS: CppEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Acc = Acc;
+    type Acc = Acc<NS::IdN>;
     type Global = SpacedGlobalData<'stores>;
     fn make(
         &mut self,
@@ -569,7 +568,7 @@ impl<'stores, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeId
         let label_id = label
             .as_ref()
             .map(|label| label_store.get_or_insert(label.as_str()));
-        let eq = eq_node(&interned_kind, label_id.as_ref(), &acc.simple.children);
+        let eq = eq_node::<_, NS>(&interned_kind, label_id.as_ref(), &acc.simple.children);
 
         let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -614,8 +613,7 @@ impl<'stores, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeId
                 // - checking some invariants when adding metadata,
                 // - checking some invariants for indentifying data on debug builds
                 // - tying up parts of accumulator (hyper_ast::tree_genBasicAccumulator) and builder (EntityBuilder).
-                let mut dyn_builder =
-                    hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+                let mut dyn_builder = NS::Builder::default();
                 dyn_builder.add(interned_kind);
                 dyn_builder.add(hashs.clone());
                 dyn_builder.add(compo::BytesLen(
@@ -642,7 +640,7 @@ impl<'stores, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeId
                     }
                 }
 
-                NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build())
+                insertion.insert_built(dyn_builder)
             };
 
             let metrics = SubTreeMetrics {
@@ -666,20 +664,19 @@ impl<'stores, 'cache, TS: CppEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeId
     }
 }
 
-fn compress<T: 'static + std::marker::Send + std::marker::Sync>(
+fn compress<T: 'static + std::marker::Send + std::marker::Sync, P: PendingInsertion>(
     label_id: Option<LabelIdentifier>,
     _ana: &Option<PartialAnalysis>,
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+    simple: BasicAccumulator<Type, P::IdN>,
+    no_space: Vec<P::IdN>,
     // bytes_len: compo::BytesLen,
     size: u32,
     height: u32,
     size_no_spaces: u32,
-    insertion: PendingInsert,
+    insertion: P,
     // hashs: SyntaxNodeHashs<u32,
-    base: (T, SyntaxNodeHashs<u32>, compo::BytesLen),
-) -> legion::Entity {
-    let vacant = insertion.vacant();
+    base: (T, SyntaxNodeHashs<HashSize>, compo::BytesLen),
+) -> P::IdN {
     // let base = (CppEnabledTypeStore::intern_cpp(s,simple.kind), hashs, bytes_len);
     macro_rules! insert {
         ( $c0:expr, $($c:expr),* $(,)? ) => {{
@@ -687,7 +684,7 @@ fn compress<T: 'static + std::marker::Send + std::marker::Sync>(
             $(
                 let c = c.concat($c);
             )*
-            NodeStore::insert_after_prepare(vacant, c)
+            insertion.insert(c)
         }};
     }
     macro_rules! children_dipatch {
@@ -2,8 +2,22 @@ use tree_sitter::Parser;
 
 use crate::types::TStore;
 
-type CppTreeGen<'store, 'cache> = crate::legion::CppTreeGen<'store, 'cache, TStore>;
-type SimpleStores = hyper_ast::store::SimpleStores<TStore>;
+/// Runs the body of a test once per node store backend,
+/// with `SimpleStores` and `CppTreeGen` using the node store of the backend.
+macro_rules! on_each_store {
+    ($backend:ident, $body:block) => {{
+        use hyper_ast::store::nodes::$backend::NodeStore;
+        type SimpleStores = hyper_ast::store::SimpleStores<TStore, NodeStore>;
+        type CppTreeGen<'store, 'cache> =
+            crate::legion::CppTreeGen<'store, 'cache, TStore, NodeStore>;
+        $body
+    }};
+    ($body:block) => {
+        on_each_store!(legion, $body);
+        on_each_store!(hecs, $body);
+    };
+}
+
 #[test]
 pub(crate) fn cpp_tree_sitter_simple() {
     let mut parser = Parser::new();
@@ -63,162 +77,171 @@ int main(int argl, int* argv) {
 
 #[test]
 pub(crate) fn cpp_simple_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 int main(int argl, int* argv) {
     printf("a %1 a",42);
 }"#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 #[test]
 pub(crate) fn cpp_issue_stockfish_movegen_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 void f() {
 f.value < s;
 }
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[test]
 pub(crate) fn cpp_template_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 void f() {
     x.g<T>();
 }
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[test]
 pub(crate) fn cpp_explicit_cast_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 void f() {
     ((unsigned(b) ^ b) * DeBruijn32);
 }
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[test]
 pub(crate) fn cpp_issue_stockfish_endgame_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 
 
 template<>
@@ -226,41 +249,43 @@ ScaleFactor ScalingFunction<KNPK>::apply() const {
 }
 
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[test]
 pub(crate) fn cpp_issue_stockfish_types_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 #ifndef TYPES_H_INCLUDED
 #define TYPES_H_INCLUDED
         
@@ -281,41 +306,43 @@ void f() {}
 #endif // #ifndef TYPES_H_INCLUDED
 
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[test]
 pub(crate) fn cpp_issue_stockfish_tbcore_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 #ifndef TBCORE_H
 #define TBCORE_H
 
@@ -337,42 +364,44 @@ __attribute__((__may_alias__))
 
 #endif
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[allow(non_snake_case)]
 #[test]
 pub(crate) fn cpp_issue_stockfish_TranslationUnitRepeat1_test() {
-    let text = {
-        let source_code1 = r#"
+    on_each_store!({
+        let text = {
+            let source_code1 = r#"
 #ifndef TBCORE_H
 #define TBCORE_H
 
@@ -380,61 +409,64 @@ struct TBEntry {};
 
 #endif
 "#;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
-    // println!("{}", tree.root_node().to_sexp());
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        // print_tree_syntax(&stores.node_store, &stores.label_store, &x.compressed_node);
+        // println!("{}", tree.root_node().to_sexp());
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+    });
 }
 
 #[test]
 pub(crate) fn cpp_def_bl_test() {
-    let text = {
-        let source_code1 = CODE_DEF_BL_SHRINK;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    // println!("{}", tree.root_node().to_sexp());
+    on_each_store!({
+        let text = {
+            let source_code1 = CODE_DEF_BL_SHRINK;
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        // println!("{}", tree.root_node().to_sexp());
+    });
 }
 
 pub(crate) const CODE_DEF_BL_SHRINK: &str = r#"
@@ -446,37 +478,39 @@ pub(crate) const CODE_DEF_BL_SHRINK: &str = r#"
 #[test]
 
 pub(crate) fn cpp_char_literal_test() {
-    let text = {
-        let source_code1 = CODE_CHAR_LIT;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    let entity = x.compressed_node;
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, entity)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
-    );
-    // println!("{}", tree.root_node().to_sexp());
+    on_each_store!({
+        let text = {
+            let source_code1 = CODE_CHAR_LIT;
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        let entity = x.compressed_node;
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, entity)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::TextSerializer::new(&stores, x.compressed_node)
+        );
+        println!(
+            "{}",
+            hyper_ast::nodes::SexpSerializer::new(&stores, x.compressed_node)
+        );
+        // println!("{}", tree.root_node().to_sexp());
+    });
 }
 
 pub(crate) const CODE_CHAR_LIT: &str = r#"
@@ -504,28 +538,30 @@ std::string trace(Position& pos) {
 
 #[test]
 pub(crate) fn cpp_asm_test() {
-    let text = {
-        let source_code1 = CODE_ASM;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    // println!("{}", tree.root_node().to_sexp());
+    on_each_store!({
+        let text = {
+            let source_code1 = CODE_ASM;
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        // println!("{}", tree.root_node().to_sexp());
+    });
 }
 
 pub(crate) const CODE_ASM: &str = r#"
@@ -543,28 +579,30 @@ int f() {
 
 #[test]
 pub(crate) fn cpp_op_test() {
-    let text = {
-        let source_code1 = CODE_OP;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    // println!("{}", tree.root_node().to_sexp());
+    on_each_store!({
+        let text = {
+            let source_code1 = CODE_OP;
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        // println!("{}", tree.root_node().to_sexp());
+    });
 }
 
 // https://github.com/official-stockfish/Stockfish/blob/d55a5a4d81b613e5a82e428770347b06fbd2d9a8/src/position.cpp
@@ -594,28 +632,30 @@ struct PieceLetters : public std::map<char, Piece> {
 
 #[test]
 pub(crate) fn cpp_3_test() {
-    let text = {
-        let source_code1 = CODE_MACRO_SEMICOLON;
-        source_code1.as_bytes()
-    };
-    let tree = match CppTreeGen::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores::default();
-    let mut md_cache = Default::default();
-    let mut tree_gen = CppTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let x = tree_gen.generate_file(b"", text, tree.walk()).local;
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
-    );
-    // println!("{}", tree.root_node().to_sexp());
+    on_each_store!({
+        let text = {
+            let source_code1 = CODE_MACRO_SEMICOLON;
+            source_code1.as_bytes()
+        };
+        let tree = match CppTreeGen::tree_sitter_parse(text) {
+            Ok(t) => t,
+            Err(t) => t,
+        };
+        println!("{:#?}", tree.root_node().to_sexp());
+        let mut stores = SimpleStores::default();
+        let mut md_cache = Default::default();
+        let mut tree_gen = CppTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores: &mut stores,
+            md_cache: &mut md_cache,
+        };
+        let x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        println!(
+            "{}",
+            hyper_ast::nodes::SyntaxSerializer::new(&stores, x.compressed_node)
+        );
+        // println!("{}", tree.root_node().to_sexp());
+    });
 }
 
 pub(crate) const CODE_MACRO_SEMICOLON: &str = r#"
@@ -121,6 +121,91 @@ mod legion_impls {
     }
 }
 
+#[cfg(feature = "hecs")]
+mod hecs_impls {
+    use super::*;
+
+    use hyper_ast::{
+        store::nodes::hecs::{HashedNodeRef, NodeIdentifier},
+        types::TypeIndex,
+    };
+
+    impl<'a> TypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        type Ty = Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Ty {
+            *n.get_component::<&Type>().unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            _n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<&Type>().unwrap() as u16,
+            }
+        }
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+            m: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> bool {
+            *n.get_component::<&Type>().unwrap() == *m.get_component::<&Type>().unwrap()
+        }
+    }
+    impl<'a> CppEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        const LANG: TypeInternalSize = Self::Cpp as u16;
+
+        fn _intern(_l: u16, _t: u16) -> Self::Ty {
+            todo!()
+        }
+        fn intern(&self, t: Type) -> Self::Ty {
+            t
+        }
+
+        fn resolve(&self, t: Self::Ty) -> Type {
+            t
+        }
+    }
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            as_any(&n.get_component::<&Type>().unwrap())
+        }
+
+        fn resolve_lang(
+            &self,
+            _n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<AnyType>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<&Type>().unwrap() as u16,
+            }
+        }
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+            m: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> bool {
+            *n.get_component::<&Type>().unwrap() == *m.get_component::<&Type>().unwrap()
+        }
+    }
+}
+
 pub trait CppEnabledTypeStore<T>: TypeStore<T> {
     const LANG: u16;
     fn intern(&self, t: Type) -> Self::Ty {
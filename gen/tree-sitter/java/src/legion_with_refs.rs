@@ -7,30 +7,38 @@ use hyper_ast::{
     full::FullNode,
     hashed::{HashedNode, IndexingHashBuilder, MetaDataHashsBuilder},
     store::{
-        labels::LabelStore,
-        nodes::legion::{compo::NoSpacesCS, HashedNodeRef, PendingInsert},
+        labels::{LabelInterner, LabelStore},
+        nodes::{
+            compo::NoSpacesCS, ComponentsBuilder, ComponentsRef, HashedNodeRef, NodeInserter,
+            PendingInsertion,
+        },
     },
     tree_gen::{
         parser::Visibility, BasicGlobalData, GlobalData, Parents, PreResult, SpacedGlobalData, SubTreeMetrics, TextedGlobalData, TreeGen, WithByteRange
     },
-    types::{self, AnyType, NodeStoreExt, TypeStore, TypeTrait, WithHashs, WithStats},
+    types::{self, AnyType, NodeStoreExt, TypeStore, TypeTrait},
     utils,
 };
-use legion::world::EntryRef;
-use num::ToPrimitive;
 ///! fully compress all subtrees from a Java CST
-use std::{collections::HashMap, fmt::Debug, hash::Hash, vec};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    vec,
+};
 use string_interner::DefaultSymbol;
 use tuples::CombinConcat;
 
 use hyper_ast::{
     filter::BF,
     filter::{Bloom, BloomSize},
-    hashed::{self, SyntaxNodeHashs, SyntaxNodeHashsKinds},
-    nodes::Space,
+    hashed::{self, SyntaxNodeHashs},
+    nodes::{HashSize, Space},
     store::{
-        nodes::legion::{compo, compo::CS},
         nodes::DefaultNodeStore as NodeStore,
+        nodes::{compo, compo::CS},
         SimpleStores,
     },
     tree_gen::parser::Node as _,
@@ -58,13 +66,11 @@ pub fn hash32<T: ?Sized + Hash>(t: &T) -> u32 {
     utils::clamp_u64_to_u32(&utils::hash(t))
 }
 
-pub type EntryR<'a> = EntryRef<'a>;
-
-pub type NodeIdentifier = legion::Entity;
+pub type NodeIdentifier = hyper_ast::store::nodes::DefaultNodeIdentifier;
 
 // pub struct HashedNodeRef<'a>(EntryRef<'a>);
 
-pub type FNode = FullNode<BasicGlobalData, Local>;
+pub type FNode<IdN = NodeIdentifier> = FullNode<BasicGlobalData, Local<IdN>>;
 
 pub type LabelIdentifier = DefaultSymbol;
 
@@ -72,20 +78,81 @@ pub type LabelIdentifier = DefaultSymbol;
 // SPC: consider spaces ie. add them to the HyperAST,
 // NOTE there is a big issue with the byteLen of subtree then.
 // just provide a view abstracting spaces (see attempt in hyper_diff)
-pub struct JavaTreeGen<'stores, 'cache, TS> {
+/// Nodes and labels are inserted through [`NodeInserter`] and [`LabelInterner`],
+/// eg. to generate files in a hecs node store,
+/// or on several threads with a [`hyper_ast::store::nodes::legion::concurrent::Worker`].
+pub struct JavaTreeGen<'stores, 'cache, TS, NS: NodeInserter = NodeStore, LS = LabelStore> {
     pub line_break: Vec<u8>,
-    pub stores: &'stores mut SimpleStores<TS>,
-    pub md_cache: &'cache mut MDCache,
+    pub stores: &'stores mut SimpleStores<TS, NS, LS>,
+    pub md_cache: &'cache mut MDCache<NS::IdN>,
+}
+
+/// Metadata of generated nodes, see [`MD`].
+///
+/// Can be layered on top of a read only cache,
+/// eg. the cache of a repository shared by generators running concurrently,
+/// it then only holds the metadata of nodes missing from the shared one.
+pub struct MDCache<IdN = NodeIdentifier> {
+    local: HashMap<IdN, MD>,
+    shared: Option<Arc<MDCache<IdN>>>,
+}
+
+impl<IdN> Default for MDCache<IdN> {
+    fn default() -> Self {
+        Self {
+            local: Default::default(),
+            shared: None,
+        }
+    }
 }
 
-pub type MDCache = HashMap<NodeIdentifier, MD>;
+impl<IdN: Eq + Hash> MDCache<IdN> {
+    pub fn layered(shared: Arc<MDCache<IdN>>) -> Self {
+        Self {
+            local: Default::default(),
+            shared: Some(shared),
+        }
+    }
+
+    /// The metadata that is not in the shared cache.
+    pub fn into_local(self) -> HashMap<IdN, MD> {
+        self.local
+    }
+
+    fn get_md(&self, id: &IdN) -> Option<&MD> {
+        self.local
+            .get(id)
+            .or_else(|| self.shared.as_ref()?.get_md(id))
+    }
+
+    fn get_or_insert_md(&mut self, id: IdN, f: impl FnOnce() -> MD) -> &MD {
+        if let Some(md) = self.shared.as_ref().and_then(|x| x.get_md(&id)) {
+            return md;
+        }
+        self.local.entry(id).or_insert_with(f)
+    }
+}
+
+impl<IdN> Deref for MDCache<IdN> {
+    type Target = HashMap<IdN, MD>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.local
+    }
+}
+
+impl<IdN> DerefMut for MDCache<IdN> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.local
+    }
+}
 
 // NOTE only keep compute intensive metadata (where space/time tradeoff is worth storing)
 // eg. decls refs, maybe hashes but not size and height
 // * metadata: computation results from concrete code of node and its children
 // they can be qualitative metadata .eg a hash or they can be quantitative .eg lines of code
 pub struct MD {
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     ana: Option<PartialAnalysis>,
     mcc: Mcc,
 }
@@ -93,8 +160,31 @@ pub struct MD {
 // Enables static reference analysis
 const ANA: bool = false;
 
-impl From<Local> for MD {
-    fn from(x: Local) -> Self {
+impl MD {
+    /// Recovers the metadata of an already stored node,
+    /// needed when the node store was reloaded from a snapshot (the cache is not persisted).
+    /// The partial analysis cannot be recovered from the store, it has to be recomputed from the accumulator.
+    fn from_stored(node: &impl ComponentsRef, kind: &Type) -> Self {
+        let hashs = node
+            .get::<SyntaxNodeHashs<HashSize>>()
+            .map_or(Default::default(), |x| *x);
+        let metrics = SubTreeMetrics {
+            size: node.get::<compo::Size>().map_or(1, |x| x.0),
+            height: node.get::<compo::Height>().map_or(1, |x| x.0),
+            size_no_spaces: node.get::<compo::SizeNoSpaces>().map_or(1, |x| x.0),
+            hashs,
+        };
+        let mcc = node.get::<Mcc>().map_or(Mcc::new(kind), |x| x.clone());
+        MD {
+            metrics,
+            ana: None,
+            mcc,
+        }
+    }
+}
+
+impl<IdN> From<Local<IdN>> for MD {
+    fn from(x: Local<IdN>) -> Self {
         MD {
             metrics: x.metrics,
             ana: x.ana,
@@ -106,17 +196,17 @@ impl From<Local> for MD {
 pub type Global<'a> = SpacedGlobalData<'a>;
 
 #[derive(Debug, Clone)]
-pub struct Local {
-    pub compressed_node: NodeIdentifier,
+pub struct Local<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
     // * metadata: computation results from concrete code of node and its children
     // they can be qualitative metadata .eg a hash or they can be quantitative .eg lines of code
-    pub metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    pub metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     pub ana: Option<PartialAnalysis>,
     pub mcc: Mcc,
 }
 
-impl Local {
-    fn acc(self, acc: &mut Acc) {
+impl<IdN: Copy> Local<IdN> {
+    fn acc(self, acc: &mut Acc<IdN>) {
         if self.metrics.size_no_spaces > 0 {
             acc.no_space.push(self.compressed_node)
         }
@@ -138,32 +228,32 @@ impl Local {
     }
 }
 
-pub struct Acc {
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+pub struct Acc<IdN = NodeIdentifier> {
+    simple: BasicAccumulator<Type, IdN>,
+    no_space: Vec<IdN>,
     labeled: bool,
     start_byte: usize,
     end_byte: usize,
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     ana: Option<PartialAnalysis>,
     mcc: Mcc,
     padding_start: usize,
     indentation: Spaces,
 }
 
-impl Accumulator for Acc {
-    type Node = FullNode<BasicGlobalData, Local>;
+impl<IdN: Copy> Accumulator for Acc<IdN> {
+    type Node = FullNode<BasicGlobalData, Local<IdN>>;
     fn push(&mut self, full_node: Self::Node) {
         full_node.local.acc(self);
     }
 }
 
-impl AccIndentation for Acc {
+impl<IdN: Copy> AccIndentation for Acc<IdN> {
     fn indentation(&self) -> &Spaces {
         &self.indentation
     }
 }
-impl WithByteRange for Acc {
+impl<IdN> WithByteRange for Acc<IdN> {
     fn has_children(&self) -> bool {
         !self.simple.children.is_empty()
     }
@@ -176,7 +266,7 @@ impl WithByteRange for Acc {
         self.end_byte
     }
 }
-impl Debug for Acc {
+impl<IdN: Debug> Debug for Acc<IdN> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Acc")
             .field("simple", &self.simple)
@@ -294,11 +384,16 @@ impl<'a> hyper_ast::tree_gen::parser::TreeCursor<'a, TNode<'a>> for TTreeCursor<
 }
 
 /// Implements [ZippedTreeGen] to offer a visitor for Java generation
-impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>>
-    ZippedTreeGen for JavaTreeGen<'stores, 'cache, TS>
+impl<
+        'stores,
+        'cache,
+        TS: JavaEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+        NS: NodeInserter,
+        LS: LabelInterner,
+    > ZippedTreeGen for JavaTreeGen<'stores, 'cache, TS, NS, LS>
 {
     // type Node1 = SimpleNode1<NodeIdentifier, String>;
-    type Stores = SimpleStores<TS>;
+    type Stores = SimpleStores<TS, NS, LS>;
     type Text = [u8];
     type Node<'b> = TNode<'b>;
     type TreeCursor<'b> = TTreeCursor<'b>;
@@ -432,28 +527,61 @@ pub fn tree_sitter_parse(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitter::
     }
 }
 
-impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>>
-    JavaTreeGen<'stores, 'cache, TS>
+impl<'stores, 'cache, TS> JavaTreeGen<'stores, 'cache, TS>
+where
+    TS: JavaEnabledTypeStore<<NodeStore as NodeInserter>::NodeRef<'stores, TIdN<NodeIdentifier>>>,
+{
+    pub fn new<'a, 'b>(
+        stores: &'a mut SimpleStores<TS>,
+        md_cache: &'b mut MDCache,
+    ) -> JavaTreeGen<'a, 'b, TS> {
+        JavaTreeGen {
+            line_break: "\n".as_bytes().to_vec(),
+            stores,
+            md_cache,
+        }
+    }
+
+    pub fn tree_sitter_parse(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitter::Tree> {
+        let mut parser = tree_sitter::Parser::new();
+        let language = tree_sitter_java::language();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(text, None).unwrap();
+        if tree.root_node().has_error() {
+            Err(tree)
+        } else {
+            Ok(tree)
+        }
+    }
+}
+
+impl<
+        'stores,
+        'cache,
+        TS: JavaEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+        NS: NodeInserter,
+        LS: LabelInterner,
+    > JavaTreeGen<'stores, 'cache, TS, NS, LS>
 {
     fn make_spacing(
         &mut self,
         spacing: Vec<u8>, //Space>,
-    ) -> Local {
+    ) -> Local<NS::IdN> {
         let bytes_len = spacing.len();
         let spacing = std::str::from_utf8(&spacing).unwrap().to_string();
-        let spacing_id = self.stores.label_store.get_or_insert(spacing.clone());
-        let hbuilder: hashed::Builder<SyntaxNodeHashs<u32>> =
+        let spacing_id = self.stores.label_store.intern(&spacing);
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
             hashed::Builder::new(Default::default(), &Type::Spaces, &spacing, 1);
         let hsyntax = hbuilder.most_discriminating();
         let hashable = &hsyntax;
 
-        let eq = |x: EntryRef| {
-            let t = x.get_component::<Type>();
-            if t != Ok(&Type::Spaces) {
+        let eq = |x: NS::Entry<'_>| {
+            let t = x.get::<Type>();
+            if t.as_deref() != Some(&Type::Spaces) {
                 return false;
             }
-            let l = x.get_component::<LabelIdentifier>();
-            if l != Ok(&spacing_id) {
+            let l = x.get::<LabelIdentifier>();
+            if l.as_deref() != Some(&spacing_id) {
                 return false;
             }
             true
@@ -468,12 +596,8 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
         let compressed_node = if let Some(id) = insertion.occupied_id() {
             id
         } else {
-            let vacant = insertion.vacant();
             let bytes_len = compo::BytesLen(bytes_len.try_into().unwrap());
-            NodeStore::insert_after_prepare(
-                vacant,
-                (Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None),
-            )
+            insertion.insert((Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None))
         };
         Local {
             compressed_node,
@@ -488,35 +612,12 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
         }
     }
 
-    pub fn new<'a, 'b>(
-        stores: &'a mut SimpleStores<TS>,
-        md_cache: &'b mut MDCache,
-    ) -> JavaTreeGen<'a, 'b, TS> {
-        JavaTreeGen {
-            line_break: "\n".as_bytes().to_vec(),
-            stores,
-            md_cache,
-        }
-    }
-
-    pub fn tree_sitter_parse(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitter::Tree> {
-        let mut parser = tree_sitter::Parser::new();
-        let language = tree_sitter_java::language();
-        parser.set_language(&language).unwrap();
-        let tree = parser.parse(text, None).unwrap();
-        if tree.root_node().has_error() {
-            Err(tree)
-        } else {
-            Ok(tree)
-        }
-    }
-
     pub fn generate_file<'b: 'stores>(
         &mut self,
         name: &[u8],
         text: &'b [u8],
         cursor: tree_sitter::TreeCursor,
-    ) -> FullNode<BasicGlobalData, Local> {
+    ) -> FNode<NS::IdN> {
         let mut global = Global::from(TextedGlobalData::new(Default::default(), text));
         let mut init = self.init_val(text, &TNode(cursor.node()));
         let mut xx = TTreeCursor(cursor);
@@ -561,16 +662,16 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
         let full_node = self.make(&mut global, acc, label);
 
         match full_node.local.ana.as_ref() {
-            Some(x) => {
+            Some(x) => self.stores.label_store.with_store(|label_store| {
                 log::debug!("refs in file:",);
-                for x in x.display_refs(&self.stores.label_store) {
+                for x in x.display_refs(label_store) {
                     log::debug!("    {}", x);
                 }
                 log::debug!("decls in file:",);
-                for x in x.display_decls(&self.stores.label_store) {
+                for x in x.display_decls(label_store) {
                     log::debug!("    {}", x);
                 }
-            }
+            }),
             None => log::debug!("None"),
         };
 
@@ -590,40 +691,37 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
             || kind == &Type::AnnotationTypeDeclaration
             || kind == &Type::Program
         {
-            Some(PartialAnalysis::init(kind, None, |x| {
-                label_store.get_or_insert(x)
-            }))
+            Some(PartialAnalysis::init(kind, None, |x| label_store.intern(x)))
         } else if kind == &Type::TypeParameter {
-            Some(PartialAnalysis::init(kind, None, |x| {
-                label_store.get_or_insert(x)
-            }))
+            Some(PartialAnalysis::init(kind, None, |x| label_store.intern(x)))
         } else {
             None
         }
     }
 }
 
-pub fn eq_node<'a, K>(
+/// Equality with the node made of `kind`, `label_id` and `children`, to insert it in a `NS`
+pub fn eq_node<'a, K, NS: NodeInserter>(
     kind: &'a K,
     label_id: Option<&'a LabelIdentifier>,
-    children: &'a [NodeIdentifier],
-) -> impl Fn(EntryRef) -> bool + 'a
+    children: &'a [NS::IdN],
+) -> impl Fn(NS::Entry<'_>) -> bool + 'a
 where
     K: 'static + Eq + std::hash::Hash + Copy + std::marker::Send + std::marker::Sync,
 {
-    move |x: EntryRef| {
-        let t = x.get_component::<K>();
-        if t != Ok(kind) {
+    move |x: NS::Entry<'_>| {
+        let t = x.get::<K>();
+        if t.as_deref() != Some(kind) {
             return false;
         }
-        let l = x.get_component::<LabelIdentifier>().ok();
-        if l != label_id {
+        let l = x.get::<LabelIdentifier>();
+        if l.as_deref() != label_id {
             return false;
         } else {
-            let cs = x.get_component::<CS<legion::Entity>>();
+            let cs = x.get::<CS<NS::IdN>>();
             let r = match cs {
-                Ok(CS(cs)) => cs.as_ref() == children,
-                Err(_) => children.is_empty(),
+                Some(cs) => cs.0.as_ref() == children,
+                None => children.is_empty(),
             };
             if !r {
                 return false;
@@ -633,10 +731,15 @@ where
     }
 }
 
-impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>>
-    TreeGen for JavaTreeGen<'stores, 'cache, TS>
+impl<
+        'stores,
+        'cache,
+        TS: JavaEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+        NS: NodeInserter,
+        LS: LabelInterner,
+    > TreeGen for JavaTreeGen<'stores, 'cache, TS, NS, LS>
 {
-    type Acc = Acc;
+    type Acc = Acc<NS::IdN>;
     type Global = SpacedGlobalData<'stores>;
     fn make(
         &mut self,
@@ -660,14 +763,27 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
             // Some notable type can contain very different labels,
             // they might benefit from a particular storing (like a blob storage, even using git's object database )
             // eg. acc.simple.kind == Type::Comment and acc.simple.kind.is_literal()
-            label_store.get_or_insert(label.as_str())
+            label_store.intern(label.as_str())
         });
-        let eq = eq_node(&interned_kind, label_id.as_ref(), &acc.simple.children);
+        let eq = eq_node::<_, NS>(&interned_kind, label_id.as_ref(), &acc.simple.children);
 
         let insertion = node_store.prepare_insertion(&hashable, eq);
 
         let local = if let Some(compressed_node) = insertion.occupied_id() {
-            let md = self.md_cache.get(&compressed_node).unwrap();
+            let md = self.md_cache.get_or_insert_md(compressed_node, || {
+                // eg. generated concurrently by another worker, or reloaded from a snapshot
+                let mut md = insertion
+                    .with_resolved(compressed_node, |x| MD::from_stored(&x, &interned_kind));
+                md.ana = make_partial_ana(
+                    acc.simple.kind,
+                    acc.ana,
+                    label,
+                    &acc.simple.children,
+                    label_store,
+                    &insertion,
+                );
+                md
+            });
             let ana = md.ana.clone();
             let metrics = md.metrics;
             let mcc = md.mcc.clone();
@@ -713,8 +829,7 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
                 // - checking some invariants when adding metadata,
                 // - checking some invariants for indentifying data on debug builds
                 // - tying up parts of accumulator (hyper_ast::tree_genBasicAccumulator) and builder (EntityBuilder).
-                let mut dyn_builder =
-                    hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+                let mut dyn_builder = NS::Builder::default();
                 dyn_builder.add(interned_kind);
                 dyn_builder.add(hashs.clone());
                 dyn_builder.add(compo::BytesLen(
@@ -794,7 +909,7 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
                     }
                 }
 
-                NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build())
+                insertion.insert_built(dyn_builder)
             };
 
             let metrics = SubTreeMetrics {
@@ -829,28 +944,27 @@ impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeI
     }
 }
 
-fn compress<T: 'static + std::marker::Send + std::marker::Sync>(
+fn compress<T: 'static + std::marker::Send + std::marker::Sync, P: PendingInsertion>(
     label_id: Option<LabelIdentifier>,
     ana: &Option<PartialAnalysis>,
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+    simple: BasicAccumulator<Type, P::IdN>,
+    no_space: Vec<P::IdN>,
     // bytes_len: compo::BytesLen,
     size: u32,
     height: u32,
     size_no_spaces: u32,
-    insertion: PendingInsert,
+    insertion: P,
     // hashs: SyntaxNodeHashs<u32>,
     mcc: Mcc,
-    base: (T, SyntaxNodeHashs<u32>, compo::BytesLen),
-) -> legion::Entity {
-    let vacant = insertion.vacant();
+    base: (T, SyntaxNodeHashs<HashSize>, compo::BytesLen),
+) -> P::IdN {
     macro_rules! insert {
         ( $c0:expr, $($c:expr),* $(,)? ) => {{
             let c = $c0;
             $(
                 let c = c.concat($c);
             )*
-            NodeStore::insert_after_prepare(vacant, c)
+            insertion.insert(c)
         }};
     }
     // NOTE needed as macro because I only implemented BulkHasher and Bloom for u8 and u16
@@ -967,19 +1081,19 @@ fn compress<T: 'static + std::marker::Send + std::marker::Sync>(
         (Some(label), _) => children_dipatch!(base, (label,),),
     }
 }
-fn make_partial_ana(
+fn make_partial_ana<P: PendingInsertion>(
     kind: Type,
     ana: Option<PartialAnalysis>,
     label: Option<String>,
-    children: &[legion::Entity],
-    label_store: &mut LabelStore,
-    insertion: &PendingInsert,
+    children: &[P::IdN],
+    label_store: &mut impl LabelInterner,
+    insertion: &P,
 ) -> Option<PartialAnalysis> {
     if !ANA {
         return None;
     }
     partial_ana_extraction(kind, ana, label, children, label_store, insertion)
-        .map(|ana| ana_resolve(kind, ana, label_store))
+        .map(|ana| label_store.with_store(|label_store| ana_resolve(kind, ana, label_store)))
 }
 
 fn ana_resolve(kind: Type, ana: PartialAnalysis, label_store: &LabelStore) -> PartialAnalysis {
@@ -1024,13 +1138,13 @@ fn ana_resolve(kind: Type, ana: PartialAnalysis, label_store: &LabelStore) -> Pa
     }
 }
 
-fn partial_ana_extraction(
+fn partial_ana_extraction<P: PendingInsertion>(
     kind: Type,
     ana: Option<PartialAnalysis>,
     label: Option<String>,
-    children: &[legion::Entity],
-    label_store: &mut LabelStore,
-    insertion: &PendingInsert,
+    children: &[P::IdN],
+    label_store: &mut impl LabelInterner,
+    insertion: &P,
 ) -> Option<PartialAnalysis> {
     let is_possibly_empty = |kind| {
         kind == Type::ArgumentList
@@ -1057,7 +1171,7 @@ fn partial_ana_extraction(
     };
     let mut make = |label| {
         Some(PartialAnalysis::init(&kind, label, |x| {
-            label_store.get_or_insert(x)
+            label_store.intern(x)
         }))
     };
     if kind == Type::Program {
@@ -1072,8 +1186,7 @@ fn partial_ana_extraction(
         };
         make(Some(label))
     } else if kind.is_primitive() {
-        let node = insertion.resolve::<TIdN<NodeIdentifier>>(children[0]);
-        let ty = node.get_type();
+        let ty = insertion.with_resolved(children[0], |node| *node.get::<Type>().unwrap());
         let label = ty.to_str();
         make(Some(label))
     } else if let Some(ana) = ana {
@@ -1094,10 +1207,7 @@ fn partial_ana_extraction(
             || kind == Type::FormalParameters
             || kind == Type::AnnotationArgumentList
         {
-            if !children
-                .iter()
-                .all(|x| !insertion.resolve::<TIdN<NodeIdentifier>>(*x).has_children())
-            {
+            if !children.iter().all(|x| !has_children(insertion, *x)) {
                 // eg. an empty body/block/paramlist/...
                 log::error!("{:?} should only contains leafs", &kind);
             }
@@ -1109,11 +1219,7 @@ fn partial_ana_extraction(
             None
         }
     } else {
-        if !children.is_empty()
-            && children
-                .iter()
-                .all(|x| !insertion.resolve::<TIdN<NodeIdentifier>>(*x).has_children())
-        {
+        if !children.is_empty() && children.iter().all(|x| !has_children(insertion, *x)) {
             // eg. an empty body/block/paramlist/...
             log::error!("{:?} should only contains leafs", kind);
         }
@@ -1121,20 +1227,29 @@ fn partial_ana_extraction(
     }
 }
 
-impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, AnyType>>>
-    hyper_ast::types::NodeStore<NodeIdentifier> for JavaTreeGen<'stores, 'cache, TS>
+fn has_children<P: PendingInsertion>(insertion: &P, id: P::IdN) -> bool {
+    insertion.with_resolved(id, |node| {
+        node.get::<CS<P::IdN>>()
+            .map_or(false, |cs| !cs.0.is_empty())
+    })
+}
+
+impl<'stores, 'cache, TS> hyper_ast::types::NodeStore<NodeIdentifier>
+    for JavaTreeGen<'stores, 'cache, TS>
+where
+    TS: JavaEnabledTypeStore<<NodeStore as NodeInserter>::NodeRef<'stores, AnyType>>,
 {
-    type R<'a> = HashedNodeRef<'a,NodeIdentifier> where Self: 'a, 'stores:'a;
+    type R<'a> = HashedNodeRef<'a> where Self: 'a, 'stores:'a;
 
     fn resolve(&self, id: &NodeIdentifier) -> Self::R<'_> {
         self.stores.node_store.resolve(*id)
     }
 }
 
-impl<'stores, 'cache, TS: JavaEnabledTypeStore<HashedNodeRef<'stores, AnyType>>>
-    NodeStoreExt<HashedNode> for JavaTreeGen<'stores, 'cache, TS>
+impl<'stores, 'cache, TS> NodeStoreExt<HashedNode> for JavaTreeGen<'stores, 'cache, TS>
 where
-    <TS as TypeStore<HashedNodeRef<'stores, AnyType>>>::Ty: TypeTrait,
+    TS: JavaEnabledTypeStore<<NodeStore as NodeInserter>::NodeRef<'stores, AnyType>>,
+    <TS as TypeStore<<NodeStore as NodeInserter>::NodeRef<'stores, AnyType>>>::Ty: TypeTrait,
 {
     fn build_then_insert(
         &mut self,
@@ -1180,30 +1295,17 @@ where
             let local = {
                 // print_tree_syntax(&self.stores.node_store, &self.stores.label_store, &c);
                 // println!();
-                let md = self.md_cache.get(&c);
+                let md = self.md_cache.get_md(&c);
                 let (ana, metrics, mcc) = if let Some(md) = md {
                     let ana = md.ana.clone();
                     let metrics = md.metrics;
                     let mcc = md.mcc.clone();
                     (ana, metrics, mcc)
                 } else {
-                    let node: HashedNodeRef<_> = self.stores.node_store.resolve(c);
-                    let hashs = SyntaxNodeHashs {
-                        structt: WithHashs::hash(&node, &SyntaxNodeHashsKinds::Struct),
-                        label: WithHashs::hash(&node, &SyntaxNodeHashsKinds::Label),
-                        syntax: WithHashs::hash(&node, &SyntaxNodeHashsKinds::Syntax),
-                    };
-                    let kind: TS::Ty = todo!(); //node.get_type();
-                    let metrics = SubTreeMetrics {
-                        size: node.size().to_u32().unwrap(),
-                        height: node.height().to_u32().unwrap(),
-                        size_no_spaces: node.size_no_spaces().to_u32().unwrap(),
-                        hashs,
-                    };
-                    let mcc = node
-                        .get_component::<Mcc>()
-                        .map_or(Mcc::new(&kind), |x| x.clone());
-                    (None, metrics, mcc)
+                    let md = self.stores.node_store.with_resolved(c, |x| {
+                        MD::from_stored(&x, x.get::<Type>().as_deref().unwrap())
+                    });
+                    (None, md.metrics, md.mcc)
                 };
                 Local {
                     compressed_node: c,
@@ -1232,12 +1334,15 @@ where
             let hashable = &hsyntax;
 
             let label_id = l;
-            let eq = eq_node(&interned_kind, label_id.as_ref(), &acc.simple.children);
+            let eq =
+                eq_node::<_, NodeStore>(&interned_kind, label_id.as_ref(), &acc.simple.children);
 
             let insertion = node_store.prepare_insertion(&hashable, eq);
 
             let local = if let Some(id) = insertion.occupied_id() {
-                let md = self.md_cache.get(&id).unwrap();
+                let md = self.md_cache.get_or_insert_md(id, || {
+                    insertion.with_resolved(id, |x| MD::from_stored(&x, &interned_kind))
+                });
                 let ana = md.ana.clone();
                 let metrics = md.metrics;
                 let mcc = md.mcc.clone();
@@ -21,215 +21,142 @@ use crate::{
     types::{TIdN, TStore},
 };
 
-fn run(text: &[u8]) {
-    let mut stores = SimpleStores {
-        label_store: LabelStore::new(),
-        type_store: TStore::default(),
-        node_store: NodeStore::new(),
-    };
-    let mut md_cache = Default::default();
-    let mut java_tree_gen = JavaTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-
-    let tree = match legion_with_refs::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{}", tree.root_node().to_sexp());
-    let full_node = java_tree_gen.generate_file(b"", text, tree.walk());
-
-    println!();
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(
-            &*java_tree_gen.stores,
-            full_node.local.compressed_node
-        )
-    );
-    println!(
-        "{}",
-        hyper_ast::nodes::SexpSerializer::new(
-            &*java_tree_gen.stores,
-            full_node.local.compressed_node
-        )
-    );
-    stdout().flush().unwrap();
-
-    // let mut out = IoOut { stream: stdout() };
-    // serialize(
-    //     &java_tree_gen.stores.node_store,
-    //     &java_tree_gen.stores.label_store,
-    //     &full_node.local.compressed_node,
-    //     &mut out,
-    //     &std::str::from_utf8(&java_tree_gen.line_break).unwrap(),
-    // );
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(
-            &*java_tree_gen.stores,
-            full_node.local.compressed_node
-        )
-    )
-}
-#[test]
-fn test_cases() {
-    let cases = [
-        CASE_1,
-        CASE_1_1,
-        CASE_1_2,
-        CASE_1_3,
-        CASE_1_4,
-        CASE_1_5,
-        CASE_1_5,
-        CASE_1_6,
-        CASE_1_7,
-        CASE_1_8,
-        CASE_1_9,
-        CASE_1_10,
-        CASE_2,
-        CASE_3,
-        CASE_4,
-        CASE_5,
-        CASE_6,
-        CASE_7,
-        CASE_8,
-        CASE_8_1,
-        CASE_9,
-        CASE_10,
-        CASE_11,
-        CASE_11_BIS,
-        CASE_12,
-        CASE_13,
-        CASE_14,
-        CASE_15,
-        CASE_15_1,
-        CASE_15_2,
-        CASE_16,
-        CASE_17,
-        CASE_18,
-        CASE_19,
-        CASE_20,
-        CASE_21,
-        CASE_22,
-        CASE_23,
-        CASE_24,
-        CASE_25,
-        CASE_26,
-        CASE_27,
-        CASE_28,
-        CASE_29,
-        CASE_30,
-        CASE_31,
-        CASE_32,
-        CASE_33,
-        A,
-    ];
-    for case in cases {
-        run(case.as_bytes())
-    }
-}
-
-#[test]
-fn test_equals() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace"))
-        .is_test(true)
-        .init();
-    let text = CASE_33.as_bytes();
-    let mut stores = SimpleStores {
-        label_store: LabelStore::new(),
-        type_store: TStore::default(),
-        node_store: NodeStore::new(),
-    };
-    let mut md_cache = Default::default();
-    let mut java_tree_gen = JavaTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
-    let tree = match legion_with_refs::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{}", tree.root_node().to_sexp());
-    let full_node = java_tree_gen.generate_file(b"", text, tree.walk());
-
-    println!();
-    println!(
-        "{}",
-        hyper_ast::nodes::SyntaxSerializer::new(
-            &*java_tree_gen.stores,
-            full_node.local.compressed_node
-        )
-    );
-    println!();
-    stdout().flush().unwrap();
-
-    println!(
-        "{}",
-        hyper_ast::nodes::TextSerializer::new(
-            &*java_tree_gen.stores,
-            full_node.local.compressed_node
-        )
-    );
-
-    {
-        let stores = java_tree_gen.stores;
-        // playing with refs
-        let a = &full_node.local.compressed_node;
-        let Some(mut ana) = full_node.local.ana else {
-            panic!("None");
-        };
-        println!("refs:",);
-        ana.print_refs(&stores.label_store);
-
-        let b = stores.node_store.resolve(*a);
-        use hyper_ast::types::LabelStore;
-        macro_rules! scoped_ref {
-            ( $o:expr, $i:expr ) => {{
-                let o = $o;
-                let i = $i;
-                let f = IdentifierFormat::from(i);
-                let i = stores.label_store.get_or_insert(i);
-                let i = LabelPtr::new(i, f);
-                ana.solver.intern_ref(RefsEnum::ScopedIdentifier(o, i))
-            }};
-        }
-        let root = ana.solver.intern(RefsEnum::Root);
-        let i = scoped_ref!(root, "B");
-        let d = ana.solver.nodes.with(i);
-        let c = b.check(d);
-        match c {
-            BloomResult::MaybeContain => println!("Maybe contains B"),
-            BloomResult::DoNotContain => println!("Do not contains B"),
-        }
-    }
-    //     use hyper_ast::position::extract_position;
-    //     let mut position = extract_position(&java_tree_gen.stores, d_it.parents(), d_it.offsets());
-    //     position.set_len(b.get_bytes_len() as usize);
-    //     println!("position: {:?}", position);
-}
-
-#[test]
-fn test_special() {
-    // let mut parser: Parser, old_tree: Option<&Tree>
-    let mut stores = SimpleStores {
-        label_store: LabelStore::new(),
-        type_store: TStore::default(),
-        node_store: NodeStore::new(),
-    };
-
-    let mut md_cache = Default::default();
-    let mut java_tree_gen = JavaTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-        md_cache: &mut md_cache,
-    };
+/// Generates the same files in each node store backend, which must be equally usable by the generator.
+macro_rules! store_agnostic {
+    ($backend:ident) => {
+        mod $backend {
+            use super::*;
+            use hyper_ast::store::nodes::$backend::NodeStore;
+
+            fn run(text: &[u8]) {
+                let mut stores = SimpleStores {
+                    label_store: LabelStore::new(),
+                    type_store: TStore::default(),
+                    node_store: NodeStore::new(),
+                };
+                let mut md_cache = Default::default();
+                let mut java_tree_gen = JavaTreeGen {
+                    line_break: "\n".as_bytes().to_vec(),
+                    stores: &mut stores,
+                    md_cache: &mut md_cache,
+                };
+
+                let tree = match legion_with_refs::tree_sitter_parse(text) {
+                    Ok(t) => t,
+                    Err(t) => t,
+                };
+                println!("{}", tree.root_node().to_sexp());
+                let full_node = java_tree_gen.generate_file(b"", text, tree.walk());
+
+                println!();
+                println!(
+                    "{}",
+                    hyper_ast::nodes::SyntaxSerializer::new(
+                        &*java_tree_gen.stores,
+                        full_node.local.compressed_node
+                    )
+                );
+                println!(
+                    "{}",
+                    hyper_ast::nodes::SexpSerializer::new(
+                        &*java_tree_gen.stores,
+                        full_node.local.compressed_node
+                    )
+                );
+                stdout().flush().unwrap();
+
+                // let mut out = IoOut { stream: stdout() };
+                // serialize(
+                //     &java_tree_gen.stores.node_store,
+                //     &java_tree_gen.stores.label_store,
+                //     &full_node.local.compressed_node,
+                //     &mut out,
+                //     &std::str::from_utf8(&java_tree_gen.line_break).unwrap(),
+                // );
+                println!(
+                    "{}",
+                    hyper_ast::nodes::TextSerializer::new(
+                        &*java_tree_gen.stores,
+                        full_node.local.compressed_node
+                    )
+                )
+            }
+            #[test]
+            fn test_cases() {
+                let cases = [
+                    CASE_1,
+                    CASE_1_1,
+                    CASE_1_2,
+                    CASE_1_3,
+                    CASE_1_4,
+                    CASE_1_5,
+                    CASE_1_5,
+                    CASE_1_6,
+                    CASE_1_7,
+                    CASE_1_8,
+                    CASE_1_9,
+                    CASE_1_10,
+                    CASE_2,
+                    CASE_3,
+                    CASE_4,
+                    CASE_5,
+                    CASE_6,
+                    CASE_7,
+                    CASE_8,
+                    CASE_8_1,
+                    CASE_9,
+                    CASE_10,
+                    CASE_11,
+                    CASE_11_BIS,
+                    CASE_12,
+                    CASE_13,
+                    CASE_14,
+                    CASE_15,
+                    CASE_15_1,
+                    CASE_15_2,
+                    CASE_16,
+                    CASE_17,
+                    CASE_18,
+                    CASE_19,
+                    CASE_20,
+                    CASE_21,
+                    CASE_22,
+                    CASE_23,
+                    CASE_24,
+                    CASE_25,
+                    CASE_26,
+                    CASE_27,
+                    CASE_28,
+                    CASE_29,
+                    CASE_30,
+                    CASE_31,
+                    CASE_32,
+                    CASE_33,
+                    A,
+                ];
+                for case in cases {
+                    run(case.as_bytes())
+                }
+            }
 
-    let text = {
-        let source_code1 = "package p.y;
+            #[test]
+            fn test_special() {
+                // let mut parser: Parser, old_tree: Option<&Tree>
+                let mut stores = SimpleStores {
+                    label_store: LabelStore::new(),
+                    type_store: TStore::default(),
+                    node_store: NodeStore::new(),
+                };
+
+                let mut md_cache = Default::default();
+                let mut java_tree_gen = JavaTreeGen {
+                    line_break: "\n".as_bytes().to_vec(),
+                    stores: &mut stores,
+                    md_cache: &mut md_cache,
+                };
+
+                let text = {
+                    let source_code1 = "package p.y;
 public class A {
     class A0 {
         int a = 0xffff;
@@ -261,24 +188,24 @@ public class A {
      }
  }
     }";
-        // let source_code1 = "class A {
-        //     class A0 {
-        //         int a = 0xffff;
-        // }
-        //     }
-        // ";
-        source_code1.as_bytes()
-    };
-    let tree = match legion_with_refs::tree_sitter_parse(text) {
-        Ok(t) => t,
-        Err(t) => t,
-    };
-    println!("{}", tree.root_node().to_sexp());
-
-    let _full_node = java_tree_gen.generate_file(b"", text, tree.walk());
-
-    let text = {
-        let source_code1 = "class A {
+                    // let source_code1 = "class A {
+                    //     class A0 {
+                    //         int a = 0xffff;
+                    // }
+                    //     }
+                    // ";
+                    source_code1.as_bytes()
+                };
+                let tree = match legion_with_refs::tree_sitter_parse(text) {
+                    Ok(t) => t,
+                    Err(t) => t,
+                };
+                println!("{}", tree.root_node().to_sexp());
+
+                let _full_node = java_tree_gen.generate_file(b"", text, tree.walk());
+
+                let text = {
+                    let source_code1 = "class A {
     class A0 {
         int a = 0xffff;
     }
@@ -361,13 +288,78 @@ public class A {
      }
  }
     }";
-        // let source_code1 = "class A {
-        //     class A0 {
-        //         int a = 0xffff;
-        // }
-        //     }
-        // ";
-        source_code1.as_bytes()
+                    // let source_code1 = "class A {
+                    //     class A0 {
+                    //         int a = 0xffff;
+                    // }
+                    //     }
+                    // ";
+                    source_code1.as_bytes()
+                };
+                let tree = match legion_with_refs::tree_sitter_parse(text) {
+                    Ok(t) => t,
+                    Err(t) => t,
+                };
+                println!("{}", tree.root_node().to_sexp());
+                let full_node = java_tree_gen.generate_file(b"", text, tree.walk());
+
+                println!("debug full node: {:?}", &full_node);
+                // let mut out = String::new();
+
+                println!(
+                    "{}",
+                    hyper_ast::nodes::SyntaxSerializer::new(
+                        &*java_tree_gen.stores,
+                        full_node.local.compressed_node
+                    )
+                );
+                stdout().flush().unwrap();
+
+                let mut out = BuffOut {
+                    buff: "".to_owned(),
+                };
+                use std::fmt::Write;
+                write!(
+                    out,
+                    "{}",
+                    hyper_ast::nodes::TextSerializer::new(
+                        &*java_tree_gen.stores,
+                        full_node.local.compressed_node
+                    )
+                );
+                assert_eq!(std::str::from_utf8(text).unwrap(), out.buff);
+
+                println!("{:?}", java_tree_gen.stores.node_store);
+                println!("{}", java_tree_gen.stores.label_store);
+
+                let mu = memusage();
+                drop(java_tree_gen);
+                let mu = mu - memusage();
+                println!("mu {}", mu);
+            }
+        }
+    };
+}
+
+store_agnostic!(legion);
+store_agnostic!(hecs);
+
+#[test]
+fn test_equals() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace"))
+        .is_test(true)
+        .init();
+    let text = CASE_33.as_bytes();
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: NodeStore::new(),
+    };
+    let mut md_cache = Default::default();
+    let mut java_tree_gen = JavaTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+        md_cache: &mut md_cache,
     };
     let tree = match legion_with_refs::tree_sitter_parse(text) {
         Ok(t) => t,
@@ -376,9 +368,7 @@ public class A {
     println!("{}", tree.root_node().to_sexp());
     let full_node = java_tree_gen.generate_file(b"", text, tree.walk());
 
-    println!("debug full node: {:?}", &full_node);
-    // let mut out = String::new();
-
+    println!();
     println!(
         "{}",
         hyper_ast::nodes::SyntaxSerializer::new(
@@ -386,29 +376,52 @@ public class A {
             full_node.local.compressed_node
         )
     );
+    println!();
     stdout().flush().unwrap();
 
-    let mut out = BuffOut {
-        buff: "".to_owned(),
-    };
-    use std::fmt::Write;
-    write!(
-        out,
+    println!(
         "{}",
         hyper_ast::nodes::TextSerializer::new(
             &*java_tree_gen.stores,
             full_node.local.compressed_node
         )
     );
-    assert_eq!(std::str::from_utf8(text).unwrap(), out.buff);
 
-    println!("{:?}", java_tree_gen.stores.node_store);
-    println!("{}", java_tree_gen.stores.label_store);
+    {
+        let stores = java_tree_gen.stores;
+        // playing with refs
+        let a = &full_node.local.compressed_node;
+        let Some(mut ana) = full_node.local.ana else {
+            panic!("None");
+        };
+        println!("refs:",);
+        ana.print_refs(&stores.label_store);
 
-    let mu = memusage();
-    drop(java_tree_gen);
-    let mu = mu - memusage();
-    println!("mu {}", mu);
+        let b = stores.node_store.resolve(*a);
+        use hyper_ast::types::LabelStore;
+        macro_rules! scoped_ref {
+            ( $o:expr, $i:expr ) => {{
+                let o = $o;
+                let i = $i;
+                let f = IdentifierFormat::from(i);
+                let i = stores.label_store.get_or_insert(i);
+                let i = LabelPtr::new(i, f);
+                ana.solver.intern_ref(RefsEnum::ScopedIdentifier(o, i))
+            }};
+        }
+        let root = ana.solver.intern(RefsEnum::Root);
+        let i = scoped_ref!(root, "B");
+        let d = ana.solver.nodes.with(i);
+        let c = b.check(d);
+        match c {
+            BloomResult::MaybeContain => println!("Maybe contains B"),
+            BloomResult::DoNotContain => println!("Do not contains B"),
+        }
+    }
+    //     use hyper_ast::position::extract_position;
+    //     let mut position = extract_position(&java_tree_gen.stores, d_it.parents(), d_it.offsets());
+    //     position.set_len(b.get_bytes_len() as usize);
+    //     println!("position: {:?}", position);
 }
 
 struct IoOut<W: std::io::Write> {
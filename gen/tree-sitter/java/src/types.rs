@@ -154,15 +154,89 @@ mod legion_impls {
             todo!("{:?} {:?}", n, m)
         }
     }
-    pub fn as_any(t: &Type) -> AnyType {
-        let t = <Java as hyper_ast::types::Lang<Type>>::to_u16(*t);
-        let t = <Java as hyper_ast::types::Lang<Type>>::make(t);
-        let t: &'static dyn HyperType = t;
-        t.into()
+}
+
+#[cfg(feature = "hecs")]
+mod hecs_impls {
+    use super::*;
+
+    use hyper_ast::{
+        store::nodes::hecs::{HashedNodeRef, NodeIdentifier},
+        types::TypeIndex,
+    };
+
+    impl<'a> TypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        type Ty = Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Ty {
+            *n.get_component::<&Type>().unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            _n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Type>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<&Type>().unwrap() as u16,
+            }
+        }
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+            m: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> bool {
+            *n.get_component::<&Type>().unwrap() == *m.get_component::<&Type>().unwrap()
+        }
+    }
+    impl<'a> JavaEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {}
+
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            as_any(&n.get_component::<&Type>().unwrap())
+        }
+
+        fn resolve_lang(
+            &self,
+            _n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<AnyType>)>::from(&Lang)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&Lang),
+                ty: *n.get_component::<&Type>().unwrap() as u16,
+            }
+        }
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+            m: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> bool {
+            *n.get_component::<&Type>().unwrap() == *m.get_component::<&Type>().unwrap()
+        }
     }
 }
-#[cfg(feature = "legion")]
-pub use legion_impls::as_any;
+
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <Java as hyper_ast::types::Lang<Type>>::to_u16(*t);
+    let t = <Java as hyper_ast::types::Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
 pub trait JavaEnabledTypeStore<T>: TypeStore<T> {}
 
 
@@ -254,7 +328,7 @@ impl HyperType for Type {
     }
 
     fn is_directory(&self) -> bool {
-        self == &Type::Directory
+        self == &Type::Directory || self == &Type::GradleDirectory
     }
 
     fn is_file(&self) -> bool {
@@ -631,7 +705,7 @@ impl Display for Type {
     }
 }
 
-const COUNT: u16 = 326 + 1 + 2;
+const COUNT: u16 = 326 + 1 + 3;
 #[repr(u16)]
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Type {
@@ -960,6 +1034,7 @@ pub enum Type {
     ReceiverParameterRepeat1,
     TypeIdentifier,
     Spaces,
+    GradleDirectory, // NOTE gradle specific
     Directory,
     ERROR,
 }
@@ -1624,6 +1699,7 @@ impl Type {
             "receiver_parameter_repeat1" => Type::ReceiverParameterRepeat1,
             "type_identifier" => Type::TypeIdentifier,
             "Spaces" => Type::Spaces,
+            "GradleDirectory" => Type::GradleDirectory,
             "Directory" => Type::Directory,
             "ERROR" => Type::ERROR,
             x => return None,
@@ -1956,6 +2032,7 @@ impl Type {
             Type::ReceiverParameterRepeat1 => "receiver_parameter_repeat1",
             Type::TypeIdentifier => "type_identifier",
             Type::Spaces => "Spaces",
+            Type::GradleDirectory => "GradleDirectory",
             Type::Directory => "Directory",
             Type::ERROR => "ERROR",
         }
@@ -2288,6 +2365,7 @@ const S_T_L: &'static [Type] = &[
     Type::ReceiverParameterRepeat1,
     Type::TypeIdentifier,
     Type::Spaces,
+    Type::GradleDirectory,
     Type::Directory,
     Type::ERROR,
 ];
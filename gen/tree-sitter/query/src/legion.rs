@@ -2,20 +2,17 @@
 use std::{collections::HashMap, fmt::Debug};
 
 use crate::{types::TIdN, TNode};
-use legion::world::EntryRef;
 
 use hyper_ast::{
     filter::BloomSize,
     full::FullNode,
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
-    nodes::Space,
+    nodes::{HashSize, Space},
     store::{
         nodes::{
-            legion::{
-                compo::{self, NoSpacesCS, CS},
-                HashedNodeRef, NodeIdentifier,
-            },
-            DefaultNodeStore as NodeStore,
+            compo::{self, NoSpacesCS, CS},
+            ComponentsBuilder, ComponentsRef, DefaultNodeIdentifier as NodeIdentifier,
+            DefaultNodeStore as NodeStore, NodeInserter, PendingInsertion,
         },
         SimpleStores,
     },
@@ -32,24 +29,24 @@ use crate::types::{TsQueryEnabledTypeStore, Type};
 
 pub type LabelIdentifier = hyper_ast::store::labels::DefaultLabelIdentifier;
 
-pub struct TsQueryTreeGen<'store, 'cache, TS> {
+pub struct TsQueryTreeGen<'store, 'cache, TS, NS: NodeInserter = NodeStore> {
     pub line_break: Vec<u8>,
-    pub stores: &'store mut SimpleStores<TS>,
-    pub md_cache: &'cache mut MDCache,
+    pub stores: &'store mut SimpleStores<TS, NS>,
+    pub md_cache: &'cache mut MDCache<NS::IdN>,
 }
 
-pub type MDCache = HashMap<NodeIdentifier, MD>;
+pub type MDCache<IdN = NodeIdentifier> = HashMap<IdN, MD>;
 
 // NOTE only keep compute intensive metadata (where space/time tradeoff is worth storing)
 // eg. decls refs, maybe hashes but not size and height
 // * metadata: computation results from concrete code of node and its children
 // they can be qualitative metadata .eg a hash or they can be quantitative .eg lines of code
 pub struct MD {
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
 }
 
-impl From<Local> for MD {
-    fn from(x: Local) -> Self {
+impl<IdN> From<Local<IdN>> for MD {
+    fn from(x: Local<IdN>) -> Self {
         MD { metrics: x.metrics }
     }
 }
@@ -61,13 +58,13 @@ pub type Global<'a> = SpacedGlobalData<'a>;
 pub struct PartialAnalysis {}
 
 #[derive(Debug, Clone)]
-pub struct Local {
-    pub compressed_node: NodeIdentifier,
-    pub metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+pub struct Local<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
+    pub metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
 }
 
-impl Local {
-    fn acc(self, acc: &mut Acc) {
+impl<IdN: Copy> Local<IdN> {
+    fn acc(self, acc: &mut Acc<IdN>) {
         if self.metrics.size_no_spaces > 0 {
             acc.no_space.push(self.compressed_node)
         }
@@ -78,32 +75,32 @@ impl Local {
     }
 }
 
-pub struct Acc {
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+pub struct Acc<IdN = NodeIdentifier> {
+    simple: BasicAccumulator<Type, IdN>,
+    no_space: Vec<IdN>,
     labeled: bool,
     start_byte: usize,
     end_byte: usize,
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     padding_start: usize,
     indentation: Spaces,
 }
 
-pub type FNode = FullNode<BasicGlobalData, Local>;
-impl Accumulator for Acc {
-    type Node = FNode;
+pub type FNode<IdN = NodeIdentifier> = FullNode<BasicGlobalData, Local<IdN>>;
+impl<IdN: Copy> Accumulator for Acc<IdN> {
+    type Node = FNode<IdN>;
     fn push(&mut self, full_node: Self::Node) {
         full_node.local.acc(self);
     }
 }
 
-impl AccIndentation for Acc {
+impl<IdN: Copy> AccIndentation for Acc<IdN> {
     fn indentation<'a>(&'a self) -> &'a Spaces {
         &self.indentation
     }
 }
 
-impl WithByteRange for Acc {
+impl<IdN> WithByteRange for Acc<IdN> {
     fn has_children(&self) -> bool {
         !self.simple.children.is_empty()
     }
@@ -116,7 +113,7 @@ impl WithByteRange for Acc {
         self.end_byte
     }
 }
-impl Debug for Acc {
+impl<IdN: Debug> Debug for Acc<IdN> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Acc")
             .field("simple", &self.simple)
@@ -175,10 +172,12 @@ pub fn tree_sitter_parse(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitter::
     }
 }
 
-impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdentifier>>>>
-    ZippedTreeGen for TsQueryTreeGen<'store, 'cache, TS>
+impl<'store, 'cache, TS, NS> ZippedTreeGen for TsQueryTreeGen<'store, 'cache, TS, NS>
+where
+    TS: TsQueryEnabledTypeStore<NS::NodeRef<'store, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Stores = SimpleStores<TS>;
+    type Stores = SimpleStores<TS, NS>;
     type Text = [u8];
     type Node<'b> = TNode<'b>;
     type TreeCursor<'b> = TTreeCursor<'b>;
@@ -298,28 +297,30 @@ impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<Node
     }
 }
 
-impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdentifier>>>>
-    TsQueryTreeGen<'store, 'cache, TS>
+impl<'store, 'cache, TS, NS> TsQueryTreeGen<'store, 'cache, TS, NS>
+where
+    TS: TsQueryEnabledTypeStore<NS::NodeRef<'store, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
     fn make_spacing(
         &mut self,
         spacing: Vec<u8>, //Space>,
-    ) -> Local {
+    ) -> Local<NS::IdN> {
         let bytes_len = spacing.len();
         let spacing = std::str::from_utf8(&spacing).unwrap().to_string();
         let spacing_id = self.stores.label_store.get_or_insert(spacing.clone());
-        let hbuilder: hashed::Builder<SyntaxNodeHashs<u32>> =
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
             hashed::Builder::new(Default::default(), &Type::Spaces, &spacing, 1);
         let hsyntax = hbuilder.most_discriminating();
         let hashable = &hsyntax;
 
-        let eq = |x: EntryRef| {
-            let t = x.get_component::<Type>();
-            if t != Ok(&Type::Spaces) {
+        let eq = |x: NS::Entry<'_>| {
+            let t = x.get::<Type>();
+            if t.as_deref() != Some(&Type::Spaces) {
                 return false;
             }
-            let l = x.get_component::<LabelIdentifier>();
-            if l != Ok(&spacing_id) {
+            let l = x.get::<LabelIdentifier>();
+            if l.as_deref() != Some(&spacing_id) {
                 return false;
             }
             true
@@ -334,12 +335,8 @@ impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<Node
         let compressed_node = if let Some(id) = insertion.occupied_id() {
             id
         } else {
-            let vacant = insertion.vacant();
             let bytes_len = compo::BytesLen(bytes_len.try_into().unwrap());
-            NodeStore::insert_after_prepare(
-                vacant,
-                (Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None),
-            )
+            insertion.insert((Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None))
         };
         Local {
             compressed_node,
@@ -354,9 +351,9 @@ impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<Node
 
     pub fn new(
         stores: &'store mut <Self as ZippedTreeGen>::Stores,
-        md_cache: &'cache mut MDCache,
-    ) -> TsQueryTreeGen<'store, 'cache, TS> {
-        TsQueryTreeGen::<'store, 'cache, TS> {
+        md_cache: &'cache mut MDCache<NS::IdN>,
+    ) -> TsQueryTreeGen<'store, 'cache, TS, NS> {
+        TsQueryTreeGen::<'store, 'cache, TS, NS> {
             line_break: "\n".as_bytes().to_vec(),
             stores,
             md_cache,
@@ -368,7 +365,7 @@ impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<Node
         name: &[u8],
         text: &'store [u8],
         cursor: tree_sitter::TreeCursor,
-    ) -> FullNode<BasicGlobalData, Local> {
+    ) -> FNode<NS::IdN> {
         let mut global = Global::from(TextedGlobalData::new(Default::default(), text));
         let mut init = self.init_val(text, &TNode(cursor.node()));
         let mut xx = TTreeCursor(cursor);
@@ -415,27 +412,27 @@ impl<'store, 'cache, TS: TsQueryEnabledTypeStore<HashedNodeRef<'store, TIdN<Node
     }
 }
 
-pub fn eq_node<'a, K>(
+pub fn eq_node<'a, K, NS: NodeInserter>(
     kind: &'a K,
     label_id: Option<&'a LabelIdentifier>,
-    children: &'a [NodeIdentifier],
-) -> impl Fn(EntryRef) -> bool + 'a
+    children: &'a [NS::IdN],
+) -> impl Fn(NS::Entry<'_>) -> bool + 'a
 where
     K: 'static + Eq + std::hash::Hash + Copy + std::marker::Send + std::marker::Sync,
 {
-    move |x: EntryRef| {
-        let t = x.get_component::<K>();
-        if t != Ok(kind) {
+    move |x: NS::Entry<'_>| {
+        let t = x.get::<K>();
+        if t.as_deref() != Some(kind) {
             return false;
         }
-        let l = x.get_component::<LabelIdentifier>().ok();
-        if l != label_id {
+        let l = x.get::<LabelIdentifier>();
+        if l.as_deref() != label_id {
             return false;
         } else {
-            let cs = x.get_component::<CS<legion::Entity>>();
+            let cs = x.get::<CS<NS::IdN>>();
             let r = match cs {
-                Ok(CS(cs)) => cs.as_ref() == children,
-                Err(_) => children.is_empty(),
+                Some(cs) => cs.0.as_ref() == children,
+                None => children.is_empty(),
             };
             if !r {
                 return false;
@@ -445,13 +442,12 @@ where
     }
 }
 
-impl<
-        'stores,
-        'cache,
-        TS: TsQueryEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>,
-    > TreeGen for TsQueryTreeGen<'stores, 'cache, TS>
+impl<'stores, 'cache, TS, NS> TreeGen for TsQueryTreeGen<'stores, 'cache, TS, NS>
+where
+    TS: TsQueryEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Acc = Acc;
+    type Acc = Acc<NS::IdN>;
     type Global = SpacedGlobalData<'stores>;
     fn make(
         &mut self,
@@ -474,7 +470,7 @@ impl<
         let label_id = label
             .as_ref()
             .map(|label| label_store.get_or_insert(label.as_str()));
-        let eq = eq_node(&interned_kind, label_id.as_ref(), &acc.simple.children);
+        let eq = eq_node::<_, NS>(&interned_kind, label_id.as_ref(), &acc.simple.children);
 
         let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -493,8 +489,7 @@ impl<
         } else {
             let hashs = hbuilder.build();
 
-            let mut dyn_builder =
-                hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+            let mut dyn_builder = NS::Builder::default();
             dyn_builder.add(interned_kind);
             dyn_builder.add(hashs.clone());
             dyn_builder.add(compo::BytesLen(
@@ -516,8 +511,7 @@ impl<
                     }
                 }
             }
-            let compressed_node =
-                NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build());
+            let compressed_node = insertion.insert_built(dyn_builder);
 
             let metrics = SubTreeMetrics {
                 size,
@@ -617,7 +611,7 @@ impl<'stores, 'cache> TsQueryTreeGen<'stores, 'cache, crate::types::TStore> {
         let hashable = &hsyntax;
 
         let label_id = l;
-        let eq = eq_node(&interned_kind, label_id.as_ref(), &acc.simple.children);
+        let eq = eq_node::<_, NodeStore>(&interned_kind, label_id.as_ref(), &acc.simple.children);
 
         let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -636,8 +630,7 @@ impl<'stores, 'cache> TsQueryTreeGen<'stores, 'cache, crate::types::TStore> {
         } else {
             let hashs = hbuilder.build();
 
-            let mut dyn_builder =
-                hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+            let mut dyn_builder = <NodeStore as NodeInserter>::Builder::default();
             dyn_builder.add(interned_kind);
             dyn_builder.add(hashs.clone());
             dyn_builder.add(compo::BytesLen(
@@ -659,8 +652,7 @@ impl<'stores, 'cache> TsQueryTreeGen<'stores, 'cache, crate::types::TStore> {
                     }
                 }
             }
-            let compressed_node =
-                NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build());
+            let compressed_node = insertion.insert_built(dyn_builder);
 
             let metrics = SubTreeMetrics {
                 size,
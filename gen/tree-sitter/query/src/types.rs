@@ -3,7 +3,7 @@ use std::fmt::Display;
 use hyper_ast::{
     store::defaults::NodeIdentifier,
     tree_gen::parser::NodeWithU16TypeId,
-    types::{AnyType, HyperType, Lang, LangRef, NodeId, TypeStore, TypeTrait, TypedNodeId},
+    types::{AnyType, HyperType, LangRef, NodeId, TypeStore, TypeTrait, TypedNodeId},
 };
 
 #[cfg(feature = "legion")]
@@ -104,6 +104,91 @@ mod legion_impls {
     }
 }
 
+#[cfg(feature = "hecs")]
+mod hecs_impls {
+    use super::*;
+
+    use hyper_ast::{
+        store::nodes::hecs::{HashedNodeRef, NodeIdentifier},
+        types::TypeIndex,
+    };
+
+    impl<'a> TypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        type Ty = Type;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Ty {
+            *n.get_component::<&Type>().unwrap()
+        }
+
+        fn resolve_lang(
+            &self,
+            _n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<Type>)>::from(&TsQuery)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&TsQuery),
+                ty: *n.get_component::<&Type>().unwrap() as u16,
+            }
+        }
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+            m: &HashedNodeRef<'a, TIdN<NodeIdentifier>>,
+        ) -> bool {
+            *n.get_component::<&Type>().unwrap() == *m.get_component::<&Type>().unwrap()
+        }
+    }
+    impl<'a> TsQueryEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>> for TStore {
+        const LANG: TypeInternalSize = Self::Ts as u16;
+
+        fn _intern(l: u16, t: u16) -> Self::Ty {
+            todo!("{} {}", l, t)
+        }
+        fn intern(&self, t: Type) -> Self::Ty {
+            t
+        }
+
+        fn resolve(&self, t: Self::Ty) -> Type {
+            t
+        }
+    }
+    impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStore {
+        type Ty = AnyType;
+        const MASK: TypeInternalSize = 0b1000_0000_0000_0000;
+        fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+            as_any(&n.get_component::<&Type>().unwrap())
+        }
+
+        fn resolve_lang(
+            &self,
+            _n: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> hyper_ast::types::LangWrapper<Self::Ty> {
+            From::<&'static (dyn LangRef<AnyType>)>::from(&TsQuery)
+        }
+
+        type Marshaled = TypeIndex;
+
+        fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+            TypeIndex {
+                lang: LangRef::<Type>::name(&TsQuery),
+                ty: *n.get_component::<&Type>().unwrap() as u16,
+            }
+        }
+        fn type_eq(
+            &self,
+            n: &HashedNodeRef<'a, NodeIdentifier>,
+            m: &HashedNodeRef<'a, NodeIdentifier>,
+        ) -> bool {
+            *n.get_component::<&Type>().unwrap() == *m.get_component::<&Type>().unwrap()
+        }
+    }
+}
+
 pub trait TsQueryEnabledTypeStore<T>: TypeStore<T> {
     const LANG: u16;
     fn intern(&self, t: Type) -> Self::Ty {
@@ -161,6 +246,14 @@ type TypeInternalSize = u16;
 pub struct T(TypeInternalSize);
 
 pub struct TsQuery;
+pub use TsQuery as Lang;
+
+pub fn as_any(t: &Type) -> AnyType {
+    let t = <TsQuery as hyper_ast::types::Lang<Type>>::to_u16(*t);
+    let t = <TsQuery as hyper_ast::types::Lang<Type>>::make(t);
+    let t: &'static dyn HyperType = t;
+    t.into()
+}
 
 impl LangRef<AnyType> for TsQuery {
     fn make(&self, t: u16) -> &'static AnyType {
@@ -191,7 +284,7 @@ impl LangRef<Type> for TsQuery {
     }
 }
 
-impl Lang<Type> for TsQuery {
+impl hyper_ast::types::Lang<Type> for TsQuery {
     fn make(t: u16) -> &'static Type {
         TsQuery.make(t)
     }
@@ -217,7 +310,7 @@ impl HyperType for Type {
     }
 
     fn is_file(&self) -> bool {
-        todo!()
+        self == &Type::Program
     }
 
     fn is_spaces(&self) -> bool {
@@ -248,8 +341,8 @@ impl HyperType for Type {
     }
 
     fn as_static(&self) -> &'static dyn HyperType {
-        let t = <TsQuery as Lang<Type>>::to_u16(*self);
-        let t = <TsQuery as Lang<Type>>::make(t);
+        let t = <TsQuery as hyper_ast::types::Lang<Type>>::to_u16(*self);
+        let t = <TsQuery as hyper_ast::types::Lang<Type>>::make(t);
         t
     }
 
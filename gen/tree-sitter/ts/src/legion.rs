@@ -2,20 +2,17 @@
 use std::{collections::HashMap, fmt::Debug};
 
 use crate::{types::TIdN, TNode};
-use legion::world::EntryRef;
 
 use hyper_ast::{
     filter::BloomSize,
     full::FullNode,
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
-    nodes::Space,
+    nodes::{HashSize, Space},
     store::{
         nodes::{
-            legion::{
-                compo::{self, NoSpacesCS, CS},
-                HashedNodeRef, NodeIdentifier,
-            },
-            DefaultNodeStore as NodeStore,
+            compo::{self, NoSpacesCS, CS},
+            ComponentsBuilder, ComponentsRef, DefaultNodeIdentifier as NodeIdentifier,
+            DefaultNodeStore as NodeStore, NodeInserter, PendingInsertion,
         },
         SimpleStores,
     },
@@ -31,24 +28,24 @@ use crate::types::{TsEnabledTypeStore, Type};
 
 pub type LabelIdentifier = hyper_ast::store::labels::DefaultLabelIdentifier;
 
-pub struct TsTreeGen<'store, 'cache, TS> {
+pub struct TsTreeGen<'store, 'cache, TS, NS: NodeInserter = NodeStore> {
     pub line_break: Vec<u8>,
-    pub stores: &'store mut SimpleStores<TS>,
-    pub md_cache: &'cache mut MDCache,
+    pub stores: &'store mut SimpleStores<TS, NS>,
+    pub md_cache: &'cache mut MDCache<NS::IdN>,
 }
 
-pub type MDCache = HashMap<NodeIdentifier, MD>;
+pub type MDCache<IdN = NodeIdentifier> = HashMap<IdN, MD>;
 
 // NOTE only keep compute intensive metadata (where space/time tradeoff is worth storing)
 // eg. decls refs, maybe hashes but not size and height
 // * metadata: computation results from concrete code of node and its children
 // they can be qualitative metadata .eg a hash or they can be quantitative .eg lines of code
 pub struct MD {
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
 }
 
-impl From<Local> for MD {
-    fn from(x: Local) -> Self {
+impl<IdN> From<Local<IdN>> for MD {
+    fn from(x: Local<IdN>) -> Self {
         MD { metrics: x.metrics }
     }
 }
@@ -60,13 +57,13 @@ pub type Global<'a> = SpacedGlobalData<'a>;
 pub struct PartialAnalysis {}
 
 #[derive(Debug, Clone)]
-pub struct Local {
-    pub compressed_node: NodeIdentifier,
-    pub metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+pub struct Local<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
+    pub metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
 }
 
-impl Local {
-    fn acc(self, acc: &mut Acc) {
+impl<IdN: Copy> Local<IdN> {
+    fn acc(self, acc: &mut Acc<IdN>) {
         if self.metrics.size_no_spaces > 0 {
             acc.no_space.push(self.compressed_node)
         }
@@ -77,32 +74,32 @@ impl Local {
     }
 }
 
-pub struct Acc {
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+pub struct Acc<IdN = NodeIdentifier> {
+    simple: BasicAccumulator<Type, IdN>,
+    no_space: Vec<IdN>,
     labeled: bool,
     start_byte: usize,
     end_byte: usize,
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     padding_start: usize,
     indentation: Spaces,
 }
 
-pub type FNode = FullNode<BasicGlobalData, Local>;
-impl Accumulator for Acc {
-    type Node = FNode;
+pub type FNode<IdN = NodeIdentifier> = FullNode<BasicGlobalData, Local<IdN>>;
+impl<IdN: Copy> Accumulator for Acc<IdN> {
+    type Node = FNode<IdN>;
     fn push(&mut self, full_node: Self::Node) {
         full_node.local.acc(self);
     }
 }
 
-impl AccIndentation for Acc {
+impl<IdN: Copy> AccIndentation for Acc<IdN> {
     fn indentation<'a>(&'a self) -> &'a Spaces {
         &self.indentation
     }
 }
 
-impl WithByteRange for Acc {
+impl<IdN> WithByteRange for Acc<IdN> {
     fn has_children(&self) -> bool {
         !self.simple.children.is_empty()
     }
@@ -115,7 +112,7 @@ impl WithByteRange for Acc {
         self.end_byte
     }
 }
-impl Debug for Acc {
+impl<IdN: Debug> Debug for Acc<IdN> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Acc")
             .field("simple", &self.simple)
@@ -162,10 +159,12 @@ impl<'a> hyper_ast::tree_gen::parser::TreeCursor<'a, TNode<'a>> for TTreeCursor<
     }
 }
 
-impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdentifier>>>>
-    ZippedTreeGen for TsTreeGen<'store, 'cache, TS>
+impl<'store, 'cache, TS, NS> ZippedTreeGen for TsTreeGen<'store, 'cache, TS, NS>
+where
+    TS: TsEnabledTypeStore<NS::NodeRef<'store, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Stores = SimpleStores<TS>;
+    type Stores = SimpleStores<TS, NS>;
     type Text = [u8];
     type Node<'b> = TNode<'b>;
     type TreeCursor<'b> = TTreeCursor<'b>;
@@ -263,28 +262,30 @@ impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdent
     }
 }
 
-impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdentifier>>>>
-    TsTreeGen<'store, 'cache, TS>
+impl<'store, 'cache, TS, NS> TsTreeGen<'store, 'cache, TS, NS>
+where
+    TS: TsEnabledTypeStore<NS::NodeRef<'store, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
     fn make_spacing(
         &mut self,
         spacing: Vec<u8>, //Space>,
-    ) -> Local {
+    ) -> Local<NS::IdN> {
         let bytes_len = spacing.len();
         let spacing = std::str::from_utf8(&spacing).unwrap().to_string();
         let spacing_id = self.stores.label_store.get_or_insert(spacing.clone());
-        let hbuilder: hashed::Builder<SyntaxNodeHashs<u32>> =
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
             hashed::Builder::new(Default::default(), &Type::Spaces, &spacing, 1);
         let hsyntax = hbuilder.most_discriminating();
         let hashable = &hsyntax;
 
-        let eq = |x: EntryRef| {
-            let t = x.get_component::<Type>();
-            if t != Ok(&Type::Spaces) {
+        let eq = |x: NS::Entry<'_>| {
+            let t = x.get::<Type>();
+            if t.as_deref() != Some(&Type::Spaces) {
                 return false;
             }
-            let l = x.get_component::<LabelIdentifier>();
-            if l != Ok(&spacing_id) {
+            let l = x.get::<LabelIdentifier>();
+            if l.as_deref() != Some(&spacing_id) {
                 return false;
             }
             true
@@ -299,12 +300,8 @@ impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdent
         let compressed_node = if let Some(id) = insertion.occupied_id() {
             id
         } else {
-            let vacant = insertion.vacant();
             let bytes_len = compo::BytesLen(bytes_len.try_into().unwrap());
-            NodeStore::insert_after_prepare(
-                vacant,
-                (Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None),
-            )
+            insertion.insert((Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None))
         };
         Local {
             compressed_node,
@@ -319,9 +316,9 @@ impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdent
 
     pub fn new(
         stores: &'store mut <Self as ZippedTreeGen>::Stores,
-        md_cache: &'cache mut MDCache,
-    ) -> TsTreeGen<'store, 'cache, TS> {
-        TsTreeGen::<'store, 'cache, TS> {
+        md_cache: &'cache mut MDCache<NS::IdN>,
+    ) -> TsTreeGen<'store, 'cache, TS, NS> {
+        TsTreeGen::<'store, 'cache, TS, NS> {
             line_break: "\n".as_bytes().to_vec(),
             stores,
             md_cache,
@@ -345,7 +342,7 @@ impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdent
         name: &[u8],
         text: &'store [u8],
         cursor: tree_sitter::TreeCursor,
-    ) -> FullNode<BasicGlobalData, Local> {
+    ) -> FNode<NS::IdN> {
         let mut global = Global::from(TextedGlobalData::new(Default::default(), text));
         let mut init = self.init_val(text, &TNode(cursor.node()));
         let mut xx = TTreeCursor(cursor);
@@ -392,27 +389,27 @@ impl<'store, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'store, TIdN<NodeIdent
     }
 }
 
-pub fn eq_node<'a, K>(
+pub fn eq_node<'a, K, NS: NodeInserter>(
     kind: &'a K,
     label_id: Option<&'a LabelIdentifier>,
-    children: &'a [NodeIdentifier],
-) -> impl Fn(EntryRef) -> bool + 'a
+    children: &'a [NS::IdN],
+) -> impl Fn(NS::Entry<'_>) -> bool + 'a
 where
     K: 'static + Eq + std::hash::Hash + Copy + std::marker::Send + std::marker::Sync,
 {
-    move |x: EntryRef| {
-        let t = x.get_component::<K>();
-        if t != Ok(kind) {
+    move |x: NS::Entry<'_>| {
+        let t = x.get::<K>();
+        if t.as_deref() != Some(kind) {
             return false;
         }
-        let l = x.get_component::<LabelIdentifier>().ok();
-        if l != label_id {
+        let l = x.get::<LabelIdentifier>();
+        if l.as_deref() != label_id {
             return false;
         } else {
-            let cs = x.get_component::<CS<legion::Entity>>();
+            let cs = x.get::<CS<NS::IdN>>();
             let r = match cs {
-                Ok(CS(cs)) => cs.as_ref() == children,
-                Err(_) => children.is_empty(),
+                Some(cs) => cs.0.as_ref() == children,
+                None => children.is_empty(),
             };
             if !r {
                 return false;
@@ -422,10 +419,12 @@ where
     }
 }
 
-impl<'stores, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>> TreeGen
-    for TsTreeGen<'stores, 'cache, TS>
+impl<'stores, 'cache, TS, NS> TreeGen for TsTreeGen<'stores, 'cache, TS, NS>
+where
+    TS: TsEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Acc = Acc;
+    type Acc = Acc<NS::IdN>;
     type Global = SpacedGlobalData<'stores>;
     fn make(
         &mut self,
@@ -447,7 +446,7 @@ impl<'stores, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIde
         let label_id = label
             .as_ref()
             .map(|label| label_store.get_or_insert(label.as_str()));
-        let eq = eq_node(&interned_kind, label_id.as_ref(), &acc.simple.children);
+        let eq = eq_node::<_, NS>(&interned_kind, label_id.as_ref(), &acc.simple.children);
 
         let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -466,8 +465,7 @@ impl<'stores, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIde
         } else {
             let hashs = hbuilder.build();
 
-            let mut dyn_builder =
-                hyper_ast::store::nodes::legion::dyn_builder::EntityBuilder::new();
+            let mut dyn_builder = NS::Builder::default();
             dyn_builder.add(interned_kind);
             dyn_builder.add(hashs.clone());
             dyn_builder.add(compo::BytesLen(
@@ -489,8 +487,7 @@ impl<'stores, 'cache, TS: TsEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIde
                     }
                 }
             }
-            let compressed_node =
-                NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build());
+            let compressed_node = insertion.insert_built(dyn_builder);
 
             let metrics = SubTreeMetrics {
                 size,
@@ -1,21 +1,18 @@
 ///! fully compress all subtrees from an Xml CST
 use std::{fmt::Debug, vec};
 
-use legion::world::EntryRef;
 use tuples::CombinConcat;
 
 use hyper_ast::{
     filter::BloomSize,
     full::FullNode,
     hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
-    nodes::Space,
+    nodes::{HashSize, Space},
     store::{
         nodes::{
-            legion::{
-                compo::{self, NoSpacesCS, CS},
-                HashedNodeRef, NodeIdentifier, PendingInsert,
-            },
-            DefaultNodeStore as NodeStore,
+            compo::{self, NoSpacesCS, CS},
+            ComponentsRef, DefaultNodeIdentifier as NodeIdentifier, DefaultNodeStore as NodeStore,
+            NodeInserter, PendingInsertion,
         },
         SimpleStores,
     },
@@ -35,9 +32,9 @@ use crate::{
 
 pub type LabelIdentifier = hyper_ast::store::labels::DefaultLabelIdentifier;
 
-pub struct XmlTreeGen<'stores, TS> {
+pub struct XmlTreeGen<'stores, TS, NS = NodeStore> {
     pub line_break: Vec<u8>,
-    pub stores: &'stores mut SimpleStores<TS>,
+    pub stores: &'stores mut SimpleStores<TS, NS>,
 }
 
 pub type Global<'a> = SpacedGlobalData<'a>;
@@ -47,14 +44,14 @@ pub type Global<'a> = SpacedGlobalData<'a>;
 pub struct PartialAnalysis {}
 
 #[derive(Debug, Clone)]
-pub struct Local {
-    pub compressed_node: NodeIdentifier,
-    pub metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+pub struct Local<IdN = NodeIdentifier> {
+    pub compressed_node: IdN,
+    pub metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     pub ana: Option<PartialAnalysis>,
 }
 
-impl Local {
-    fn acc(self, acc: &mut Acc) {
+impl<IdN: Copy> Local<IdN> {
+    fn acc(self, acc: &mut Acc<IdN>) {
         if self.metrics.size_no_spaces > 0 {
             acc.no_space.push(self.compressed_node)
         }
@@ -65,33 +62,33 @@ impl Local {
     }
 }
 
-pub struct Acc {
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+pub struct Acc<IdN = NodeIdentifier> {
+    simple: BasicAccumulator<Type, IdN>,
+    no_space: Vec<IdN>,
     labeled: bool,
     start_byte: usize,
     end_byte: usize,
-    metrics: SubTreeMetrics<SyntaxNodeHashs<u32>>,
+    metrics: SubTreeMetrics<SyntaxNodeHashs<HashSize>>,
     ana: Option<PartialAnalysis>,
     padding_start: usize,
     indentation: Spaces,
 }
 
-pub type FNode = FullNode<BasicGlobalData, Local>;
-impl Accumulator for Acc {
-    type Node = FNode;
+pub type FNode<IdN = NodeIdentifier> = FullNode<BasicGlobalData, Local<IdN>>;
+impl<IdN: Copy> Accumulator for Acc<IdN> {
+    type Node = FNode<IdN>;
     fn push(&mut self, full_node: Self::Node) {
         full_node.local.acc(self);
     }
 }
 
-impl AccIndentation for Acc {
+impl<IdN: Copy> AccIndentation for Acc<IdN> {
     fn indentation<'a>(&'a self) -> &'a Spaces {
         &self.indentation
     }
 }
 
-impl WithByteRange for Acc {
+impl<IdN> WithByteRange for Acc<IdN> {
     fn has_children(&self) -> bool {
         !self.simple.children.is_empty()
     }
@@ -137,11 +134,13 @@ impl<'a> hyper_ast::tree_gen::parser::TreeCursor<'a, TNode<'a>> for TTreeCursor<
     }
 }
 
-impl<'stores, TS: XmlEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>> ZippedTreeGen
-    for XmlTreeGen<'stores, TS>
+impl<'stores, TS, NS> ZippedTreeGen for XmlTreeGen<'stores, TS, NS>
+where
+    TS: XmlEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
     // type Node1 = SimpleNode1<NodeIdentifier, String>;
-    type Stores = SimpleStores<TS>;
+    type Stores = SimpleStores<TS, NS>;
     type Text = [u8];
     type Node<'b> = TNode<'b>;
     type TreeCursor<'b> = TTreeCursor<'b>;
@@ -274,26 +273,30 @@ pub fn tree_sitter_parse_xml(text: &[u8]) -> Result<tree_sitter::Tree, tree_sitt
     }
 }
 
-impl<'a, TS: XmlEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>>> XmlTreeGen<'a, TS> {
+impl<'a, TS, NS> XmlTreeGen<'a, TS, NS>
+where
+    TS: XmlEnabledTypeStore<NS::NodeRef<'a, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
+{
     fn make_spacing(
         &mut self,
         spacing: Vec<u8>, //Space>,
-    ) -> Local {
+    ) -> Local<NS::IdN> {
         let bytes_len = spacing.len();
         let spacing = std::str::from_utf8(&spacing).unwrap().to_string();
         let spacing_id = self.stores.label_store.get_or_insert(spacing.clone());
-        let hbuilder: hashed::Builder<SyntaxNodeHashs<u32>> =
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
             hashed::Builder::new(Default::default(), &Type::Spaces, &spacing, 1);
         let hsyntax = hbuilder.most_discriminating();
         let hashable = &hsyntax;
 
-        let eq = |x: EntryRef| {
-            let t = x.get_component::<Type>();
-            if t != Ok(&Type::Spaces) {
+        let eq = |x: NS::Entry<'_>| {
+            let t = x.get::<Type>();
+            if t.as_deref() != Some(&Type::Spaces) {
                 return false;
             }
-            let l = x.get_component::<LabelIdentifier>();
-            if l != Ok(&spacing_id) {
+            let l = x.get::<LabelIdentifier>();
+            if l.as_deref() != Some(&spacing_id) {
                 return false;
             }
             true
@@ -308,12 +311,8 @@ impl<'a, TS: XmlEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>>> XmlTr
         let compressed_node = if let Some(id) = insertion.occupied_id() {
             id
         } else {
-            let vacant = insertion.vacant();
             let bytes_len = compo::BytesLen(bytes_len.try_into().unwrap());
-            NodeStore::insert_after_prepare(
-                vacant,
-                (Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None),
-            )
+            insertion.insert((Type::Spaces, spacing_id, bytes_len, hashs, BloomSize::None))
         };
         Local {
             compressed_node,
@@ -327,7 +326,7 @@ impl<'a, TS: XmlEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>>> XmlTr
         }
     }
 
-    pub fn new(stores: &mut SimpleStores<TS>) -> XmlTreeGen<TS> {
+    pub fn new(stores: &mut SimpleStores<TS, NS>) -> XmlTreeGen<TS, NS> {
         XmlTreeGen {
             line_break: "\n".as_bytes().to_vec(),
             stores,
@@ -351,7 +350,7 @@ impl<'a, TS: XmlEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>>> XmlTr
         name: &[u8],
         text: &'a [u8],
         cursor: tree_sitter::TreeCursor,
-    ) -> FullNode<BasicGlobalData, Local> {
+    ) -> FNode<NS::IdN> {
         let mut global = Global::from(TextedGlobalData::new(Default::default(), text));
         let mut init = self.init_val(text, &TNode(cursor.node()));
         let mut xx = TTreeCursor(cursor);
@@ -402,24 +401,24 @@ impl<'a, TS: XmlEnabledTypeStore<HashedNodeRef<'a, TIdN<NodeIdentifier>>>> XmlTr
     }
 }
 
-pub fn eq_node<'a>(
+pub fn eq_node<'a, NS: NodeInserter>(
     kind: &'a Type,
     label_id: Option<&'a LabelIdentifier>,
-    children: &'a [NodeIdentifier],
-) -> impl Fn(EntryRef) -> bool + 'a {
-    move |x: EntryRef| {
-        let t = x.get_component::<Type>();
-        if t != Ok(kind) {
+    children: &'a [NS::IdN],
+) -> impl Fn(NS::Entry<'_>) -> bool + 'a {
+    move |x: NS::Entry<'_>| {
+        let t = x.get::<Type>();
+        if t.as_deref() != Some(kind) {
             return false;
         }
-        let l = x.get_component::<LabelIdentifier>().ok();
-        if l != label_id {
+        let l = x.get::<LabelIdentifier>();
+        if l.as_deref() != label_id {
             return false;
         } else {
-            let cs = x.get_component::<CS<legion::Entity>>();
+            let cs = x.get::<CS<NS::IdN>>();
             let r = match cs {
-                Ok(CS(cs)) => cs.as_ref() == children,
-                Err(_) => children.is_empty(),
+                Some(cs) => cs.0.as_ref() == children,
+                None => children.is_empty(),
             };
             if !r {
                 return false;
@@ -429,10 +428,12 @@ pub fn eq_node<'a>(
     }
 }
 
-impl<'stores, TS: XmlEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier>>>> TreeGen
-    for XmlTreeGen<'stores, TS>
+impl<'stores, TS, NS> TreeGen for XmlTreeGen<'stores, TS, NS>
+where
+    TS: XmlEnabledTypeStore<NS::NodeRef<'stores, TIdN<NS::IdN>>>,
+    NS: NodeInserter,
 {
-    type Acc = Acc;
+    type Acc = Acc<NS::IdN>;
     type Global = SpacedGlobalData<'stores>;
     fn make(
         &mut self,
@@ -453,7 +454,7 @@ impl<'stores, TS: XmlEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier
         let label_id = label
             .as_ref()
             .map(|label| label_store.get_or_insert(label.as_str()));
-        let eq = eq_node(&acc.simple.kind, label_id.as_ref(), &acc.simple.children);
+        let eq = eq_node::<NS>(&acc.simple.kind, label_id.as_ref(), &acc.simple.children);
 
         let insertion = node_store.prepare_insertion(&hashable, eq);
 
@@ -516,26 +517,25 @@ impl<'stores, TS: XmlEnabledTypeStore<HashedNodeRef<'stores, TIdN<NodeIdentifier
     }
 }
 
-fn compress(
+fn compress<P: PendingInsertion>(
     label_id: Option<LabelIdentifier>,
     _ana: &Option<PartialAnalysis>,
-    simple: BasicAccumulator<Type, NodeIdentifier>,
-    no_space: Vec<NodeIdentifier>,
+    simple: BasicAccumulator<Type, P::IdN>,
+    no_space: Vec<P::IdN>,
     bytes_len: compo::BytesLen,
     size: u32,
     height: u32,
     size_no_spaces: u32,
-    insertion: PendingInsert,
-    hashs: SyntaxNodeHashs<u32>,
-) -> legion::Entity {
-    let vacant = insertion.vacant();
+    insertion: P,
+    hashs: SyntaxNodeHashs<HashSize>,
+) -> P::IdN {
     macro_rules! insert {
         ( $c0:expr, $($c:expr),* $(,)? ) => {{
             let c = $c0;
             $(
                 let c = c.concat($c);
             )*
-            NodeStore::insert_after_prepare(vacant, c)
+            insertion.insert(c)
         }};
     }
     macro_rules! children_dipatch {
@@ -107,16 +107,23 @@ fn xml_issue_cdata() {
         Err(t) => t,
     };
     println!("{:#?}", tree.root_node().to_sexp());
-    let mut stores = SimpleStores {
-        label_store: LabelStore::new(),
-        type_store: TStore::default(),
-        node_store: hyper_ast::store::nodes::legion::NodeStore::new(),
-    };
-    let mut tree_gen = XmlTreeGen {
-        line_break: "\n".as_bytes().to_vec(),
-        stores: &mut stores,
-    };
-    let _x = tree_gen.generate_file(b"", text, tree.walk()).local;
+    // the generator must work the same with each node store backend
+    macro_rules! generate_in {
+        ($backend:ident) => {{
+            let mut stores = SimpleStores {
+                label_store: LabelStore::new(),
+                type_store: TStore::default(),
+                node_store: hyper_ast::store::nodes::$backend::NodeStore::new(),
+            };
+            let mut tree_gen = XmlTreeGen {
+                line_break: "\n".as_bytes().to_vec(),
+                stores: &mut stores,
+            };
+            let _x = tree_gen.generate_file(b"", text, tree.walk()).local;
+        }};
+    }
+    generate_in!(legion);
+    generate_in!(hecs);
     // println!("{}", tree.root_node().to_sexp());
 }
 
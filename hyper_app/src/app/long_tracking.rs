@@ -114,6 +114,28 @@ pub(crate) struct Flags {
     pub(crate) dependent: bool,
     pub(crate) references: bool,
     pub(crate) declaration: bool,
+    /// sent along the flags, as query parameters
+    #[serde(flatten)]
+    pub(crate) params: MatcherParams,
+}
+
+/// Thresholds of the matchers used by the tracker
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub(crate) struct MatcherParams {
+    pub(crate) min_height: usize,
+    pub(crate) size_threshold: usize,
+    pub(crate) sim_threshold: f64,
+}
+
+impl Default for MatcherParams {
+    fn default() -> Self {
+        Self {
+            min_height: 1,
+            size_threshold: 1000,
+            sim_threshold: 0.5,
+        }
+    }
 }
 
 pub(super) fn show_menu(
@@ -184,6 +206,23 @@ pub(super) fn show_menu(
             // );
             show_wip(ui, Some("need more parameters ?"));
         });
+        ui.add(egui::Label::new(
+            egui::RichText::from("Matcher").font(egui::FontId::proportional(16.0)),
+        ));
+        let params = &mut flags.params;
+        ui.add(
+            egui::Slider::new(&mut params.min_height, 1..=10)
+                .text("min height")
+                .integer(),
+        );
+        ui.add(
+            egui::Slider::new(&mut params.size_threshold, 10..=10_000)
+                .text("size threshold")
+                .clamp_to_range(false)
+                .integer()
+                .logarithmic(true),
+        );
+        ui.add(egui::Slider::new(&mut params.sim_threshold, 0.0..=1.0).text("sim threshold"));
     };
 
     radio_collapsing(ui, id, title, selected, &wanted, add_body);
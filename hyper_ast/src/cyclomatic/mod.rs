@@ -27,7 +27,7 @@ pub fn is_cyclomatic_persisted<K: TypeTrait>(t: &K) -> bool {
 /// same POV https://github.com/qxo/eclipse-metrics-plugin/blob/08e51bd48725494aaa82023716ce659504948610/net.sourceforge.metrics/src/net/sourceforge/metrics/calculators/McCabe.java
 #[derive(Clone, Debug)]
 pub struct Mcc {
-    value: u32,
+    pub(crate) value: u32,
 }
 
 impl Mcc {
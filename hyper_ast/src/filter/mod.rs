@@ -51,6 +51,28 @@ impl<T, V: BitViewSized> Default for Bloom<T, V> {
         }
     }
 }
+impl<T, V: BitViewSized> Bloom<T, V> {
+    /// The bits of the filter packed in bytes, mostly useful to persist it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut r = vec![0u8; (self.bits.len() + 7) / 8];
+        for i in self.bits.iter_ones() {
+            r[i / 8] |= 1 << (i % 8);
+        }
+        r
+    }
+
+    /// The inverse of [`Bloom::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut r = Self::default();
+        for i in 0..r.bits.len().min(bytes.len() * 8) {
+            if bytes[i / 8] & (1 << (i % 8)) != 0 {
+                r.bits.set(i, true);
+            }
+        }
+        r
+    }
+}
+
 impl<T, V: BitViewSized> Debug for Bloom<T, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Bloom").field("bits", &self.bits).finish()
@@ -8,7 +8,7 @@ use crate::{
     types::{AnyType, HashKind, HyperType, MySlice, NodeId},
     PrimInt,
 };
-use num::traits::WrappingAdd;
+use num::traits::{WrappingAdd, WrappingMul};
 
 use crate::nodes::{CompressedNode, HashSize};
 
@@ -33,7 +33,7 @@ pub trait ComputableNodeHashs: NodeHashs {
         kind: &Self::Kind,
         k: Self::Hash,
         l: Self::Hash,
-        size: Self::Hash,
+        size: u32,
     ) -> Self::Hash;
 }
 
@@ -45,7 +45,7 @@ pub struct SyntaxNodeHashs<T: PrimInt> {
 }
 
 pub trait IndexingHashBuilder<H: NodeHashs> {
-    fn new<K: ?Sized + Hash, L: ?Sized + Hash>(hashs: H, k: &K, l: &L, size: H::Hash) -> Self;
+    fn new<K: ?Sized + Hash, L: ?Sized + Hash>(hashs: H, k: &K, l: &L, size: u32) -> Self;
     fn most_discriminating(&self) -> H::Hash;
 }
 pub trait MetaDataHashsBuilder<H: NodeHashs>: IndexingHashBuilder<H> {
@@ -56,12 +56,12 @@ pub struct Builder<H: NodeHashs> {
     h0: H::Hash,
     k: H::Hash,
     l: H::Hash,
-    size: H::Hash,
+    size: u32,
     hashs: H,
 }
 
 impl<H: ComputableNodeHashs> IndexingHashBuilder<H> for Builder<H> {
-    fn new<K: ?Sized + Hash, L: ?Sized + Hash>(hashs: H, k: &K, l: &L, size: H::Hash) -> Self {
+    fn new<K: ?Sized + Hash, L: ?Sized + Hash>(hashs: H, k: &K, l: &L, size: u32) -> Self {
         let k = H::prepare(k);
         let l = H::prepare(l);
         let h0 = hashs.compute(&Default::default(), k, l, size);
@@ -114,9 +114,7 @@ impl<T: PrimInt> Debug for SyntaxNodeHashs<T> {
         write!(
             f,
             "H: {:?}/{:?}/{:?}",
-            &self.structt.to_usize().unwrap(),
-            &self.label.to_usize().unwrap(),
-            &self.syntax.to_usize().unwrap(),
+            &self.structt, &self.label, &self.syntax,
         )
     }
 }
@@ -161,28 +159,81 @@ impl<T: PrimInt + WrappingAdd> NodeHashs for SyntaxNodeHashs<T> {
     }
 }
 
-impl ComputableNodeHashs for SyntaxNodeHashs<u32> {
-    fn prepare<U: ?Sized + Hash>(x: &U) -> u32 {
+/// Integers usable as node hashes, see [`HashSize`].
+pub trait HashWidth:
+    PrimInt + WrappingAdd + WrappingMul + Hash + Default + Send + Sync + 'static
+{
+    /// Name of the [`SyntaxNodeHashs`] component of this width, eg. in snapshots
+    const COMPONENT: &'static str;
+    fn prepare<T: ?Sized + Hash>(x: &T) -> Self;
+    fn write_le(self, out: &mut Vec<u8>);
+    /// `bytes` must be `size_of::<Self>()` long
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+impl HashWidth for u32 {
+    const COMPONENT: &'static str = "hashs_u32";
+    fn prepare<T: ?Sized + Hash>(x: &T) -> Self {
         use crate::utils::{self, clamp_u64_to_u32};
         clamp_u64_to_u32(&utils::hash(x))
     }
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(u32::to_le_bytes(self))
+    }
+    fn read_le(bytes: &[u8]) -> Self {
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    }
+}
+
+impl HashWidth for u64 {
+    const COMPONENT: &'static str = "hashs_u64";
+    fn prepare<T: ?Sized + Hash>(x: &T) -> Self {
+        crate::utils::hash(x)
+    }
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(u64::to_le_bytes(self))
+    }
+    fn read_le(bytes: &[u8]) -> Self {
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+}
+
+impl HashWidth for u128 {
+    const COMPONENT: &'static str = "hashs_u128";
+    fn prepare<T: ?Sized + Hash>(x: &T) -> Self {
+        use crate::utils::hash;
+        // the high half is salted to be independent from the low one
+        (hash(&(0x9e37_79b9u32, x)) as u128) << 64 | hash(x) as u128
+    }
+    fn write_le(self, out: &mut Vec<u8>) {
+        out.extend(u128::to_le_bytes(self))
+    }
+    fn read_le(bytes: &[u8]) -> Self {
+        u128::from_le_bytes(bytes.try_into().unwrap())
+    }
+}
+
+impl<T: HashWidth> ComputableNodeHashs for SyntaxNodeHashs<T> {
+    fn prepare<U: ?Sized + Hash>(x: &U) -> T {
+        T::prepare(x)
+    }
 
     fn compute(
         &self,
         kind: &Self::Kind,
         k: Self::Hash,
         l: Self::Hash,
-        size: Self::Hash,
+        size: u32,
     ) -> Self::Hash {
-        inner_node_hash(k, l, size as u32, self.hash(kind))
+        inner_node_hash(k, l, size, self.hash(kind))
     }
 }
 
-impl MetaDataHashsBuilder<SyntaxNodeHashs<u32>> for Builder<SyntaxNodeHashs<u32>> {
-    fn build(&self) -> SyntaxNodeHashs<u32> {
+impl<T: HashWidth> MetaDataHashsBuilder<SyntaxNodeHashs<T>> for Builder<SyntaxNodeHashs<T>> {
+    fn build(&self) -> SyntaxNodeHashs<T> {
         type K = SyntaxNodeHashsKinds;
         SyntaxNodeHashs {
-            structt: self.hashs.compute(&K::Struct, self.k, 0, self.size),
+            structt: self.hashs.compute(&K::Struct, self.k, T::zero(), self.size),
             label: self.hashs.compute(&K::Label, self.k, self.l, self.size),
             syntax: self.h0,
         }
@@ -350,41 +401,65 @@ static LEAVE: u32 = {
 };
 static BASE: &u32 = &33u32;
 
-pub fn inner_node_hash(kind: u32, label: u32, size: u32, middle_hash: u32) -> u32 {
-    let mut left = 1u32;
-    left = 31 * left + kind;
-    left = 31 * left + label;
-    left = 31 * left + ENTER;
+/// Hash of a node from the hashs of its kind, its label, its size and the accumulated hashs of its children.
+///
+/// Computations wrap, 32 bits hashes are left unchanged by the configurable width.
+pub fn inner_node_hash<T: HashWidth>(kind: T, label: T, size: u32, middle_hash: T) -> T {
+    let c = |x: u32| T::from(x).unwrap();
+    let step = |acc: T, x: T| c(31).wrapping_mul(&acc).wrapping_add(&x);
 
-    let mut right = 1u32;
-    right = 31 * right + kind;
-    right = 31 * right + label;
-    right = 31 * right + LEAVE;
+    let left = step(step(step(c(1), kind), label), c(ENTER));
+    let right = step(step(step(c(1), kind), label), c(LEAVE));
 
-    left.wrapping_add(middle_hash)
-        .wrapping_add(right.wrapping_mul(hash_factor(size)))
+    left.wrapping_add(&middle_hash)
+        .wrapping_add(&right.wrapping_mul(&hash_factor(size)))
 }
 
-fn hash_factor(exponent: u32) -> u32 {
-    fast_exponentiation(*BASE, exponent)
+fn hash_factor<T: HashWidth>(exponent: u32) -> T {
+    fast_exponentiation(T::from(*BASE).unwrap(), exponent)
 }
 
-fn fast_exponentiation(base: u32, exponent: u32) -> u32 {
+fn fast_exponentiation<T: HashWidth>(base: T, exponent: u32) -> T {
     if exponent == 0 {
-        1
+        T::one()
     } else if exponent == 1 {
         base
     } else {
-        let mut result: u32 = 1;
+        let mut result = T::one();
         let mut exponent = exponent;
         let mut base = base;
         while exponent > 0 {
             if (exponent & 1) != 0 {
-                result = result.wrapping_mul(base);
+                result = result.wrapping_mul(&base);
             }
             exponent >>= 1;
-            base = base.wrapping_mul(base);
+            base = base.wrapping_mul(&base);
         }
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build<T: HashWidth>(l: &str, size: u32) -> SyntaxNodeHashs<T> {
+        Builder::new(SyntaxNodeHashs::<T>::default(), &0u16, l, size).build()
+    }
+
+    #[test]
+    fn widths() {
+        // 32 bits hashes are unchanged
+        assert_eq!(inner_node_hash(1u32, 2, 3, 4), {
+            let left = 31 * (31 * (31 + 1) + 2) + ENTER;
+            let right = 31 * (31 * (31 + 1) + 2) + LEAVE;
+            left.wrapping_add(4).wrapping_add(right.wrapping_mul(33 * 33 * 33))
+        });
+        let (a, b) = (build::<u64>("a", 1), build::<u64>("b", 1));
+        assert_eq!(a.structt, b.structt);
+        assert_ne!(a.label, b.label);
+        let a = build::<u128>("a", 1);
+        assert!(a.syntax > u64::MAX as u128);
+        assert_eq!(a, build::<u128>("a", 1));
+    }
+}
@@ -27,7 +27,16 @@ pub trait RefContainer {
 
 // pub type DefaultLabelIdentifier = DefaultSymbol;
 // pub type DefaultNodeIdentifier = legion::Entity;
+/// Width of the hashes identifying nodes, see [`crate::hashed::HashWidth`].
+///
+/// 32 bits by default, wider hashes (features `hash64`, `hash128`) make collisions
+/// during hash-consing and matching unlikely on very large repositories, at a memory cost.
+#[cfg(not(any(feature = "hash64", feature = "hash128")))]
 pub type HashSize = u32;
+#[cfg(all(feature = "hash64", not(feature = "hash128")))]
+pub type HashSize = u64;
+#[cfg(feature = "hash128")]
+pub type HashSize = u128;
 
 #[derive(Hash, PartialEq, Eq, Copy, Clone)]
 pub enum Space {
@@ -0,0 +1,127 @@
+//! Mark-and-sweep collection of the subtrees that are not reachable anymore.
+//!
+//! Nodes are marked from a set of roots (eg. the roots of the commits to keep) by following children.
+//! Unmarked nodes are removed from the legion world and from the dedup table,
+//! then the labels that are not referenced by marked nodes are freed.
+//! Kept node identifiers stay valid,
+//! but kept labels are given new identifiers, see [`crate::store::labels::LabelRemap`].
+//!
+//! Any node identifier held outside of the stores (eg. in processing caches) and not given as a root
+//! must be forgotten after collecting if its node was removed, eg. with [`crate::store::merkle::MerkleIndex::retain_nodes`].
+//! Any label identifier held outside of the stores must be remapped with [`Collected::labels`].
+//! A [`crate::store::journal::Journal`] cannot continue after a collection, a new snapshot must be taken.
+
+use std::collections::HashSet;
+
+use crate::store::{
+    defaults::{LabelIdentifier, NodeIdentifier},
+    labels::{LabelRemap, LabelStore},
+    nodes::legion::NodeStore,
+    SimpleStores,
+};
+
+/// What was removed by a collection.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Collected {
+    pub nodes: usize,
+    /// new identifiers of the kept labels
+    pub labels: LabelRemap,
+}
+
+impl<TS> SimpleStores<TS, NodeStore, LabelStore> {
+    /// Removes every node that is not reachable from `roots`,
+    /// `label_roots` are labels to keep even if no kept node references them.
+    pub fn collect_garbage(
+        &mut self,
+        roots: impl IntoIterator<Item = NodeIdentifier>,
+        label_roots: impl IntoIterator<Item = LabelIdentifier>,
+    ) -> Collected {
+        let mut labels: HashSet<_> = label_roots.into_iter().collect();
+        let marked = self.node_store.mark(roots, &mut labels);
+        let nodes = self.node_store.sweep(&marked);
+        let labels = self.label_store.compact(|l| labels.contains(l));
+        self.node_store.remap_labels(&labels);
+        log::info!("collected {} nodes and {} labels", nodes, labels.freed());
+        Collected { nodes, labels }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+        nodes::HashSize,
+        store::nodes::legion::compo::{self, CS},
+        types::{LabelStore as _, WithChildren},
+    };
+
+    fn insert(
+        stores: &mut SimpleStores<()>,
+        l: &str,
+        cs: Vec<NodeIdentifier>,
+    ) -> NodeIdentifier {
+        let label = stores.label_store.get_or_insert(l);
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1 + cs.len() as u32);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = stores.node_store.prepare_insertion(hashable, |_| false);
+        NodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (
+                label,
+                hbuilder.build(),
+                compo::Size(1 + cs.len() as u32),
+                CS(cs.into_boxed_slice()),
+            ),
+        )
+    }
+
+    #[test]
+    fn keep_reachable() {
+        let mut stores = SimpleStores::<()>::default();
+        let shared = insert(&mut stores, "shared", vec![]);
+        let dead = insert(&mut stores, "dead", vec![]);
+        let kept = insert(&mut stores, "kept", vec![shared]);
+        let old = insert(&mut stores, "old", vec![shared, dead]);
+
+        let kept_label = stores.label_store.get("kept").unwrap();
+        let collected = stores.collect_garbage([kept], []);
+        assert_eq!(collected.nodes, 2);
+        assert_eq!(collected.labels.freed(), 2);
+        assert_eq!(stores.node_store.len(), 2);
+        assert!(stores.node_store.try_resolve(old).is_none());
+        assert!(stores.node_store.try_resolve(dead).is_none());
+        let n = stores.node_store.resolve(kept);
+        assert_eq!(n.children().unwrap().0, [shared]);
+        let n = stores.node_store.resolve(shared);
+        let l = crate::types::Labeled::get_label_unchecked(&n);
+        assert_eq!(stores.label_store.resolve(l), "shared");
+        assert_eq!(stores.label_store.get("dead"), None);
+
+        // kept nodes reference the new identifiers of their labels
+        let kept_label = collected.labels.get(&kept_label);
+        assert_eq!(stores.label_store.get("kept"), kept_label);
+        let n = stores.node_store.resolve(kept);
+        let l = crate::types::Labeled::get_label_unchecked(&n);
+        assert_eq!(stores.label_store.resolve(l), "kept");
+        let label = stores.label_store.get_or_insert("dead");
+        assert_eq!(stores.label_store.resolve(&label), "dead");
+    }
+
+    #[test]
+    fn keep_builtin_labels() {
+        let mut stores = SimpleStores::<()> {
+            label_store: LabelStore::new(),
+            ..Default::default()
+        };
+        let length = stores.label_store.get("length").unwrap();
+        let dead = insert(&mut stores, "dead", vec![]);
+        let collected = stores.collect_garbage([], []);
+        assert_eq!(collected.nodes, 1);
+        assert_eq!(collected.labels.freed(), 1);
+        assert!(stores.node_store.try_resolve(dead).is_none());
+        assert_eq!(stores.label_store.get("length"), Some(length));
+        assert_eq!(stores.label_store.resolve(&length), "length");
+    }
+}
@@ -0,0 +1,203 @@
+//! Append-only journal of the content added to [`SimpleStores`].
+//!
+//! Complements [`crate::store::snapshot`]: instead of persisting whole stores,
+//! each record only contains the labels and nodes created since the previous record,
+//! eg. the few thousand nodes of a newly processed commit.
+//! Replaying the journal on top of the stores it started from (empty or reloaded from a snapshot)
+//! gives back the same label and node identifiers.
+//!
+//! A record that was only partially written (eg. after a crash) is ignored during replay.
+//!
+//! # Layout
+//! All integers are little endian.
+//! ```text
+//! magic: b"HYPERLOG", version: u32,
+//! records: [byte_len: u64, labels, nodes, checksum: u64]*
+//! ```
+//! where `labels` and `nodes` are laid out as in snapshots.
+
+use std::io::{Read, Write};
+
+use crate::store::{
+    labels::LabelStore,
+    nodes::legion::NodeStore,
+    snapshot::{read_labels, read_nodes, write_labels, write_nodes, Codecs, SnapshotError},
+    SimpleStores,
+};
+
+pub const MAGIC: &[u8; 8] = b"HYPERLOG";
+/// Bumped on each incompatible change of the layout.
+pub const VERSION: u32 = 1;
+
+/// FNV-1a, only meant to detect torn writes
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Appends records to a journal, see [`self`] for details.
+pub struct Journal<W: Write> {
+    out: W,
+    /// labels already recorded
+    labels: usize,
+}
+
+impl<W: Write> Journal<W> {
+    /// Starts a new journal in `out`,
+    /// following records will only contain what is added to `stores` from now on.
+    pub fn new<TS>(
+        stores: &mut SimpleStores<TS, NodeStore, LabelStore>,
+        mut out: W,
+    ) -> Result<Self, SnapshotError> {
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.flush()?;
+        Ok(Self::append(stores, out))
+    }
+
+    /// Continues a journal that was just replayed with [`replay`], `out` must be positioned at its end.
+    pub fn append<TS>(stores: &mut SimpleStores<TS, NodeStore, LabelStore>, out: W) -> Self {
+        stores.node_store.start_journal();
+        let labels = stores.label_store.len();
+        Self { out, labels }
+    }
+
+    /// Appends a record with everything added to `stores` since the previous one,
+    /// returns the number of recorded nodes.
+    pub fn record<TS>(
+        &mut self,
+        stores: &mut SimpleStores<TS, NodeStore, LabelStore>,
+        codecs: &Codecs,
+    ) -> Result<usize, SnapshotError> {
+        if stores.label_store.len() < self.labels {
+            // NOTE see crate::store::gc, compacted labels do not match the recorded ones anymore
+            return Err(SnapshotError::Corrupted(
+                "labels were compacted since the previous record",
+            ));
+        }
+        let ids = stores.node_store.take_journal();
+        let mut buf = vec![];
+        let r = write_labels(&mut buf, &stores.label_store, self.labels).and_then(|_| {
+            // NOTE records are not meant to be mapped, no need for alignment
+            write_nodes(&mut buf, &stores.node_store, ids.iter().copied(), codecs, 0)
+        });
+        if let Err(e) = r {
+            stores.node_store.untake_journal(ids);
+            return Err(e);
+        }
+        self.out.write_all(&(buf.len() as u64).to_le_bytes())?;
+        self.out.write_all(&buf)?;
+        self.out.write_all(&checksum(&buf).to_le_bytes())?;
+        self.out.flush()?;
+        self.labels = stores.label_store.len();
+        Ok(ids.len())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Replays on `stores` the records of a journal,
+/// `stores` must be in the state they were when the journal was started.
+///
+/// Returns the number of replayed records.
+pub fn replay<TS>(
+    stores: &mut SimpleStores<TS, NodeStore, LabelStore>,
+    r: &mut impl Read,
+    codecs: &Codecs,
+) -> Result<usize, SnapshotError> {
+    let mut magic = [0; 8];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(SnapshotError::BadMagic);
+    }
+    let mut version = [0; 4];
+    r.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    let mut records = 0;
+    loop {
+        let mut len = [0; 8];
+        match r.read_exact(&mut len) {
+            Ok(()) => (),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let len = u64::from_le_bytes(len) as usize;
+        let mut buf = vec![];
+        r.by_ref().take(len as u64 + 8).read_to_end(&mut buf)?;
+        if buf.len() < len + 8 {
+            log::warn!("ignoring truncated record {}", records);
+            break;
+        }
+        let (payload, sum) = buf.split_at(len);
+        if checksum(payload).to_le_bytes() != sum {
+            log::warn!("ignoring corrupted record {}", records);
+            break;
+        }
+        let mut payload = payload;
+        read_labels(&mut payload, &mut stores.label_store)?;
+        read_nodes(&mut payload, &mut stores.node_store, codecs)?;
+        records += 1;
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+        nodes::HashSize,
+        store::{defaults::LabelIdentifier, nodes::legion::compo},
+        types::LabelStore as _,
+    };
+
+    fn insert_leaf(stores: &mut SimpleStores<()>, l: &str) -> crate::store::defaults::NodeIdentifier {
+        let label = stores.label_store.get_or_insert(l);
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = stores.node_store.prepare_insertion(hashable, |x| {
+            x.get_component::<LabelIdentifier>()
+                .map_or(false, |x| x == &label)
+        });
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        NodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (label, hbuilder.build(), compo::BytesLen(l.len() as u32)),
+        )
+    }
+
+    #[test]
+    fn replay_records() {
+        let codecs = Codecs::default();
+        let mut stores = SimpleStores::<()>::default();
+        let mut journal = Journal::new(&mut stores, vec![]).unwrap();
+        let a = insert_leaf(&mut stores, "a");
+        assert_eq!(journal.record(&mut stores, &codecs).unwrap(), 1);
+        insert_leaf(&mut stores, "a");
+        let b = insert_leaf(&mut stores, "b");
+        assert_eq!(journal.record(&mut stores, &codecs).unwrap(), 1);
+        let log = journal.into_inner();
+
+        let mut replayed = SimpleStores::<()>::default();
+        assert_eq!(replay(&mut replayed, &mut &log[..], &codecs).unwrap(), 2);
+        assert_eq!(replayed.node_store.len(), 2);
+        assert_eq!(replayed.label_store.get("b"), stores.label_store.get("b"));
+        assert_eq!(insert_leaf(&mut replayed, "a"), a);
+        assert_eq!(insert_leaf(&mut replayed, "b"), b);
+
+        // a torn write only loses the last record
+        let mut replayed = SimpleStores::<()>::default();
+        let torn = &log[..log.len() - 3];
+        assert_eq!(replay(&mut replayed, &mut &torn[..], &codecs).unwrap(), 1);
+        assert_eq!(replayed.node_store.len(), 1);
+    }
+}
@@ -0,0 +1,438 @@
+//! Labels stored front-coded, ie. each label only stores what differs from the previous one.
+//!
+//! Labels are dominated by qualified names, eg. `java.util.List` then `java.util.Map`,
+//! that share long prefixes with the labels inserted just before them.
+//! Labels are grouped in blocks of [`BLOCK`], the first label of a block is stored as is,
+//! so decoding a label only needs to go through the start of its block.
+//!
+//! [`LabelStore::resolve`](crate::types::LabelStore::resolve) still lends a `&str`:
+//! labels stored as is are lent directly, others are decoded once in an arena.
+//! Lent labels borrow the store, so the arena can only be emptied through `&mut self`:
+//! it is emptied by the next insertion once it holds more than [`RESOLVED_CAPACITY`] bytes,
+//! or explicitly with [`LabelStore::clear_resolved`], eg. after a long read only phase.
+
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    fmt::{Debug, Display},
+    sync::Mutex,
+};
+
+use hashbrown::hash_map::DefaultHashBuilder;
+use string_interner::Symbol;
+
+use super::{DefaultLabelIdentifier, DefaultLabelValue};
+use crate::{types::LabelStore as _, utils::make_hash};
+
+/// Number of labels per block.
+pub const BLOCK: usize = 16;
+
+/// Bytes of decoded labels above which the arena is emptied by the next insertion.
+pub const RESOLVED_CAPACITY: usize = 1 << 20;
+
+/// Entry of the dedup table,
+/// keeps the hash so that growing the table does not need to decode labels.
+#[derive(Clone, Copy)]
+struct Key {
+    id: u32,
+    hash: u64,
+}
+
+#[derive(Default)]
+pub struct LabelStore {
+    count: usize,
+    len: usize,
+    /// `[shared: varint, len: varint, suffix]*`
+    data: Vec<u8>,
+    /// offset in `data` of each block
+    blocks: Vec<usize>,
+    /// last inserted label, the next one is coded against it
+    last: String,
+    dedup: hashbrown::HashMap<Key, (), ()>,
+    hasher: DefaultHashBuilder,
+    /// arena of decoded labels
+    resolved: Mutex<Resolved>,
+}
+
+#[derive(Default)]
+struct Resolved {
+    labels: HashMap<u32, Box<str>>,
+    bytes: usize,
+}
+
+fn write_varint(out: &mut Vec<u8>, mut x: usize) {
+    while x >= 0x80 {
+        out.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    out.push(x as u8);
+}
+
+fn read_varint(data: &[u8], i: &mut usize) -> usize {
+    let mut x = 0;
+    let mut shift = 0;
+    loop {
+        let b = data[*i];
+        *i += 1;
+        x |= ((b & 0x7f) as usize) << shift;
+        if b < 0x80 {
+            return x;
+        }
+        shift += 7;
+    }
+}
+
+fn common_prefix(a: &str, b: &str) -> usize {
+    let mut n = a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count();
+    while !b.is_char_boundary(n) {
+        n -= 1;
+    }
+    n
+}
+
+/// Returns the shared prefix length and the range in `data` of the suffix of the label `id`.
+fn entry(data: &[u8], blocks: &[usize], id: usize) -> (usize, std::ops::Range<usize>) {
+    let block = id / BLOCK;
+    let mut i = blocks[block];
+    for _ in block * BLOCK..id {
+        read_varint(data, &mut i);
+        let len = read_varint(data, &mut i);
+        i += len;
+    }
+    let shared = read_varint(data, &mut i);
+    let len = read_varint(data, &mut i);
+    (shared, i..i + len)
+}
+
+fn decode_into(data: &[u8], blocks: &[usize], id: usize, out: &mut String) {
+    out.clear();
+    let block = id / BLOCK;
+    let mut i = blocks[block];
+    for _ in block * BLOCK..=id {
+        let shared = read_varint(data, &mut i);
+        let len = read_varint(data, &mut i);
+        out.truncate(shared);
+        out.push_str(std::str::from_utf8(&data[i..i + len]).expect("labels are utf8"));
+        i += len;
+    }
+}
+
+fn to_id(id: u32) -> DefaultLabelIdentifier {
+    DefaultLabelIdentifier::try_from_usize(id as usize).unwrap()
+}
+
+impl LabelStore {
+    fn decode(&self, id: usize) -> String {
+        let mut s = String::new();
+        decode_into(&self.data, &self.blocks, id, &mut s);
+        s
+    }
+
+    fn find(&self, hash: u64, label: &str) -> Option<u32> {
+        let mut buf = String::new();
+        self.dedup
+            .raw_entry()
+            .from_hash(hash, |k| {
+                decode_into(&self.data, &self.blocks, k.id as usize, &mut buf);
+                buf == label
+            })
+            .map(|(k, _)| k.id)
+    }
+
+    /// Appends a label that is not already stored.
+    fn push(&mut self, hash: u64, label: &str) -> u32 {
+        let id = self.len;
+        let shared = if id % BLOCK == 0 {
+            self.blocks.push(self.data.len());
+            0
+        } else {
+            common_prefix(&self.last, label)
+        };
+        write_varint(&mut self.data, shared);
+        write_varint(&mut self.data, label.len() - shared);
+        self.data.extend_from_slice(&label.as_bytes()[shared..]);
+        self.last.clear();
+        self.last.push_str(label);
+        self.len += 1;
+        let key = Key {
+            id: id as u32,
+            hash,
+        };
+        match self.dedup.raw_entry_mut().from_hash(hash, |_| false) {
+            hashbrown::hash_map::RawEntryMut::Occupied(_) => unreachable!(),
+            hashbrown::hash_map::RawEntryMut::Vacant(vacant) => {
+                vacant.insert_with_hasher(hash, key, (), |k| k.hash);
+            }
+        }
+        id as u32
+    }
+
+    fn intern(&mut self, label: &str) -> u32 {
+        let resolved = self.resolved.get_mut().unwrap();
+        if resolved.bytes > RESOLVED_CAPACITY {
+            *resolved = Default::default();
+        }
+        let hash = make_hash(&self.hasher, label);
+        match self.find(hash, label) {
+            Some(id) => id,
+            None => self.push(hash, label),
+        }
+    }
+
+    /// Bytes used by the coded labels, excluding the dedup table and resolved labels.
+    pub fn coded_bytes(&self) -> usize {
+        self.data.len() + self.blocks.len() * std::mem::size_of::<usize>()
+    }
+
+    /// Estimated bytes used by the store, see [`crate::store::stats`]
+    pub(crate) fn bytes(&self) -> usize {
+        let resolved = self.resolved.lock().unwrap().bytes;
+        self.coded_bytes() + self.dedup.capacity() * (std::mem::size_of::<Key>() + 1) + resolved
+    }
+
+    /// Frees the decoded labels lent by `resolve`.
+    pub fn clear_resolved(&mut self) {
+        *self.resolved.get_mut().unwrap() = Default::default();
+    }
+
+    /// Iterates over stored labels, ordered by identifier.
+    ///
+    /// Labels are decoded one after the other, without going through the arena of resolved labels,
+    /// so only the labels stored as is are borrowed.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            data: &self.data,
+            i: 0,
+            id: 0,
+            len: self.len,
+            last: String::new(),
+        }
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Stores persisted `labels` in the given order,
+    /// giving back the same identifiers as the store that produced them.
+    pub(crate) fn extend_restored<'a>(&mut self, count: usize, labels: impl Iterator<Item = &'a str>) {
+        for l in labels {
+            self.intern(l);
+        }
+        self.count = count;
+    }
+
+    /// Frees the labels that are not kept, except the builtin ones.
+    ///
+    /// Kept labels are stored again, in the same order, so their identifiers change.
+    /// Returns the new identifiers, the ones held outside of the store must be remapped,
+    /// eg. with [`crate::store::nodes::legion::NodeStore::remap_labels`].
+    pub(crate) fn compact(
+        &mut self,
+        keep: impl Fn(&DefaultLabelIdentifier) -> bool,
+    ) -> super::LabelRemap {
+        let mut compacted = Self {
+            count: self.count,
+            ..Default::default()
+        };
+        let mut remap = Vec::with_capacity(self.len);
+        let mut l = String::new();
+        for i in 0..self.len {
+            decode_into(&self.data, &self.blocks, i, &mut l);
+            let id = to_id(i as u32);
+            remap.push(
+                (keep(&id) || super::BUILTIN_LABELS.contains(&l.as_str()))
+                    .then(|| to_id(compacted.intern(&l))),
+            );
+        }
+        *self = compacted;
+        super::LabelRemap(remap)
+    }
+
+    pub fn new() -> Self {
+        let mut r = Self {
+            count: 1,
+            ..Default::default()
+        };
+        for l in super::BUILTIN_LABELS {
+            r.get_or_insert(*l); // TODO verify/model statically
+        }
+        r
+    }
+}
+
+impl Debug for LabelStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("compressed::LabelStore")
+            .field("count", &self.count)
+            .field("len", &self.len)
+            .field("coded_bytes", &self.coded_bytes())
+            .finish()
+    }
+}
+
+impl Display for LabelStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for i in 0..self.len {
+            writeln!(f, "{:?}:{:?}", i, self.decode(i))?
+        }
+        Ok(())
+    }
+}
+
+impl crate::types::LabelStore<DefaultLabelValue> for LabelStore {
+    type I = DefaultLabelIdentifier;
+    fn get_or_insert<T: Borrow<DefaultLabelValue>>(&mut self, node: T) -> Self::I {
+        self.count += 1;
+        to_id(self.intern(node.borrow()))
+    }
+    fn get<T: Borrow<DefaultLabelValue>>(&self, node: T) -> Option<Self::I> {
+        let node = node.borrow();
+        self.find(make_hash(&self.hasher, node), node).map(to_id)
+    }
+
+    fn resolve(&self, id: &Self::I) -> &DefaultLabelValue {
+        let i = id.to_usize();
+        assert!(i < self.len, "unknown label {}", i);
+        let (shared, suffix) = entry(&self.data, &self.blocks, i);
+        if shared == 0 {
+            return std::str::from_utf8(&self.data[suffix]).expect("labels are utf8");
+        }
+        let mut resolved = self.resolved.lock().unwrap();
+        let Resolved { labels, bytes } = &mut *resolved;
+        let s: *const str = &**labels.entry(i as u32).or_insert_with(|| {
+            let l = self.decode(i).into_boxed_str();
+            *bytes += l.len();
+            l
+        });
+        // SAFETY: a decoded label is boxed, it is neither moved nor dropped while `self` is borrowed,
+        // as the arena is only emptied through `&mut self`
+        unsafe { &*s }
+    }
+}
+
+impl crate::types::LabelStore<DefaultLabelValue> for &LabelStore {
+    type I = DefaultLabelIdentifier;
+    fn get_or_insert<T: Borrow<DefaultLabelValue>>(&mut self, _node: T) -> Self::I {
+        unimplemented!("&mut & does not allow to mutate in place :/")
+    }
+    fn get<T: Borrow<DefaultLabelValue>>(&self, node: T) -> Option<Self::I> {
+        (*self).get(node)
+    }
+
+    fn resolve(&self, id: &Self::I) -> &DefaultLabelValue {
+        (*self).resolve(id)
+    }
+}
+
+/// Iterator over the labels of a [`LabelStore`], see [`LabelStore::iter`].
+pub struct Iter<'a> {
+    data: &'a [u8],
+    /// offset in `data` of the next label
+    i: usize,
+    id: usize,
+    len: usize,
+    last: String,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (DefaultLabelIdentifier, Cow<'a, str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.id >= self.len {
+            return None;
+        }
+        let id = to_id(self.id as u32);
+        self.id += 1;
+        let shared = read_varint(self.data, &mut self.i);
+        let len = read_varint(self.data, &mut self.i);
+        let suffix = &self.data[self.i..self.i + len];
+        let suffix = std::str::from_utf8(suffix).expect("labels are utf8");
+        self.i += len;
+        self.last.truncate(shared);
+        self.last.push_str(suffix);
+        if shared == 0 {
+            Some((id, Cow::Borrowed(suffix)))
+        } else {
+            Some((id, Cow::Owned(self.last.clone())))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.len - self.id;
+        (n, Some(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn front_coding() {
+        let mut store = LabelStore::new();
+        let labels: Vec<_> = (0..100)
+            .map(|i| format!("java.util.concurrent.atomic.Atomic{}", i))
+            .chain(["é", "éa", "", "java"].map(String::from))
+            .collect();
+        let ids: Vec<_> = labels
+            .iter()
+            .map(|l| store.get_or_insert(l.as_str()))
+            .collect();
+        for (l, id) in labels.iter().zip(&ids) {
+            assert_eq!(store.get(l.as_str()), Some(*id));
+            assert_eq!(store.get_or_insert(l.as_str()), *id);
+            assert_eq!(store.resolve(id), l);
+        }
+        assert_eq!(store.get("java.util"), None);
+        assert_eq!(store.len(), labels.len() + 1);
+        let plain: usize = labels.iter().map(|l| l.len()).sum();
+        assert!(store.coded_bytes() < plain / 2);
+
+        store.clear_resolved();
+        assert_eq!(store.resolve(&ids[42]), labels[42]);
+
+        let iterated: Vec<_> = store.iter().skip(1).map(|(_, l)| l.into_owned()).collect();
+        assert_eq!(iterated, labels);
+        assert_eq!(store.resolved.get_mut().unwrap().bytes, labels[42].len());
+    }
+
+    #[test]
+    fn bounded_resolved() {
+        let mut store = LabelStore::new();
+        let long = "a".repeat(RESOLVED_CAPACITY / 4);
+        let ids: Vec<_> = (0..8)
+            .map(|i| store.get_or_insert(format!("{}{}", long, i)))
+            .collect();
+        for id in &ids {
+            store.resolve(id);
+        }
+        assert!(store.resolved.get_mut().unwrap().bytes > RESOLVED_CAPACITY);
+        store.get_or_insert("b");
+        assert_eq!(store.resolved.get_mut().unwrap().bytes, 0);
+        assert_eq!(store.resolve(&ids[3]), format!("{}3", long));
+    }
+
+    #[test]
+    fn compact_remaps_ids() {
+        let mut store = LabelStore::new();
+        let a = store.get_or_insert("a.b.c");
+        let b = store.get_or_insert("a.b.d");
+        let c = store.get_or_insert("a.e");
+        let len = store.len();
+        let remap = store.compact(|x| x != &b);
+        assert_eq!(remap.freed(), 1);
+        assert_eq!(store.len(), len - 1);
+        assert_eq!(remap.get(&a), Some(a));
+        assert_eq!(remap.get(&b), None);
+        let c = remap.get(&c).unwrap();
+        assert_eq!(store.resolve(&a), "a.b.c");
+        assert_eq!(store.resolve(&c), "a.e");
+        assert_eq!(store.get("a.b.d"), None);
+        assert_eq!(store.resolve(&store.get_or_insert("a.b.d")), "a.b.d");
+    }
+}
@@ -0,0 +1,128 @@
+//! Labels interned as is, see [`string_interner::StringInterner`].
+
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+};
+
+use string_interner::{StringInterner, Symbol};
+
+use super::{DefaultLabelIdentifier, DefaultLabelValue};
+use crate::types::LabelStore as _;
+
+#[derive(Default)]
+pub struct LabelStore {
+    count: usize,
+    internal: StringInterner, //VecMapStore<OwnedLabel, LabelIdentifier>,
+}
+
+impl Debug for LabelStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LabelStore")
+            .field("count", &self.count)
+            .field("internal_len", &self.internal.len())
+            .field("internal", &self.internal)
+            .finish()
+    }
+}
+
+impl Display for LabelStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, x) in self.internal.clone().into_iter() {
+            writeln!(f, "{:?}:{:?}", i.to_usize(), x)?
+        }
+        Ok(())
+    }
+}
+
+impl crate::types::LabelStore<DefaultLabelValue> for LabelStore {
+    type I = DefaultLabelIdentifier;
+    fn get_or_insert<T: Borrow<DefaultLabelValue>>(&mut self, node: T) -> Self::I {
+        self.count += 1;
+        self.internal.get_or_intern(node.borrow())
+    }
+    fn get<T: Borrow<DefaultLabelValue>>(&self, node: T) -> Option<Self::I> {
+        self.internal.get(node.borrow())
+    }
+
+    fn resolve(&self, id: &Self::I) -> &DefaultLabelValue {
+        self.internal.resolve(*id).unwrap()
+    }
+}
+
+impl crate::types::LabelStore<DefaultLabelValue> for &LabelStore {
+    type I = DefaultLabelIdentifier;
+    fn get_or_insert<T: Borrow<DefaultLabelValue>>(&mut self, _node: T) -> Self::I {
+        unimplemented!("&mut & does not allow to mutate in place :/")
+    }
+    fn get<T: Borrow<DefaultLabelValue>>(&self, node: T) -> Option<Self::I> {
+        self.internal.get(node.borrow())
+    }
+
+    fn resolve(&self, id: &Self::I) -> &DefaultLabelValue {
+        self.internal.resolve(*id).unwrap()
+    }
+}
+
+impl LabelStore {
+    /// Iterates over interned labels, ordered by identifier.
+    pub fn iter(&self) -> impl Iterator<Item = (DefaultLabelIdentifier, &str)> {
+        (&self.internal).into_iter()
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Estimated bytes used by the store, see [`crate::store::stats`]
+    pub(crate) fn bytes(&self) -> usize {
+        // NOTE the interner keeps each label contiguously, plus its end offset and its hash entry
+        let overhead = 2 * std::mem::size_of::<usize>() + std::mem::size_of::<DefaultLabelIdentifier>();
+        self.iter().map(|(_, l)| l.len() + overhead).sum()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.internal.len()
+    }
+
+    /// Interns persisted `labels` in the given order,
+    /// giving back the same identifiers as the store that produced them.
+    pub(crate) fn extend_restored<'a>(&mut self, count: usize, labels: impl Iterator<Item = &'a str>) {
+        for l in labels {
+            self.internal.get_or_intern(l);
+        }
+        self.count = count;
+    }
+
+    /// Frees the labels that are not kept, except the builtin ones.
+    ///
+    /// Kept labels are interned again, in the same order, so their identifiers change.
+    /// Returns the new identifiers, the ones held outside of the store must be remapped,
+    /// eg. with [`crate::store::nodes::legion::NodeStore::remap_labels`].
+    pub(crate) fn compact(
+        &mut self,
+        keep: impl Fn(&DefaultLabelIdentifier) -> bool,
+    ) -> super::LabelRemap {
+        let mut internal = StringInterner::with_capacity(self.internal.len());
+        let mut remap = Vec::with_capacity(self.internal.len());
+        for (id, l) in &self.internal {
+            remap.push(
+                (keep(&id) || super::BUILTIN_LABELS.contains(&l))
+                    .then(|| internal.get_or_intern(l)),
+            );
+        }
+        self.internal = internal;
+        super::LabelRemap(remap)
+    }
+
+    pub fn new() -> Self {
+        let mut r = Self {
+            count: 1,
+            internal: Default::default(),
+        };
+        for l in super::BUILTIN_LABELS {
+            r.get_or_insert(*l); // TODO verify/model statically
+        }
+        r
+    }
+}
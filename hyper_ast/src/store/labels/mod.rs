@@ -1,80 +1,80 @@
-use std::{
-    borrow::Borrow,
-    fmt::{Debug, Display},
-};
+//! Label stores, [`LabelStore`] is selected with the `compressed_labels` feature.
 
-use string_interner::{DefaultSymbol, StringInterner, Symbol};
+use std::sync::Mutex;
 
-use crate::types::LabelStore as _;
+use string_interner::{DefaultSymbol, Symbol};
 
-#[derive(Default)]
-pub struct LabelStore {
-    count: usize,
-    internal: StringInterner, //VecMapStore<OwnedLabel, LabelIdentifier>,
-}
+#[cfg(feature = "compressed_labels")]
+pub mod compressed;
+pub mod interned;
 
-impl Debug for LabelStore {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LabelStore")
-            .field("count", &self.count)
-            .field("internal_len", &self.internal.len())
-            .field("internal", &self.internal)
-            .finish()
-    }
-}
+#[cfg(feature = "compressed_labels")]
+pub use compressed::LabelStore;
+#[cfg(not(feature = "compressed_labels"))]
+pub use interned::LabelStore;
 
-impl Display for LabelStore {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, x) in self.internal.clone().into_iter() {
-            writeln!(f, "{:?}:{:?}", i.to_usize(), x)?
-        }
-        Ok(())
-    }
-}
+/// Labels interned when creating a store, they are kept when compacting
+pub(crate) const BUILTIN_LABELS: &[&str] = &["length"];
 
 pub type DefaultLabelValue = str;
 pub type DefaultLabelIdentifier = DefaultSymbol;
 
+/// Identifiers given to the kept labels by a compaction,
+/// the labels freed by the compaction do not have one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LabelRemap(Vec<Option<DefaultLabelIdentifier>>);
+
+impl LabelRemap {
+    /// New identifier of the label that was identified by `old`, none if it was freed
+    pub fn get(&self, old: &DefaultLabelIdentifier) -> Option<DefaultLabelIdentifier> {
+        self.0.get(old.to_usize()).copied().flatten()
+    }
+
+    /// Number of freed labels
+    pub fn freed(&self) -> usize {
+        self.0.iter().filter(|x| x.is_none()).count()
+    }
+}
+
 pub fn label_id_from_usize(x: usize) -> Option<DefaultLabelIdentifier> {
     DefaultLabelIdentifier::try_from_usize(x)
 }
 
-impl crate::types::LabelStore<DefaultLabelValue> for LabelStore {
-    type I = DefaultLabelIdentifier;
-    fn get_or_insert<T: Borrow<DefaultLabelValue>>(&mut self, node: T) -> Self::I {
-        self.count += 1;
-        self.internal.get_or_intern(node.borrow())
-    }
-    fn get<T: Borrow<DefaultLabelValue>>(&self, node: T) -> Option<Self::I> {
-        self.internal.get(node.borrow())
-    }
+/// Interning of labels by tree generators,
+/// either directly in a [`LabelStore`] or in one shared between threads, see [`SharedLabels`].
+pub trait LabelInterner {
+    fn intern(&mut self, label: &str) -> DefaultLabelIdentifier;
 
-    fn resolve(&self, id: &Self::I) -> &DefaultLabelValue {
-        self.internal.resolve(*id).unwrap()
-    }
+    /// Gives `f` the underlying store, eg. to resolve labels
+    fn with_store<R>(&self, f: impl FnOnce(&LabelStore) -> R) -> R;
 }
 
-impl crate::types::LabelStore<DefaultLabelValue> for &LabelStore {
-    type I = DefaultLabelIdentifier;
-    fn get_or_insert<T: Borrow<DefaultLabelValue>>(&mut self, _node: T) -> Self::I {
-        unimplemented!("&mut & does not allow to mutate in place :/")
+impl LabelInterner for LabelStore {
+    fn intern(&mut self, label: &str) -> DefaultLabelIdentifier {
+        crate::types::LabelStore::get_or_insert(self, label)
     }
-    fn get<T: Borrow<DefaultLabelValue>>(&self, node: T) -> Option<Self::I> {
-        self.internal.get(node.borrow())
+
+    fn with_store<R>(&self, f: impl FnOnce(&LabelStore) -> R) -> R {
+        f(self)
     }
+}
+
+/// Interns labels in a store shared by the threads of a concurrent generation,
+/// see [`crate::store::nodes::legion::concurrent`]
+pub struct SharedLabels<'a>(&'a Mutex<LabelStore>);
 
-    fn resolve(&self, id: &Self::I) -> &DefaultLabelValue {
-        self.internal.resolve(*id).unwrap()
+impl<'a> SharedLabels<'a> {
+    pub fn new(store: &'a Mutex<LabelStore>) -> Self {
+        Self(store)
     }
 }
 
-impl LabelStore {
-    pub fn new() -> Self {
-        let mut r = Self {
-            count: 1,
-            internal: Default::default(),
-        };
-        r.get_or_insert("length"); // TODO verify/model statically
-        r
+impl<'a> LabelInterner for SharedLabels<'a> {
+    fn intern(&mut self, label: &str) -> DefaultLabelIdentifier {
+        crate::types::LabelStore::get_or_insert(&mut *self.0.lock().unwrap(), label)
+    }
+
+    fn with_store<R>(&self, f: impl FnOnce(&LabelStore) -> R) -> R {
+        f(&self.0.lock().unwrap())
     }
 }
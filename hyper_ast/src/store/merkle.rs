@@ -0,0 +1,278 @@
+//! Content-addressed identifiers of nodes, stable across processes and stores.
+//!
+//! A [`NodeIdentifier`] is a legion entity, only meaningful in the process that created it.
+//! A [`MerkleId`] is derived from the type, the label and the [`MerkleId`]s of the children of a node,
+//! like a Merkle tree, so the same subtree gets the same [`MerkleId`] in any store,
+//! eg. after a restart of the server or on another server.
+//!
+//! Types are identified by their name and labels by their text,
+//! thus identifiers do not depend on interning, nor on the width of hashs (see [`crate::nodes::HashSize`]).
+//! They are computed with FNV-1a on 128 bits, which is stable across platforms and versions.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+    str::FromStr,
+};
+
+use crate::{
+    store::{
+        labels::LabelStore,
+        nodes::legion::{HashedNodeRef, NodeIdentifier, NodeStore},
+        SimpleStores,
+    },
+    types::{LabelStore as _, Labeled, WithChildren},
+};
+
+/// Content-addressed identifier of a node, displayed as 32 hexadecimal digits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "String", try_from = "String")
+)]
+pub struct MerkleId(pub u128);
+
+impl Display for MerkleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+impl FromStr for MerkleId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u128::from_str_radix(s, 16).map(MerkleId)
+    }
+}
+
+impl From<MerkleId> for String {
+    fn from(value: MerkleId) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for MerkleId {
+    type Error = std::num::ParseIntError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// FNV-1a on 128 bits
+struct Fnv(u128);
+
+impl Fnv {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// length-prefixed, so that consecutive fields cannot be confused
+    fn field(&mut self, bytes: &[u8]) {
+        self.bytes(&(bytes.len() as u64).to_le_bytes());
+        self.bytes(bytes);
+    }
+}
+
+impl Write for Fnv {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Two-way index between the nodes of a store and their [`MerkleId`]s,
+/// filled lazily as identifiers are computed.
+#[derive(Default)]
+pub struct MerkleIndex {
+    ids: HashMap<NodeIdentifier, MerkleId>,
+    nodes: HashMap<MerkleId, NodeIdentifier>,
+}
+
+impl MerkleIndex {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// [`MerkleId`] of `node` if it was already computed
+    pub fn get(&self, node: &NodeIdentifier) -> Option<MerkleId> {
+        self.ids.get(node).copied()
+    }
+
+    /// Node with the given [`MerkleId`],
+    /// among the subtrees whose identifiers were already computed.
+    pub fn node(&self, id: &MerkleId) -> Option<NodeIdentifier> {
+        self.nodes.get(id).copied()
+    }
+
+    /// Forgets the nodes that are not in `node_store` anymore,
+    /// eg. after collecting garbage (see [`crate::store::gc`]).
+    pub fn retain_nodes(&mut self, node_store: &NodeStore) {
+        self.ids.retain(|n, _| node_store.try_resolve(*n).is_some());
+        self.nodes
+            .retain(|_, n| node_store.try_resolve(*n).is_some());
+        for (n, id) in &self.ids {
+            self.nodes.entry(*id).or_insert(*n);
+        }
+    }
+
+    /// Computes the [`MerkleId`] of `root` and of all its descendants,
+    /// `ty` gives the type of a node, eg. with the type store of the nodes.
+    pub fn compute<K: Display>(
+        &mut self,
+        node_store: &NodeStore,
+        label_store: &LabelStore,
+        ty: impl Fn(&HashedNodeRef<NodeIdentifier>) -> K,
+        root: NodeIdentifier,
+    ) -> MerkleId {
+        if let Some(id) = self.get(&root) {
+            return id;
+        }
+        // iterative post-order, trees can be too deep for the stack
+        let mut stack = vec![(root, false)];
+        while let Some((node, visited)) = stack.pop() {
+            if self.ids.contains_key(&node) {
+                continue;
+            }
+            let n = node_store.resolve(node);
+            let cs = n.children().map_or(&[][..], |cs| &cs.0);
+            if !visited {
+                stack.push((node, true));
+                let pending = cs.iter().rev().filter(|c| !self.ids.contains_key(c));
+                stack.extend(pending.map(|c| (*c, false)));
+                continue;
+            }
+            let mut h = Fnv::new();
+            write!(h, "{}", ty(&n)).unwrap();
+            h.bytes(&[0xff]);
+            match n.try_get_label() {
+                Some(l) => h.field(label_store.resolve(l).as_bytes()),
+                None => h.bytes(&[0]),
+            }
+            h.bytes(&(cs.len() as u64).to_le_bytes());
+            for c in cs {
+                h.bytes(&self.ids[c].0.to_le_bytes());
+            }
+            let id = MerkleId(h.0);
+            self.ids.insert(node, id);
+            self.nodes.entry(id).or_insert(node);
+        }
+        self.ids[&root]
+    }
+}
+
+impl<TS> SimpleStores<TS, NodeStore, LabelStore> {
+    /// see [`MerkleIndex::compute`]
+    pub fn merkle_id<K: Display>(
+        &self,
+        index: &mut MerkleIndex,
+        ty: impl Fn(&TS, &HashedNodeRef<NodeIdentifier>) -> K,
+        root: NodeIdentifier,
+    ) -> MerkleId {
+        index.compute(
+            &self.node_store,
+            &self.label_store,
+            |n| ty(&self.type_store, n),
+            root,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+        nodes::HashSize,
+        store::{defaults::LabelIdentifier, nodes::legion::compo::CS},
+    };
+
+    fn insert(stores: &mut SimpleStores<()>, l: &str, cs: Vec<NodeIdentifier>) -> NodeIdentifier {
+        let label = stores.label_store.get_or_insert(l);
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1 + cs.len() as u32);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = stores.node_store.prepare_insertion(hashable, |x| {
+            x.get_component::<LabelIdentifier>()
+                .map_or(false, |x| x == &label)
+                && x.get_component::<CS<NodeIdentifier>>()
+                    .map_or(cs.is_empty(), |x| *x.0 == *cs)
+        });
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        if cs.is_empty() {
+            NodeStore::insert_after_prepare(insertion.vacant(), (label, hbuilder.build()))
+        } else {
+            NodeStore::insert_after_prepare(
+                insertion.vacant(),
+                (label, hbuilder.build(), CS(cs.into_boxed_slice())),
+            )
+        }
+    }
+
+    fn ty(_: &(), n: &HashedNodeRef<NodeIdentifier>) -> &'static str {
+        if n.children().is_some() {
+            "inner"
+        } else {
+            "leaf"
+        }
+    }
+
+    #[test]
+    fn stable_across_stores() {
+        let mut stores = SimpleStores::<()>::default();
+        let a = insert(&mut stores, "a", vec![]);
+        let b = insert(&mut stores, "b", vec![]);
+        let root = insert(&mut stores, "p", vec![a, b, a]);
+        let mut index = MerkleIndex::default();
+        let id = stores.merkle_id(&mut index, ty, root);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.node(&id), Some(root));
+
+        // other store, other insertion order, thus other entities and labels
+        let mut other = SimpleStores::<()>::default();
+        insert(&mut other, "x", vec![]);
+        let b2 = insert(&mut other, "b", vec![]);
+        let a2 = insert(&mut other, "a", vec![]);
+        let root2 = insert(&mut other, "p", vec![a2, b2, a2]);
+        assert_ne!(root, root2);
+        let mut index2 = MerkleIndex::default();
+        assert_eq!(other.merkle_id(&mut index2, ty, root2), id);
+        assert_eq!(index2.get(&b2), index.get(&b));
+
+        let swapped = insert(&mut other, "p", vec![b2, a2, a2]);
+        assert_ne!(other.merkle_id(&mut index2, ty, swapped), id);
+        assert_eq!(id.to_string().parse(), Ok(id));
+    }
+
+    #[test]
+    fn forget_collected() {
+        let mut stores = SimpleStores::<()>::default();
+        let a = insert(&mut stores, "a", vec![]);
+        let dead = insert(&mut stores, "dead", vec![a]);
+        let kept = insert(&mut stores, "kept", vec![a]);
+        let mut index = MerkleIndex::default();
+        let dead_id = stores.merkle_id(&mut index, ty, dead);
+        let kept_id = stores.merkle_id(&mut index, ty, kept);
+        assert_eq!(index.len(), 3);
+        stores.collect_garbage([kept], []);
+        index.retain_nodes(&stores.node_store);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get(&dead), None);
+        assert_eq!(index.node(&dead_id), None);
+        assert_eq!(index.node(&kept_id), Some(kept));
+    }
+}
@@ -2,10 +2,20 @@ use std::borrow::Borrow;
 
 use crate::types::{SimpleHyperAST, TypeStore};
 
+#[cfg(feature = "legion")]
+pub mod gc;
 pub mod handle;
+#[cfg(feature = "legion")]
+pub mod journal;
 pub mod labels;
+#[cfg(feature = "legion")]
+pub mod merkle;
 // pub mod mapped_world;
 pub mod nodes;
+#[cfg(feature = "legion")]
+pub mod snapshot;
+#[cfg(feature = "legion")]
+pub mod stats;
 // pub mod ecs; // TODO try a custom ecs ?
 // pub mod radix_hash_store; // TODO yet another WIP store
 // pub mod vec_map_store; // TODO yet another WIP store
@@ -1,4 +1,9 @@
-mod compo;
+//! Node store backed by hecs, with the same read and insertion API as [`super::legion::NodeStore`].
+//!
+//! Tree generators insert through [`super::NodeInserter`] and the components of [`super::compo`],
+//! so they can fill this store as well as the legion one.
+
+pub mod compo;
 mod elem;
 pub use elem::{HashedNodeRef, NodeIdentifier};
 
@@ -26,6 +31,12 @@ impl NodeStore {
     }
 }
 
+impl Default for NodeStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 mod store_read;
 mod store_write;
 pub use store_write::PendingInsert;
@@ -84,8 +95,7 @@ fn simple() {
     let entity = NodeStore::insert_after_prepare(vacant, built);
     {
         // check if everything is there
-        use crate::types::NodeStore;
-        let entry = node_store.resolve(&entity);
+        let entry = node_store.resolve(entity);
         use std::ops::Deref;
         if let Some(t) = entry.get_component::<&Ty>() {
             assert_eq!(&ty, t.deref());
@@ -13,11 +13,14 @@ use super::compo;
 
 pub type NodeIdentifier = hecs::Entity;
 
-#[repr(transparent)]
-pub struct HashedNodeRef<'a, T = NodeIdentifier>(
-    pub(super) hecs::EntityRef<'a>,
-    std::marker::PhantomData<T>,
-);
+/// Contrary to legion, hecs only lends components through guards,
+/// so the label and children are fetched upfront to be able to lend them with the lifetime of the node.
+pub struct HashedNodeRef<'a, T = NodeIdentifier> {
+    pub(super) entity: hecs::EntityRef<'a>,
+    label: Option<LabelIdentifier>,
+    cs: Option<ChildrenSlice<'a, NodeIdentifier>>,
+    _phantom: std::marker::PhantomData<T>,
+}
 
 impl NodeId for NodeIdentifier {
     type IdN = Self;
@@ -39,12 +42,30 @@ impl TypedNodeId for NodeIdentifier {
 
 impl<'a, T> HashedNodeRef<'a, T> {
     pub(super) fn new(e: hecs::EntityRef<'a>) -> Self {
-        Self(e, std::marker::PhantomData)
+        Self {
+            label: e.get::<&LabelIdentifier>().map(|x| *x),
+            cs: e.get::<&compo::CS<NodeIdentifier>>().map(ChildrenSlice),
+            entity: e,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn cast_type<U: NodeId>(self) -> HashedNodeRef<'a, U>
+    where
+        T: NodeId<IdN = U::IdN>,
+    {
+        HashedNodeRef {
+            entity: self.entity,
+            label: self.label,
+            cs: self.cs,
+            _phantom: std::marker::PhantomData,
+        }
     }
 
     /// Returns a reference to one of the entity's components.
-    pub fn get_component<C: hecs::ComponentRef<'a>>(self) -> Option<C::Ref> {
-        self.0.get::<C>()
+    pub fn get_component<C: hecs::ComponentRef<'a>>(&self) -> Option<C::Ref> {
+        self.entity.get::<C>()
     }
 }
 
@@ -57,26 +78,26 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> std::hash::Hash for HashedNodeRe
 impl<'a, Id: 'static + TypedNodeId<IdN = NodeIdentifier>> crate::types::Typed
     for HashedNodeRef<'a, Id>
 where
-    Id::Ty: Copy + Hash + Eq + hecs::ComponentRef<'a>,
+    Id::Ty: Copy + Hash + Eq + hecs::Component,
 {
     type Type = Id::Ty;
 
     fn get_type(&self) -> Id::Ty {
-        let t = self.0.get::<&Id::Ty>().unwrap();
+        let t = self.entity.get::<&Id::Ty>().unwrap();
         *t
     }
 }
 
 impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::WithStats for HashedNodeRef<'a, Id> {
     fn size(&self) -> usize {
-        self.0
+        self.entity
             .get::<&compo::Size>()
             .and_then(|x| x.0.to_usize())
             .unwrap_or(1)
     }
 
     fn height(&self) -> usize {
-        self.0
+        self.entity
             .get::<&compo::Height>()
             .and_then(|x| x.0.to_usize())
             .unwrap_or(1)
@@ -86,7 +107,7 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::WithSerialization
     for HashedNodeRef<'a, Id>
 {
     fn try_bytes_len(&self) -> Option<usize> {
-        self.0
+        self.entity
             .get::<&compo::BytesLen>()
             .and_then(|x| x.0.to_usize())
     }
@@ -94,7 +115,7 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::WithSerialization
 
 impl<'a, T> HashedNodeRef<'a, T> {
     pub fn size_no_spaces(&self) -> usize {
-        self.0
+        self.entity
             .get::<&compo::SizeNoSpaces>()
             .and_then(|x| x.0.to_usize())
             .unwrap_or(1)
@@ -108,16 +129,14 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::Stored for HashedN
 }
 
 impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> HashedNodeRef<'a, Id> {
-    pub fn cs(&self) -> Option<<Self as crate::types::WithChildren>::Children<'_>> {
-        self.0
-            .get::<&'a compo::CS<NodeIdentifier>>()
-            .map(|x| ChildrenSlice(x))
+    pub fn cs(&self) -> Option<&<Self as crate::types::WithChildren>::Children<'_>> {
+        self.cs.as_ref()
     }
     // pub fn no_spaces(&self) -> Option<&<Self as crate::types::WithChildren>::Children<'_>> {
     //     self.0
     //         .get::<&'a compo::NoSpacesCS<NodeIdentifier>>()
     //         .map(|x| x)
-    //         .or_else(|| self.0.get::<&compo::CS<NodeIdentifier>>().map(|x| x))
+    //         .or_else(|| self.entity.get::<&compo::CS<NodeIdentifier>>().map(|x| x))
     //         .map(|x| ChildrenSlice(x))
     // }
 }
@@ -238,7 +257,7 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::WithChildren
     }
 
     fn children(&self) -> Option<&Self::Children<'_>> {
-        todo!() // perdu
+        self.cs()
     }
 }
 
@@ -248,7 +267,7 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::WithHashs for Hash
 
     fn hash(&self, kind: &Self::HK) -> Self::HP {
         use crate::hashed::NodeHashs;
-        self.0
+        self.entity
             .get::<&SyntaxNodeHashs<Self::HP>>()
             .unwrap()
             .deref()
@@ -266,7 +285,7 @@ where
     }
 
     fn has_label(&self) -> bool {
-        self.0.get::<&LabelIdentifier>().is_some()
+        self.label.is_some()
     }
 }
 
@@ -274,15 +293,9 @@ impl<'a, Id: TypedNodeId<IdN = NodeIdentifier>> crate::types::Labeled for Hashed
     type Label = LabelIdentifier;
 
     fn get_label_unchecked(&self) -> &LabelIdentifier {
-        todo!("API changes needed")
-        // TODO it shows issues with the current API, i.e. should use GAT
-        // self.0
-        //     .get::<&LabelIdentifier>()
-        //     .expect("check with self.has_label()")
-        //     .deref()
+        self.label.as_ref().expect("check with self.has_label()")
     }
     fn try_get_label(&self) -> Option<&Self::Label> {
-        todo!("API changes needed")
-        // self.0.get::<&LabelIdentifier>().as_deref()
+        self.label.as_ref()
     }
 }
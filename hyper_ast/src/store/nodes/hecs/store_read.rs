@@ -1,6 +1,7 @@
 use num::ToPrimitive;
 
 use super::*;
+use crate::types::{NodeId, TypedNodeId};
 use std::fmt::Debug;
 impl Debug for NodeStore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -23,7 +24,85 @@ impl crate::types::NodeStore<NodeIdentifier> for NodeStore {
     }
 }
 
+impl<'a> crate::types::NodeStoreLean<NodeIdentifier> for &'a NodeStore {
+    type R = HashedNodeRef<'a, NodeIdentifier>;
+    fn resolve(&self, id: &NodeIdentifier) -> Self::R {
+        self.internal
+            .entity(id.clone())
+            .map(|x| HashedNodeRef::new(x))
+            .unwrap()
+    }
+}
+
+impl<TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>> crate::types::TypedNodeStore<TIdN>
+    for NodeStore
+where
+    TIdN::Ty: hecs::Component,
+{
+    type R<'a> = HashedNodeRef<'a, TIdN>;
+    fn resolve(&self, id: &TIdN) -> Self::R<'_> {
+        let x = self.internal.entity(id.as_id().clone()).unwrap();
+        HashedNodeRef::new(x)
+    }
+
+    fn try_typed(&self, id: &<TIdN as NodeId>::IdN) -> Option<TIdN> {
+        let x = self.internal.entity(id.clone()).unwrap();
+        x.has::<TIdN::Ty>()
+            .then(|| unsafe { TIdN::from_id(id.clone()) })
+    }
+}
+
 impl NodeStore {
+    pub fn resolve(&self, id: NodeIdentifier) -> HashedNodeRef<NodeIdentifier> {
+        self.internal
+            .entity(id)
+            .map(|x| HashedNodeRef::new(x))
+            .unwrap()
+    }
+
+    pub fn try_resolve(&self, id: NodeIdentifier) -> Option<HashedNodeRef<NodeIdentifier>> {
+        self.internal
+            .entity(id)
+            .map(|x| HashedNodeRef::new(x))
+            .ok()
+    }
+
+    pub fn resolve_with_type<T: 'static + TypedNodeId<IdN = NodeIdentifier>>(
+        &self,
+        id: &T::IdN,
+    ) -> (T::Ty, HashedNodeRef<T>)
+    where
+        T::Ty: Copy + std::hash::Hash + Eq + hecs::Component,
+    {
+        use crate::types::Typed;
+        let n = self
+            .internal
+            .entity(*id)
+            .map(|x| HashedNodeRef::new(x))
+            .unwrap();
+        (n.get_type(), n)
+    }
+
+    pub fn resolve_typed<TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>>(
+        &self,
+        id: &TIdN,
+    ) -> HashedNodeRef<TIdN> {
+        let x = self.internal.entity(id.as_id().clone()).unwrap();
+        HashedNodeRef::new(x)
+    }
+
+    pub fn try_resolve_typed<TIdN: 'static + TypedNodeId<IdN = NodeIdentifier>>(
+        &self,
+        id: &TIdN::IdN,
+    ) -> Option<(HashedNodeRef<TIdN>, TIdN)>
+    where
+        TIdN::Ty: hecs::Component,
+    {
+        let x = self.internal.entity(id.clone()).ok()?;
+        x.has::<TIdN::Ty>().then_some(())?;
+        Some((HashedNodeRef::new(x), unsafe { TIdN::from_id(id.clone()) }))
+    }
+
     pub fn len(&self) -> usize {
         self.internal.len().to_usize().unwrap()
     }
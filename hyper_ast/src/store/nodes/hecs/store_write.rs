@@ -82,3 +82,55 @@ impl NodeStore {
         symbol
     }
 }
+
+impl<'a> crate::store::nodes::ComponentsRef for EntryRef<'a> {
+    type Ref<'b, C: Send + Sync + 'static> = hecs::Ref<'b, C> where Self: 'b;
+
+    fn get<C: Send + Sync + 'static>(&self) -> Option<Self::Ref<'_, C>> {
+        EntryRef::get::<&C>(self)
+    }
+}
+
+impl crate::store::nodes::ComponentsBuilder for hecs::EntityBuilder {
+    fn add<C: Send + Sync + 'static>(&mut self, component: C) -> &mut Self {
+        hecs::EntityBuilder::add(self, component)
+    }
+}
+
+impl crate::store::nodes::NodeInserter for NodeStore {
+    type IdN = NodeIdentifier;
+    type Entry<'a> = EntryRef<'a>;
+    type Builder = hecs::EntityBuilder;
+    type NodeRef<'a, T> = HashedNodeRef<'a, T>;
+    type Pending<'a> = PendingInsert<'a>;
+
+    fn prepare_insertion<'a, Eq: Fn(Self::Entry<'_>) -> bool, V: std::hash::Hash>(
+        &'a mut self,
+        hashable: &'a V,
+        eq: Eq,
+    ) -> Self::Pending<'a> {
+        NodeStore::prepare_insertion(self, hashable, eq)
+    }
+
+    fn with_resolved<R>(&self, id: NodeIdentifier, f: impl FnOnce(Self::Entry<'_>) -> R) -> R {
+        f(self.internal.entity(id).unwrap())
+    }
+}
+
+impl<'a> crate::store::nodes::PendingInsertion for PendingInsert<'a> {
+    type IdN = NodeIdentifier;
+    type Entry<'b> = EntryRef<'b>;
+    type Builder = hecs::EntityBuilder;
+
+    fn occupied_id(&self) -> Option<NodeIdentifier> {
+        PendingInsert::occupied_id(self)
+    }
+
+    fn with_resolved<R>(&self, id: NodeIdentifier, f: impl FnOnce(Self::Entry<'_>) -> R) -> R {
+        f(self.1 .1.entity(id).unwrap())
+    }
+
+    fn insert_built(self, mut components: hecs::EntityBuilder) -> NodeIdentifier {
+        NodeStore::insert_after_prepare(self.vacant(), components.build())
+    }
+}
@@ -0,0 +1,466 @@
+//! Concurrent insertion of nodes, eg. to build the files of a commit on several threads.
+//!
+//! [`NodeStore::prepare_insertion`] needs a `&mut NodeStore`,
+//! so a [`ConcurrentNodeStore`] takes the store over for the time of the concurrent phase:
+//! - the nodes already in the store are only read, without locking,
+//! - new nodes are pushed in one of several legion worlds (ideally one per thread),
+//! - new nodes are deduplicated through a table split in shards, each behind its own lock.
+//!
+//! Legion allocates entities from a process-wide counter,
+//! so when the worlds are merged back with [`ConcurrentNodeStore::into_inner`] nodes keep their identifiers.
+//!
+//! A node is only inserted once its shard is locked and no equal node was found,
+//! thus hash-consing holds across threads just as with a single [`NodeStore`].
+//!
+//! Tree generators insert through [`super::NodeInserter`],
+//! so they can be given a [`Worker`] of the concurrent store instead of the [`NodeStore`].
+
+use std::{
+    hash::Hash,
+    sync::{Mutex, MutexGuard, RwLock},
+};
+
+use legion::{storage::IntoComponentSource, EntityStore};
+
+use super::{
+    dyn_builder, EntryRef, HashedNodeRef, NodeIdentifier, NodeInserter, NodeStore, PendingInsertion,
+};
+use crate::utils::make_hash;
+
+/// Entry of the sharded dedup table,
+/// keeps the hash so that growing a shard does not need to resolve nodes.
+#[derive(Clone, Copy)]
+struct Slot {
+    id: NodeIdentifier,
+    world: u16,
+    hash: u64,
+}
+
+type Shard = hashbrown::HashMap<Slot, (), ()>;
+
+pub struct ConcurrentNodeStore {
+    main: NodeStore,
+    shards: Box<[Mutex<Shard>]>,
+    worlds: Box<[RwLock<legion::World>]>,
+}
+
+impl NodeStore {
+    /// Allows to insert nodes from multiple threads,
+    /// `workers` is the number of worlds new nodes are spread into.
+    pub fn into_concurrent(self, workers: usize) -> ConcurrentNodeStore {
+        ConcurrentNodeStore::new(self, workers)
+    }
+}
+
+impl ConcurrentNodeStore {
+    const SHARDS_PER_WORKER: usize = 4;
+
+    pub fn new(main: NodeStore, workers: usize) -> Self {
+        let workers = workers.clamp(1, u16::MAX as usize);
+        let shards = (0..workers * Self::SHARDS_PER_WORKER)
+            .map(|_| Mutex::new(Shard::with_hasher(Default::default())))
+            .collect();
+        let worlds = (0..workers).map(|_| Default::default()).collect();
+        Self {
+            main,
+            shards,
+            worlds,
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.worlds.len()
+    }
+
+    fn shard(&self, hash: u64) -> &Mutex<Shard> {
+        // the low bits are used by the shards themselves
+        &self.shards[(hash >> 32) as usize % self.shards.len()]
+    }
+
+    /// Gets the node equal to the one described by `hashable` and `eq`,
+    /// otherwise inserts the components built by `components` in the world of `worker`.
+    ///
+    /// `components` must not insert nodes itself.
+    pub fn get_or_insert<T, V: Hash>(
+        &self,
+        worker: usize,
+        hashable: &V,
+        eq: impl Fn(EntryRef) -> bool,
+        components: impl FnOnce() -> T,
+    ) -> NodeIdentifier
+    where
+        Option<T>: IntoComponentSource,
+    {
+        self.get_or_insert_with(worker, hashable, eq, |world| world.push(components()))
+    }
+
+    /// Same as [`ConcurrentNodeStore::get_or_insert`] using the dyn builder, see [`dyn_builder::EntityBuilder`]
+    pub fn get_or_insert_built<V: Hash>(
+        &self,
+        worker: usize,
+        hashable: &V,
+        eq: impl Fn(EntryRef) -> bool,
+        components: impl FnOnce() -> dyn_builder::BuiltEntity,
+    ) -> NodeIdentifier {
+        self.get_or_insert_with(worker, hashable, eq, |world| {
+            world.extend(components())[0]
+        })
+    }
+
+    fn get_or_insert_with<V: Hash>(
+        &self,
+        worker: usize,
+        hashable: &V,
+        eq: impl Fn(EntryRef) -> bool,
+        push: impl FnOnce(&mut legion::World) -> NodeIdentifier,
+    ) -> NodeIdentifier {
+        let insertion = self.prepare_insertion(worker, hashable, eq);
+        if let Some(id) = insertion.found {
+            return id;
+        }
+        insertion.insert_with(push)
+    }
+
+    /// Looks for a node equal to the one described by `hashable` and `eq`,
+    /// if there is none its shard stays locked until the insertion is done or dropped.
+    pub fn prepare_insertion<V: Hash>(
+        &self,
+        worker: usize,
+        hashable: &V,
+        eq: impl Fn(EntryRef) -> bool,
+    ) -> ConcurrentPendingInsert<'_> {
+        let hash = make_hash(&self.main.hasher, hashable);
+        let main = &self.main;
+        if let Some((id, _)) = main
+            .dedup
+            .raw_entry()
+            .from_hash(hash, |id| eq(main.internal.entry_ref(*id).unwrap()))
+        {
+            return ConcurrentPendingInsert {
+                store: self,
+                worker,
+                hash,
+                found: Some(*id),
+                shard: None,
+            };
+        }
+        let shard = self.shard(hash).lock().unwrap();
+        let found = shard
+            .raw_entry()
+            .from_hash(hash, |slot| {
+                let world = self.worlds[slot.world as usize].read().unwrap();
+                let r = eq(world.entry_ref(slot.id).unwrap());
+                r
+            })
+            .map(|(slot, ())| slot.id);
+        ConcurrentPendingInsert {
+            store: self,
+            worker,
+            hash,
+            found,
+            shard: Some(shard),
+        }
+    }
+
+    /// Gives `f` the node identified by `id`, whether it was inserted before or during the concurrent phase.
+    ///
+    /// `f` must not insert nodes itself.
+    pub fn with_resolved<T, R>(
+        &self,
+        id: NodeIdentifier,
+        f: impl FnOnce(HashedNodeRef<T>) -> R,
+    ) -> R {
+        if let Ok(x) = self.main.internal.entry_ref(id) {
+            return f(HashedNodeRef::new(x));
+        }
+        for world in self.worlds.iter() {
+            let world = world.read().unwrap();
+            if let Ok(x) = world.entry_ref(id) {
+                return f(HashedNodeRef::new(x));
+            }
+        }
+        panic!("{:?} is not in this store", id)
+    }
+
+    /// Inserts nodes in the world of the `index`th worker, see [`NodeInserter`].
+    ///
+    /// Several threads can use the same index, at the cost of contention on its world.
+    pub fn worker(&self, index: usize) -> Worker<'_> {
+        Worker { store: self, index }
+    }
+
+    /// Number of nodes inserted during the concurrent phase.
+    pub fn inserted(&self) -> usize {
+        self.shards.iter().map(|x| x.lock().unwrap().len()).sum()
+    }
+
+    /// Merges the nodes inserted concurrently into the underlying store, identifiers are kept.
+    pub fn into_inner(self) -> NodeStore {
+        let Self {
+            mut main,
+            shards,
+            worlds,
+        } = self;
+        for world in worlds.into_vec() {
+            let mut world = world.into_inner().unwrap();
+            main.internal.move_from(&mut world, &legion::query::any());
+        }
+        let NodeStore {
+            dedup,
+            internal,
+            journal,
+            hasher,
+            ..
+        } = &mut main;
+        for shard in shards.into_vec() {
+            for (slot, ()) in shard.into_inner().unwrap() {
+                match dedup.raw_entry_mut().from_hash(slot.hash, |x| *x == slot.id) {
+                    hashbrown::hash_map::RawEntryMut::Occupied(_) => unreachable!(),
+                    hashbrown::hash_map::RawEntryMut::Vacant(vacant) => {
+                        vacant.insert_with_hasher(slot.hash, slot.id, (), |id| {
+                            let node: HashedNodeRef<'_, NodeIdentifier> = internal
+                                .entry_ref(*id)
+                                .map(|x| HashedNodeRef::new(x))
+                                .unwrap();
+                            make_hash(hasher, &node)
+                        });
+                    }
+                }
+                if let Some(journal) = journal.as_mut() {
+                    journal.push(slot.id);
+                }
+            }
+        }
+        main
+    }
+}
+
+/// Insertion prepared by [`ConcurrentNodeStore::prepare_insertion`]
+pub struct ConcurrentPendingInsert<'a> {
+    store: &'a ConcurrentNodeStore,
+    worker: usize,
+    hash: u64,
+    found: Option<NodeIdentifier>,
+    /// locked until the node is inserted, so that an equal node cannot be inserted meanwhile
+    shard: Option<MutexGuard<'a, Shard>>,
+}
+
+impl<'a> ConcurrentPendingInsert<'a> {
+    fn insert_with(
+        self,
+        push: impl FnOnce(&mut legion::World) -> NodeIdentifier,
+    ) -> NodeIdentifier {
+        assert!(self.found.is_none(), "an equal node is already stored");
+        let Self {
+            store,
+            worker,
+            hash,
+            shard,
+            ..
+        } = self;
+        let mut shard = shard.unwrap();
+        let w = worker % store.worlds.len();
+        let id = push(&mut store.worlds[w].write().unwrap());
+        let slot = Slot {
+            id,
+            world: w as u16,
+            hash,
+        };
+        match shard.raw_entry_mut().from_hash(hash, |_| false) {
+            hashbrown::hash_map::RawEntryMut::Occupied(_) => unreachable!(),
+            hashbrown::hash_map::RawEntryMut::Vacant(vacant) => {
+                vacant.insert_with_hasher(hash, slot, (), |slot| slot.hash);
+            }
+        }
+        id
+    }
+}
+
+impl<'a> PendingInsertion for ConcurrentPendingInsert<'a> {
+    type IdN = NodeIdentifier;
+    type Entry<'b> = EntryRef<'b>;
+    type Builder = dyn_builder::EntityBuilder;
+
+    fn occupied_id(&self) -> Option<NodeIdentifier> {
+        self.found
+    }
+
+    fn with_resolved<R>(&self, id: NodeIdentifier, f: impl FnOnce(Self::Entry<'_>) -> R) -> R {
+        self.store.with_resolved(id, |x: HashedNodeRef<()>| f(x.0))
+    }
+
+    fn insert_built(self, components: dyn_builder::EntityBuilder) -> NodeIdentifier {
+        let components = components.build();
+        self.insert_with(|world| world.extend(components)[0])
+    }
+}
+
+/// Inserts nodes in a [`ConcurrentNodeStore`] from one thread, see [`ConcurrentNodeStore::worker`]
+pub struct Worker<'a> {
+    store: &'a ConcurrentNodeStore,
+    index: usize,
+}
+
+impl<'s> NodeInserter for Worker<'s> {
+    type IdN = NodeIdentifier;
+    type Entry<'a> = EntryRef<'a>;
+    type Builder = dyn_builder::EntityBuilder;
+    type NodeRef<'a, T> = HashedNodeRef<'a, T>;
+    type Pending<'a> = ConcurrentPendingInsert<'a> where Self: 'a;
+
+    fn prepare_insertion<'a, Eq: Fn(Self::Entry<'_>) -> bool, V: Hash>(
+        &'a mut self,
+        hashable: &'a V,
+        eq: Eq,
+    ) -> Self::Pending<'a> {
+        self.store.prepare_insertion(self.index, hashable, eq)
+    }
+
+    fn with_resolved<R>(&self, id: NodeIdentifier, f: impl FnOnce(Self::Entry<'_>) -> R) -> R {
+        self.store.with_resolved(id, |x: HashedNodeRef<()>| f(x.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+        nodes::HashSize,
+        store::{
+            defaults::LabelIdentifier,
+            labels::LabelStore,
+            nodes::{
+                legion::compo::{self, CS},
+                ComponentsRef,
+            },
+        },
+        types::{LabelStore as _, WithChildren},
+    };
+
+    fn insert(
+        store: &ConcurrentNodeStore,
+        worker: usize,
+        label: LabelIdentifier,
+        cs: Vec<NodeIdentifier>,
+    ) -> NodeIdentifier {
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1 + cs.len() as u32);
+        let hashable = &hbuilder.most_discriminating();
+        let eq = |x: EntryRef| {
+            x.get_component::<LabelIdentifier>()
+                .map_or(false, |l| l == &label)
+                && x.get_component::<CS<NodeIdentifier>>()
+                    .map_or(false, |x| *x.0 == *cs)
+        };
+        store.get_or_insert(worker, hashable, eq, || {
+            (
+                label,
+                hbuilder.build(),
+                compo::Size(1 + cs.len() as u32),
+                CS(cs.clone().into_boxed_slice()),
+            )
+        })
+    }
+
+    #[test]
+    fn concurrent_dedup() {
+        let mut labels = LabelStore::new();
+        let names: Vec<_> = (0..20)
+            .map(|i| labels.get_or_insert(format!("l{}", i).as_str()))
+            .collect();
+        let store = NodeStore::new().into_concurrent(4);
+        let leaf = insert(&store, 0, names[0], vec![]);
+
+        // every thread builds the same trees
+        let roots: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|w| {
+                    let store = &store;
+                    let names = &names;
+                    s.spawn(move || {
+                        let mut cs = vec![leaf];
+                        for l in &names[1..] {
+                            let x = insert(store, w, *l, vec![]);
+                            cs.push(insert(store, w, *l, vec![x, leaf]));
+                        }
+                        insert(store, w, names[0], cs)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(roots.iter().all(|x| *x == roots[0]));
+        assert_eq!(store.inserted(), 1 + 19 * 2 + 1);
+        let len = store.with_resolved(roots[0], |x: HashedNodeRef| x.children().unwrap().0.len());
+        assert_eq!(len, 20);
+
+        let mut store = store.into_inner();
+        assert_eq!(store.len(), 40);
+        assert_eq!(store.resolve(roots[0]).children().unwrap().0.len(), 20);
+        // merged nodes are found by sequential insertions
+        let label = names[0];
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = store.prepare_insertion(hashable, |x| {
+            x.get_component::<LabelIdentifier>()
+                .map_or(false, |l| l == &label)
+                && x.get_component::<CS<NodeIdentifier>>()
+                    .map_or(false, |x| x.0.is_empty())
+        });
+        assert_eq!(insertion.occupied_id(), Some(leaf));
+    }
+
+    fn insert_leaf<S: NodeInserter<IdN = NodeIdentifier>>(
+        store: &mut S,
+        label: LabelIdentifier,
+    ) -> NodeIdentifier {
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = store.prepare_insertion(hashable, |x: S::Entry<'_>| {
+            x.get::<LabelIdentifier>().map_or(false, |l| *l == label)
+        });
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        insertion.insert((label, hbuilder.build(), compo::Size(1)))
+    }
+
+    #[test]
+    fn workers_insert_like_the_store() {
+        let mut labels = LabelStore::new();
+        let names: Vec<_> = (0..8)
+            .map(|i| labels.get_or_insert(format!("l{}", i).as_str()))
+            .collect();
+        let mut store = NodeStore::new();
+        let first = insert_leaf(&mut store, names[0]);
+        assert_eq!(insert_leaf(&mut store, names[0]), first);
+
+        let store = store.into_concurrent(2);
+        let ids: Vec<Vec<_>> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..2)
+                .map(|w| {
+                    let store = &store;
+                    let names = &names;
+                    s.spawn(move || {
+                        let mut worker = store.worker(w);
+                        names
+                            .iter()
+                            .map(|l| insert_leaf(&mut worker, *l))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(ids[0], ids[1]);
+        assert_eq!(ids[0][0], first);
+        assert_eq!(store.inserted(), names.len() - 1);
+
+        let mut store = store.into_inner();
+        assert_eq!(store.len(), names.len());
+        assert_eq!(insert_leaf(&mut store, names[5]), ids[0][5]);
+    }
+}
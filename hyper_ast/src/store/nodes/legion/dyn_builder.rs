@@ -139,6 +139,12 @@ impl EntityBuilder {
     }
 }
 
+impl crate::store::nodes::ComponentsBuilder for EntityBuilder {
+    fn add<C: Send + Sync + 'static>(&mut self, component: C) -> &mut Self {
+        EntityBuilder::add(self, component)
+    }
+}
+
 // impl Iterator for BuiltEntity {
 //     type Item = Self;
 
@@ -176,6 +182,51 @@ impl IntoComponentSource for EntityBuilder
     }
 }
 
+impl BuiltEntity {
+    /// Use an already known identifier instead of allocating a new one when pushed in a world.
+    ///
+    /// Mainly useful to restore persisted entities (see [`crate::store::snapshot`]).
+    pub fn with_id(self, id: Entity) -> IdentifiedEntity {
+        IdentifiedEntity { built: self, id }
+    }
+}
+
+/// A [`BuiltEntity`] with a predefined identifier.
+pub struct IdentifiedEntity {
+    built: BuiltEntity,
+    id: Entity,
+}
+
+impl IntoComponentSource for IdentifiedEntity {
+    type Source = IdentifiedEntity;
+
+    fn into(self) -> Self::Source {
+        self
+    }
+}
+
+impl ArchetypeSource for IdentifiedEntity {
+    type Filter = ComponentSourceFilter;
+
+    fn filter(&self) -> Self::Filter {
+        self.built.filter()
+    }
+
+    fn layout(&mut self) -> EntityLayout {
+        self.built.layout()
+    }
+}
+
+impl ComponentSource for IdentifiedEntity {
+    fn push_components<'a>(
+        &mut self,
+        writer: &mut ArchetypeWriter<'a>,
+        _entities: impl Iterator<Item = Entity>,
+    ) {
+        self.built.push_components(writer, std::iter::once(self.id))
+    }
+}
+
 /// A layout filter used to select the appropriate archetype for inserting
 /// entities from a component source into a world.
 pub struct ComponentSourceFilter(Vec<ComponentTypeId>);
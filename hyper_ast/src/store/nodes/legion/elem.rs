@@ -58,7 +58,7 @@ impl TypedNodeId for NodeIdentifier {
 
 pub struct HashedNode<Id: TypedNodeId<IdN = NodeIdentifier>> {
     node: CompressedNode<NodeIdentifier, LabelIdentifier, Id::Ty>,
-    hashs: SyntaxNodeHashs<u32>,
+    hashs: SyntaxNodeHashs<HashSize>,
 }
 
 // impl<'a> Symbol<HashedNodeRef<'a>> for legion::Entity {}
@@ -7,7 +7,11 @@ use legion::{
 };
 
 use crate::{
-    types::{NodeId, Typed, TypedNodeId},
+    filter::BloomSize,
+    hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, NodeHashs, SyntaxNodeHashs},
+    nodes::HashSize,
+    store::defaults::LabelIdentifier,
+    types::{NodeId, Typed, TypedNodeId, WithStats},
     utils::make_hash,
 };
 
@@ -15,6 +19,8 @@ pub mod dyn_builder;
 
 pub mod compo;
 
+pub mod concurrent;
+
 mod elem;
 
 pub use elem::{EntryRef, HashedNode, HashedNodeRef, NodeIdentifier};
@@ -25,6 +31,11 @@ pub struct NodeStore {
     // roots: HashMap<(u8, u8, u8), NodeIdentifier>,
     dedup: hashbrown::HashMap<NodeIdentifier, (), ()>,
     internal: legion::World,
+    /// nodes inserted since the last flush of the journal, see [`crate::store::journal`]
+    journal: Option<Vec<NodeIdentifier>>,
+    /// prepared insertions and how many found an equal node, see [`crate::store::stats`]
+    lookups: usize,
+    hits: usize,
     hasher: DefaultHashBuilder, //fasthash::city::Hash64,//fasthash::RandomState<fasthash::>,
                                 // internal: VecMapStore<HashedNode, NodeIdentifier, legion::World>,
 }
@@ -33,7 +44,12 @@ pub struct NodeStore {
 
 pub struct PendingInsert<'a>(
     crate::compat::hash_map::RawEntryMut<'a, legion::Entity, (), ()>,
-    (u64, &'a mut legion::World, &'a DefaultHashBuilder),
+    (
+        u64,
+        &'a mut legion::World,
+        &'a DefaultHashBuilder,
+        Option<&'a mut Vec<NodeIdentifier>>,
+    ),
 );
 
 impl<'a> PendingInsert<'a> {
@@ -68,7 +84,12 @@ impl<'a> PendingInsert<'a> {
         self,
     ) -> (
         crate::compat::hash_map::RawVacantEntryMut<'a, legion::Entity, (), ()>,
-        (u64, &'a mut legion::World, &'a DefaultHashBuilder),
+        (
+            u64,
+            &'a mut legion::World,
+            &'a DefaultHashBuilder,
+            Option<&'a mut Vec<NodeIdentifier>>,
+        ),
     ) {
         match self.0 {
             hashbrown::hash_map::RawEntryMut::Vacant(occupied) => (occupied, self.1),
@@ -97,6 +118,8 @@ impl NodeStore {
         let Self {
             dedup,
             internal: backend,
+            lookups,
+            hits,
             ..
         } = self;
         let hash = make_hash(&self.hasher, hashable);
@@ -104,13 +127,30 @@ impl NodeStore {
             let r = eq(backend.entry_ref(*symbol).unwrap());
             r
         });
-        PendingInsert(entry, (hash, &mut self.internal, &self.hasher))
+        *lookups += 1;
+        if let hashbrown::hash_map::RawEntryMut::Occupied(_) = &entry {
+            *hits += 1;
+        }
+        PendingInsert(
+            entry,
+            (
+                hash,
+                &mut self.internal,
+                &self.hasher,
+                self.journal.as_mut(),
+            ),
+        )
     }
 
     pub fn insert_after_prepare<T>(
-        (vacant, (hash, internal, hasher)): (
+        (vacant, (hash, internal, hasher, journal)): (
             crate::compat::hash_map::RawVacantEntryMut<legion::Entity, (), ()>,
-            (u64, &mut legion::World, &DefaultHashBuilder),
+            (
+                u64,
+                &mut legion::World,
+                &DefaultHashBuilder,
+                Option<&mut Vec<NodeIdentifier>>,
+            ),
         ),
         components: T,
     ) -> legion::Entity
@@ -128,14 +168,22 @@ impl NodeStore {
                 make_hash(hasher, &node)
             })
         };
+        if let Some(journal) = journal {
+            journal.push(symbol);
+        }
         symbol
     }
 
     /// uses the dyn builder see dyn_builder::EntityBuilder
     pub fn insert_built_after_prepare(
-        (vacant, (hash, internal, hasher)): (
+        (vacant, (hash, internal, hasher, journal)): (
             crate::compat::hash_map::RawVacantEntryMut<legion::Entity, (), ()>,
-            (u64, &mut legion::World, &DefaultHashBuilder),
+            (
+                u64,
+                &mut legion::World,
+                &DefaultHashBuilder,
+                Option<&mut Vec<NodeIdentifier>>,
+            ),
         ),
         components: dyn_builder::BuiltEntity,
     ) -> legion::Entity {
@@ -150,6 +198,9 @@ impl NodeStore {
                 make_hash(hasher, &node)
             })
         };
+        if let Some(journal) = journal {
+            journal.push(symbol);
+        }
         symbol
     }
 
@@ -204,6 +255,54 @@ impl NodeStore {
     }
 }
 
+pub use super::{NodeInserter, PendingInsertion};
+
+impl<'a> super::ComponentsRef for EntryRef<'a> {
+    type Ref<'b, C: Send + Sync + 'static> = &'b C where Self: 'b;
+
+    fn get<C: Send + Sync + 'static>(&self) -> Option<Self::Ref<'_, C>> {
+        self.get_component::<C>().ok()
+    }
+}
+
+impl NodeInserter for NodeStore {
+    type IdN = NodeIdentifier;
+    type Entry<'a> = EntryRef<'a>;
+    type Builder = dyn_builder::EntityBuilder;
+    type NodeRef<'a, T> = HashedNodeRef<'a, T>;
+    type Pending<'a> = PendingInsert<'a>;
+
+    fn prepare_insertion<'a, Eq: Fn(Self::Entry<'_>) -> bool, V: Hash>(
+        &'a mut self,
+        hashable: &'a V,
+        eq: Eq,
+    ) -> Self::Pending<'a> {
+        NodeStore::prepare_insertion(self, hashable, eq)
+    }
+
+    fn with_resolved<R>(&self, id: NodeIdentifier, f: impl FnOnce(Self::Entry<'_>) -> R) -> R {
+        f(self.internal.entry_ref(id).unwrap())
+    }
+}
+
+impl<'a> PendingInsertion for PendingInsert<'a> {
+    type IdN = NodeIdentifier;
+    type Entry<'b> = EntryRef<'b>;
+    type Builder = dyn_builder::EntityBuilder;
+
+    fn occupied_id(&self) -> Option<NodeIdentifier> {
+        PendingInsert::occupied_id(self)
+    }
+
+    fn with_resolved<R>(&self, id: NodeIdentifier, f: impl FnOnce(Self::Entry<'_>) -> R) -> R {
+        f(self.1 .1.entry_ref(id).unwrap())
+    }
+
+    fn insert_built(self, components: dyn_builder::EntityBuilder) -> NodeIdentifier {
+        NodeStore::insert_built_after_prepare(self.vacant(), components.build())
+    }
+}
+
 impl Debug for NodeStore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NodeStore")
@@ -258,6 +357,171 @@ impl NodeStore {
     }
 }
 
+// * garbage collection, see crate::store::gc
+
+impl NodeStore {
+    /// Marks the nodes reachable from `roots` by following children,
+    /// also collects the labels they reference in `labels`.
+    pub(crate) fn mark(
+        &self,
+        roots: impl IntoIterator<Item = NodeIdentifier>,
+        labels: &mut std::collections::HashSet<LabelIdentifier>,
+    ) -> std::collections::HashSet<NodeIdentifier> {
+        let mut marked = std::collections::HashSet::new();
+        let mut stack: Vec<_> = roots.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if !marked.insert(id) {
+                continue;
+            }
+            let Ok(entry) = self.internal.entry_ref(id) else {
+                log::warn!("{:?} is not in the store", id);
+                continue;
+            };
+            if let Ok(l) = entry.get_component::<LabelIdentifier>() {
+                labels.insert(*l);
+            }
+            if let Ok(cs) = entry.get_component::<compo::CS<LabelIdentifier>>() {
+                labels.extend(cs.0.iter().copied());
+            }
+            if let Ok(cs) = entry.get_component::<compo::CS<NodeIdentifier>>() {
+                stack.extend(cs.0.iter().copied());
+            }
+            if let Ok(cs) = entry.get_component::<compo::NoSpacesCS<NodeIdentifier>>() {
+                stack.extend(cs.0.iter().copied());
+            }
+        }
+        marked
+    }
+
+    /// Removes all nodes that are not `marked`, returns the number of removed nodes.
+    pub(crate) fn sweep(&mut self, marked: &std::collections::HashSet<NodeIdentifier>) -> usize {
+        let Self {
+            dedup,
+            internal,
+            journal,
+            ..
+        } = self;
+        let before = dedup.len();
+        dedup.retain(|id, _| {
+            let keep = marked.contains(id);
+            if !keep {
+                internal.remove(*id);
+            }
+            keep
+        });
+        if let Some(journal) = journal {
+            journal.retain(|id| marked.contains(id));
+        }
+        before - dedup.len()
+    }
+}
+
+impl NodeStore {
+    /// Gives their new identifiers to the labels referenced by nodes, after compacting the label store.
+    ///
+    /// Labels referenced by nodes must have been kept, see [`NodeStore::mark`].
+    pub(crate) fn remap_labels(&mut self, remap: &crate::store::labels::LabelRemap) {
+        let get = |l: &LabelIdentifier| {
+            remap.get(l).unwrap_or_else(|| {
+                log::error!("label {:?} of a kept node was freed", l);
+                *l
+            })
+        };
+        let ids: Vec<_> = self.dedup.keys().copied().collect();
+        for id in ids {
+            let Some(mut entry) = self.internal.entry(id) else {
+                continue;
+            };
+            if let Ok(l) = entry.get_component_mut::<LabelIdentifier>() {
+                *l = get(l);
+            }
+            if let Ok(cs) = entry.get_component_mut::<compo::CS<LabelIdentifier>>() {
+                cs.0.iter_mut().for_each(|l| *l = get(l));
+            }
+        }
+    }
+}
+
+// * journal support, see crate::store::journal
+
+impl NodeStore {
+    /// Starts recording the identifiers of inserted nodes.
+    pub(crate) fn start_journal(&mut self) {
+        if self.journal.is_none() {
+            self.journal = Some(vec![]);
+        }
+    }
+
+    /// Nodes inserted since the journal started or since the last call, in insertion order.
+    pub(crate) fn take_journal(&mut self) -> Vec<NodeIdentifier> {
+        self.journal.as_mut().map_or(vec![], std::mem::take)
+    }
+
+    /// Gives back identifiers that could not be persisted.
+    pub(crate) fn untake_journal(&mut self, mut ids: Vec<NodeIdentifier>) {
+        if let Some(journal) = &mut self.journal {
+            ids.append(journal);
+            *journal = ids;
+        }
+    }
+}
+
+// * snapshot support, see crate::store::snapshot
+
+impl NodeStore {
+    /// Iterates over the identifiers of all stored nodes, in no particular order.
+    pub fn iter_ids(&self) -> impl Iterator<Item = NodeIdentifier> + '_ {
+        self.dedup.keys().copied()
+    }
+
+    pub(crate) fn entry_ref(&self, id: NodeIdentifier) -> Option<EntryRef<'_>> {
+        self.internal.entry_ref(id).ok()
+    }
+
+    pub(crate) fn counters(&self) -> (usize, usize) {
+        (self.count, self.errors)
+    }
+
+    pub(crate) fn set_counters(&mut self, (count, errors): (usize, usize)) {
+        self.count = count;
+        self.errors = errors;
+    }
+
+    /// Pushes back a previously persisted node with its original identifier,
+    /// then registers it in the dedup table.
+    ///
+    /// The caller must make sure that `id` cannot be allocated again,
+    /// see [`crate::store::snapshot::reserve_ids`].
+    pub(crate) fn restore(&mut self, id: NodeIdentifier, components: dyn_builder::BuiltEntity) {
+        let Self {
+            dedup,
+            internal,
+            hasher,
+            ..
+        } = self;
+        internal.extend(components.with_id(id));
+        let node: HashedNodeRef<'_, NodeIdentifier> = internal
+            .entry_ref(id)
+            .map(|x| HashedNodeRef::new(x))
+            .unwrap();
+        let hash = make_hash(hasher, &node);
+        match dedup.raw_entry_mut().from_hash(hash, |x| *x == id) {
+            hashbrown::hash_map::RawEntryMut::Occupied(_) => {
+                log::warn!("{:?} was already restored", id)
+            }
+            hashbrown::hash_map::RawEntryMut::Vacant(vacant) => {
+                vacant.insert_with_hasher(hash, id, (), |id| {
+                    let node: HashedNodeRef<'_, NodeIdentifier> = internal
+                        .entry_ref(*id)
+                        .map(|x| HashedNodeRef::new(x))
+                        .unwrap();
+                    make_hash(hasher, &node)
+                });
+            }
+        }
+    }
+}
+
 impl NodeStore {
     pub fn new() -> Self {
         Self {
@@ -269,6 +533,9 @@ impl NodeStore {
                 1 << 10,
                 Default::default(),
             ),
+            journal: None,
+            lookups: 0,
+            hits: 0,
             hasher: Default::default(),
         }
     }
@@ -335,33 +602,145 @@ impl Default for NodeStore {
 //     }
 // }
 
-// // impl<'a> crate::types::NodeStoreExt<'a, HashedNode, HashedNodeRef<'a>> for NodeStore {
-// //     fn build_then_insert(
-// //         &mut self,
-// //         t: <HashedNodeRef<'a> as crate::types::Typed>::Type,
-// //         l: <HashedNodeRef<'a> as crate::types::Labeled>::Label,
-// //         cs: Vec<<HashedNodeRef<'a> as crate::types::Stored>::TreeId>,
-// //     ) -> <HashedNodeRef<'a> as crate::types::Stored>::TreeId {
-// //         todo!()
-// //     }
-// // }
+/// A [`NodeStore`] along with the labels of its nodes, seen through the types of a language,
+/// eg. to apply an edit script on trees built by a generator.
+///
+/// Nodes are resolved as [`HashedNodeRef`]`<Id>`, so their type is the one put by the generator,
+/// and rebuilt nodes are inserted with [`NodeStore::build_then_insert_typed`].
+pub struct TypedNodeStoreMut<'a, Id, LS = crate::store::labels::LabelStore> {
+    pub node_store: &'a mut NodeStore,
+    pub label_store: &'a LS,
+    _phantom: std::marker::PhantomData<Id>,
+}
 
-// /// WARN this is polyglote related
-// /// for now I only implemented for java.
-// /// In the future you should use the Type of the node
-// /// and maybe an additional context might be necessary depending on choices to materialize polyglot nodes
-// impl crate::types::NodeStoreExt<HashedNode> for NodeStore {
-//     fn build_then_insert(
-//         &mut self,
-//         i: <HashedNode as crate::types::Stored>::TreeId,
-//         t: <HashedNode as crate::types::Typed>::Type,
-//         l: Option<<HashedNode as crate::types::Labeled>::Label>,
-//         cs: Vec<<HashedNode as crate::types::Stored>::TreeId>,
-//     ) -> <HashedNode as crate::types::Stored>::TreeId {
-//         // self.internal.
-//         todo!()
-//     }
-// }
+impl<'a, Id, LS> TypedNodeStoreMut<'a, Id, LS> {
+    pub fn new(node_store: &'a mut NodeStore, label_store: &'a LS) -> Self {
+        Self {
+            node_store,
+            label_store,
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<'a, Id: 'static + TypedNodeId<IdN = NodeIdentifier>, LS>
+    crate::types::NodeStore<NodeIdentifier> for TypedNodeStoreMut<'a, Id, LS>
+{
+    type R<'b> = HashedNodeRef<'b, Id> where Self: 'b;
+    fn resolve(&self, id: &NodeIdentifier) -> Self::R<'_> {
+        self.node_store
+            .internal
+            .entry_ref(id.clone())
+            .map(|x| HashedNodeRef::new(x))
+            .unwrap()
+    }
+}
+
+impl<'a, Id, LS> crate::types::NodeStoreExt<HashedNodeRef<'static, Id>>
+    for TypedNodeStoreMut<'a, Id, LS>
+where
+    Id: 'static + TypedNodeId<IdN = NodeIdentifier>,
+    Id::Ty: 'static + crate::types::HyperType + Eq + Hash + Copy + Send + Sync,
+    LS: crate::types::LabelStore<str, I = LabelIdentifier>,
+{
+    fn build_then_insert(
+        &mut self,
+        _i: NodeIdentifier,
+        t: Id::Ty,
+        l: Option<LabelIdentifier>,
+        cs: Vec<NodeIdentifier>,
+    ) -> NodeIdentifier {
+        let l = l.map(|l| (l, self.label_store.resolve(&l)));
+        self.node_store.build_then_insert_typed(t, l, cs)
+    }
+}
+
+impl NodeStore {
+    /// Builds a node from its type, label and children, eg. when applying an edit script,
+    /// then inserts it if there is no equal node.
+    ///
+    /// The node is hashed and stored like generators do, ie. `t` is its type component
+    /// and the text of its label goes in its hashes, so it matches the nodes they generated.
+    /// Spaces are handled as in generators, they do not count in `size_no_spaces`.
+    pub fn build_then_insert_typed<Ty>(
+        &mut self,
+        t: Ty,
+        l: Option<(LabelIdentifier, &str)>,
+        cs: Vec<NodeIdentifier>,
+    ) -> NodeIdentifier
+    where
+        Ty: 'static + crate::types::HyperType + Eq + Hash + Copy + Send + Sync,
+    {
+        let label = l.map(|(_, l)| l);
+        let l = l.map(|(l, _)| l);
+        let eq = |x: EntryRef| {
+            x.get_component::<Ty>() == Ok(&t)
+                && x.get_component::<LabelIdentifier>().ok() == l.as_ref()
+                && match x.get_component::<compo::CS<NodeIdentifier>>() {
+                    Ok(compo::CS(x)) => x.as_ref() == cs.as_slice(),
+                    Err(_) => cs.is_empty(),
+                }
+        };
+        if t.is_spaces() {
+            let label = label.unwrap_or_default();
+            let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+                hashed::Builder::new(Default::default(), &t, label, 1);
+            let hashable = hbuilder.most_discriminating();
+            let insertion = self.prepare_insertion(&hashable, eq);
+            if let Some(id) = insertion.occupied_id() {
+                return id;
+            }
+            let mut hashs = hbuilder.build();
+            hashs.structt = 0;
+            hashs.label = 0;
+            let mut dyn_builder = dyn_builder::EntityBuilder::new();
+            dyn_builder.add(t);
+            dyn_builder.add(hashs);
+            dyn_builder.add(BloomSize::None);
+            if let Some(l) = l {
+                dyn_builder.add(l);
+            }
+            return NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build());
+        }
+        let mut hashs = SyntaxNodeHashs::<HashSize>::default();
+        let (mut size, mut height, mut size_no_spaces) = (1, 0, 1);
+        let mut no_spaces = vec![];
+        for id in &cs {
+            let c = self.resolve(*id);
+            hashs.acc(c.0.get_component::<SyntaxNodeHashs<HashSize>>().unwrap());
+            size += c.size() as u32;
+            if c.0.get_component::<Ty>().map_or(false, |t| t.is_spaces()) {
+                continue;
+            }
+            height = height.max(c.height() as u32);
+            size_no_spaces += c.size_no_spaces() as u32;
+            no_spaces.push(*id);
+        }
+        let hbuilder = hashed::Builder::new(hashs, &t, &label, size_no_spaces);
+        let hashable = hbuilder.most_discriminating();
+        let insertion = self.prepare_insertion(&hashable, eq);
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        let mut dyn_builder = dyn_builder::EntityBuilder::new();
+        dyn_builder.add(t);
+        dyn_builder.add(hbuilder.build());
+        dyn_builder.add(BloomSize::None);
+        if let Some(l) = l {
+            dyn_builder.add(l);
+        }
+        if !cs.is_empty() {
+            dyn_builder.add(compo::Size(size));
+            dyn_builder.add(compo::SizeNoSpaces(size_no_spaces));
+            dyn_builder.add(compo::Height(height + 1));
+            if no_spaces.len() != cs.len() {
+                dyn_builder.add(compo::NoSpacesCS(no_spaces.into_boxed_slice()));
+            }
+            dyn_builder.add(compo::CS(cs.into_boxed_slice()));
+        }
+        NodeStore::insert_built_after_prepare(insertion.vacant(), dyn_builder.build())
+    }
+}
 
 mod stores_impl {
     use crate::{
@@ -0,0 +1,429 @@
+//! Read-only node store over a memory-mapped snapshot (see [`crate::store::snapshot`]).
+//!
+//! Opening a snapshot only builds an index from identifiers to records,
+//! resolving a node gives a view borrowing the mapped bytes (children are not copied).
+//! Several processes can thus share the same precomputed HyperAST through the page cache.
+//!
+//! Only the components of [`Codecs::default`] and node types registered with
+//! [`Codecs::register_type`] are exposed by views, other components are skipped.
+
+use std::{collections::HashMap, fmt::Debug, marker::PhantomData, path::Path};
+
+use string_interner::Symbol;
+
+use crate::{
+    hashed::{HashWidth, NodeHashs, SyntaxNodeHashs, SyntaxNodeHashsKinds},
+    nodes::HashSize,
+    store::{
+        defaults::LabelIdentifier,
+        snapshot::{id_from_u64, Codecs, Input, SnapshotError, MAGIC, VERSION},
+    },
+    types::{AnyType, MySlice, NodeId},
+};
+
+pub use super::legion::NodeIdentifier;
+
+/// What a mapped view knows about a component
+#[derive(Clone, Copy)]
+enum Kind {
+    Label,
+    Cs,
+    NoSpacesCs,
+    Size,
+    SizeNoSpaces,
+    Height,
+    BytesLen,
+    Hashs,
+    Type(fn(u16) -> AnyType),
+    Other,
+}
+
+impl Kind {
+    fn new(name: &str, codecs: &Codecs) -> Self {
+        match name {
+            "label" => Kind::Label,
+            "cs" => Kind::Cs,
+            "no_spaces_cs" => Kind::NoSpacesCs,
+            "size" => Kind::Size,
+            "size_no_spaces" => Kind::SizeNoSpaces,
+            "height" => Kind::Height,
+            "bytes_len" => Kind::BytesLen,
+            name if name == HashSize::COMPONENT => Kind::Hashs,
+            name => codecs.type_decoder(name).map_or(Kind::Other, Kind::Type),
+        }
+    }
+}
+
+pub struct NodeStore {
+    /// offsets of the component lists of nodes
+    index: HashMap<NodeIdentifier, usize>,
+    kinds: Vec<Kind>,
+    labels: Vec<&'static str>,
+    label_index: HashMap<&'static str, LabelIdentifier>,
+    // NOTE must be dropped last, other fields borrow from it
+    mmap: memmap2::Mmap,
+}
+
+impl NodeStore {
+    /// Maps the snapshot at `path`, which must not be modified while mapped.
+    pub fn open(path: impl AsRef<Path>, codecs: &Codecs) -> Result<Self, SnapshotError> {
+        let file = std::fs::File::open(path)?;
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::index(mmap, codecs)
+    }
+
+    fn index(mmap: memmap2::Mmap, codecs: &Codecs) -> Result<Self, SnapshotError> {
+        // SAFETY the mapping is owned by the store and never remapped,
+        // references to it do not outlive the store as they are only given with its lifetime
+        let bytes: &'static [u8] = unsafe { std::mem::transmute::<&[u8], _>(&mmap[..]) };
+        let mut input = Input(bytes);
+        if input.bytes(8)? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = input.u32()?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let _count = input.u64()?;
+        if input.u64()? != 0 {
+            return Err(SnapshotError::Corrupted("labels do not start at 0"));
+        }
+        let len = input.u64()? as usize;
+        let mut labels = Vec::with_capacity(len);
+        let mut label_index = HashMap::with_capacity(len);
+        for i in 0..len {
+            let l = input.u32()? as usize;
+            let l = std::str::from_utf8(input.bytes(l)?)
+                .map_err(|_| SnapshotError::Corrupted("label is not utf8"))?;
+            labels.push(l);
+            label_index.insert(l, LabelIdentifier::try_from_usize(i).unwrap());
+        }
+
+        let len = input.u16()?;
+        let mut kinds = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let l = input.u16()? as usize;
+            let name = std::str::from_utf8(input.bytes(l)?)
+                .map_err(|_| SnapshotError::Corrupted("codec name is not utf8"))?;
+            kinds.push(Kind::new(name, codecs));
+        }
+        let _count = input.u64()?;
+        let _errors = input.u64()?;
+        let len = input.u64()? as usize;
+        let mut index = HashMap::with_capacity(len);
+        for _ in 0..len {
+            let id = id_from_u64(input.u64()?)
+                .ok_or(SnapshotError::Corrupted("null node identifier"))?;
+            index.insert(id, bytes.len() - input.0.len());
+            for _ in 0..input.u8()? {
+                let kind = *kinds
+                    .get(input.u16()? as usize)
+                    .ok_or(SnapshotError::Corrupted("undeclared codec"))?;
+                let pad = input.u8()? as usize;
+                let l = input.u32()? as usize;
+                input.bytes(pad)?;
+                let payload = input.bytes(l)?;
+                if let Kind::Cs | Kind::NoSpacesCs = kind {
+                    // checked once here, so that views can reinterpret children in place
+                    let aligned =
+                        payload.as_ptr() as usize % std::mem::align_of::<NodeIdentifier>() == 0;
+                    if !aligned || l % 8 != 0 {
+                        return Err(SnapshotError::Corrupted("misaligned children"));
+                    }
+                    if payload.chunks(8).any(|x| x.iter().all(|x| *x == 0)) {
+                        return Err(SnapshotError::Corrupted("null node identifier"));
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            index,
+            kinds,
+            labels,
+            label_index,
+            mmap,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn try_resolve(&self, id: NodeIdentifier) -> Option<HashedNodeRef<'_, NodeIdentifier>> {
+        let offset = *self.index.get(&id)?;
+        let mut input = Input(&self.mmap[offset..]);
+        let mut node = HashedNodeRef {
+            id,
+            label: None,
+            ty: None,
+            cs: None,
+            no_spaces_cs: None,
+            hashs: None,
+            size: None,
+            size_no_spaces: None,
+            height: None,
+            bytes_len: None,
+            phantom: PhantomData,
+        };
+        // NOTE already validated while indexing
+        for _ in 0..input.u8().ok()? {
+            let kind = self.kinds[input.u16().ok()? as usize];
+            let pad = input.u8().ok()? as usize;
+            let l = input.u32().ok()? as usize;
+            input.bytes(pad).ok()?;
+            let mut payload = Input(input.bytes(l).ok()?);
+            match kind {
+                Kind::Label => node.label = Some(payload.label().ok()?),
+                Kind::Cs => node.cs = Some(children(payload.0)),
+                Kind::NoSpacesCs => node.no_spaces_cs = Some(children(payload.0)),
+                Kind::Size => node.size = Some(payload.u32().ok()?),
+                Kind::SizeNoSpaces => node.size_no_spaces = Some(payload.u32().ok()?),
+                Kind::Height => node.height = Some(payload.u32().ok()?),
+                Kind::BytesLen => node.bytes_len = Some(payload.u32().ok()?),
+                Kind::Hashs => {
+                    node.hashs = Some(SyntaxNodeHashs {
+                        structt: payload.hash().ok()?,
+                        label: payload.hash().ok()?,
+                        syntax: payload.hash().ok()?,
+                    })
+                }
+                Kind::Type(f) => node.ty = Some(f(payload.u16().ok()?)),
+                Kind::Other => (),
+            }
+        }
+        Some(node)
+    }
+
+    pub fn resolve(&self, id: NodeIdentifier) -> HashedNodeRef<'_, NodeIdentifier> {
+        self.try_resolve(id).unwrap()
+    }
+
+    /// Labels of the snapshot, also mapped
+    pub fn labels(&self) -> LabelStore<'_> {
+        LabelStore(self)
+    }
+}
+
+fn children(bytes: &[u8]) -> &[NodeIdentifier] {
+    // SAFETY alignment and non nullity checked while indexing,
+    // a legion Entity is a transparent NonZeroU64 stored in little endian
+    unsafe {
+        std::slice::from_raw_parts(
+            bytes.as_ptr() as *const NodeIdentifier,
+            bytes.len() / std::mem::size_of::<NodeIdentifier>(),
+        )
+    }
+}
+
+impl crate::types::NodeStore<NodeIdentifier> for NodeStore {
+    type R<'a> = HashedNodeRef<'a, NodeIdentifier>;
+
+    fn resolve(&self, id: &NodeIdentifier) -> Self::R<'_> {
+        NodeStore::resolve(self, *id)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct LabelStore<'a>(&'a NodeStore);
+
+impl<'a> crate::types::LabelStore<str> for LabelStore<'a> {
+    type I = LabelIdentifier;
+
+    fn get_or_insert<T: std::borrow::Borrow<str>>(&mut self, _node: T) -> Self::I {
+        unimplemented!("mapped stores are read-only")
+    }
+
+    fn get<T: std::borrow::Borrow<str>>(&self, node: T) -> Option<Self::I> {
+        self.0.label_index.get(node.borrow()).copied()
+    }
+
+    fn resolve(&self, id: &Self::I) -> &str {
+        self.0.labels[id.to_usize()]
+    }
+}
+
+/// View of a mapped node
+pub struct HashedNodeRef<'a, T> {
+    id: NodeIdentifier,
+    label: Option<LabelIdentifier>,
+    ty: Option<AnyType>,
+    cs: Option<&'a [NodeIdentifier]>,
+    no_spaces_cs: Option<&'a [NodeIdentifier]>,
+    hashs: Option<SyntaxNodeHashs<HashSize>>,
+    size: Option<u32>,
+    size_no_spaces: Option<u32>,
+    height: Option<u32>,
+    bytes_len: Option<u32>,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T> HashedNodeRef<'a, T> {
+    pub fn id(&self) -> NodeIdentifier {
+        self.id
+    }
+
+    pub fn size_no_spaces(&self) -> usize {
+        self.size_no_spaces.unwrap_or(1) as usize
+    }
+
+    /// Children without spaces if there are some, like the legion store
+    fn cs(&self) -> Option<&'a [NodeIdentifier]> {
+        self.no_spaces_cs.or(self.cs)
+    }
+}
+
+impl<'a, T> Debug for HashedNodeRef<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashedNodeRef")
+            .field("id", &self.id)
+            .field("label", &self.label)
+            .field("cs", &self.cs)
+            .finish()
+    }
+}
+
+impl<'a, T> crate::types::Node for HashedNodeRef<'a, T> {}
+
+impl<'a, T> crate::types::Stored for HashedNodeRef<'a, T> {
+    type TreeId = NodeIdentifier;
+}
+
+impl<'a, T> crate::types::Typed for HashedNodeRef<'a, T> {
+    type Type = AnyType;
+
+    fn get_type(&self) -> AnyType {
+        self.ty.expect("the type of this node is not registered in the codecs")
+    }
+
+    fn try_get_type(&self) -> Option<Self::Type> {
+        self.ty
+    }
+}
+
+impl<'a, T> crate::types::Labeled for HashedNodeRef<'a, T> {
+    type Label = LabelIdentifier;
+
+    fn get_label_unchecked(&self) -> &LabelIdentifier {
+        self.label.as_ref().expect("check with self.has_label()")
+    }
+
+    fn try_get_label(&self) -> Option<&LabelIdentifier> {
+        self.label.as_ref()
+    }
+}
+
+impl<'a, T> crate::types::WithChildren for HashedNodeRef<'a, T> {
+    type ChildIdx = u16;
+    type Children<'b> = MySlice<<Self::TreeId as NodeId>::IdN> where Self: 'b;
+
+    fn child_count(&self) -> u16 {
+        self.cs().map_or(0, |x| x.len().try_into().expect("too much children"))
+    }
+
+    fn child(&self, idx: &u16) -> Option<NodeIdentifier> {
+        self.cs()?.get(*idx as usize).copied()
+    }
+
+    fn child_rev(&self, idx: &u16) -> Option<NodeIdentifier> {
+        let cs = self.cs()?;
+        let i = cs.len().checked_sub(*idx as usize + 1)?;
+        cs.get(i).copied()
+    }
+
+    fn children(&self) -> Option<&Self::Children<'_>> {
+        self.cs().map(|x| x.into())
+    }
+}
+
+impl<'a, T> crate::types::Tree for HashedNodeRef<'a, T> {
+    fn has_children(&self) -> bool {
+        self.cs().map_or(false, |x| !x.is_empty())
+    }
+
+    fn has_label(&self) -> bool {
+        self.label.is_some()
+    }
+}
+
+impl<'a, T> crate::types::WithStats for HashedNodeRef<'a, T> {
+    fn size(&self) -> usize {
+        self.size.unwrap_or(1) as usize
+    }
+
+    fn height(&self) -> usize {
+        self.height.unwrap_or(1) as usize
+    }
+}
+
+impl<'a, T> crate::types::WithSerialization for HashedNodeRef<'a, T> {
+    fn try_bytes_len(&self) -> Option<usize> {
+        self.bytes_len.map(|x| x as usize)
+    }
+}
+
+impl<'a, T> crate::types::WithHashs for HashedNodeRef<'a, T> {
+    type HK = SyntaxNodeHashsKinds;
+    type HP = HashSize;
+
+    fn hash(&self, kind: &Self::HK) -> HashSize {
+        self.hashs.expect("no hashs for this node").hash(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        store::{
+            nodes::legion::{compo, NodeStore as LegionNodeStore},
+            SimpleStores,
+        },
+        types::{LabelStore as _, Labeled, WithChildren, WithHashs, WithStats},
+    };
+
+    #[test]
+    fn resolve_mapped() {
+        let mut stores = SimpleStores::<()>::default();
+        let leaf = |stores: &mut SimpleStores<()>, l: &str, h: HashSize| {
+            let label = stores.label_store.get_or_insert(l);
+            let insertion = stores.node_store.prepare_insertion(&h, |_| false);
+            let hashs = SyntaxNodeHashs {
+                structt: h,
+                label: h,
+                syntax: h,
+            };
+            LegionNodeStore::insert_after_prepare(insertion.vacant(), (label, hashs))
+        };
+        let a = leaf(&mut stores, "a", 1);
+        let b = leaf(&mut stores, "b", 2);
+        let insertion = stores.node_store.prepare_insertion(&3u32, |_| false);
+        let root = LegionNodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (
+                SyntaxNodeHashs::<HashSize>::default(),
+                compo::Size(3),
+                compo::Height(2),
+                compo::CS(vec![a, b].into_boxed_slice()),
+            ),
+        );
+
+        let path = std::env::temp_dir().join(format!("hyperast_mapped_{}", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        stores.save_snapshot(&mut file, &Codecs::default()).unwrap();
+        drop(file);
+
+        let mapped = NodeStore::open(&path, &Codecs::default()).unwrap();
+        assert_eq!(mapped.len(), 3);
+        let n = mapped.resolve(root);
+        assert_eq!(n.children().unwrap().0, [a, b]);
+        assert_eq!(n.size(), 3);
+        assert_eq!(n.height(), 2);
+        let n = mapped.resolve(b);
+        assert_eq!(n.hash(&SyntaxNodeHashsKinds::Label), 2);
+        assert_eq!(mapped.labels().resolve(n.get_label_unchecked()), "b");
+        assert_eq!(mapped.labels().get("a"), stores.label_store.get("a"));
+        drop(mapped);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,21 +1,258 @@
+pub mod compo;
 pub mod fetched;
 #[cfg(feature = "hecs")]
 pub mod hecs;
 #[cfg(feature = "legion")]
 pub mod legion;
 pub mod boxed_components;
+#[cfg(all(feature = "mmap", feature = "legion", target_endian = "little"))]
+pub mod mapped;
+
+// NOTE legion stays the default backend when both are enabled,
+// hecs is used when it is enabled alone, eg. `--no-default-features --features hecs,native`
+// generators insert through [`NodeInserter`] so they work with either of them.
 
 #[cfg(feature = "legion")]
 pub type DefaultNodeStore = legion::NodeStore;
-#[cfg(not(feature = "legion"))]
+#[cfg(all(feature = "hecs", not(feature = "legion")))]
+pub type DefaultNodeStore = hecs::NodeStore;
+#[cfg(not(any(feature = "legion", feature = "hecs")))]
 pub type DefaultNodeStore = boxed_components::NodeStore;
 
 #[cfg(feature = "legion")]
 pub type DefaultNodeIdentifier = legion::NodeIdentifier;
-#[cfg(not(feature = "legion"))]
+#[cfg(all(feature = "hecs", not(feature = "legion")))]
+pub type DefaultNodeIdentifier = hecs::NodeIdentifier;
+#[cfg(not(any(feature = "legion", feature = "hecs")))]
 pub type DefaultNodeIdentifier = boxed_components::NodeIdentifier;
 
 #[cfg(feature = "legion")]
 pub type HashedNodeRef<'store> = legion::HashedNodeRef<'store, DefaultNodeIdentifier>;
-#[cfg(not(feature = "legion"))]
+#[cfg(all(feature = "hecs", not(feature = "legion")))]
+pub type HashedNodeRef<'store> = hecs::HashedNodeRef<'store, DefaultNodeIdentifier>;
+#[cfg(not(any(feature = "legion", feature = "hecs")))]
 pub type HashedNodeRef<'store> = boxed_components::HashedNodeRef<'store, DefaultNodeIdentifier>;
+
+/// Read access to the components of a stored node, whatever the backend,
+/// eg. to compare a stored node with the one about to be inserted.
+pub trait ComponentsRef {
+    type Ref<'b, C: Send + Sync + 'static>: std::ops::Deref<Target = C>
+    where
+        Self: 'b;
+
+    fn get<C: Send + Sync + 'static>(&self) -> Option<Self::Ref<'_, C>>;
+}
+
+/// Accumulates the components of a node, see [`PendingInsertion::insert_built`]
+pub trait ComponentsBuilder: Default {
+    fn add<C: Send + Sync + 'static>(&mut self, component: C) -> &mut Self;
+}
+
+/// Components given all at once to [`PendingInsertion::insert`], ie. tuples of components
+pub trait Components {
+    fn add_to<B: ComponentsBuilder>(self, builder: &mut B);
+}
+
+macro_rules! impl_components {
+    ( $($t:ident),+ ) => {
+        impl<$($t: Send + Sync + 'static),+> Components for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn add_to<B: ComponentsBuilder>(self, builder: &mut B) {
+                let ($($t,)+) = self;
+                $(builder.add($t);)+
+            }
+        }
+    };
+}
+
+impl_components!(T0);
+impl_components!(T0, T1);
+impl_components!(T0, T1, T2);
+impl_components!(T0, T1, T2, T3);
+impl_components!(T0, T1, T2, T3, T4);
+impl_components!(T0, T1, T2, T3, T4, T5);
+impl_components!(T0, T1, T2, T3, T4, T5, T6);
+impl_components!(T0, T1, T2, T3, T4, T5, T6, T7);
+impl_components!(T0, T1, T2, T3, T4, T5, T6, T7, T8);
+impl_components!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_components!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_components!(T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+
+/// Hash-consed insertion of nodes, as done by tree generators.
+///
+/// Implemented by the legion and hecs node stores,
+/// and by the workers of a legion `ConcurrentNodeStore`,
+/// so that a generator neither depends on a backend nor on building files one at a time.
+pub trait NodeInserter {
+    type IdN: Copy + Eq + std::hash::Hash + std::fmt::Debug + Send + Sync + 'static;
+    /// The components of a stored node, as given to equality checks
+    type Entry<'a>: ComponentsRef;
+    type Builder: ComponentsBuilder;
+    /// The references to stored nodes, eg. to bound the type store used along with the inserter
+    type NodeRef<'a, T>;
+    type Pending<'a>: PendingInsertion<IdN = Self::IdN, Builder = Self::Builder>
+    where
+        Self: 'a;
+
+    /// Looks for a node equal to the one described by `hashable` and `eq`,
+    /// the returned insertion must be done (or dropped) before preparing another one.
+    fn prepare_insertion<'a, Eq: Fn(Self::Entry<'_>) -> bool, V: std::hash::Hash>(
+        &'a mut self,
+        hashable: &'a V,
+        eq: Eq,
+    ) -> Self::Pending<'a>;
+
+    /// Gives `f` the components of the node identified by `id`
+    fn with_resolved<R>(&self, id: Self::IdN, f: impl FnOnce(Self::Entry<'_>) -> R) -> R;
+}
+
+/// Insertion prepared by [`NodeInserter::prepare_insertion`]
+pub trait PendingInsertion: Sized {
+    type IdN: Copy + Eq + std::hash::Hash + std::fmt::Debug + Send + Sync + 'static;
+    type Entry<'a>: ComponentsRef;
+    type Builder: ComponentsBuilder;
+
+    /// The equal node, if one is already stored
+    fn occupied_id(&self) -> Option<Self::IdN>;
+
+    /// Gives `f` the components of the node identified by `id`, eg. a child of the node to insert
+    fn with_resolved<R>(&self, id: Self::IdN, f: impl FnOnce(Self::Entry<'_>) -> R) -> R;
+
+    /// Inserts the node made of `components`, there must not be an equal node
+    fn insert<C: Components>(self, components: C) -> Self::IdN {
+        let mut builder = Self::Builder::default();
+        components.add_to(&mut builder);
+        self.insert_built(builder)
+    }
+
+    /// Same as [`PendingInsertion::insert`] with components added one by one beforehand
+    fn insert_built(self, components: Self::Builder) -> Self::IdN;
+}
+
+/// The same scenarios run on each backend, to keep them interchangeable.
+#[cfg(test)]
+mod backend_tests {
+    macro_rules! same_behavior {
+        ($backend:ident, |$x:ident| $get_label:expr) => {
+            mod $backend {
+                use crate::{
+                    hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+                    nodes::HashSize,
+                    store::{
+                        defaults::LabelIdentifier,
+                        labels::LabelStore,
+                        nodes::$backend::{NodeIdentifier, NodeStore},
+                    },
+                    types::{LabelStore as _, Labeled, Tree, WithChildren, WithStats},
+                };
+
+                fn insert(
+                    store: &mut NodeStore,
+                    label: LabelIdentifier,
+                    cs: Vec<NodeIdentifier>,
+                ) -> NodeIdentifier {
+                    let size = 1 + cs.len() as u32;
+                    let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+                        hashed::Builder::new(Default::default(), &0u16, &label, size);
+                    let hashable = &hbuilder.most_discriminating();
+                    let insertion =
+                        store.prepare_insertion(hashable, |$x| $get_label == Some(label));
+                    if let Some(id) = insertion.occupied_id() {
+                        return id;
+                    }
+                    NodeStore::insert_after_prepare(
+                        insertion.vacant(),
+                        (
+                            label,
+                            hbuilder.build(),
+                            crate::store::nodes::$backend::compo::Size(size),
+                            crate::store::nodes::$backend::compo::CS(cs.into_boxed_slice()),
+                        ),
+                    )
+                }
+
+                #[test]
+                fn insert_dedup_resolve() {
+                    let mut labels = LabelStore::new();
+                    let [a, b, p] = ["a", "b", "p"].map(|x| labels.get_or_insert(x));
+                    let mut store = NodeStore::new();
+                    let na = insert(&mut store, a, vec![]);
+                    let nb = insert(&mut store, b, vec![]);
+                    let np = insert(&mut store, p, vec![na, nb]);
+                    assert_eq!(insert(&mut store, a, vec![]), na);
+                    assert_eq!(store.len(), 3);
+
+                    let n = store.resolve(np);
+                    assert!(n.has_children());
+                    assert_eq!(n.child_count(), 2);
+                    assert_eq!(n.child(&0), Some(na));
+                    assert_eq!(n.child_rev(&0), Some(nb));
+                    assert_eq!(n.size(), 3);
+                    assert_eq!(n.get_label_unchecked(), &p);
+                    let n = store.resolve(na);
+                    assert!(!n.has_children());
+                    assert_eq!(n.try_get_label(), Some(&a));
+                }
+
+                #[test]
+                fn insert_through_inserter() {
+                    let mut labels = LabelStore::new();
+                    let [a, p] = ["a", "p"].map(|x| labels.get_or_insert(x));
+                    let mut store = NodeStore::new();
+                    let na = super::insert_generic(&mut store, a, vec![]);
+                    let np = super::insert_generic(&mut store, p, vec![na]);
+                    assert_eq!(super::insert_generic(&mut store, a, vec![]), na);
+                    // interoperates with the backend specific insertion
+                    assert_eq!(insert(&mut store, p, vec![na]), np);
+                    assert_eq!(store.len(), 2);
+
+                    let n = store.resolve(np);
+                    assert_eq!(n.child(&0), Some(na));
+                    assert_eq!(n.size(), 2);
+                    assert_eq!(n.get_label_unchecked(), &p);
+                }
+            }
+        };
+    }
+
+    use super::{compo, ComponentsRef, NodeInserter, PendingInsertion};
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+        nodes::HashSize,
+        store::defaults::LabelIdentifier,
+    };
+
+    /// Inserts only through [`NodeInserter`], like the generators
+    fn insert_generic<S: NodeInserter>(
+        store: &mut S,
+        label: LabelIdentifier,
+        cs: Vec<S::IdN>,
+    ) -> S::IdN {
+        let size = 1 + cs.len() as u32;
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, size);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = store.prepare_insertion(hashable, |x: S::Entry<'_>| {
+            x.get::<LabelIdentifier>().map_or(false, |l| *l == label)
+                && x.get::<compo::CS<S::IdN>>()
+                    .map_or(cs.is_empty(), |x| *x.0 == *cs)
+        });
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        insertion.insert((
+            label,
+            hbuilder.build(),
+            compo::Size(size),
+            compo::CS(cs.into_boxed_slice()),
+        ))
+    }
+
+    #[cfg(feature = "legion")]
+    same_behavior!(legion, |x| x
+        .get_component::<LabelIdentifier>()
+        .ok()
+        .copied());
+    #[cfg(feature = "hecs")]
+    same_behavior!(hecs, |x| x.get::<&LabelIdentifier>().map(|l| *l));
+}
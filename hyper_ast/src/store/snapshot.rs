@@ -0,0 +1,675 @@
+//! Versioned on-disk snapshots of [`SimpleStores`].
+//!
+//! A snapshot contains the interned labels, in identifier order,
+//! and every node of the legion [`NodeStore`] with its identifier and components.
+//! Reloading it gives back the same node and label identifiers,
+//! and rebuilds the dedup table, so that construction can resume where it stopped.
+//!
+//! Components are (de)serialized through a registry of [`Codecs`], indexed by stable names.
+//! The components defined in this crate are registered by [`Codecs::default`],
+//! node types and language specific metadata must be registered by their users, eg.
+//! ```ignore
+//! let mut codecs = Codecs::default();
+//! codecs.register_type::<hyper_ast_gen_ts_java::types::Type>("java");
+//! ```
+//!
+//! Type stores are not persisted, they are recreated with `Default`.
+//!
+//! # Layout
+//! All integers are little endian.
+//! ```text
+//! magic: b"HYPERAST", version: u32,
+//! labels: count: u64, from: u64, len: u64, [byte_len: u32, utf8 bytes]*,
+//! codecs: len: u16, [byte_len: u16, name]*,
+//! nodes: count: u64, errors: u64, len: u64,
+//!        [id: u64, len: u8, [codec: u16, pad: u8, byte_len: u32, [0]{pad}, bytes]*]*
+//! ```
+//! Component payloads (`bytes`) are aligned on 8 bytes from the start of the snapshot,
+//! so that a mapped snapshot can be read in place.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{Read, Write},
+    num::NonZeroU64,
+};
+
+use legion::{
+    storage::{Component, ComponentTypeId},
+    world::EntryRef,
+};
+use string_interner::Symbol;
+
+use crate::{
+    cyclomatic::Mcc,
+    filter::{Bloom, BloomSize},
+    hashed::{HashWidth, SyntaxNodeHashs},
+    nodes::HashSize,
+    store::{
+        defaults::LabelIdentifier,
+        labels::LabelStore,
+        nodes::legion::{
+            compo::{self, NoSpacesCS, CS},
+            dyn_builder::EntityBuilder,
+            NodeIdentifier, NodeStore,
+        },
+        SimpleStores,
+    },
+    types::{AnyType, HyperType, Lang, TypeTrait},
+};
+
+pub const MAGIC: &[u8; 8] = b"HYPERAST";
+/// Bumped on each incompatible change of the layout.
+pub const VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    /// The snapshot uses a codec that was not registered
+    UnknownCodec(String),
+    /// A stored node holds a component without registered codec
+    UnregisteredComponent(String),
+    Corrupted(&'static str),
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(value: std::io::Error) -> Self {
+        SnapshotError::Io(value)
+    }
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "io error: {}", e),
+            SnapshotError::BadMagic => write!(f, "not a hyperast snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {} (expected {})", v, VERSION)
+            }
+            SnapshotError::UnknownCodec(name) => write!(f, "no codec registered for {}", name),
+            SnapshotError::UnregisteredComponent(ty) => {
+                write!(f, "component {} cannot be persisted", ty)
+            }
+            SnapshotError::Corrupted(what) => write!(f, "corrupted snapshot: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+pub fn id_to_u64(id: NodeIdentifier) -> u64 {
+    // NOTE a legion Entity is a transparent NonZeroU64
+    unsafe { std::mem::transmute::<NodeIdentifier, u64>(id) }
+}
+
+pub fn id_from_u64(id: u64) -> Option<NodeIdentifier> {
+    let id = NonZeroU64::new(id)?;
+    Some(unsafe { std::mem::transmute::<NonZeroU64, NodeIdentifier>(id) })
+}
+
+/// Makes sure that legion will never allocate identifiers up to `max`,
+/// needed before restoring nodes with their original identifiers.
+pub fn reserve_ids(max: u64) {
+    let mut alloc = legion::world::Allocate::new();
+    while alloc.next().map_or(false, |x| id_to_u64(x) < max) {}
+}
+
+/// Cursor over the bytes of a persisted component
+pub struct Input<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Input<'a> {
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.0.len() < len {
+            return Err(SnapshotError::Corrupted("component too short"));
+        }
+        let (r, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(r)
+    }
+    pub fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.0)
+    }
+    pub fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.bytes(1)?[0])
+    }
+    pub fn u16(&mut self) -> Result<u16, SnapshotError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+    pub fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+    pub fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+    /// Reads a node hash of the configured width, see [`HashSize`]
+    pub fn hash<T: HashWidth>(&mut self) -> Result<T, SnapshotError> {
+        Ok(T::read_le(self.bytes(std::mem::size_of::<T>())?))
+    }
+    pub fn id(&mut self) -> Result<NodeIdentifier, SnapshotError> {
+        id_from_u64(self.u64()?).ok_or(SnapshotError::Corrupted("null node identifier"))
+    }
+    pub fn label(&mut self) -> Result<LabelIdentifier, SnapshotError> {
+        let l = self.u32()? as usize;
+        LabelIdentifier::try_from_usize(l).ok_or(SnapshotError::Corrupted("bad label identifier"))
+    }
+}
+
+type Encode = Box<dyn Fn(&EntryRef, &mut Vec<u8>) + Send + Sync>;
+type Decode = Box<dyn Fn(&mut Input, &mut EntityBuilder) -> Result<(), SnapshotError> + Send + Sync>;
+
+struct Codec {
+    name: &'static str,
+    encode: Encode,
+    decode: Decode,
+    /// set for node types, see [`Codecs::register_type`]
+    as_type: Option<fn(u16) -> AnyType>,
+}
+
+/// Registry of the persistable components
+pub struct Codecs {
+    codecs: Vec<Codec>,
+    by_type: HashMap<ComponentTypeId, usize>,
+    by_name: HashMap<&'static str, usize>,
+}
+
+impl Codecs {
+    pub fn empty() -> Self {
+        Self {
+            codecs: vec![],
+            by_type: Default::default(),
+            by_name: Default::default(),
+        }
+    }
+
+    /// Registers a component under a `name` that must stay the same between versions.
+    pub fn register<T: Component>(
+        &mut self,
+        name: &'static str,
+        encode: fn(&T, &mut Vec<u8>),
+        decode: fn(&mut Input) -> Result<T, SnapshotError>,
+    ) -> &mut Self {
+        assert!(
+            !self.by_name.contains_key(name),
+            "{} is already registered",
+            name
+        );
+        let i = self.codecs.len();
+        self.codecs.push(Codec {
+            name,
+            encode: Box::new(move |e, out| encode(e.get_component::<T>().unwrap(), out)),
+            decode: Box::new(move |input, builder| {
+                builder.add(decode(input)?);
+                Ok(())
+            }),
+            as_type: None,
+        });
+        self.by_type.insert(ComponentTypeId::of::<T>(), i);
+        self.by_name.insert(name, i);
+        self
+    }
+
+    /// Registers the node types of a language,
+    /// they are persisted as their index in the language.
+    pub fn register_type<T: 'static + TypeTrait>(&mut self, name: &'static str) -> &mut Self {
+        self.register::<T>(
+            name,
+            |t, out| out.extend(<T::Lang as Lang<T>>::to_u16(*t).to_le_bytes()),
+            |input| Ok(*<T::Lang as Lang<T>>::make(input.u16()?)),
+        );
+        self.codecs.last_mut().unwrap().as_type = Some(|t| {
+            let t: &'static dyn HyperType = <T::Lang as Lang<T>>::make(t);
+            t.into()
+        });
+        self
+    }
+
+    /// Gives the type of a node from its persisted index, if `name` is a codec of node types.
+    pub(crate) fn type_decoder(&self, name: &str) -> Option<fn(u16) -> AnyType> {
+        self.by_name
+            .get(name)
+            .and_then(|&i| self.codecs[i].as_type)
+    }
+
+    /// Encodes the components of `entry`,
+    /// each payload is aligned on 8 bytes considering that `out` starts at `base` in the output.
+    fn encode(&self, entry: &EntryRef, out: &mut Vec<u8>, base: usize) -> Result<u8, SnapshotError> {
+        let tys = entry.archetype().layout().component_types();
+        let len: u8 = tys
+            .len()
+            .try_into()
+            .map_err(|_| SnapshotError::Corrupted("too many components"))?;
+        out.push(len);
+        for ty in tys {
+            let Some(&i) = self.by_type.get(ty) else {
+                return Err(SnapshotError::UnregisteredComponent(format!("{:?}", ty)));
+            };
+            out.extend((i as u16).to_le_bytes());
+            let pad = (8 - (base + out.len() + 5) % 8) % 8;
+            out.push(pad as u8);
+            let at = out.len();
+            out.extend(0u32.to_le_bytes());
+            out.extend(std::iter::repeat(0).take(pad));
+            (self.codecs[i].encode)(entry, out);
+            let l = (out.len() - at - 4 - pad) as u32;
+            out[at..at + 4].copy_from_slice(&l.to_le_bytes());
+        }
+        Ok(len)
+    }
+}
+
+fn encode_entities(cs: &[NodeIdentifier], out: &mut Vec<u8>) {
+    for x in cs {
+        out.extend(id_to_u64(*x).to_le_bytes());
+    }
+}
+
+fn decode_entities(input: &mut Input) -> Result<Box<[NodeIdentifier]>, SnapshotError> {
+    let mut r = vec![];
+    while !input.0.is_empty() {
+        r.push(input.id()?);
+    }
+    Ok(r.into_boxed_slice())
+}
+
+fn encode_labels(cs: &[LabelIdentifier], out: &mut Vec<u8>) {
+    for x in cs {
+        out.extend((x.to_usize() as u32).to_le_bytes());
+    }
+}
+
+fn decode_labels(input: &mut Input) -> Result<Box<[LabelIdentifier]>, SnapshotError> {
+    let mut r = vec![];
+    while !input.0.is_empty() {
+        r.push(input.label()?);
+    }
+    Ok(r.into_boxed_slice())
+}
+
+fn bloom_size_to_u8(x: &BloomSize) -> u8 {
+    match x {
+        BloomSize::None => 0,
+        BloomSize::B16 => 1,
+        BloomSize::B32 => 2,
+        BloomSize::B64 => 3,
+        BloomSize::B128 => 4,
+        BloomSize::B256 => 5,
+        BloomSize::B512 => 6,
+        BloomSize::B1024 => 7,
+        BloomSize::B2048 => 8,
+        BloomSize::B4096 => 9,
+        BloomSize::Much => 10,
+    }
+}
+
+fn bloom_size_from_u8(x: u8) -> Result<BloomSize, SnapshotError> {
+    Ok(match x {
+        0 => BloomSize::None,
+        1 => BloomSize::B16,
+        2 => BloomSize::B32,
+        3 => BloomSize::B64,
+        4 => BloomSize::B128,
+        5 => BloomSize::B256,
+        6 => BloomSize::B512,
+        7 => BloomSize::B1024,
+        8 => BloomSize::B2048,
+        9 => BloomSize::B4096,
+        10 => BloomSize::Much,
+        _ => return Err(SnapshotError::Corrupted("bad bloom size")),
+    })
+}
+
+impl Default for Codecs {
+    /// Codecs for the components defined in hyper_ast
+    fn default() -> Self {
+        let mut r = Self::empty();
+        r.register::<LabelIdentifier>(
+            "label",
+            |x, out| out.extend((x.to_usize() as u32).to_le_bytes()),
+            |input| input.label(),
+        );
+        r.register::<CS<NodeIdentifier>>(
+            "cs",
+            |x, out| encode_entities(&x.0, out),
+            |input| Ok(CS(decode_entities(input)?)),
+        );
+        r.register::<NoSpacesCS<NodeIdentifier>>(
+            "no_spaces_cs",
+            |x, out| encode_entities(&x.0, out),
+            |input| Ok(NoSpacesCS(decode_entities(input)?)),
+        );
+        r.register::<CS<LabelIdentifier>>(
+            "cs_names",
+            |x, out| encode_labels(&x.0, out),
+            |input| Ok(CS(decode_labels(input)?)),
+        );
+        r.register::<compo::Size>(
+            "size",
+            |x, out| out.extend(x.0.to_le_bytes()),
+            |input| Ok(compo::Size(input.u32()?)),
+        );
+        r.register::<compo::SizeNoSpaces>(
+            "size_no_spaces",
+            |x, out| out.extend(x.0.to_le_bytes()),
+            |input| Ok(compo::SizeNoSpaces(input.u32()?)),
+        );
+        r.register::<compo::Height>(
+            "height",
+            |x, out| out.extend(x.0.to_le_bytes()),
+            |input| Ok(compo::Height(input.u32()?)),
+        );
+        r.register::<compo::BytesLen>(
+            "bytes_len",
+            |x, out| out.extend(x.0.to_le_bytes()),
+            |input| Ok(compo::BytesLen(input.u32()?)),
+        );
+        // NOTE the width is part of the name, snapshots of another width see them as unregistered
+        r.register::<SyntaxNodeHashs<HashSize>>(
+            HashSize::COMPONENT,
+            |x, out| {
+                x.structt.write_le(out);
+                x.label.write_le(out);
+                x.syntax.write_le(out);
+            },
+            |input| {
+                Ok(SyntaxNodeHashs {
+                    structt: input.hash()?,
+                    label: input.hash()?,
+                    syntax: input.hash()?,
+                })
+            },
+        );
+        r.register::<Mcc>(
+            "mcc",
+            |x, out| out.extend(x.value.to_le_bytes()),
+            |input| Ok(Mcc { value: input.u32()? }),
+        );
+        r.register::<BloomSize>(
+            "bloom_size",
+            |x, out| out.push(bloom_size_to_u8(x)),
+            |input| bloom_size_from_u8(input.u8()?),
+        );
+        macro_rules! bloom {
+            ( $($name:literal => $t:ty),* $(,)? ) => {$(
+                r.register::<Bloom<&'static [u8], $t>>(
+                    $name,
+                    |x, out| out.extend(x.to_bytes()),
+                    |input| Ok(Bloom::from_bytes(input.rest())),
+                );
+            )*};
+        }
+        bloom!(
+            "bloom16" => u16,
+            "bloom32" => u32,
+            "bloom64" => u64,
+            "bloom128" => [u64; 2],
+            "bloom256" => [u64; 4],
+            "bloom512" => [u64; 8],
+            "bloom1024" => [u64; 16],
+            "bloom2048" => [u64; 32],
+            "bloom4096" => [u64; 64],
+        );
+        r
+    }
+}
+
+fn read_u8(r: &mut impl Read) -> std::io::Result<u8> {
+    let mut b = [0; 1];
+    r.read_exact(&mut b)?;
+    Ok(b[0])
+}
+fn read_u16(r: &mut impl Read) -> std::io::Result<u16> {
+    let mut b = [0; 2];
+    r.read_exact(&mut b)?;
+    Ok(u16::from_le_bytes(b))
+}
+fn read_u32(r: &mut impl Read) -> std::io::Result<u32> {
+    let mut b = [0; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+fn read_u64(r: &mut impl Read) -> std::io::Result<u64> {
+    let mut b = [0; 8];
+    r.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
+fn read_vec(r: &mut impl Read, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut b = vec![0; len];
+    r.read_exact(&mut b)?;
+    Ok(b)
+}
+
+/// Writes the labels interned from the `from`th one.
+pub(crate) fn write_labels(
+    w: &mut impl Write,
+    store: &LabelStore,
+    from: usize,
+) -> Result<(), SnapshotError> {
+    w.write_all(&(store.count() as u64).to_le_bytes())?;
+    w.write_all(&(from as u64).to_le_bytes())?;
+    w.write_all(&((store.len() - from) as u64).to_le_bytes())?;
+    for (i, (id, l)) in store.iter().enumerate().skip(from) {
+        if id.to_usize() != i {
+            return Err(SnapshotError::Corrupted("label identifiers are not contiguous"));
+        }
+        w.write_all(&(l.len() as u32).to_le_bytes())?;
+        w.write_all(l.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Interns labels written by [`write_labels`],
+/// `store` must contain exactly the labels preceding them.
+pub(crate) fn read_labels(r: &mut impl Read, store: &mut LabelStore) -> Result<(), SnapshotError> {
+    let count = read_u64(r)? as usize;
+    let from = read_u64(r)? as usize;
+    let len = read_u64(r)? as usize;
+    if from != store.len() {
+        return Err(SnapshotError::Corrupted("labels are not contiguous"));
+    }
+    let mut labels = Vec::with_capacity(len);
+    for _ in 0..len {
+        let l = read_u32(r)? as usize;
+        let l = String::from_utf8(read_vec(r, l)?)
+            .map_err(|_| SnapshotError::Corrupted("label is not utf8"))?;
+        labels.push(l);
+    }
+    store.extend_restored(count, labels.iter().map(|x| x.as_str()));
+    Ok(())
+}
+
+/// Writes the nodes identified by `ids`, along with the declaration of all codecs.
+///
+/// `offset` is the number of bytes already written in the output,
+/// needed to align component payloads (see [`crate::store::nodes::mapped`]).
+pub(crate) fn write_nodes(
+    w: &mut impl Write,
+    store: &NodeStore,
+    ids: impl Iterator<Item = NodeIdentifier>,
+    codecs: &Codecs,
+    offset: usize,
+) -> Result<(), SnapshotError> {
+    let ids: Vec<_> = ids.collect();
+    let mut head = vec![];
+    head.extend((codecs.codecs.len() as u16).to_le_bytes());
+    for c in &codecs.codecs {
+        head.extend((c.name.len() as u16).to_le_bytes());
+        head.extend(c.name.as_bytes());
+    }
+    let (count, errors) = store.counters();
+    head.extend((count as u64).to_le_bytes());
+    head.extend((errors as u64).to_le_bytes());
+    head.extend((ids.len() as u64).to_le_bytes());
+    let base = offset + head.len();
+    let mut buf = vec![];
+    for id in ids {
+        let entry = store.entry_ref(id).unwrap();
+        buf.extend(id_to_u64(id).to_le_bytes());
+        codecs.encode(&entry, &mut buf, base)?;
+    }
+    w.write_all(&head)?;
+    w.write_all(&buf)?;
+    Ok(())
+}
+
+/// Restores in `store` the nodes written by [`write_nodes`], returns how many were read.
+pub(crate) fn read_nodes(
+    r: &mut impl Read,
+    store: &mut NodeStore,
+    codecs: &Codecs,
+) -> Result<usize, SnapshotError> {
+    let len = read_u16(r)?;
+    let mut mapping = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let l = read_u16(r)? as usize;
+        let name = String::from_utf8(read_vec(r, l)?)
+            .map_err(|_| SnapshotError::Corrupted("codec name is not utf8"))?;
+        // NOTE codecs only need to be known if they are actually used
+        mapping.push(codecs.by_name.get(name.as_str()).copied().ok_or(name));
+    }
+    let count = read_u64(r)? as usize;
+    let errors = read_u64(r)? as usize;
+    let len = read_u64(r)? as usize;
+    let mut max = 0;
+    let mut nodes = Vec::with_capacity(len);
+    for _ in 0..len {
+        let id = read_u64(r)?;
+        max = max.max(id);
+        let id = id_from_u64(id).ok_or(SnapshotError::Corrupted("null node identifier"))?;
+        let mut builder = EntityBuilder::new();
+        for _ in 0..read_u8(r)? {
+            let c = read_u16(r)? as usize;
+            let c = match mapping.get(c) {
+                Some(Ok(c)) => *c,
+                Some(Err(name)) => return Err(SnapshotError::UnknownCodec(name.clone())),
+                None => return Err(SnapshotError::Corrupted("undeclared codec")),
+            };
+            let pad = read_u8(r)? as usize;
+            let l = read_u32(r)? as usize;
+            read_vec(r, pad)?;
+            let bytes = read_vec(r, l)?;
+            (codecs.codecs[c].decode)(&mut Input(&bytes), &mut builder)?;
+        }
+        nodes.push((id, builder.build()));
+    }
+    reserve_ids(max);
+    for (id, components) in nodes {
+        store.restore(id, components);
+    }
+    store.set_counters((count, errors));
+    Ok(len)
+}
+
+impl<TS: Default> SimpleStores<TS, NodeStore, LabelStore> {
+    /// Writes a snapshot of the label and node stores, see [`self`] for details.
+    pub fn save_snapshot(&self, w: &mut impl Write, codecs: &Codecs) -> Result<(), SnapshotError> {
+        let mut head = vec![];
+        head.extend(MAGIC);
+        head.extend(VERSION.to_le_bytes());
+        write_labels(&mut head, &self.label_store, 0)?;
+        w.write_all(&head)?;
+        let ids = self.node_store.iter_ids();
+        write_nodes(w, &self.node_store, ids, codecs, head.len())?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Reloads stores written by [`SimpleStores::save_snapshot`].
+    ///
+    /// Must be called before any other node is built in this process,
+    /// as restored nodes keep their original identifiers.
+    pub fn load_snapshot(r: &mut impl Read, codecs: &Codecs) -> Result<Self, SnapshotError> {
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = read_u32(r)?;
+        if version != VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let mut label_store = LabelStore::default();
+        read_labels(r, &mut label_store)?;
+        let mut node_store = NodeStore::new();
+        read_nodes(r, &mut node_store, codecs)?;
+        Ok(Self {
+            label_store,
+            type_store: Default::default(),
+            node_store,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        types::{LabelStore as _, WithChildren, WithHashs},
+    };
+
+    fn insert_leaf(stores: &mut SimpleStores<()>, t: u16, l: &str) -> NodeIdentifier {
+        let label = stores.label_store.get_or_insert(l);
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &t, &label, 1);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = stores.node_store.prepare_insertion(hashable, |x: EntryRef| {
+            x.get_component::<LabelIdentifier>().map_or(false, |l| l == &label)
+        });
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        NodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (label, hbuilder.build(), compo::BytesLen(l.len() as u32)),
+        )
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut stores = SimpleStores::<()>::default();
+        let a = insert_leaf(&mut stores, 0, "a");
+        let b = insert_leaf(&mut stores, 0, "b");
+        let hashs: SyntaxNodeHashs<HashSize> = SyntaxNodeHashs {
+            structt: 1,
+            label: 2,
+            syntax: 3,
+        };
+        let insertion = stores.node_store.prepare_insertion(&3u32, |_| false);
+        let root = NodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (hashs, CS(vec![a, b, a].into_boxed_slice()), compo::Size(4)),
+        );
+
+        let mut buf = vec![];
+        stores.save_snapshot(&mut buf, &Codecs::default()).unwrap();
+        let loaded = SimpleStores::<()>::load_snapshot(&mut &buf[..], &Codecs::default()).unwrap();
+
+        assert_eq!(loaded.node_store.len(), 3);
+        let n = loaded.node_store.resolve(root);
+        assert_eq!(n.children().unwrap().0, [a, b, a]);
+        assert_eq!(n.hash(&Default::default()), 3);
+        let n = loaded.node_store.resolve(b);
+        let l = crate::types::Labeled::get_label_unchecked(&n);
+        assert_eq!(loaded.label_store.resolve(l), "b");
+        assert_eq!(loaded.label_store.get("a"), stores.label_store.get("a"));
+
+        // the dedup table is rebuilt
+        let mut loaded = loaded;
+        assert_eq!(insert_leaf(&mut loaded, 0, "b"), b);
+    }
+
+    #[test]
+    fn unregistered_component() {
+        let mut stores = SimpleStores::<()>::default();
+        let insertion = stores.node_store.prepare_insertion(&0u32, |_| false);
+        NodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (SyntaxNodeHashs::<HashSize>::default(), 42u64),
+        );
+        let mut buf = vec![];
+        let r = stores.save_snapshot(&mut buf, &Codecs::default());
+        assert!(matches!(r, Err(SnapshotError::UnregisteredComponent(_))));
+    }
+}
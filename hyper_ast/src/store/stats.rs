@@ -0,0 +1,298 @@
+//! Statistics about the content and memory footprint of [`SimpleStores`].
+//!
+//! Nodes are accounted per type, per archetype (ie. set of components) and per component.
+//! Bytes are estimated from the size of components and their heap allocations,
+//! the overhead of the ecs itself and of the allocator is not accounted.
+
+use std::{collections::BTreeMap, fmt::Display, hash::Hash, mem::size_of};
+
+use crate::{
+    cyclomatic::Mcc,
+    filter::{Bloom, BloomSize},
+    hashed::SyntaxNodeHashs,
+    nodes::HashSize,
+    store::{
+        defaults::LabelIdentifier,
+        labels::LabelStore,
+        nodes::legion::{
+            compo::{self, More, NoSpacesCS, CS},
+            HashedNodeRef, NodeIdentifier, NodeStore,
+        },
+        SimpleStores,
+    },
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntityStats {
+    pub entities: usize,
+    pub bytes: usize,
+}
+
+impl EntityStats {
+    fn add(&mut self, bytes: usize) {
+        self.entities += 1;
+        self.bytes += bytes;
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeStoreStats {
+    pub total: EntityStats,
+    /// bytes used by the dedup table
+    pub dedup_bytes: usize,
+    /// insertions that were prepared
+    pub dedup_lookups: usize,
+    /// prepared insertions that found an equal node
+    pub dedup_hits: usize,
+    pub per_type: BTreeMap<String, EntityStats>,
+    pub per_archetype: BTreeMap<String, EntityStats>,
+    /// only components defined in this crate are accounted, others are in `"other"`
+    pub per_component: BTreeMap<String, EntityStats>,
+}
+
+impl NodeStoreStats {
+    pub fn dedup_hit_ratio(&self) -> f64 {
+        if self.dedup_lookups == 0 {
+            return 0.;
+        }
+        self.dedup_hits as f64 / self.dedup_lookups as f64
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelStoreStats {
+    pub labels: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoreStats {
+    pub nodes: NodeStoreStats,
+    pub labels: LabelStoreStats,
+}
+
+/// Accounts the known components of a node,
+/// returns the bytes of the node and the name of its archetype.
+fn account(
+    n: &HashedNodeRef<NodeIdentifier>,
+    per_component: &mut BTreeMap<String, EntityStats>,
+) -> (usize, String) {
+    let mut total = 0;
+    let mut names = vec![];
+    let mut add = |name: &'static str, bytes: usize| {
+        per_component
+            .entry(name.to_string())
+            .or_default()
+            .add(bytes);
+        total += bytes;
+        names.push(name);
+    };
+    // NOTE each component is also accounted when wrapped in a `More`
+    macro_rules! inline {
+        ( $($name:literal => $t:ty),* $(,)? ) => {$(
+            if n.get_component::<$t>().is_ok() {
+                add($name, size_of::<$t>());
+            }
+            if n.get_component::<More<$t>>().is_ok() {
+                add(concat!("more_", $name), size_of::<More<$t>>());
+            }
+        )*};
+    }
+    macro_rules! boxed {
+        ( $($name:literal => $t:ident<$c:ty>),* $(,)? ) => {$(
+            if let Ok(x) = n.get_component::<$t<$c>>() {
+                add($name, size_of::<$t<$c>>() + x.0.len() * size_of::<$c>());
+            }
+            if let Ok(x) = n.get_component::<More<$t<$c>>>() {
+                add(
+                    concat!("more_", $name),
+                    size_of::<More<$t<$c>>>() + x.0 .0.len() * size_of::<$c>(),
+                );
+            }
+        )*};
+    }
+    inline!(
+        "label" => LabelIdentifier,
+        "size" => compo::Size,
+        "size_no_spaces" => compo::SizeNoSpaces,
+        "height" => compo::Height,
+        "bytes_len" => compo::BytesLen,
+        "hashs" => SyntaxNodeHashs<HashSize>,
+        "mcc" => Mcc,
+        "bloom_size" => BloomSize,
+        "bloom16" => Bloom<&'static [u8], u16>,
+        "bloom32" => Bloom<&'static [u8], u32>,
+        "bloom64" => Bloom<&'static [u8], u64>,
+        "bloom128" => Bloom<&'static [u8], [u64; 2]>,
+        "bloom256" => Bloom<&'static [u8], [u64; 4]>,
+        "bloom512" => Bloom<&'static [u8], [u64; 8]>,
+        "bloom1024" => Bloom<&'static [u8], [u64; 16]>,
+        "bloom2048" => Bloom<&'static [u8], [u64; 32]>,
+        "bloom4096" => Bloom<&'static [u8], [u64; 64]>,
+    );
+    boxed!(
+        "cs" => CS<NodeIdentifier>,
+        "no_spaces_cs" => NoSpacesCS<NodeIdentifier>,
+        "cs_names" => CS<LabelIdentifier>,
+    );
+    let others = n.archetype().layout().component_types().len() - names.len();
+    let mut archetype = names.join("+");
+    if others > 0 {
+        // NOTE the size of foreign components (eg. types) is unknown, at least count them
+        per_component
+            .entry("other".to_string())
+            .or_default()
+            .entities += others;
+        archetype += &format!("+{} other", others);
+    }
+    (total, archetype)
+}
+
+impl NodeStore {
+    /// Computes statistics about stored nodes,
+    /// `ty` gives the type of a node, eg. with the type store of the nodes.
+    pub fn stats<K: Hash + Eq + Display>(
+        &self,
+        ty: impl Fn(&HashedNodeRef<NodeIdentifier>) -> K,
+    ) -> NodeStoreStats {
+        let mut per_type: std::collections::HashMap<K, EntityStats> = Default::default();
+        let mut r = NodeStoreStats {
+            dedup_bytes: self.dedup.capacity() * (size_of::<NodeIdentifier>() + 1),
+            dedup_lookups: self.lookups,
+            dedup_hits: self.hits,
+            ..Default::default()
+        };
+        for id in self.dedup.keys() {
+            let n = self.resolve(*id);
+            let (bytes, archetype) = account(&n, &mut r.per_component);
+            r.total.add(bytes);
+            per_type.entry(ty(&n)).or_default().add(bytes);
+            r.per_archetype.entry(archetype).or_default().add(bytes);
+        }
+        r.per_type = per_type
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        r
+    }
+}
+
+impl LabelStore {
+    pub fn stats(&self) -> LabelStoreStats {
+        LabelStoreStats {
+            labels: self.len(),
+            bytes: self.bytes(),
+        }
+    }
+}
+
+impl<TS> SimpleStores<TS, NodeStore, LabelStore> {
+    /// see [`NodeStore::stats`]
+    pub fn stats<K: Hash + Eq + Display>(
+        &self,
+        ty: impl Fn(&TS, &HashedNodeRef<NodeIdentifier>) -> K,
+    ) -> StoreStats {
+        StoreStats {
+            nodes: self.node_store.stats(|n| ty(&self.type_store, n)),
+            labels: self.label_store.stats(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder},
+        types::{LabelStore as _, Tree},
+    };
+
+    fn insert(stores: &mut SimpleStores<()>, l: &str, cs: Vec<NodeIdentifier>) -> NodeIdentifier {
+        let label = stores.label_store.get_or_insert(l);
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 1 + cs.len() as u32);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = stores.node_store.prepare_insertion(hashable, |x| {
+            x.get_component::<LabelIdentifier>()
+                .map_or(false, |x| x == &label)
+        });
+        if let Some(id) = insertion.occupied_id() {
+            return id;
+        }
+        if cs.is_empty() {
+            NodeStore::insert_after_prepare(insertion.vacant(), (label, hbuilder.build()))
+        } else {
+            NodeStore::insert_after_prepare(
+                insertion.vacant(),
+                (label, hbuilder.build(), CS(cs.into_boxed_slice())),
+            )
+        }
+    }
+
+    #[test]
+    fn account_components() {
+        let mut stores = SimpleStores::<()>::default();
+        let a = insert(&mut stores, "a", vec![]);
+        insert(&mut stores, "a", vec![]);
+        let b = insert(&mut stores, "b", vec![]);
+        insert(&mut stores, "p", vec![a, b]);
+
+        let stats = stores.stats(|_, n| n.has_children());
+        let nodes = &stats.nodes;
+        assert_eq!(nodes.total.entities, 3);
+        assert_eq!((nodes.dedup_lookups, nodes.dedup_hits), (4, 1));
+        assert_eq!(nodes.dedup_hit_ratio(), 0.25);
+        assert_eq!(nodes.per_type["false"].entities, 2);
+        assert_eq!(nodes.per_type["true"].entities, 1);
+        assert_eq!(nodes.per_archetype["label+hashs"].entities, 2);
+        assert_eq!(nodes.per_archetype["label+hashs+cs"].entities, 1);
+        assert_eq!(nodes.per_component["label"].entities, 3);
+        let cs = nodes.per_component["cs"];
+        assert_eq!(cs.entities, 1);
+        assert_eq!(
+            cs.bytes,
+            size_of::<CS<NodeIdentifier>>() + 2 * size_of::<NodeIdentifier>()
+        );
+        assert_eq!(stats.labels.labels, stores.label_store.len());
+    }
+
+    #[test]
+    fn account_more_components() {
+        let mut stores = SimpleStores::<()>::default();
+        let a = insert(&mut stores, "a", vec![]);
+        let label = stores.label_store.get_or_insert("m");
+        let hbuilder: hashed::Builder<SyntaxNodeHashs<HashSize>> =
+            hashed::Builder::new(Default::default(), &0u16, &label, 2);
+        let hashable = &hbuilder.most_discriminating();
+        let insertion = stores.node_store.prepare_insertion(hashable, |_| false);
+        NodeStore::insert_after_prepare(
+            insertion.vacant(),
+            (
+                label,
+                hbuilder.build(),
+                More(compo::Height(2)),
+                More(CS(vec![a].into_boxed_slice())),
+            ),
+        );
+
+        let stats = stores.stats(|_, _| 0);
+        let nodes = &stats.nodes;
+        assert_eq!(
+            nodes.per_archetype["label+more_height+hashs+more_cs"].entities,
+            1
+        );
+        assert_eq!(
+            nodes.per_component["more_height"].bytes,
+            size_of::<More<compo::Height>>()
+        );
+        assert_eq!(
+            nodes.per_component["more_cs"].bytes,
+            size_of::<More<CS<NodeIdentifier>>>() + size_of::<NodeIdentifier>()
+        );
+        assert!(!nodes.per_component.contains_key("other"));
+    }
+}
@@ -442,11 +442,12 @@ impl HyperType for u8 {
     }
 
     fn is_file(&self) -> bool {
-        todo!()
+        // trees typed with u8 (eg. in tests) are not made of files nor directories
+        false
     }
 
     fn is_directory(&self) -> bool {
-        todo!()
+        false
     }
 
     fn is_spaces(&self) -> bool {
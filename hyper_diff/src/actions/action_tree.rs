@@ -1,85 +1,183 @@
-///! WIP this action collection aims to better express all possible order (of application) of actions from edit scripts,
-///! as action_vec only allow one order to apply actions.
-///! Maybe it can be integrated in the existing script generator or it needs major changes.
-///! Maybe an other algorithm similar to the Chawathe that better fits my needs exists in the literature.
-use hyper_ast::PrimInt;
+//! Expresses the possible orders of application of actions from an edit script,
+//! as action_vec only allows one order to apply actions.
+//!
+//! Actions are nodes of a DAG, an action depends on a previous one
+//! if they touch the same subtree, or if the previous one shifts the position of a node on its path.
+//! Actions that are not ordered by the DAG commute,
+//! thus the DAG can be used to reorder, group or only partially apply an edit script.
+use std::ops::Index;
+
+use hyper_ast::{position::position_accessors::SharedPath, PrimInt};
 
 use crate::tree::tree_path::CompressedTreePath;
-use std::fmt::Debug;
 
-use super::{script_generator2::SimpleAction, Actions};
+use super::{
+    action_vec::ActionsVec,
+    script_generator2::{Act, SimpleAction},
+    Actions,
+};
 
 pub struct ActionsTree<A> {
-    atomics: Vec<Node<A>>,
-    composed: Vec<A>,
-} // TODO use NS ? or a decompressed tree ?
-
-pub struct Node<A> {
-    action: A,
-    children: Vec<Node<A>>,
+    atomics: Vec<A>,
+    /// previous actions that must be applied before a given action, in increasing order
+    dependencies: Vec<Vec<usize>>,
+    /// next actions that must be applied after a given action, in increasing order
+    dependents: Vec<Vec<usize>>,
 }
 
-impl<A: Debug> Actions for ActionsTree<A> {
+impl<A> Actions for ActionsTree<A> {
     fn len(&self) -> usize {
         self.atomics.len()
     }
 }
 
+impl<A> Index<usize> for ActionsTree<A> {
+    type Output = A;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.atomics[index]
+    }
+}
+
+impl<A> ActionsTree<A> {
+    pub fn iter(&self) -> impl Iterator<Item = &A> + '_ {
+        self.atomics.iter()
+    }
+
+    /// Direct dependencies of the `i`th action
+    pub fn dependencies(&self, i: usize) -> &[usize] {
+        &self.dependencies[i]
+    }
+
+    /// Actions directly depending on the `i`th action
+    pub fn dependents(&self, i: usize) -> &[usize] {
+        &self.dependents[i]
+    }
+
+    /// Actions without dependencies, they can be applied first and in any order
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.atomics.len()).filter(|i| self.dependencies[*i].is_empty())
+    }
+
+    /// All the actions needed to apply the `targets`, including them, in order of application.
+    ///
+    /// Applying only those actions gives a valid partial application of the edit script.
+    pub fn closure(&self, targets: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        let mut included = vec![false; self.atomics.len()];
+        let mut waiting: Vec<usize> = targets.into_iter().collect();
+        while let Some(i) = waiting.pop() {
+            if included[i] {
+                continue;
+            }
+            included[i] = true;
+            waiting.extend(self.dependencies[i].iter().filter(|j| !included[**j]));
+        }
+        (0..self.atomics.len()).filter(|i| included[*i]).collect()
+    }
+
+    /// true if the `i`th and `j`th actions can be applied in any order
+    pub fn independent(&self, i: usize, j: usize) -> bool {
+        let (i, j) = if i < j { (i, j) } else { (j, i) };
+        i != j && !self.closure([j]).contains(&i)
+    }
+
+    /// Groups actions by the length of their longest chain of dependencies.
+    ///
+    /// Actions of a layer are pairwise independent,
+    /// so a layer can be applied in any order once previous layers are applied.
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        let mut depths = vec![0; self.atomics.len()];
+        let mut layers: Vec<Vec<usize>> = vec![];
+        for i in 0..self.atomics.len() {
+            // dependencies always come before, so their depth is already known
+            let d = self.dependencies[i]
+                .iter()
+                .map(|j| depths[*j] + 1)
+                .max()
+                .unwrap_or(0);
+            depths[i] = d;
+            if layers.len() <= d {
+                layers.push(vec![]);
+            }
+            layers[d].push(i);
+        }
+        layers
+    }
+}
+
 impl<L, Idx: PrimInt, I> ActionsTree<SimpleAction<L, CompressedTreePath<Idx>, I>> {
     pub(crate) fn push(&mut self, action: SimpleAction<L, CompressedTreePath<Idx>, I>) {
-        Self::push_aux(
-            Node {
-                action,
-                children: vec![],
-            },
-            &mut self.atomics,
-        );
+        let i = self.atomics.len();
+        let deps: Vec<usize> = (0..i)
+            .filter(|j| Self::depends(&self.atomics[*j], &action))
+            .collect();
+        for j in &deps {
+            self.dependents[*j].push(i);
+        }
+        self.dependencies.push(deps);
+        self.dependents.push(vec![]);
+        self.atomics.push(action);
     }
-    fn push_aux(
-        node: Node<SimpleAction<L, CompressedTreePath<Idx>, I>>,
-        r: &mut Vec<Node<SimpleAction<L, CompressedTreePath<Idx>, I>>>,
-    ) {
-        let mut i = 0;
-        for x in r.iter_mut() {
-            i += 1;
-            use hyper_ast::position::position_accessors::SharedPath;
-            match x.action.path.mid.shared_ancestors(&node.action.path.mid) {
-                SharedPath::Exact(_) => panic!(),
-                SharedPath::Remain(_) => panic!(),
-                SharedPath::Submatch(_) => return Self::push_aux(node, &mut x.children),
-                SharedPath::Different(_) => break,
+
+    /// Locations touched by an action, in mid paths,
+    /// with true if siblings following the location are shifted
+    fn touched(
+        action: &SimpleAction<L, CompressedTreePath<Idx>, I>,
+    ) -> Vec<(&CompressedTreePath<Idx>, bool)> {
+        match &action.action {
+            Act::Update { .. } => vec![(&action.path.mid, false)],
+            Act::Delete {} | Act::Insert { .. } => vec![(&action.path.mid, true)],
+            Act::Move { from } | Act::MovUpd { from, .. } => {
+                vec![(&from.mid, true), (&action.path.mid, true)]
             }
         }
-        r.insert(i, node)
-        // match &action.action {
-        //     Act::Delete {} => todo!(),
-        //     Act::Update { new } => todo!(),
-        //     Act::Move { from } => todo!(),
-        //     Act::Insert { sub } => todo!(),
-        // }
     }
 
-    fn push_node(&mut self, node: Node<SimpleAction<L, CompressedTreePath<Idx>, I>>) {
-        Self::push_aux(node, &mut self.atomics);
+    fn depends(
+        before: &SimpleAction<L, CompressedTreePath<Idx>, I>,
+        after: &SimpleAction<L, CompressedTreePath<Idx>, I>,
+    ) -> bool {
+        let after = Self::touched(after);
+        Self::touched(before)
+            .into_iter()
+            .any(|a| after.iter().any(|b| Self::conflict(a, *b)))
+    }
+
+    fn conflict(
+        (a, a_shifts): (&CompressedTreePath<Idx>, bool),
+        (b, b_shifts): (&CompressedTreePath<Idx>, bool),
+    ) -> bool {
+        let shared = match a.shared_ancestors(b) {
+            SharedPath::Exact(_) | SharedPath::Remain(_) | SharedPath::Submatch(_) => {
+                // same node or one is in the subtree of the other
+                return true;
+            }
+            SharedPath::Different(shared) => shared.len(),
+        };
+        let a: Vec<Idx> = a.iter().collect();
+        let b: Vec<Idx> = b.iter().collect();
+        // a sibling of a node on the path of the other one shifts it if it comes before it
+        (a_shifts && a.len() == shared + 1 && a[shared] < b[shared])
+            || (b_shifts && b.len() == shared + 1 && b[shared] < a[shared])
     }
 
     pub(crate) fn new() -> Self {
         Self {
             atomics: Default::default(),
-            composed: Default::default(),
+            dependencies: Default::default(),
+            dependents: Default::default(),
         }
     }
 }
 
-// impl<L,Idx,I> ActionsTree<SimpleAction<L,Idx,I>>
-// {
-//     /// WARN should be more efficient than vec variant
-//     /// and even more consise if made well
-//     fn apply_actions<S: for<'b> NodeStoreMut<'b, T, &'b T>>(
-//         &self,
-//         r: T::TreeId,
-//         s: &mut S,
-//     ) -> <T as Stored>::TreeId {
-//         todo!()
-//     }
-// }
+impl<L, Idx: PrimInt, I> From<ActionsVec<SimpleAction<L, CompressedTreePath<Idx>, I>>>
+    for ActionsTree<SimpleAction<L, CompressedTreePath<Idx>, I>>
+{
+    fn from(actions: ActionsVec<SimpleAction<L, CompressedTreePath<Idx>, I>>) -> Self {
+        let mut r = Self::new();
+        for a in actions.0 {
+            r.push(a);
+        }
+        r
+    }
+}
@@ -206,6 +206,9 @@ impl<L: Debug, P: TreePath, I: Debug> ActionsVec<SimpleAction<L, P, I>> {
 // //     fn build(t: T::Type, l: T::Label, cs: Vec<T::TreeId>) -> T;
 // // }
 
+/// Replays `actions` on the trees in `root`, usually only the src tree,
+/// then returns the last root, for a complete edit script it is the dst tree.
+///
 /// WARN for now it is very inneficient because it completly apply actions every times,
 /// most likely it would need a temporary structure.
 /// Also actions are applied in order, thus there is a single way of applying actions.
@@ -215,7 +218,8 @@ pub fn apply_actions<T, S, P>(
     actions: ActionsVec<SimpleAction<T::Label, P, T::TreeId>>,
     root: &mut Vec<T::TreeId>,
     node_store: &mut S,
-) where
+) -> T::TreeId
+where
     P: TreePath<Item = T::ChildIdx> + Debug,
     T: hyper_ast::types::TypedTree,
     T::Type: Debug + Copy + Send + Sync,
@@ -237,6 +241,7 @@ pub fn apply_actions<T, S, P>(
         // );
         apply_action(a, root, node_store);
     }
+    *root.last().unwrap()
 }
 
 pub fn apply_action<T, S, P>(
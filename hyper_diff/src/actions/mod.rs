@@ -1,4 +1,3 @@
-#[allow(unused)] // still very experimental
 pub mod action_tree;
 pub mod action_vec;
 pub mod script_generator;
@@ -0,0 +1,116 @@
+use std::{fmt::Debug, time::Instant};
+
+use crate::{
+    actions::script_generator2::{ScriptGenerator, SimpleAction},
+    decompressed_tree_store::{bfs_wrapper::SimpleBfsMapper, CompletePostOrder},
+    matchers::{
+        heuristic::gt::{
+            greedy_subtree_matcher::GreedySubtreeMatcher,
+            hybrid_bottom_up_matcher::HybridBottomUpMatcher,
+        },
+        mapping_store::{DefaultMultiMappingStore, MappingStore, VecStore},
+        GumtreeParams, Mapper,
+    },
+    tree::tree_path::CompressedTreePath,
+};
+use hyper_ast::types::{self, HyperAST};
+
+type CDS<T> = CompletePostOrder<T, u32>;
+
+use super::MappingDurations;
+
+use super::{DiffResult, PreparedMappingDurations};
+
+/// Same as [`super::gumtree::diff`] but with the hybrid bottom-up matcher of upstream GumTree ("gumtree-hybrid"),
+/// using [`GumtreeParams::hybrid`]
+pub fn diff<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+) -> DiffResult<
+    SimpleAction<
+        HAST::Label,
+        CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+        HAST::IdN,
+    >,
+    Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    PreparedMappingDurations<2>,
+>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::Label: Debug + Clone + Copy,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    diff_with_params(hyperast, src, dst, GumtreeParams::hybrid())
+}
+
+/// Same as [`diff`] but with the given hyperparameters
+pub fn diff_with_params<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+    params: GumtreeParams,
+) -> DiffResult<
+    SimpleAction<
+        HAST::Label,
+        CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+        HAST::IdN,
+    >,
+    Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    PreparedMappingDurations<2>,
+>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::Label: Debug + Clone + Copy,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let now = Instant::now();
+    let mut mapper: Mapper<_, CDS<HAST::T>, CDS<HAST::T>, VecStore<_>> =
+        hyperast.decompress_pair(src, dst).into();
+    mapper.params = params;
+    let subtree_prepare_t = now.elapsed().as_secs_f64();
+    let now = Instant::now();
+    let mapper =
+        GreedySubtreeMatcher::<_, _, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    let subtree_matcher_t = now.elapsed().as_secs_f64();
+    let subtree_mappings_s = mapper.mappings().len();
+    log::debug!(
+        "subtree: {} mappings in {}s",
+        subtree_mappings_s,
+        subtree_matcher_t
+    );
+    let now = Instant::now();
+    let mapper = HybridBottomUpMatcher::<_, _, _, _, _>::match_it(mapper);
+    let bottomup_matcher_t = now.elapsed().as_secs_f64();
+    let bottomup_mappings_s = mapper.mappings().len();
+    log::debug!(
+        "bottom-up: {} mappings in {}s",
+        bottomup_mappings_s,
+        bottomup_matcher_t
+    );
+    let now = Instant::now();
+
+    let node_store = hyperast.node_store();
+
+    let mapper = mapper.map(
+        |x| x,
+        |dst_arena| SimpleBfsMapper::from(node_store, dst_arena),
+    );
+    let prepare_gen_t = now.elapsed().as_secs_f64();
+    let now = Instant::now();
+    let actions = ScriptGenerator::compute_actions(mapper.hyperast, &mapper.mapping).ok();
+    let gen_t = now.elapsed().as_secs_f64();
+    let mapper = mapper.map(|x| x, |dst_arena| dst_arena.back);
+    DiffResult {
+        mapping_durations: PreparedMappingDurations {
+            mappings: MappingDurations([subtree_matcher_t, bottomup_matcher_t]),
+            preparation: [subtree_prepare_t, 0.0],
+        },
+        mapper,
+        actions,
+        prepare_gen_t,
+        gen_t,
+    }
+}
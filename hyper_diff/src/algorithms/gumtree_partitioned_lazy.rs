@@ -0,0 +1,342 @@
+use std::{fmt::Debug, time::Instant};
+
+use specs::prelude::ParallelIterator;
+use specs::rayon::prelude::IntoParallelRefIterator;
+
+use crate::{
+    actions::script_generator2::{ScriptGenerator, SimpleAction},
+    decompressed_tree_store::{
+        bfs_wrapper::SimpleBfsMapper, lazy_post_order::LazyPostOrder, CompletePostOrder,
+        DecompressedTreeStore, LazyDecompressedTreeStore, ShallowDecompressedTreeStore,
+    },
+    matchers::{
+        heuristic::gt::{
+            lazy2_greedy_bottom_up_matcher::GreedyBottomUpMatcher,
+            lazy2_greedy_subtree_matcher::LazyGreedySubtreeMatcher,
+        },
+        mapping_store::{
+            DefaultMultiMappingStore, MappingStore, MonoMappingStore, MultiMappingStore, VecStore,
+        },
+        GumtreeParams, Mapper,
+    },
+    tree::tree_path::CompressedTreePath,
+};
+use hyper_ast::types::{self, HyperAST, HyperType, Labeled, NodeStore};
+
+type DS<T> = LazyPostOrder<T, u32>;
+type CDS<T> = CompletePostOrder<T, u32>;
+
+use crate::algorithms::MappingDurations;
+
+use super::{DiffResult, PreparedMappingDurations};
+
+/// Same as [`super::gumtree_lazy::diff`],
+/// but subtrees with identical paths are matched in parallel, see [`match_it`].
+pub fn diff<'store, HAST: HyperAST<'store> + Sync>(
+    hyperast: &'store HAST,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+) -> DiffResult<
+    SimpleAction<
+        HAST::Label,
+        CompressedTreePath<<HAST::T as types::WithChildren>::ChildIdx>,
+        HAST::IdN,
+    >,
+    Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    PreparedMappingDurations<2>,
+>
+where
+    HAST::IdN: Clone + Debug + Eq + Send + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let now = Instant::now();
+    let mut mapper: Mapper<_, DS<HAST::T>, DS<HAST::T>, VecStore<_>> =
+        hyperast.decompress_pair(src, dst).into();
+    mapper.mapping.mappings.topit(
+        mapper.mapping.src_arena.len(),
+        mapper.mapping.dst_arena.len(),
+    );
+    let partitions = partition(
+        hyperast,
+        &mut mapper.mapping.src_arena,
+        &mut mapper.mapping.dst_arena,
+    );
+    let partitioned_prepare_t = now.elapsed().as_secs_f64();
+    let now = Instant::now();
+    match_partitions(&mut mapper, &partitions, true);
+    match_moved_subtrees(&mut mapper);
+    let partitioned_matcher_t = now.elapsed().as_secs_f64();
+    let partitioned_mappings_s = mapper.mappings().len();
+    log::debug!(
+        "partitioned: {} mappings in {}s",
+        partitioned_mappings_s,
+        partitioned_matcher_t
+    );
+    let bottomup_prepare_t = 0.;
+    let now = Instant::now();
+    GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>>::execute(&mut mapper, hyperast.label_store());
+    let bottomup_matcher_t = now.elapsed().as_secs_f64();
+    let bottomup_mappings_s = mapper.mappings().len();
+    log::debug!(
+        "bottom-up: {} mappings in {}s",
+        bottomup_mappings_s,
+        bottomup_matcher_t
+    );
+    let now = Instant::now();
+
+    let node_store = hyperast.node_store();
+    let mapper = mapper.map(
+        |src_arena| CompletePostOrder::from(src_arena.complete(node_store)),
+        |dst_arena| {
+            let complete = CompletePostOrder::from(dst_arena.complete(node_store));
+            SimpleBfsMapper::from(node_store, complete)
+        },
+    );
+
+    let prepare_gen_t = now.elapsed().as_secs_f64();
+    let now = Instant::now();
+    let actions = ScriptGenerator::compute_actions(mapper.hyperast, &mapper.mapping).ok();
+    let gen_t = now.elapsed().as_secs_f64();
+    let mapper = mapper.map(|x| x, |dst_arena| dst_arena.back);
+    DiffResult {
+        mapping_durations: PreparedMappingDurations {
+            mappings: MappingDurations([partitioned_matcher_t, bottomup_matcher_t]),
+            preparation: [partitioned_prepare_t, bottomup_prepare_t],
+        },
+        mapper,
+        actions,
+        prepare_gen_t,
+        gen_t,
+    }
+}
+
+/// Match subtrees with identical paths independently on a thread pool,
+/// then match the subtrees moved across partitions and the remaining nodes
+/// with a [`GreedyBottomUpMatcher`] on whole trees.
+///
+/// Mappings are merged in the order of the partitions,
+/// so the result is the same as the one of [`match_it_sequential`].
+pub fn match_it<'store, HAST: HyperAST<'store> + Sync>(
+    mapper: Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+) -> Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>
+where
+    HAST::IdN: Clone + Debug + Eq + Send + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    match_aux(mapper, true)
+}
+
+/// see [`match_it`]
+pub fn match_it_sequential<'store, HAST: HyperAST<'store> + Sync>(
+    mapper: Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+) -> Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>
+where
+    HAST::IdN: Clone + Debug + Eq + Send + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    match_aux(mapper, false)
+}
+
+fn match_aux<'store, HAST: HyperAST<'store> + Sync>(
+    mut mapper: Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+    parallel: bool,
+) -> Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>
+where
+    HAST::IdN: Clone + Debug + Eq + Send + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let partitions = partition(
+        mapper.hyperast,
+        &mut mapper.mapping.src_arena,
+        &mut mapper.mapping.dst_arena,
+    );
+    match_with_partitions(mapper, &partitions, parallel)
+}
+
+/// Same as [`match_it`] but with the given partitions, eg. computed with [`partition_by`]
+pub fn match_with_partitions<'store, HAST: HyperAST<'store> + Sync>(
+    mut mapper: Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+    partitions: &[(u32, u32)],
+    parallel: bool,
+) -> Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>
+where
+    HAST::IdN: Clone + Debug + Eq + Send + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    mapper.mapping.mappings.topit(
+        mapper.mapping.src_arena.len(),
+        mapper.mapping.dst_arena.len(),
+    );
+    match_partitions(&mut mapper, partitions, parallel);
+    match_moved_subtrees(&mut mapper);
+    let label_store = mapper.hyperast.label_store();
+    GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>>::execute(&mut mapper, label_store);
+    mapper
+}
+
+/// Pairs subtrees with identical paths, going down the directories of both trees.
+///
+/// Entries of a directory are paired by name,
+/// the ones only present on one side are left to the matching of whole trees.
+/// Partitions are sorted in post-order of src subtrees.
+pub fn partition<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src_arena: &mut DS<HAST::T>,
+    dst_arena: &mut DS<HAST::T>,
+) -> Vec<(u32, u32)>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq,
+    HAST::T: 'store + types::WithStats,
+{
+    partition_by(hyperast, src_arena, dst_arena, |id| {
+        hyperast.resolve_type(id).is_directory()
+    })
+}
+
+/// Same as [`partition`] but directories are the nodes selected by `is_directory`
+pub fn partition_by<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    src_arena: &mut DS<HAST::T>,
+    dst_arena: &mut DS<HAST::T>,
+    is_directory: impl Fn(&HAST::IdN) -> bool,
+) -> Vec<(u32, u32)>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq,
+    HAST::T: 'store + types::WithStats,
+{
+    let node_store = hyperast.node_store();
+    let label = |id: &HAST::IdN| node_store.resolve(id).try_get_label().copied();
+    let mut partitions = vec![];
+    let mut waiting = vec![(src_arena.root(), dst_arena.root())];
+    while let Some((src, dst)) = waiting.pop() {
+        let src_id = src_arena.original(&src);
+        let dst_id = dst_arena.original(&dst);
+        if !(is_directory(&src_id) && is_directory(&dst_id)) {
+            partitions.push((src, dst));
+            continue;
+        }
+        let src_children = src_arena.decompress_children(node_store, &src);
+        let dst_children = dst_arena.decompress_children(node_store, &dst);
+        let dst_labels: Vec<_> = dst_children
+            .iter()
+            .map(|x| label(&dst_arena.original(x)))
+            .collect();
+        for src in src_children {
+            let Some(l) = label(&src_arena.original(&src)) else {
+                continue;
+            };
+            if let Some(i) = dst_labels.iter().position(|x| x == &Some(l)) {
+                waiting.push((src, dst_children[i]));
+            }
+        }
+    }
+    partitions.sort_unstable();
+    partitions
+}
+
+/// Match each pair of subtrees independently, in parallel if asked to,
+/// then merge their mappings in `mapper`
+pub fn match_partitions<'store, HAST: HyperAST<'store> + Sync>(
+    mapper: &mut Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+    partitions: &[(u32, u32)],
+    parallel: bool,
+) where
+    HAST::IdN: Clone + Debug + Eq + Send + Sync,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let hyperast = mapper.hyperast;
+    let params = mapper.params;
+    let originals: Vec<_> = partitions
+        .iter()
+        .map(|(src, dst)| {
+            (
+                mapper.mapping.src_arena.original(src),
+                mapper.mapping.dst_arena.original(dst),
+            )
+        })
+        .collect();
+    let f = |(src, dst): &(HAST::IdN, HAST::IdN)| match_partition(hyperast, params, src, dst);
+    let results: Vec<VecStore<u32>> = if parallel {
+        originals.par_iter().map(f).collect()
+    } else {
+        originals.iter().map(f).collect()
+    };
+    let node_store = hyperast.node_store();
+    for ((src, dst), local) in partitions.iter().zip(results) {
+        // the bottom-up matcher needs parents of mapped nodes
+        mapper
+            .mapping
+            .src_arena
+            .decompress_descendants(node_store, src);
+        mapper
+            .mapping
+            .dst_arena
+            .decompress_descendants(node_store, dst);
+        let src_offset = mapper.mapping.src_arena.first_descendant(src);
+        let dst_offset = mapper.mapping.dst_arena.first_descendant(dst);
+        for (s, d) in local.iter() {
+            mapper.mapping.mappings.link(src_offset + s, dst_offset + d);
+        }
+    }
+}
+
+/// Match the subtrees moved across partitions, eg. a method moved to another file.
+///
+/// Isomorphic subtrees are searched on whole trees,
+/// but only the ones left unmapped by the partitions can be mapped.
+fn match_moved_subtrees<'store, HAST: HyperAST<'store>>(
+    mapper: &mut Mapper<'store, HAST, DS<HAST::T>, DS<HAST::T>, VecStore<u32>>,
+) where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let mm: DefaultMultiMappingStore<u32> =
+        LazyGreedySubtreeMatcher::<_, _, _, _>::compute_multi_mapping(mapper);
+    let mappings = &mapper.mapping.mappings;
+    let src_arena = &mapper.mapping.src_arena;
+    let dst_arena = &mapper.mapping.dst_arena;
+    let mut unmapped = DefaultMultiMappingStore::default();
+    unmapped.topit(src_arena.len(), dst_arena.len());
+    for src in mm.all_mapped_srcs() {
+        // subtrees are mapped recursively, so none of their nodes can be already mapped
+        if (src_arena.first_descendant(&src)..=src).any(|x| mappings.is_src(&x)) {
+            continue;
+        }
+        for dst in mm.get_dsts(&src) {
+            if !(dst_arena.first_descendant(dst)..=*dst).any(|x| mappings.is_dst(&x)) {
+                unmapped.link(src, *dst);
+            }
+        }
+    }
+    LazyGreedySubtreeMatcher::<_, _, _, _>::filter_mappings(mapper, &unmapped);
+}
+
+fn match_partition<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    params: GumtreeParams,
+    src: &HAST::IdN,
+    dst: &HAST::IdN,
+) -> VecStore<u32>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq + Debug,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let mut mapper: Mapper<_, DS<HAST::T>, DS<HAST::T>, VecStore<_>> =
+        hyperast.decompress_pair(src, dst).into();
+    mapper.params = params;
+    let mapper =
+        LazyGreedySubtreeMatcher::<_, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    let mapper = GreedyBottomUpMatcher::<_, _, _, _, VecStore<_>>::match_it(mapper);
+    mapper.mapping.mappings
+}
@@ -3,9 +3,13 @@ use crate::{
     matchers::{mapping_store::VecStore, Mapper},
 };
 
+pub mod changedistiller;
 pub mod gumtree;
+pub mod gumtree_hybrid;
 pub mod gumtree_lazy;
 pub mod gumtree_partial_lazy;
+pub mod gumtree_partitioned_lazy;
+pub mod gumtree_simple;
 
 #[derive(Debug, Clone)]
 pub struct MappingDurations<const N: usize>(pub [f64; N]);
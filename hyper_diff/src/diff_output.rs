@@ -0,0 +1,626 @@
+//! Diffs in the JSON and XML formats of GumTree, and as annotated unified diffs.
+//!
+//! [`F`] also deserializes the outputs of GumTree, to compare them with the diffs of HyperAST.
+use std::{
+    fmt::{Debug, Display},
+    io::{self, Write},
+    ops::Range,
+};
+
+use crate::{
+    actions::{
+        action_vec::ActionsVec,
+        script_generator2::{Act as Act2, SimpleAction},
+    },
+    decompressed_tree_store::{
+        CompletePostOrder, DecompressedWithParent, ShallowDecompressedTreeStore,
+    },
+    matchers::{
+        mapping_store::{MonoMappingStore, VecStore},
+        Mapper,
+    },
+    tree::tree_path::CompressedTreePath,
+};
+use hyper_ast::{
+    position::{compute_position, Position},
+    types::{self, HyperAST, LabelStore, Labeled, NodeStore, TypeStore, WithSerialization},
+};
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct F<T> {
+    pub times: Vec<usize>,
+    pub matches: Vec<Match<T>>,
+    pub actions: Option<Vec<Act<T>>>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
+pub struct Match<T> {
+    pub src: T,
+    pub dest: T,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct Act<T> {
+    pub action: Kind,
+    pub tree: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
+pub struct Tree {
+    pub r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub file: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Clone)]
+pub struct Path(pub Vec<u32>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub enum Kind {
+    #[serde(rename = "update-node")]
+    Upd,
+    #[serde(rename = "move-tree")]
+    Move,
+    #[serde(rename = "insert-node")]
+    Ins,
+    #[serde(rename = "delete-node")]
+    Del,
+}
+
+impl<'a, HAST> From<(&'a HAST, HAST::IdN, &CompressedTreePath<HAST::Idx>)> for Tree
+where
+    HAST: types::HyperAST<'a>,
+    HAST::T: types::Tree + WithSerialization,
+{
+    fn from((stores, ori, p): (&'a HAST, HAST::IdN, &CompressedTreePath<HAST::Idx>)) -> Self {
+        (stores, compute_position(ori, &mut p.iter(), stores)).into()
+    }
+}
+
+impl<'a, HAST> From<(&'a HAST, (Position, HAST::IdN))> for Tree
+where
+    HAST: types::HyperAST<'a>,
+    HAST::T: types::Tree,
+{
+    fn from((stores, (pos, x)): (&'a HAST, (Position, HAST::IdN))) -> Self {
+        let Range { start, end } = pos.range();
+        let file = pos.file().to_string_lossy().to_string();
+        let r = stores.node_store().resolve(&x);
+        let t = stores.type_store().resolve_type(&r);
+        Tree {
+            r#type: t.to_string(),
+            label: r
+                .try_get_label()
+                .map(|x| stores.label_store().resolve(&x).to_string())
+                .filter(|x| !x.is_empty()),
+            file,
+            start,
+            end,
+        }
+    }
+}
+
+impl Kind {
+    /// name of the action in GumTree outputs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Upd => "update-node",
+            Kind::Move => "move-tree",
+            Kind::Ins => "insert-node",
+            Kind::Del => "delete-node",
+        }
+    }
+}
+
+/// same as GumTree's textual representation of a tree
+impl Display for Tree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(label) = &self.label {
+            write!(
+                f,
+                "{}: {} [{},{}]",
+                self.r#type, label, self.start, self.end
+            )
+        } else {
+            write!(f, "{} [{},{}]", self.r#type, self.start, self.end)
+        }
+    }
+}
+
+type CDS<T> = CompletePostOrder<T, u32>;
+
+/// Formats the mappings and the edit script of a diff like GumTree does,
+/// to be written with [`write_json`] or [`write_xml`].
+///
+/// Nodes of src are used for matches and for the trees of deletions, updates and moves,
+/// nodes of dst are used for the trees of insertions and for parents without a mapping.
+pub fn format_diff<'store, HAST>(
+    mapper: &Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    actions: &ActionsVec<SimpleAction<HAST::Label, CompressedTreePath<HAST::Idx>, HAST::IdN>>,
+) -> F<Tree>
+where
+    HAST: HyperAST<'store>,
+    HAST::IdN: Clone + Debug,
+    HAST::Label: Copy + Eq,
+    HAST::T: WithSerialization,
+{
+    let f = Formatter { mapper };
+    let matches = (mapper.mapping.mappings.iter())
+        .map(|(src, dst)| Match {
+            src: f.tree(&mapper.mapping.src_arena, src),
+            dest: f.tree(&mapper.mapping.dst_arena, dst),
+        })
+        .collect();
+    let actions = actions.iter().flat_map(|a| f.actions(a)).collect();
+    F {
+        times: vec![],
+        matches,
+        actions: Some(actions),
+    }
+}
+
+struct Formatter<'a, 'store, HAST: HyperAST<'store>> {
+    mapper: &'a Mapper<'store, HAST, CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+}
+
+impl<'a, 'store, HAST> Formatter<'a, 'store, HAST>
+where
+    HAST: HyperAST<'store>,
+    HAST::IdN: Clone + Debug,
+    HAST::Label: Copy + Eq,
+    HAST::T: WithSerialization,
+{
+    fn tree(&self, arena: &CDS<HAST::T>, x: u32) -> Tree {
+        let stores = self.mapper.hyperast;
+        let root = arena.original(&arena.root());
+        let path = arena.path_rooted(&x);
+        (
+            stores,
+            compute_position(root, &mut path.into_iter(), stores),
+        )
+            .into()
+    }
+
+    fn resolve(&self, arena: &CDS<HAST::T>, path: &[HAST::Idx]) -> Option<u32> {
+        let node_store = self.mapper.hyperast.node_store();
+        let mut x = arena.root();
+        for i in path {
+            x = *arena.children(node_store, &x).get(i.to_usize()?)?;
+        }
+        Some(x)
+    }
+
+    fn label(&self, arena: &CDS<HAST::T>, x: u32) -> Option<HAST::Label> {
+        let node_store = self.mapper.hyperast.node_store();
+        node_store
+            .resolve(&arena.original(&x))
+            .try_get_label()
+            .copied()
+    }
+
+    /// parent and position of the node at `path` in dst
+    fn parent(&self, path: &[HAST::Idx]) -> (Option<Tree>, Option<usize>) {
+        let Some((at, path)) = path.split_last() else {
+            return (None, None);
+        };
+        let mapping = &self.mapper.mapping;
+        let parent = self.resolve(&mapping.dst_arena, path).map(|p| {
+            if let Some(p) = mapping.mappings.get_src(&p) {
+                self.tree(&mapping.src_arena, p)
+            } else {
+                self.tree(&mapping.dst_arena, p)
+            }
+        });
+        (parent, at.to_usize())
+    }
+
+    /// The node of src updated by an action,
+    /// its path is either a path in src or a path in dst depending on the preceding actions
+    fn updated(&self, path: &[HAST::Idx], new: &HAST::Label) -> Option<u32> {
+        let mapping = &self.mapper.mapping;
+        let in_src = self.resolve(&mapping.src_arena, path).filter(|src| {
+            self.label(&mapping.src_arena, *src).as_ref() != Some(new)
+                && mapping.mappings.get_dst(src).map_or(false, |dst| {
+                    self.label(&mapping.dst_arena, dst).as_ref() == Some(new)
+                })
+        });
+        in_src.or_else(|| {
+            let dst = self.resolve(&mapping.dst_arena, path)?;
+            mapping.mappings.get_src(&dst)
+        })
+    }
+
+    fn update(&self, path: &[HAST::Idx], new: &HAST::Label) -> Option<Act<Tree>> {
+        let src = self.updated(path, new)?;
+        let label = self.mapper.hyperast.label_store().resolve(new).to_string();
+        Some(Act {
+            action: Kind::Upd,
+            tree: self.tree(&self.mapper.mapping.src_arena, src),
+            parent: None,
+            at: None,
+            label: Some(label),
+        })
+    }
+
+    fn actions(
+        &self,
+        a: &SimpleAction<HAST::Label, CompressedTreePath<HAST::Idx>, HAST::IdN>,
+    ) -> Vec<Act<Tree>> {
+        let mapping = &self.mapper.mapping;
+        let path: Vec<_> = a.path.ori.iter().collect();
+        let moved = |from: &CompressedTreePath<HAST::Idx>| {
+            let from: Vec<_> = from.iter().collect();
+            let src = self.resolve(&mapping.src_arena, &from)?;
+            let (parent, at) = self.parent(&path);
+            Some(Act {
+                action: Kind::Move,
+                tree: self.tree(&mapping.src_arena, src),
+                parent,
+                at,
+                label: None,
+            })
+        };
+        match &a.action {
+            Act2::Delete {} => self
+                .resolve(&mapping.src_arena, &path)
+                .map(|src| Act {
+                    action: Kind::Del,
+                    tree: self.tree(&mapping.src_arena, src),
+                    parent: None,
+                    at: None,
+                    label: None,
+                })
+                .into_iter()
+                .collect(),
+            Act2::Update { new } => self.update(&path, new).into_iter().collect(),
+            Act2::Move { from } => moved(&from.ori).into_iter().collect(),
+            Act2::MovUpd { from, new } => (moved(&from.ori).into_iter())
+                .chain(self.update(&path, new))
+                .collect(),
+            Act2::Insert { .. } => self
+                .resolve(&mapping.dst_arena, &path)
+                .map(|dst| {
+                    let (parent, at) = self.parent(&path);
+                    Act {
+                        action: Kind::Ins,
+                        tree: self.tree(&mapping.dst_arena, dst),
+                        parent,
+                        at,
+                        label: None,
+                    }
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Writes a formatted diff in the JSON format of GumTree
+pub fn write_json<W: io::Write>(out: W, diff: &F<Tree>) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(out, diff)
+}
+
+/// Writes a formatted diff in the XML format of GumTree
+pub fn write_xml<W: io::Write>(mut out: W, diff: &F<Tree>) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, "<diff>")?;
+    writeln!(out, "  <matches>")?;
+    for m in &diff.matches {
+        writeln!(
+            out,
+            r#"    <match src="{}" dest="{}"/>"#,
+            escape_xml(&m.src.to_string()),
+            escape_xml(&m.dest.to_string())
+        )?;
+    }
+    writeln!(out, "  </matches>")?;
+    writeln!(out, "  <actions>")?;
+    for a in diff.actions.iter().flatten() {
+        write!(
+            out,
+            r#"    <action type="{}" tree="{}""#,
+            a.action.name(),
+            escape_xml(&a.tree.to_string())
+        )?;
+        if let Some(parent) = &a.parent {
+            write!(out, r#" parent="{}""#, escape_xml(&parent.to_string()))?;
+        }
+        if let Some(at) = a.at {
+            write!(out, r#" at="{}""#, at)?;
+        }
+        if let Some(label) = &a.label {
+            write!(out, r#" label="{}""#, escape_xml(label))?;
+        }
+        writeln!(out, "/>")?;
+    }
+    writeln!(out, "  </actions>")?;
+    writeln!(out, "</diff>")
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut r = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => r.push_str("&amp;"),
+            '<' => r.push_str("&lt;"),
+            '>' => r.push_str("&gt;"),
+            '"' => r.push_str("&quot;"),
+            '\'' => r.push_str("&apos;"),
+            c => r.push(c),
+        }
+    }
+    r
+}
+
+/// A line level diff of two versions of a file, in the unified format,
+/// where each hunk is annotated with the actions of `diff` located in it.
+///
+/// Deletions, updates and moves are located in `src` while insertions are located in `dst`,
+/// annotations are lines starting with `#` following the header of hunks.
+pub fn unified_diff(file: &str, src: &str, dst: &str, diff: &F<Tree>, context: usize) -> String {
+    let src_lines: Vec<&str> = src.split_inclusive('\n').collect();
+    let dst_lines: Vec<&str> = dst.split_inclusive('\n').collect();
+    let src_offsets = line_offsets(&src_lines);
+    let dst_offsets = line_offsets(&dst_lines);
+    let ops = diff_lines(&src_lines, &dst_lines);
+
+    let mut out = format!("--- a/{file}\n+++ b/{file}\n");
+    let changed: Vec<usize> = (0..ops.len())
+        .filter(|i| !matches!(ops[*i], LineOp::Same(..)))
+        .collect();
+    let mut i = 0;
+    while i < changed.len() {
+        // extend the hunk while changes are close enough to share context
+        let start = changed[i].saturating_sub(context);
+        let mut end = changed[i];
+        while i < changed.len() && changed[i] <= end + 2 * context {
+            end = changed[i];
+            i += 1;
+        }
+        let end = (end + context + 1).min(ops.len());
+        let hunk = &ops[start..end];
+
+        let (s_first, d_first) = first_lines(&ops[start..], src_lines.len(), dst_lines.len());
+        let s_len = hunk.iter().filter(|x| !matches!(x, LineOp::Ins(_))).count();
+        let d_len = hunk.iter().filter(|x| !matches!(x, LineOp::Del(_))).count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            s_first + 1,
+            s_len,
+            d_first + 1,
+            d_len
+        ));
+
+        let s_range = src_offsets[s_first]..src_offsets[s_first + s_len];
+        let d_range = dst_offsets[d_first]..dst_offsets[d_first + d_len];
+        for a in diff.actions.iter().flatten() {
+            let range = if let Kind::Ins = a.action {
+                &d_range
+            } else {
+                &s_range
+            };
+            if a.tree.file == file && a.tree.start < range.end && range.start < a.tree.end {
+                out.push_str(&format!("# {} {}", a.action.name(), a.tree));
+                if let Some(label) = &a.label {
+                    out.push_str(&format!(" to {}", label));
+                }
+                out.push('\n');
+            }
+        }
+
+        for op in hunk {
+            let (c, line) = match op {
+                LineOp::Same(s, _) => (' ', src_lines[*s]),
+                LineOp::Del(s) => ('-', src_lines[*s]),
+                LineOp::Ins(d) => ('+', dst_lines[*d]),
+            };
+            out.push(c);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LineOp {
+    Same(usize, usize),
+    Del(usize),
+    Ins(usize),
+}
+
+/// byte offset of the start of each line, followed by the total length
+fn line_offsets(lines: &[&str]) -> Vec<usize> {
+    let mut offsets = vec![0];
+    for l in lines {
+        offsets.push(offsets.last().unwrap() + l.len());
+    }
+    offsets
+}
+
+/// index of the first src and dst lines of a hunk starting with `ops`
+fn first_lines(ops: &[LineOp], src_len: usize, dst_len: usize) -> (usize, usize) {
+    let mut s = None;
+    let mut d = None;
+    for op in ops {
+        match op {
+            LineOp::Same(a, b) => {
+                s = s.or(Some(*a));
+                d = d.or(Some(*b));
+            }
+            LineOp::Del(a) => s = s.or(Some(*a)),
+            LineOp::Ins(b) => d = d.or(Some(*b)),
+        }
+        if s.is_some() && d.is_some() {
+            break;
+        }
+    }
+    (s.unwrap_or(src_len), d.unwrap_or(dst_len))
+}
+
+/// Diff of lines using the linear space variant of Myers' algorithm,
+/// deletions come before insertions in each block of changes
+fn diff_lines(src: &[&str], dst: &[&str]) -> Vec<LineOp> {
+    let max_d = max_d(src.len(), dst.len());
+    let mut vf = V::new(max_d);
+    let mut vb = V::new(max_d);
+    let mut ops = Vec::with_capacity(src.len().max(dst.len()));
+    conquer(
+        src,
+        0..src.len(),
+        dst,
+        0..dst.len(),
+        &mut vf,
+        &mut vb,
+        &mut ops,
+    );
+    for block in ops.split_mut(|x| matches!(x, LineOp::Same(..))) {
+        block.sort_by_key(|x| matches!(x, LineOp::Ins(_)));
+    }
+    ops
+}
+
+/// bound on the number of steps needed to find a middle snake
+fn max_d(src_len: usize, dst_len: usize) -> usize {
+    (src_len + dst_len + 1) / 2 + 1
+}
+
+/// furthest reaching x on each diagonal k, with k in -max_d..max_d
+struct V {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl V {
+    fn new(max_d: usize) -> Self {
+        Self {
+            offset: max_d as isize,
+            v: vec![0; 2 * max_d],
+        }
+    }
+}
+
+impl std::ops::Index<isize> for V {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl std::ops::IndexMut<isize> for V {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
+
+fn common_prefix_len(src: &[&str], s: Range<usize>, dst: &[&str], d: Range<usize>) -> usize {
+    s.zip(d).take_while(|(s, d)| src[*s] == dst[*d]).count()
+}
+
+fn common_suffix_len(src: &[&str], s: Range<usize>, dst: &[&str], d: Range<usize>) -> usize {
+    s.rev()
+        .zip(d.rev())
+        .take_while(|(s, d)| src[*s] == dst[*d])
+        .count()
+}
+
+/// Diffs `src[s]` and `dst[d]` by splitting them at a middle snake
+fn conquer(
+    src: &[&str],
+    mut s: Range<usize>,
+    dst: &[&str],
+    mut d: Range<usize>,
+    vf: &mut V,
+    vb: &mut V,
+    ops: &mut Vec<LineOp>,
+) {
+    let prefix = common_prefix_len(src, s.clone(), dst, d.clone());
+    ops.extend((0..prefix).map(|i| LineOp::Same(s.start + i, d.start + i)));
+    s.start += prefix;
+    d.start += prefix;
+    let suffix = common_suffix_len(src, s.clone(), dst, d.clone());
+    s.end -= suffix;
+    d.end -= suffix;
+
+    if s.is_empty() {
+        ops.extend(d.clone().map(LineOp::Ins));
+    } else if d.is_empty() {
+        ops.extend(s.clone().map(LineOp::Del));
+    } else if let Some((x, y)) = middle_snake(src, s.clone(), dst, d.clone(), vf, vb) {
+        conquer(src, s.start..x, dst, d.start..y, vf, vb, ops);
+        conquer(src, x..s.end, dst, y..d.end, vf, vb, ops);
+    } else {
+        ops.extend(s.clone().map(LineOp::Del));
+        ops.extend(d.clone().map(LineOp::Ins));
+    }
+    ops.extend((0..suffix).map(|i| LineOp::Same(s.end + i, d.end + i)));
+}
+
+/// Searches for the middle snake of an optimal path by going forward and backward at the same time,
+/// returns the start of the snake as indexes in `src` and `dst`
+fn middle_snake(
+    src: &[&str],
+    s: Range<usize>,
+    dst: &[&str],
+    d: Range<usize>,
+    vf: &mut V,
+    vb: &mut V,
+) -> Option<(usize, usize)> {
+    let n = s.len();
+    let m = d.len();
+    // the length of an optimal path has the parity of delta
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+    vf[1] = 0;
+    vb[1] = 0;
+    for step in 0..max_d(n, m) as isize {
+        for k in (-step..=step).rev().step_by(2) {
+            let mut x = if k == -step || (k != step && vf[k - 1] < vf[k + 1]) {
+                vf[k + 1]
+            } else {
+                vf[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+            if x < n && y < m {
+                x += common_prefix_len(src, s.start + x..s.end, dst, d.start + y..d.end);
+            }
+            vf[k] = x;
+            if odd && (k - delta).abs() < step && vf[k] + vb[-(k - delta)] >= n {
+                return Some((s.start + x0, d.start + y0));
+            }
+        }
+        for k in (-step..=step).rev().step_by(2) {
+            let mut x = if k == -step || (k != step && vb[k - 1] < vb[k + 1]) {
+                vb[k + 1]
+            } else {
+                vb[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            if x < n && y < m {
+                let advance = common_suffix_len(src, s.start..s.end - x, dst, d.start..d.end - y);
+                x += advance;
+                y += advance;
+            }
+            vb[k] = x;
+            if !odd && (k - delta).abs() <= step && vb[k] + vf[-(k - delta)] >= n {
+                return Some((s.end - x, d.end - y));
+            }
+        }
+    }
+    None
+}
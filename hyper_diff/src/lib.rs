@@ -1,9 +1,13 @@
 #![feature(test)]
 pub mod actions;
 pub mod decompressed_tree_store;
+pub mod diff_output;
 #[cfg(feature = "experimental")]
 pub mod mapping;
 pub mod matchers;
+pub mod merge;
+#[cfg(feature = "java")]
+pub mod refactorings;
 pub mod tree;
 pub mod utils;
 // TODO rename to helpers
@@ -0,0 +1,122 @@
+use std::fmt::Debug;
+
+use num_traits::{cast, PrimInt, ToPrimitive};
+
+use crate::decompressed_tree_store::{
+    ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent, PostOrderIterable,
+};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::{similarity_metrics, Mapper};
+use hyper_ast::types::{HyperAST, Tree};
+
+/// Similarity needed to map inner nodes with more than [`MAX_NUMBER_OF_LEAVES`] leaves
+pub const STRUCT_SIM_THRESHOLD_1: f64 = 0.6;
+/// Similarity needed to map inner nodes with at most [`MAX_NUMBER_OF_LEAVES`] leaves
+pub const STRUCT_SIM_THRESHOLD_2: f64 = 0.4;
+pub const MAX_NUMBER_OF_LEAVES: usize = 4;
+
+/// Maps each inner node to the first unmapped inner node of the same type, in post-order,
+/// whose chawathe similarity is above the threshold.
+/// Meant to be used after [`super::leaves_matcher::LeavesMatcher`].
+pub struct BottomUpMatcher<'a, Dsrc, Ddst, HAST, M> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, T, M::Src>
+            + ContiguousDescendants<'a, T, M::Src>
+            + PostOrderIterable<'a, T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, T, M::Dst>
+            + DecompressedWithParent<'a, T, M::Dst>
+            + ContiguousDescendants<'a, T, M::Dst>,
+        T: 'a + Tree,
+        HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
+        M: MonoMappingStore,
+    > BottomUpMatcher<'a, Dsrc, Ddst, HAST, M>
+where
+    M::Src: PrimInt + Debug,
+    M::Dst: PrimInt + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(&mut matcher.internal);
+        matcher.internal
+    }
+
+    pub fn execute(internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>) {
+        let hyperast = internal.hyperast;
+        let mapping = &mut internal.mapping;
+        let src_arena = &mapping.src_arena;
+        // number of leaves before each node, descendants being contiguous
+        let leaves: Vec<usize> = std::iter::once(0)
+            .chain((0..src_arena.len()).scan(0, |n, i| {
+                let x: M::Src = cast(i).unwrap();
+                *n += (src_arena.first_descendant(&x) == x) as usize;
+                Some(*n)
+            }))
+            .collect();
+        for a in src_arena.iter_df_post::<true>() {
+            let range = src_arena.descendants_range(&a);
+            if range.start == range.end || mapping.mappings.is_src(&a) {
+                continue;
+            }
+            let n = leaves[a.to_usize().unwrap()] - leaves[range.start.to_usize().unwrap()];
+            let threshold = if n > MAX_NUMBER_OF_LEAVES {
+                STRUCT_SIM_THRESHOLD_1
+            } else {
+                STRUCT_SIM_THRESHOLD_2
+            };
+            for cand in Self::dst_candidates(hyperast, mapping, &a) {
+                let sim = similarity_metrics::SimilarityMeasure::range(
+                    &range,
+                    &mapping.dst_arena.descendants_range(&cand),
+                    &mapping.mappings,
+                )
+                .chawathe();
+                if sim >= threshold {
+                    mapping.mappings.link(a, cand);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Unmapped ancestors of the same type of the nodes mapped to the descendants of `src`, in post-order.
+    /// Other nodes have a null similarity.
+    fn dst_candidates(
+        hyperast: &'a HAST,
+        mapping: &crate::matchers::Mapping<Dsrc, Ddst, M>,
+        src: &M::Src,
+    ) -> Vec<M::Dst> {
+        let t = hyperast.resolve_type(&mapping.src_arena.original(src));
+        let range = mapping.src_arena.descendants_range(src);
+        let mut visited = bitvec::bitbox![0; mapping.dst_arena.len()];
+        let mut candidates = vec![];
+        for c in range.start.to_usize().unwrap()..range.end.to_usize().unwrap() {
+            let Some(mut seed) = mapping.mappings.get_dst(&cast(c).unwrap()) else {
+                continue;
+            };
+            while let Some(parent) = mapping.dst_arena.parent(&seed) {
+                if visited[parent.to_usize().unwrap()] {
+                    break;
+                }
+                visited.set(parent.to_usize().unwrap(), true);
+                if !mapping.mappings.is_dst(&parent)
+                    && hyperast.resolve_type(&mapping.dst_arena.original(&parent)) == t
+                {
+                    candidates.push(parent);
+                }
+                seed = parent;
+            }
+        }
+        candidates.sort();
+        candidates
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Debug;
+
+use num_traits::PrimInt;
+
+use super::Bigrams;
+use crate::decompressed_tree_store::{DecompressedTreeStore, PostOrderIterable};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::Mapper;
+use hyper_ast::compat::HashMap;
+use hyper_ast::types::{HyperAST, Tree};
+
+/// Leaves of the same type are mapped when the similarity of their bigrams is above this threshold
+pub const LABEL_SIM_THRESHOLD: f64 = 0.5;
+
+/// Maps leaves by decreasing similarity of their labels, see [`Bigrams::similarity`]
+pub struct LeavesMatcher<'a, Dsrc, Ddst, HAST, M> {
+    internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
+}
+
+impl<
+        'a,
+        Dsrc: DecompressedTreeStore<'a, T, M::Src> + PostOrderIterable<'a, T, M::Src>,
+        Ddst: DecompressedTreeStore<'a, T, M::Dst> + PostOrderIterable<'a, T, M::Dst>,
+        T: 'a + Tree,
+        HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
+        M: MonoMappingStore,
+    > LeavesMatcher<'a, Dsrc, Ddst, HAST, M>
+where
+    M::Src: PrimInt + Debug,
+    M::Dst: PrimInt + Debug,
+{
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self { internal: mapping };
+        matcher.internal.mapping.mappings.topit(
+            matcher.internal.mapping.src_arena.len(),
+            matcher.internal.mapping.dst_arena.len(),
+        );
+        Self::execute(&mut matcher.internal);
+        matcher.internal
+    }
+
+    pub fn execute(internal: &mut Mapper<'a, HAST, Dsrc, Ddst, M>) {
+        let hyperast = internal.hyperast;
+        let mapping = &mut internal.mapping;
+        // only leaves of the same type can be mapped
+        let mut dst_leaves: HashMap<_, Vec<(M::Dst, Bigrams)>> = HashMap::default();
+        for d in mapping.dst_arena.iter_df_post::<true>() {
+            if mapping.dst_arena.first_descendant(&d) != d || mapping.mappings.is_dst(&d) {
+                continue;
+            }
+            let o = mapping.dst_arena.original(&d);
+            dst_leaves
+                .entry(hyperast.resolve_type(&o))
+                .or_default()
+                .push((d, Bigrams::of::<T, _>(hyperast, &o)));
+        }
+        let mut candidates: Vec<(M::Src, M::Dst, f64)> = vec![];
+        for s in mapping.src_arena.iter_df_post::<true>() {
+            if mapping.src_arena.first_descendant(&s) != s || mapping.mappings.is_src(&s) {
+                continue;
+            }
+            let o = mapping.src_arena.original(&s);
+            let Some(dsts) = dst_leaves.get(&hyperast.resolve_type(&o)) else {
+                continue;
+            };
+            let bigrams = Bigrams::of::<T, _>(hyperast, &o);
+            for (d, b) in dsts {
+                let sim = bigrams.similarity(b);
+                if sim > LABEL_SIM_THRESHOLD {
+                    candidates.push((s, *d, sim));
+                }
+            }
+        }
+        // stable, ties stay in post-order
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+        for (s, d, _) in candidates {
+            if !mapping.mappings.is_src(&s) && !mapping.mappings.is_dst(&d) {
+                mapping.mappings.link(s, d);
+            }
+        }
+    }
+}
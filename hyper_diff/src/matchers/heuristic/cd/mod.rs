@@ -0,0 +1,75 @@
+//! ChangeDistiller, Fluri et al. 2007
+//!
+//! Leaves are matched first, on the similarity of the bigrams of their labels,
+//! then inner nodes are matched bottom-up, on the proportion of their descendants already mapped.
+//! Thresholds are the ones of the implementation in GumTree.
+use hyper_ast::types::{HyperAST, LabelStore, NodeStore, Tree};
+
+pub mod bottom_up_matcher;
+pub mod leaves_matcher;
+
+/// Sorted bigrams of a label.
+/// Labels shorter than 2 bytes are their own (padded) bigram,
+/// so that they are only similar to the same label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bigrams(Vec<[u8; 2]>);
+
+impl Bigrams {
+    pub fn new(label: &str) -> Self {
+        let b = label.as_bytes();
+        let mut grams: Vec<[u8; 2]> = match b {
+            [] => vec![[0, 0]],
+            [x] => vec![[*x, 0]],
+            _ => b.windows(2).map(|w| [w[0], w[1]]).collect(),
+        };
+        grams.sort_unstable();
+        Self(grams)
+    }
+
+    /// Bigrams of the label of `id`, the empty label if it has none
+    pub(crate) fn of<'a, T, HAST>(hyperast: &'a HAST, id: &T::TreeId) -> Self
+    where
+        T: 'a + Tree,
+        HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let n = hyperast.node_store().resolve(id);
+        match n.try_get_label() {
+            Some(l) => Self::new(hyperast.label_store().resolve(l)),
+            None => Self::new(""),
+        }
+    }
+
+    /// Dice coefficient of the two multisets of bigrams
+    pub fn similarity(&self, other: &Self) -> f64 {
+        let (mut i, mut j, mut common) = (0, 0, 0);
+        while i < self.0.len() && j < other.0.len() {
+            match self.0[i].cmp(&other.0[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    common += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        (2 * common) as f64 / (self.0.len() + other.0.len()) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bigrams() {
+        let sim = |a, b| Bigrams::new(a).similarity(&Bigrams::new(b));
+        assert_eq!(sim("night", "nacht"), 0.25);
+        assert_eq!(sim("foo", "foo"), 1.0);
+        assert_eq!(sim("aaa", "aa"), 2.0 / 3.0);
+        assert_eq!(sim("", ""), 1.0);
+        assert_eq!(sim("a", "a"), 1.0);
+        assert_eq!(sim("a", "b"), 0.0);
+        assert_eq!(sim("a", "ab"), 0.0);
+    }
+}
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fmt::Debug, hash::Hash, marker::PhantomData};
 
-use num_traits::{PrimInt, ToPrimitive, Zero};
+use num_traits::{PrimInt, ToPrimitive};
 
 use crate::{
     decompressed_tree_store::{DecompressedTreeStore, DecompressedWithParent, Shallow},
@@ -81,9 +81,11 @@ where
     pub fn last_chance_match_histogram(&mut self, src: &M::Src, dst: &M::Dst) {
         self.lcs_equal_matching(src, dst);
         self.lcs_structure_matching(src, dst);
-        if !src.is_zero() && !dst.is_zero() {
+        let src_is_root = self.src_arena.parent(src).is_none();
+        let dst_is_root = self.dst_arena.parent(dst).is_none();
+        if src_is_root && dst_is_root {
             self.histogram_matching(src, dst); //self.histogramMaking(src, dst),
-        } else if !(src.is_zero() || dst.is_zero()) {
+        } else if !(src_is_root || dst_is_root) {
             if self.stores.resolve_type(
                 &self
                     .src_arena
@@ -101,21 +103,26 @@ where
     pub(super) fn are_srcs_unmapped(&self, src: &M::Src) -> bool {
         // look at descendants
         // in mappings
-        self.src_arena
-            .descendants(self.stores.node_store(), src)
-            .iter()
-            .all(|x| !self.mappings.is_src(x))
+        !self.mappings.is_src(src)
+            && self
+                .src_arena
+                .descendants(self.stores.node_store(), src)
+                .iter()
+                .all(|x| !self.mappings.is_src(x))
     }
     pub(super) fn are_dsts_unmapped(&self, dst: &M::Dst) -> bool {
         // look at descendants
         // in mappings
-        self.dst_arena
-            .descendants(self.stores.node_store(), dst)
-            .iter()
-            .all(|x| !self.mappings.is_dst(x))
+        !self.mappings.is_dst(dst)
+            && self
+                .dst_arena
+                .descendants(self.stores.node_store(), dst)
+                .iter()
+                .all(|x| !self.mappings.is_dst(x))
     }
 
     pub(crate) fn add_mapping_recursively(&mut self, src: &M::Src, dst: &M::Dst) {
+        self.mappings.link(*src, *dst);
         self.src_arena
             .descendants(self.stores.node_store(), src)
             .iter()
@@ -213,7 +220,6 @@ where
                 }
             }
         }
-        todo!()
     }
 }
 
@@ -288,9 +294,11 @@ where
     pub fn last_chance_match_histogram(&mut self, src: &M::Src, dst: &M::Dst) {
         self.lcs_equal_matching(src, dst);
         self.lcs_structure_matching(src, dst);
-        if !src.is_zero() && !dst.is_zero() {
+        let src_is_root = self.src_arena.parent(src).is_none();
+        let dst_is_root = self.dst_arena.parent(dst).is_none();
+        if src_is_root && dst_is_root {
             self.histogram_matching(src, dst); //self.histogramMaking(src, dst),
-        } else if !(src.is_zero() || dst.is_zero()) {
+        } else if !(src_is_root || dst_is_root) {
             if self.hyperast.resolve_type(
                 &self
                     .src_arena
@@ -308,18 +316,22 @@ where
     pub(super) fn are_srcs_unmapped(&self, src: &M::Src) -> bool {
         // look at descendants
         // in mappings
-        self.src_arena
-            .descendants(self.hyperast.node_store(), src)
-            .iter()
-            .all(|x| !self.mappings.is_src(x))
+        !self.mappings.is_src(src)
+            && self
+                .src_arena
+                .descendants(self.hyperast.node_store(), src)
+                .iter()
+                .all(|x| !self.mappings.is_src(x))
     }
     pub(super) fn are_dsts_unmapped(&self, dst: &M::Dst) -> bool {
         // look at descendants
         // in mappings
-        self.dst_arena
-            .descendants(self.hyperast.node_store(), dst)
-            .iter()
-            .all(|x| !self.mappings.is_dst(x))
+        !self.mappings.is_dst(dst)
+            && self
+                .dst_arena
+                .descendants(self.hyperast.node_store(), dst)
+                .iter()
+                .all(|x| !self.mappings.is_dst(x))
     }
 
     // pub(crate) fn add_mapping_recursively(&mut self, src: &M::Src, dst: &M::Dst) {
@@ -411,6 +423,5 @@ where
                 }
             }
         }
-        todo!()
     }
 }
@@ -1,23 +1,22 @@
 use std::{fmt::Debug, marker::PhantomData};
 
-use num_traits::{cast, one, PrimInt};
+use num_traits::{cast, one, PrimInt, ToPrimitive};
 
 use crate::decompressed_tree_store::{
     ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent, POBorrowSlice, PostOrder,
     PostOrderIterable, PostOrderKeyRoots,
 };
 use crate::matchers::mapping_store::MonoMappingStore;
-use crate::matchers::{optimal::zs::ZsMatcher, similarity_metrics};
+use crate::matchers::optimal::{OptimalMatcher, Zs};
+use crate::matchers::similarity_metrics;
+use crate::matchers::GumtreeParams;
 use hyper_ast::types::{
     DecompressedSubtree, HyperAST, NodeId, NodeStore, Tree, WithHashs,
 };
 
 use super::bottom_up_matcher::BottomUpMatcher;
 use crate::decompressed_tree_store::SimpleZsTree as ZsTree;
-/// TODO wait for #![feature(adt_const_params)] #95174 to be improved
-///
-/// it will allow to make use complex types as const generics
-/// ie. make the different threshold neater
+/// The size and similarity thresholds are read at runtime from [`GumtreeParams`]
 pub struct GreedyBottomUpMatcher<
     'a,
     Dsrc,
@@ -25,11 +24,11 @@ pub struct GreedyBottomUpMatcher<
     T: 'a + Tree + WithHashs,
     HAST,
     M: MonoMappingStore,
-    const SIZE_THRESHOLD: usize = 1000,
-    const SIM_THRESHOLD_NUM: u64 = 1,
-    const SIM_THRESHOLD_DEN: u64 = 2,
+    Opt: OptimalMatcher = Zs,
 > {
     internal: BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M>,
+    params: GumtreeParams,
+    _phantom: PhantomData<*const Opt>,
 }
 
 /// Enable using a slice instead of recreating a ZsTree for each call to ZsMatch, see last_chance_match
@@ -49,21 +48,9 @@ impl<
         T: Tree + WithHashs,
         HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
         M: MonoMappingStore,
-        const SIZE_THRESHOLD: usize,  // = 1000,
-        const SIM_THRESHOLD_NUM: u64, // = 1,
-        const SIM_THRESHOLD_DEN: u64, // = 2,
+        Opt: OptimalMatcher,
     > Into<BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M>>
-    for GreedyBottomUpMatcher<
-        'a,
-        Dsrc,
-        Ddst,
-        T,
-        HAST,
-        M,
-        SIZE_THRESHOLD,
-        SIM_THRESHOLD_NUM,
-        SIM_THRESHOLD_DEN,
-    >
+    for GreedyBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, Opt>
 {
     fn into(self) -> BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M> {
         self.internal
@@ -92,28 +79,21 @@ impl<
         T: 'a + Tree + WithHashs,
         HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
         M: MonoMappingStore + Default,
-        const SIZE_THRESHOLD: usize,
-        const SIM_THRESHOLD_NUM: u64,
-        const SIM_THRESHOLD_DEN: u64,
-    >
-    GreedyBottomUpMatcher<
-        'a,
-        Dsrc,
-        Ddst,
-        T,
-        HAST,
-        M,
-        SIZE_THRESHOLD,
-        SIM_THRESHOLD_NUM,
-        SIM_THRESHOLD_DEN,
-    >
+        Opt: OptimalMatcher,
+    > GreedyBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, Opt>
 where
     T::TreeId: 'a + Clone + Debug + NodeId<IdN = T::TreeId>,
     // T::Type: Debug + Eq + Copy + Send + Sync,
     M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
     M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
 {
-    pub fn new(stores: &'a HAST, src_arena: Dsrc, dst_arena: Ddst, mappings: M) -> Self {
+    pub fn new(
+        stores: &'a HAST,
+        src_arena: Dsrc,
+        dst_arena: Ddst,
+        mappings: M,
+        params: GumtreeParams,
+    ) -> Self {
         Self {
             internal: BottomUpMatcher {
                 stores,
@@ -122,6 +102,8 @@ where
                 mappings,
                 _phantom: PhantomData,
             },
+            params,
+            _phantom: PhantomData,
         }
     }
 
@@ -136,6 +118,8 @@ where
                 mappings: mapping.mapping.mappings,
                 _phantom: PhantomData,
             },
+            params: mapping.params,
+            _phantom: PhantomData,
         };
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len(),
@@ -149,9 +133,11 @@ where
                 dst_arena: matcher.internal.dst_arena,
                 mappings: matcher.internal.mappings,
             },
+            params: mapping.params,
         }
     }
 
+    /// match with the default [`GumtreeParams`], use [`Self::match_it`] to tune them
     pub fn matchh(
         compressed_node_store: &'a HAST,
         src: &'a T::TreeId,
@@ -163,6 +149,7 @@ where
             Dsrc::decompress(compressed_node_store.node_store(), src),
             Ddst::decompress(compressed_node_store.node_store(), dst),
             mappings,
+            GumtreeParams::default(),
         );
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len(),
@@ -189,16 +176,21 @@ where
             }
             if !(self.internal.mappings.is_src(&a) || !self.src_has_children(a)) {
                 let candidates = self.internal.get_dst_candidates(&a);
+                let src_range = self.internal.src_arena.descendants_range(&a);
+                let src_size = (src_range.end - src_range.start).to_usize().unwrap();
                 let mut best = None;
                 let mut max: f64 = -1.;
                 for cand in candidates {
+                    let dst_range = self.internal.dst_arena.descendants_range(&cand);
+                    let dst_size = (dst_range.end - dst_range.start).to_usize().unwrap();
+                    let threshold = self.params.sim_threshold(src_size, dst_size);
                     let sim = similarity_metrics::SimilarityMeasure::range(
-                        &self.internal.src_arena.descendants_range(&a),
-                        &self.internal.dst_arena.descendants_range(&cand),
+                        &src_range,
+                        &dst_range,
                         &self.internal.mappings,
                     )
                     .dice();
-                    if sim > max && sim >= SIM_THRESHOLD_NUM as f64 / SIM_THRESHOLD_DEN as f64 {
+                    if sim > max && sim >= threshold {
                         max = sim;
                         best = Some(cand);
                     }
@@ -224,7 +216,6 @@ where
     }
 
     fn src_has_children(&mut self, src: M::Src) -> bool {
-        use num_traits::ToPrimitive;
         let r = self
             .internal
             .stores
@@ -251,7 +242,8 @@ where
             .internal
             .dst_arena
             .descendants_count(self.internal.stores.node_store(), &dst);
-        if !(src_s < cast(SIZE_THRESHOLD).unwrap() || dst_s < cast(SIZE_THRESHOLD).unwrap()) {
+        let size_threshold = self.params.size_threshold;
+        if !(src_s < cast(size_threshold).unwrap() || dst_s < cast(size_threshold).unwrap()) {
             return;
         }
         let node_store = self.internal.stores.node_store();
@@ -261,7 +253,7 @@ where
             let src_arena = self.internal.src_arena.slice_po(&src);
             src_offset = src - src_arena.root();
             let dst_arena = self.internal.dst_arena.slice_po(&dst);
-            ZsMatcher::match_with(self.internal.stores, src_arena, dst_arena)
+            Opt::match_with(self.internal.stores, src_arena, dst_arena)
         } else {
             let o_src = self.internal.src_arena.original(&src);
             let o_dst = self.internal.dst_arena.original(&dst);
@@ -273,7 +265,6 @@ where
                     assert_eq!(src_arena.tree(&i), src_arena_z.tree(&i));
                     assert_eq!(src_arena.lld(&i), src_arena_z.lld(&i));
                 }
-                use num_traits::ToPrimitive;
                 let mut last = src_arena_z.root();
                 for k in src_arena_z.iter_kr() {
                     assert!(src_arena.kr[k.to_usize().unwrap()]);
@@ -289,7 +280,6 @@ where
                     assert_eq!(dst_arena.tree(&i), dst_arena_z.tree(&i));
                     assert_eq!(dst_arena.lld(&i), dst_arena_z.lld(&i));
                 }
-                use num_traits::ToPrimitive;
                 let mut last = dst_arena_z.root();
                 for k in dst_arena_z.iter_kr() {
                     assert!(dst_arena.kr[k.to_usize().unwrap()]);
@@ -298,7 +288,7 @@ where
                 assert!(dst_arena.kr[dst_arena.kr.len() - 1]);
                 dbg!(last == dst_arena_z.root());
             }
-            ZsMatcher::match_with(self.internal.stores, src_arena, dst_arena)
+            Opt::match_with(self.internal.stores, src_arena, dst_arena)
         };
         let dst_offset = self.internal.dst_arena.first_descendant(&dst);
         assert_eq!(self.internal.src_arena.first_descendant(&src), src_offset);
@@ -6,7 +6,7 @@ use crate::decompressed_tree_store::{
 };
 use crate::matchers::heuristic::gt::height;
 use crate::matchers::mapping_store::MonoMappingStore;
-use crate::matchers::{mapping_store::MultiMappingStore, similarity_metrics};
+use crate::matchers::{mapping_store::MultiMappingStore, similarity_metrics, GumtreeParams};
 use crate::utils::sequence_algorithms::longest_common_subsequence;
 use hyper_ast::compat::HashMap;
 use hyper_ast::types::{
@@ -15,8 +15,10 @@ use hyper_ast::types::{
 };
 use num_traits::{one, zero, PrimInt, ToPrimitive};
 
-pub struct GreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, const MIN_HEIGHT: usize = 1> {
-    internal: SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>,
+/// Subtrees lower than [`GumtreeParams::min_height`] are not matched.
+pub struct GreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M> {
+    internal: SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>,
+    params: GumtreeParams,
 }
 
 impl<
@@ -34,8 +36,7 @@ impl<
         T: Tree + WithHashs,
         HAST,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize, // = 2
-    > GreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    > GreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
 where
     HAST: HyperAST<'a, IdN = T::TreeId, T = T>,
     // HAST::NS: hyper_ast::types::NodeStore<<T as hyper_ast::types::Stored>::TreeId>,
@@ -60,6 +61,7 @@ where
                 mappings: mapping.mapping.mappings,
                 phantom: PhantomData,
             },
+            params: mapping.params,
         };
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len(),
@@ -73,19 +75,21 @@ where
                 dst_arena: matcher.internal.dst_arena,
                 mappings: matcher.internal.mappings,
             },
+            params: mapping.params,
         }
     }
 
+    /// match with the default [`GumtreeParams`], use [`Self::match_it`] to tune them
     pub fn matchh<MM: MultiMappingStore<Src = M::Src, Dst = M::Dst> + Default>(
         stores: &'a HAST,
         src: &'a T::TreeId,
         dst: &'a T::TreeId,
         mappings: M,
-    ) -> GreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    ) -> GreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
     where
         Self: 'a,
     {
-        let mut matcher = GreedySubtreeMatcher::<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT> {
+        let mut matcher = GreedySubtreeMatcher::<'a, Dsrc, Ddst, T, HAST, M> {
             internal: SubtreeMatcher {
                 stores: stores,
                 src_arena: Dsrc::decompress(stores.node_store(), src),
@@ -93,6 +97,7 @@ where
                 mappings,
                 phantom: PhantomData,
             },
+            params: GumtreeParams::default(),
         };
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len(),
@@ -105,7 +110,8 @@ where
     pub(crate) fn execute<MM: MultiMappingStore<Src = M::Src, Dst = M::Dst> + Default>(&mut self) {
         let mut mm: MM = Default::default();
         mm.topit(self.internal.src_arena.len(), self.internal.dst_arena.len());
-        self.internal.matchh_to_be_filtered(&mut mm);
+        self.internal
+            .matchh_to_be_filtered(&mut mm, self.params.min_height);
         self.filter_mappings(&mm);
     }
 
@@ -414,11 +420,10 @@ where
 //             })
 //         })
 // }
-impl<'a, Dsrc, Ddst, T: Tree, S, M: MonoMappingStore, const MIN_HEIGHT: usize>
-    Into<SubtreeMatcher<'a, Dsrc, Ddst, T, S, M, MIN_HEIGHT>>
-    for GreedySubtreeMatcher<'a, Dsrc, Ddst, T, S, M, MIN_HEIGHT>
+impl<'a, Dsrc, Ddst, T: Tree, S, M: MonoMappingStore> Into<SubtreeMatcher<'a, Dsrc, Ddst, T, S, M>>
+    for GreedySubtreeMatcher<'a, Dsrc, Ddst, T, S, M>
 {
-    fn into(self) -> SubtreeMatcher<'a, Dsrc, Ddst, T, S, M, MIN_HEIGHT> {
+    fn into(self) -> SubtreeMatcher<'a, Dsrc, Ddst, T, S, M> {
         self.internal
     }
 }
@@ -451,7 +456,7 @@ impl<'a, Dsrc, Ddst, T: Tree, S, M: MonoMappingStore, const MIN_HEIGHT: usize>
 //     }
 // }
 
-pub struct SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, const MIN_HEIGHT: usize> {
+pub struct SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M> {
     pub(super) stores: &'a HAST,
     pub src_arena: Dsrc,
     pub dst_arena: Ddst,
@@ -466,8 +471,7 @@ impl<
         T: Tree + WithHashs,
         HAST,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize,
-    > SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    > SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
 where
     HAST: HyperAST<'a, IdN = T::TreeId, T = T>,
     M::Src: PrimInt + Debug,
@@ -490,8 +494,8 @@ where
 
     fn pop_larger<'b>(
         &self,
-        src_trees: &mut PriorityTreeList<'a, 'b, Dsrc, M::Src, T, HAST::NS, MIN_HEIGHT>,
-        dst_trees: &mut PriorityTreeList<'a, 'b, Ddst, M::Dst, T, HAST::NS, MIN_HEIGHT>,
+        src_trees: &mut PriorityTreeList<'a, 'b, Dsrc, M::Src, T, HAST::NS>,
+        dst_trees: &mut PriorityTreeList<'a, 'b, Ddst, M::Dst, T, HAST::NS>,
     ) {
         if src_trees.peek_height() > dst_trees.peek_height() {
             src_trees.open();
@@ -503,16 +507,19 @@ where
     fn matchh_to_be_filtered<MM: MultiMappingStore<Src = M::Src, Dst = M::Dst>>(
         &self,
         multi_mappings: &mut MM,
+        min_height: usize,
     ) {
         let mut src_trees = PriorityTreeList::new(
             self.stores.node_store(),
             &self.src_arena,
             self.src_arena.root(),
+            min_height,
         );
         let mut dst_trees = PriorityTreeList::new(
             self.stores.node_store(),
             &self.dst_arena,
             self.dst_arena.root(),
+            min_height,
         );
         // let mut aaa = 0;
         while src_trees.peek_height() != -1 && dst_trees.peek_height() != -1 {
@@ -701,13 +708,14 @@ where
     }
 }
 
-struct PriorityTreeList<'a, 'b, D, IdD, T: Tree, S, const MIN_HEIGHT: usize> {
+struct PriorityTreeList<'a, 'b, D, IdD, T: Tree, S> {
     trees: Vec<Option<Vec<IdD>>>,
 
     store: &'a S,
     arena: &'b D,
 
     max_height: usize,
+    min_height: usize,
 
     current_idx: isize,
 
@@ -721,15 +729,14 @@ impl<
         IdD: PrimInt,
         T: Tree,
         S: 'a + NodeStore<T::TreeId, R<'a> = T>,
-        const MIN_HEIGHT: usize,
-    > PriorityTreeList<'a, 'b, D, IdD, T, S, MIN_HEIGHT>
+    > PriorityTreeList<'a, 'b, D, IdD, T, S>
 where
     T::TreeId: Clone + NodeId<IdN = T::TreeId>,
 {
-    pub(super) fn new(store: &'a S, arena: &'b D, tree: IdD) -> Self {
+    pub(super) fn new(store: &'a S, arena: &'b D, tree: IdD, min_height: usize) -> Self {
         let h = height(store, &arena.original(&tree)); // TODO subtree opti, use metadata
-        let list_size = if h >= MIN_HEIGHT {
-            h + 1 - MIN_HEIGHT
+        let list_size = if h >= min_height {
+            h + 1 - min_height
         } else {
             0
         };
@@ -738,6 +745,7 @@ where
             store,
             arena,
             max_height: h,
+            min_height,
             current_idx: if list_size == 0 { -1 } else { 0 },
             phantom: PhantomData,
         };
@@ -759,7 +767,7 @@ where
     }
 
     fn add_tree_aux(&mut self, tree: IdD, h: usize) {
-        if h >= MIN_HEIGHT {
+        if h >= self.min_height {
             let idx = self.idx(h);
             if self.trees[idx].is_none() {
                 self.trees[idx] = Some(vec![]);
@@ -0,0 +1,197 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num_traits::{cast, one, PrimInt, ToPrimitive};
+
+use crate::decompressed_tree_store::{
+    ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent, POBorrowSlice, PostOrder,
+    PostOrderIterable, ShallowDecompressedTreeStore,
+};
+use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::optimal::{OptimalMatcher, Zs};
+use crate::matchers::similarity_metrics;
+use crate::matchers::GumtreeParams;
+use hyper_ast::types::{HyperAST, NodeId, Tree, WithHashs};
+
+use super::bottom_up_matcher::BottomUpMatcher;
+
+/// Hybrid bottom-up matcher of upstream GumTree ("gumtree-hybrid"),
+/// like [`super::simple_bottom_up_matcher::SimpleBottomUpMatcher`]
+/// but last chance matching uses an optimal matcher on subtrees smaller than [`GumtreeParams::size_threshold`].
+pub struct HybridBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, Opt: OptimalMatcher = Zs> {
+    internal: BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M>,
+    params: GumtreeParams,
+    _phantom: PhantomData<*const Opt>,
+}
+
+impl<
+        'a,
+        Dsrc: 'a
+            + DecompressedTreeStore<'a, T, M::Src>
+            + DecompressedWithParent<'a, T, M::Src>
+            + PostOrder<'a, T, M::Src>
+            + PostOrderIterable<'a, T, M::Src>
+            + ContiguousDescendants<'a, T, M::Src>
+            + POBorrowSlice<'a, T, M::Src>,
+        Ddst: 'a
+            + DecompressedTreeStore<'a, T, M::Dst>
+            + DecompressedWithParent<'a, T, M::Dst>
+            + PostOrder<'a, T, M::Dst>
+            + PostOrderIterable<'a, T, M::Dst>
+            + ContiguousDescendants<'a, T, M::Dst>
+            + POBorrowSlice<'a, T, M::Dst>,
+        T: 'a + Tree + WithHashs,
+        HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
+        M: MonoMappingStore + Default,
+        Opt: OptimalMatcher,
+    > HybridBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, Opt>
+where
+    T::TreeId: 'a + Clone + Debug + NodeId<IdN = T::TreeId>,
+    M::Src: 'a + PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: 'a + PrimInt + std::ops::SubAssign + Debug,
+{
+    pub fn new(
+        stores: &'a HAST,
+        src_arena: Dsrc,
+        dst_arena: Ddst,
+        mappings: M,
+        params: GumtreeParams,
+    ) -> Self {
+        Self {
+            internal: BottomUpMatcher {
+                stores,
+                src_arena,
+                dst_arena,
+                mappings,
+                _phantom: PhantomData,
+            },
+            params,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn match_it(
+        mapping: crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M>,
+    ) -> crate::matchers::Mapper<'a, HAST, Dsrc, Ddst, M> {
+        let mut matcher = Self::new(
+            mapping.hyperast,
+            mapping.mapping.src_arena,
+            mapping.mapping.dst_arena,
+            mapping.mapping.mappings,
+            mapping.params,
+        );
+        matcher.internal.mappings.topit(
+            matcher.internal.src_arena.len(),
+            matcher.internal.dst_arena.len(),
+        );
+        Self::execute(&mut matcher);
+        crate::matchers::Mapper {
+            hyperast: mapping.hyperast,
+            mapping: crate::matchers::Mapping {
+                src_arena: matcher.internal.src_arena,
+                dst_arena: matcher.internal.dst_arena,
+                mappings: matcher.internal.mappings,
+            },
+            params: mapping.params,
+        }
+    }
+
+    pub fn execute(&mut self) {
+        assert_eq!(
+            self.internal.src_arena.root(),
+            cast::<_, M::Src>(self.internal.src_arena.len()).unwrap() - one()
+        );
+        for a in self.internal.src_arena.iter_df_post::<true>() {
+            if self.internal.src_arena.parent(&a).is_none() {
+                // root is handled after the loop
+                break;
+            }
+            if !(self.internal.mappings.is_src(&a) || self.internal.src_arena.lld(&a) == a) {
+                if let Some(best) = self.best_dst_candidate(a) {
+                    self.last_chance_match(a, best);
+                    self.internal.mappings.link(a, best);
+                }
+            } else if let Some(dst) = self.internal.mappings.get_dst(&a) {
+                if self.has_unmapped_children(a, dst) {
+                    self.last_chance_match(a, dst);
+                }
+            }
+        }
+        // for root
+        let src = self.internal.src_arena.root();
+        let dst = self.internal.dst_arena.root();
+        self.internal.mappings.link(src, dst);
+        self.last_chance_match(src, dst);
+    }
+
+    fn best_dst_candidate(&self, src: M::Src) -> Option<M::Dst> {
+        let candidates = self.internal.get_dst_candidates(&src);
+        let src_range = self.internal.src_arena.descendants_range(&src);
+        let src_size = (src_range.end - src_range.start).to_usize().unwrap();
+        let mut best = None;
+        let mut max: f64 = -1.;
+        for cand in candidates {
+            let dst_range = self.internal.dst_arena.descendants_range(&cand);
+            let dst_size = (dst_range.end - dst_range.start).to_usize().unwrap();
+            let threshold = self.params.sim_threshold(src_size, dst_size);
+            let sim = similarity_metrics::SimilarityMeasure::range(
+                &src_range,
+                &dst_range,
+                &self.internal.mappings,
+            )
+            .chawathe();
+            if sim > max && sim >= threshold {
+                max = sim;
+                best = Some(cand);
+            }
+        }
+        best
+    }
+
+    fn has_unmapped_children(&self, src: M::Src, dst: M::Dst) -> bool {
+        let node_store = self.internal.stores.node_store();
+        let src_children = self.internal.src_arena.children(node_store, &src);
+        let dst_children = self.internal.dst_arena.children(node_store, &dst);
+        src_children
+            .iter()
+            .any(|c| !self.internal.mappings.is_src(c))
+            && dst_children
+                .iter()
+                .any(|c| !self.internal.mappings.is_dst(c))
+    }
+
+    fn last_chance_match(&mut self, src: M::Src, dst: M::Dst) {
+        let node_store = self.internal.stores.node_store();
+        let src_s = self.internal.src_arena.descendants_count(node_store, &src);
+        let dst_s = self.internal.dst_arena.descendants_count(node_store, &dst);
+        if src_s < self.params.size_threshold || dst_s < self.params.size_threshold {
+            self.last_chance_match_optimal(src, dst)
+        } else {
+            self.internal.last_chance_match_histogram(&src, &dst)
+        }
+    }
+
+    fn last_chance_match_optimal(&mut self, src: M::Src, dst: M::Dst) {
+        let src_arena = self.internal.src_arena.slice_po(&src);
+        let src_offset = src - src_arena.root();
+        let dst_arena = self.internal.dst_arena.slice_po(&dst);
+        let dst_offset = dst - dst_arena.root();
+        let mappings: M = Opt::match_with(self.internal.stores, src_arena, dst_arena);
+        for (i, t) in mappings.iter() {
+            let src: M::Src = src_offset + cast(i).unwrap();
+            let dst: M::Dst = dst_offset + cast(t).unwrap();
+            if !self.internal.mappings.is_src(&src) && !self.internal.mappings.is_dst(&dst) {
+                let tsrc = self
+                    .internal
+                    .stores
+                    .resolve_type(&self.internal.src_arena.original(&src));
+                let tdst = self
+                    .internal
+                    .stores
+                    .resolve_type(&self.internal.dst_arena.original(&dst));
+                if tsrc == tdst {
+                    self.internal.mappings.link(src, dst);
+                }
+            }
+        }
+    }
+}
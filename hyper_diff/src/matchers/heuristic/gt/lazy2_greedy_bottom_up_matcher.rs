@@ -3,7 +3,7 @@
 //!
 use std::{fmt::Debug, marker::PhantomData};
 
-use num_traits::{cast, one, PrimInt};
+use num_traits::{cast, one, PrimInt, ToPrimitive};
 
 use crate::decompressed_tree_store::{
     ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent,
@@ -11,16 +11,14 @@ use crate::decompressed_tree_store::{
     Shallow, ShallowDecompressedTreeStore,
 };
 use crate::matchers::mapping_store::MonoMappingStore;
+use crate::matchers::optimal::{OptimalMatcher, Zs};
+use crate::matchers::similarity_metrics;
 use crate::matchers::Mapper;
-use crate::matchers::{optimal::zs::ZsMatcher, similarity_metrics};
 use hyper_ast::types::{DecompressedSubtree, HyperAST, NodeStore, Tree, WithHashs, WithStats};
 
 use crate::decompressed_tree_store::SimpleZsTree as ZsTree;
 
-/// TODO wait for `#![feature(adt_const_params)]` #95174 to be improved
-///
-/// it will allow to make use complex types as const generics
-/// ie. make the different threshold neater
+/// The size and similarity thresholds are read at runtime from [`crate::matchers::GumtreeParams`]
 pub struct GreedyBottomUpMatcher<
     'a,
     Dsrc,
@@ -28,13 +26,11 @@ pub struct GreedyBottomUpMatcher<
     HAST: HyperAST<'a>,
     M: MonoMappingStore,
     MZs: MonoMappingStore = M,
-    const SIZE_THRESHOLD: usize = 1000,
-    const SIM_THRESHOLD_NUM: u64 = 1,
-    const SIM_THRESHOLD_DEN: u64 = 2,
+    Opt: OptimalMatcher = Zs,
 > {
     label_store: &'a HAST::LS,
     internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
-    _phantom: PhantomData<*const MZs>,
+    _phantom: PhantomData<*const (MZs, Opt)>,
 }
 
 /// Enable using a slice instead of recreating a ZsTree for each call to ZsMatch, see last_chance_match
@@ -142,21 +138,8 @@ impl<
         HAST: HyperAST<'a>,
         M: MonoMappingStore,
         MZs: MonoMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD> + Default,
-        const SIZE_THRESHOLD: usize,
-        const SIM_THRESHOLD_NUM: u64,
-        const SIM_THRESHOLD_DEN: u64,
-    >
-    GreedyBottomUpMatcher<
-        'a,
-        Dsrc,
-        Ddst,
-        HAST,
-        M,
-        MZs,
-        SIZE_THRESHOLD,
-        SIM_THRESHOLD_NUM,
-        SIM_THRESHOLD_DEN,
-    >
+        Opt: OptimalMatcher,
+    > GreedyBottomUpMatcher<'a, Dsrc, Ddst, HAST, M, MZs, Opt>
 where
     HAST::T: 'a + Tree + WithHashs + WithStats,
     HAST::IdN: 'a + Clone + Eq + Debug,
@@ -207,14 +190,19 @@ where
                 let candidates = internal.get_dst_candidates_lazily(&a);
                 let mut best = None;
                 let mut max: f64 = -1.;
+                let src_range = internal.src_arena.descendants_range(&a);
+                let src_size = (src_range.end - src_range.start).to_usize().unwrap();
                 for cand in candidates {
+                    let dst_range = internal.dst_arena.descendants_range(&cand);
+                    let dst_size = (dst_range.end - dst_range.start).to_usize().unwrap();
+                    let threshold = internal.params.sim_threshold(src_size, dst_size);
                     let sim = similarity_metrics::SimilarityMeasure::range(
-                        &internal.src_arena.descendants_range(&a),
-                        &internal.dst_arena.descendants_range(&cand),
+                        &src_range,
+                        &dst_range,
                         &internal.mappings,
                     )
                     .dice();
-                    if sim > max && sim >= SIM_THRESHOLD_NUM as f64 / SIM_THRESHOLD_DEN as f64 {
+                    if sim > max && sim >= threshold {
                         max = sim;
                         best = Some(cand);
                     }
@@ -261,12 +249,13 @@ where
         // allow using another internal mapping store
         // WIP https://blog.rust-lang.org/2022/10/28/gats-stabilization.html#implied-static-requirement-from-higher-ranked-trait-bounds
         let node_store = internal.hyperast.node_store();
+        let size_threshold = internal.params.size_threshold;
         let mapping = &mut internal.mapping;
         let src_arena = &mut mapping.src_arena;
         let dst_arena = &mut mapping.dst_arena;
         let src_s = src_arena.descendants_count(node_store, &src);
         let dst_s = dst_arena.descendants_count(node_store, &dst);
-        if !(src_s < cast(SIZE_THRESHOLD).unwrap() || dst_s < cast(SIZE_THRESHOLD).unwrap()) {
+        if !(src_s < cast(size_threshold).unwrap() || dst_s < cast(size_threshold).unwrap()) {
             // dbg!(src_s, dst_s);
             return;
         }
@@ -278,7 +267,7 @@ where
             src_offset = src - src_arena.root();
             let dst_arena = dst_arena.slice_po(node_store, &dst);
             dst_offset = dst - dst_arena.root();
-            ZsMatcher::match_with(internal.hyperast, src_arena, dst_arena)
+            Opt::match_with(internal.hyperast, src_arena, dst_arena)
         } else {
             let o_src = src_arena.original(&src);
             let o_dst = dst_arena.original(&dst);
@@ -314,9 +303,8 @@ where
                 assert!(dst_arena.kr[dst_arena.kr.len() - 1]);
                 dbg!(last == dst_arena_z.root());
             }
-            ZsMatcher::match_with(internal.hyperast, src_arena, dst_arena)
+            Opt::match_with(internal.hyperast, src_arena, dst_arena)
         };
-        use num_traits::ToPrimitive;
         assert_eq!(
             mapping.src_arena.first_descendant(&src).to_usize(),
             src_offset.to_usize()
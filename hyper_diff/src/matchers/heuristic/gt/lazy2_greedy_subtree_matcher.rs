@@ -17,7 +17,8 @@ use hyper_ast::types::{
 use logging_timer::time;
 use num_traits::{PrimInt, ToPrimitive};
 
-pub struct LazyGreedySubtreeMatcher<'a, HAST, Dsrc, Ddst, M, const MIN_HEIGHT: usize = 1> {
+/// Subtrees lower than [`crate::matchers::GumtreeParams::min_height`] are not matched.
+pub struct LazyGreedySubtreeMatcher<'a, HAST, Dsrc, Ddst, M> {
     internal: Mapper<'a, HAST, Dsrc, Ddst, M>,
 }
 
@@ -35,8 +36,7 @@ impl<
         // T: Tree + WithHashs + WithStats,
         // S: 'a + NodeStore<T::TreeId, R<'a> = T>,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize, // = 2
-    > LazyGreedySubtreeMatcher<'a, HAST, Dsrc, Ddst, M, MIN_HEIGHT>
+    > LazyGreedySubtreeMatcher<'a, HAST, Dsrc, Ddst, M>
 where
     HAST::T: Tree + WithHashs + WithStats,
     HAST::IdN: Clone + Eq,
@@ -99,11 +99,12 @@ where
     ) -> MM {
         let mut mm: MM = Default::default();
         mm.topit(internal.src_arena.len(), internal.dst_arena.len());
-        Mapper::<HAST, Dsrc, Ddst, M>::compute_multimapping::<_, MIN_HEIGHT>(
+        Mapper::<HAST, Dsrc, Ddst, M>::compute_multimapping(
             internal.hyperast,
             &mut internal.mapping.src_arena,
             &mut internal.mapping.dst_arena,
             &mut mm,
+            internal.params.min_height,
         );
         mm
     }
@@ -123,8 +124,7 @@ impl<
         // T: Tree + WithHashs + WithStats,
         // S: 'a + NodeStore<T::TreeId, R<'a> = T>,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize, // = 2
-    > LazyGreedySubtreeMatcher<'a, HAST, Dsrc, Ddst, M, MIN_HEIGHT>
+    > LazyGreedySubtreeMatcher<'a, HAST, Dsrc, Ddst, M>
 where
     HAST::T: Tree + WithHashs + WithStats,
     HAST::IdN: Clone,
@@ -487,36 +487,28 @@ where
     M::Dst: Debug + Copy,
 {
     #[time("warn")]
-    pub fn compute_multimapping<
-        MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD>,
-        const MIN_HEIGHT: usize,
-    >(
+    pub fn compute_multimapping<MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD>>(
         hyperast: &'a HAST,
         src_arena: &mut Dsrc,
         dst_arena: &mut Ddst,
         multi_mappings: &mut MM,
+        min_height: usize,
     ) {
         let now = std::time::Instant::now();
-        let mut src_trees = PriorityTreeList::<
-            'a,
-            '_,
-            Dsrc,
-            M::Src,
-            Dsrc::IdD,
-            HAST::T,
-            HAST::NS,
-            MIN_HEIGHT,
-        >::new(hyperast.node_store(), src_arena.starter(), src_arena);
-        let mut dst_trees = PriorityTreeList::<
-            'a,
-            '_,
-            Ddst,
-            M::Dst,
-            Ddst::IdD,
-            HAST::T,
-            HAST::NS,
-            MIN_HEIGHT,
-        >::new(hyperast.node_store(), dst_arena.starter(), dst_arena);
+        let mut src_trees =
+            PriorityTreeList::<'a, '_, Dsrc, M::Src, Dsrc::IdD, HAST::T, HAST::NS>::new(
+                hyperast.node_store(),
+                src_arena.starter(),
+                src_arena,
+                min_height,
+            );
+        let mut dst_trees =
+            PriorityTreeList::<'a, '_, Ddst, M::Dst, Ddst::IdD, HAST::T, HAST::NS>::new(
+                hyperast.node_store(),
+                dst_arena.starter(),
+                dst_arena,
+                min_height,
+            );
         let match_init_t = now.elapsed().as_secs_f64();
         dbg!(match_init_t);
         while src_trees.peek_height() != -1 && dst_trees.peek_height() != -1 {
@@ -623,7 +615,7 @@ where
     }
 }
 
-struct PriorityTreeList<'a, 'b, D, IdS, IdD, T: Tree, S, const MIN_HEIGHT: usize> {
+struct PriorityTreeList<'a, 'b, D, IdS, IdD, T: Tree, S> {
     trees: Vec<Option<Vec<IdD>>>,
 
     store: &'a S,
@@ -631,6 +623,8 @@ struct PriorityTreeList<'a, 'b, D, IdS, IdD, T: Tree, S, const MIN_HEIGHT: usize
 
     max_height: usize,
 
+    min_height: usize,
+
     current_idx: isize,
 
     phantom: PhantomData<*const (T, IdS)>,
@@ -643,16 +637,15 @@ impl<
         IdD,
         T: Tree + WithStats,
         S: 'a + NodeStore<T::TreeId, R<'a> = T>,
-        const MIN_HEIGHT: usize,
-    > PriorityTreeList<'a, 'b, D, IdD, D::IdD, T, S, MIN_HEIGHT>
+    > PriorityTreeList<'a, 'b, D, IdD, D::IdD, T, S>
 where
     T::TreeId: Clone,
     D::IdD: Clone,
 {
-    pub(super) fn new(store: &'a S, tree: D::IdD, arena: &'b mut D) -> Self {
+    pub(super) fn new(store: &'a S, tree: D::IdD, arena: &'b mut D, min_height: usize) -> Self {
         let h = store.resolve(&arena.original(&tree)).height() - 1;
-        let list_size = if h >= MIN_HEIGHT {
-            h + 1 - MIN_HEIGHT
+        let list_size = if h >= min_height {
+            h + 1 - min_height
         } else {
             0
         };
@@ -661,6 +654,7 @@ where
             store,
             arena,
             max_height: h,
+            min_height,
             current_idx: if list_size == 0 { -1 } else { 0 },
             phantom: PhantomData,
         };
@@ -682,7 +676,7 @@ where
     }
 
     fn add_tree_aux(&mut self, tree: D::IdD, h: usize) {
-        if h >= MIN_HEIGHT {
+        if h >= self.min_height {
             let idx = self.idx(h);
             let t = self.trees[idx].get_or_insert_with(Vec::new);
             t.push(tree);
@@ -3,7 +3,7 @@
 //!
 use std::{fmt::Debug, marker::PhantomData};
 
-use num_traits::{cast, one, PrimInt};
+use num_traits::{cast, one, PrimInt, ToPrimitive};
 
 use crate::decompressed_tree_store::{
     ContiguousDescendants, DecompressedTreeStore, DecompressedWithParent,
@@ -11,7 +11,9 @@ use crate::decompressed_tree_store::{
     Shallow, ShallowDecompressedTreeStore,
 };
 use crate::matchers::mapping_store::MonoMappingStore;
-use crate::matchers::{optimal::zs::ZsMatcher, similarity_metrics};
+use crate::matchers::optimal::{OptimalMatcher, Zs};
+use crate::matchers::similarity_metrics;
+use crate::matchers::GumtreeParams;
 use hyper_ast::types::{
     DecompressedSubtree, HyperAST, NodeId, NodeStore, Tree, WithHashs, WithStats,
 };
@@ -19,10 +21,7 @@ use hyper_ast::types::{
 use super::lazy_bottom_up_matcher::BottomUpMatcher;
 use crate::decompressed_tree_store::SimpleZsTree as ZsTree;
 
-/// TODO wait for `#![feature(adt_const_params)]` #95174 to be improved
-///
-/// it will allow to make use complex types as const generics
-/// ie. make the different threshold neater
+/// The size and similarity thresholds are read at runtime from [`GumtreeParams`]
 pub struct GreedyBottomUpMatcher<
     'a,
     Dsrc,
@@ -31,12 +30,11 @@ pub struct GreedyBottomUpMatcher<
     HAST,
     M: MonoMappingStore,
     MZs: MonoMappingStore = M,
-    const SIZE_THRESHOLD: usize = 1000,
-    const SIM_THRESHOLD_NUM: u64 = 1,
-    const SIM_THRESHOLD_DEN: u64 = 2,
+    Opt: OptimalMatcher = Zs,
 > {
     internal: BottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M>,
-    _phantom: PhantomData<*const MZs>,
+    params: GumtreeParams,
+    _phantom: PhantomData<*const (MZs, Opt)>,
 }
 
 /// Enable using a slice instead of recreating a ZsTree for each call to ZsMatch, see last_chance_match
@@ -50,52 +48,24 @@ impl<
         S,
         M: MonoMappingStore,
         MZs: MonoMappingStore<Src = M::Src, Dst = M::Dst>,
-        const SIZE_THRESHOLD: usize,
-        const SIM_THRESHOLD_NUM: u64,
-        const SIM_THRESHOLD_DEN: u64,
+        Opt: OptimalMatcher,
     > Into<BottomUpMatcher<'a, Dsrc, Ddst, T, S, M>>
-    for GreedyBottomUpMatcher<
-        'a,
-        Dsrc,
-        Ddst,
-        T,
-        S,
-        M,
-        MZs,
-        SIZE_THRESHOLD,
-        SIM_THRESHOLD_NUM,
-        SIM_THRESHOLD_DEN,
-    >
+    for GreedyBottomUpMatcher<'a, Dsrc, Ddst, T, S, M, MZs, Opt>
 {
     fn into(self) -> BottomUpMatcher<'a, Dsrc, Ddst, T, S, M> {
         self.internal
     }
 }
-impl<
-        'a,
-        Dsrc,
-        Ddst,
-        T: 'a + Tree + WithHashs,
-        HAST,
-        M: MonoMappingStore,
-        const SIZE_THRESHOLD: usize,  // = 1000,
-        const SIM_THRESHOLD_NUM: u64, // = 1,
-        const SIM_THRESHOLD_DEN: u64, // = 2,
-    >
-    GreedyBottomUpMatcher<
-        'a,
-        Dsrc,
-        Ddst,
-        T,
-        HAST,
-        M,
-        M,
-        SIZE_THRESHOLD,
-        SIM_THRESHOLD_NUM,
-        SIM_THRESHOLD_DEN,
-    >
+impl<'a, Dsrc, Ddst, T: 'a + Tree + WithHashs, HAST, M: MonoMappingStore, Opt: OptimalMatcher>
+    GreedyBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, M, Opt>
 {
-    pub fn new(stores: &'a HAST, src_arena: Dsrc, dst_arena: Ddst, mappings: M) -> Self {
+    pub fn new(
+        stores: &'a HAST,
+        src_arena: Dsrc,
+        dst_arena: Ddst,
+        mappings: M,
+        params: GumtreeParams,
+    ) -> Self {
         Self {
             internal: BottomUpMatcher {
                 stores,
@@ -104,6 +74,7 @@ impl<
                 mappings,
                 _phantom: PhantomData,
             },
+            params,
             _phantom: PhantomData,
         }
     }
@@ -136,22 +107,8 @@ impl<
         HAST: HyperAST<'a, IdN = T::TreeId, T = T, Label = T::Label>,
         M: MonoMappingStore,
         MZs: MonoMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD> + Default,
-        const SIZE_THRESHOLD: usize,
-        const SIM_THRESHOLD_NUM: u64,
-        const SIM_THRESHOLD_DEN: u64,
-    >
-    GreedyBottomUpMatcher<
-        'a,
-        Dsrc,
-        Ddst,
-        T,
-        HAST,
-        M,
-        MZs,
-        SIZE_THRESHOLD,
-        SIM_THRESHOLD_NUM,
-        SIM_THRESHOLD_DEN,
-    >
+        Opt: OptimalMatcher,
+    > GreedyBottomUpMatcher<'a, Dsrc, Ddst, T, HAST, M, MZs, Opt>
 where
     T::TreeId: 'a + Clone + Debug + NodeId<IdN = T::TreeId>,
     // T::Type: Debug + Eq + Copy + Send + Sync,
@@ -197,6 +154,7 @@ where
                 mappings: mapping.mapping.mappings,
                 _phantom: PhantomData,
             },
+            params: mapping.params,
             _phantom: PhantomData,
         };
         matcher.internal.mappings.topit(
@@ -211,6 +169,7 @@ where
                 dst_arena: matcher.internal.dst_arena,
                 mappings: matcher.internal.mappings,
             },
+            params: mapping.params,
         }
     }
 
@@ -240,16 +199,21 @@ where
                 .decompress_to(self.internal.stores.node_store(), &a);
             if self.src_has_children(a) {
                 let candidates = self.internal.get_dst_candidates(&a);
+                let src_range = self.internal.src_arena.descendants_range(&a);
+                let src_size = (src_range.end - src_range.start).to_usize().unwrap();
                 let mut best = None;
                 let mut max: f64 = -1.;
                 for cand in candidates {
+                    let dst_range = self.internal.dst_arena.descendants_range(&cand);
+                    let dst_size = (dst_range.end - dst_range.start).to_usize().unwrap();
+                    let threshold = self.params.sim_threshold(src_size, dst_size);
                     let sim = similarity_metrics::SimilarityMeasure::range(
-                        &self.internal.src_arena.descendants_range(&a),
-                        &self.internal.dst_arena.descendants_range(&cand),
+                        &src_range,
+                        &dst_range,
                         &self.internal.mappings,
                     )
                     .dice();
-                    if sim > max && sim >= SIM_THRESHOLD_NUM as f64 / SIM_THRESHOLD_DEN as f64 {
+                    if sim > max && sim >= threshold {
                         max = sim;
                         best = Some(cand);
                     }
@@ -277,7 +241,6 @@ where
     fn src_has_children(&mut self, src: Dsrc::IdD) -> bool {
         let o = self.internal.src_arena.original(&src);
         let r = self.internal.stores.node_store().resolve(&o).has_children();
-        use num_traits::ToPrimitive;
         debug_assert_eq!(
             r,
             self.internal.src_arena.lld(&src) < *src.shallow(),
@@ -301,7 +264,8 @@ where
             .internal
             .dst_arena
             .descendants_count(self.internal.stores.node_store(), &dst);
-        if !(src_s < cast(SIZE_THRESHOLD).unwrap() || dst_s < cast(SIZE_THRESHOLD).unwrap()) {
+        let size_threshold = self.params.size_threshold;
+        if !(src_s < cast(size_threshold).unwrap() || dst_s < cast(size_threshold).unwrap()) {
             return;
         }
         let node_store = self.internal.stores.node_store();
@@ -312,7 +276,7 @@ where
             src_offset = src - src_arena.root();
             let dst_arena = self.internal.dst_arena.slice_po(node_store, &dst);
             dst_offset = dst - dst_arena.root();
-            ZsMatcher::match_with(self.internal.stores, src_arena, dst_arena)
+            Opt::match_with(self.internal.stores, src_arena, dst_arena)
         } else {
             let o_src = self.internal.src_arena.original(&src);
             let o_dst = self.internal.dst_arena.original(&dst);
@@ -348,9 +312,8 @@ where
                 assert!(dst_arena.kr[dst_arena.kr.len() - 1]);
                 dbg!(last == dst_arena_z.root());
             }
-            ZsMatcher::match_with(self.internal.stores, src_arena, dst_arena)
+            Opt::match_with(self.internal.stores, src_arena, dst_arena)
         };
-        use num_traits::ToPrimitive;
         assert_eq!(
             self.internal.src_arena.first_descendant(&src).to_usize(),
             src_offset.to_usize()
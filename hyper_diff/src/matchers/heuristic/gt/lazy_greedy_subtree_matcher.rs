@@ -5,7 +5,7 @@ use crate::decompressed_tree_store::{
     ContiguousDescendants, DecompressedWithParent, LazyDecompressedTreeStore, Shallow,
 };
 use crate::matchers::mapping_store::MonoMappingStore;
-use crate::matchers::{mapping_store::MultiMappingStore, similarity_metrics};
+use crate::matchers::{mapping_store::MultiMappingStore, similarity_metrics, GumtreeParams};
 use crate::utils::sequence_algorithms::longest_common_subsequence;
 use hyper_ast::compat::HashMap;
 use hyper_ast::types::{
@@ -15,8 +15,10 @@ use hyper_ast::types::{
 use logging_timer::time;
 use num_traits::{PrimInt, ToPrimitive};
 
-pub struct LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, const MIN_HEIGHT: usize = 1> {
-    internal: SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>,
+/// Subtrees lower than [`GumtreeParams::min_height`] are not matched.
+pub struct LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M> {
+    internal: SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>,
+    params: GumtreeParams,
 }
 
 impl<
@@ -34,8 +36,7 @@ impl<
         T: Tree + WithHashs + WithStats,
         HAST: HyperAST<'a, IdN = T::TreeId, T = T>,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize, // = 2
-    > LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    > LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
 where
     T::TreeId: Clone + NodeId<IdN = T::TreeId>,
     T::Label: Clone,
@@ -60,6 +61,7 @@ where
                 mappings: mapping.mapping.mappings,
                 phantom: PhantomData,
             },
+            params: mapping.params,
         };
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len(),
@@ -73,21 +75,29 @@ where
                 dst_arena: matcher.internal.dst_arena,
                 mappings: matcher.internal.mappings,
             },
+            params: mapping.params,
         }
     }
 
+    /// match with the default [`GumtreeParams`], use [`Self::match_it`] to tune them
     pub fn matchh<MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD> + Default>(
         stores: &'a HAST,
         src: &'a T::TreeId,
         dst: &'a T::TreeId,
         mappings: M,
-    ) -> LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    ) -> LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
     where
         Self: 'a,
     {
         let src_arena = Dsrc::decompress(stores.node_store(), src);
         let dst_arena = Ddst::decompress(stores.node_store(), dst);
-        let mut matcher = Self::new(stores, src_arena, dst_arena, mappings);
+        let mut matcher = Self::new(
+            stores,
+            src_arena,
+            dst_arena,
+            mappings,
+            GumtreeParams::default(),
+        );
         Self::execute::<MM>(&mut matcher);
         matcher
     }
@@ -112,7 +122,8 @@ where
     ) -> MM {
         let mut mm: MM = Default::default();
         mm.topit(self.internal.src_arena.len(), self.internal.dst_arena.len());
-        self.internal.matchh_to_be_filtered(&mut mm);
+        self.internal
+            .matchh_to_be_filtered(&mut mm, self.params.min_height);
         mm
     }
 }
@@ -132,8 +143,7 @@ impl<
         T: Tree + WithHashs + WithStats,
         HAST: HyperAST<'a, IdN = T::TreeId, T = T>,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize, // = 2
-    > LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    > LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
 where
     T::TreeId: Clone + NodeId<IdN = T::TreeId>,
     T::Label: Clone,
@@ -148,7 +158,8 @@ where
         src_arena: Dsrc,
         dst_arena: Ddst,
         mappings: M,
-    ) -> LazyGreedySubtreeMatcher<Dsrc, Ddst, T, HAST, M, MIN_HEIGHT> {
+        params: GumtreeParams,
+    ) -> LazyGreedySubtreeMatcher<Dsrc, Ddst, T, HAST, M> {
         let mut matcher = LazyGreedySubtreeMatcher {
             internal: SubtreeMatcher {
                 stores,
@@ -157,6 +168,7 @@ where
                 mappings,
                 phantom: PhantomData,
             },
+            params,
         };
         matcher.internal.mappings.topit(
             matcher.internal.src_arena.len() + 1,
@@ -407,16 +419,15 @@ impl<
         T: Tree + WithStats,
         S: 'a + NodeStore<T::TreeId, R<'a> = T>,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize,
-    > Into<SubtreeMatcher<'a, Dsrc, Ddst, T, S, M, MIN_HEIGHT>>
-    for LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, S, M, MIN_HEIGHT>
+    > Into<SubtreeMatcher<'a, Dsrc, Ddst, T, S, M>>
+    for LazyGreedySubtreeMatcher<'a, Dsrc, Ddst, T, S, M>
 {
-    fn into(self) -> SubtreeMatcher<'a, Dsrc, Ddst, T, S, M, MIN_HEIGHT> {
+    fn into(self) -> SubtreeMatcher<'a, Dsrc, Ddst, T, S, M> {
         self.internal
     }
 }
 
-pub struct SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, const MIN_HEIGHT: usize> {
+pub struct SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M> {
     pub(super) stores: &'a HAST,
     pub src_arena: Dsrc,
     pub dst_arena: Ddst,
@@ -431,8 +442,7 @@ impl<
         T: Tree + WithHashs + WithStats,
         HAST: HyperAST<'a, IdN = T::TreeId, T = T>,
         M: MonoMappingStore,
-        const MIN_HEIGHT: usize,
-    > SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M, MIN_HEIGHT>
+    > SubtreeMatcher<'a, Dsrc, Ddst, T, HAST, M>
 where
     T::TreeId: Clone + NodeId<IdN = T::TreeId>,
     // T::Type: Copy + Eq + Send + Sync,
@@ -460,17 +470,20 @@ where
     fn matchh_to_be_filtered<MM: MultiMappingStore<Src = Dsrc::IdD, Dst = Ddst::IdD>>(
         &mut self,
         multi_mappings: &mut MM,
+        min_height: usize,
     ) {
         let now = std::time::Instant::now();
         let mut src_trees = PriorityTreeList::new(
             self.stores.node_store(),
             self.src_arena.starter(),
             &mut self.src_arena,
+            min_height,
         );
         let mut dst_trees = PriorityTreeList::new(
             self.stores.node_store(),
             self.dst_arena.starter(),
             &mut self.dst_arena,
+            min_height,
         );
         let match_init_t = now.elapsed().as_secs_f64();
         dbg!(match_init_t);
@@ -482,7 +495,6 @@ where
             Dsrc::IdD,
             T,
             HAST::NS,
-            MIN_HEIGHT,
         >,
 
                           dst_trees: &mut PriorityTreeList<
@@ -493,7 +505,6 @@ where
             Ddst::IdD,
             T,
             HAST::NS,
-            MIN_HEIGHT,
         >| {
             if src_trees.peek_height() > dst_trees.peek_height() {
                 src_trees.open();
@@ -611,13 +622,14 @@ where
     }
 }
 
-struct PriorityTreeList<'a, 'b, D, IdS, IdD, T: Tree, S, const MIN_HEIGHT: usize> {
+struct PriorityTreeList<'a, 'b, D, IdS, IdD, T: Tree, S> {
     trees: Vec<Option<Vec<IdD>>>,
 
     store: &'a S,
     arena: &'b mut D,
 
     max_height: usize,
+    min_height: usize,
 
     current_idx: isize,
 
@@ -631,16 +643,15 @@ impl<
         IdD,
         T: Tree + WithStats,
         S: 'a + NodeStore<T::TreeId, R<'a> = T>,
-        const MIN_HEIGHT: usize,
-    > PriorityTreeList<'a, 'b, D, IdD, D::IdD, T, S, MIN_HEIGHT>
+    > PriorityTreeList<'a, 'b, D, IdD, D::IdD, T, S>
 where
     T::TreeId: Clone,
     D::IdD: Clone,
 {
-    pub(super) fn new(store: &'a S, tree: D::IdD, arena: &'b mut D) -> Self {
+    pub(super) fn new(store: &'a S, tree: D::IdD, arena: &'b mut D, min_height: usize) -> Self {
         let h = store.resolve(&arena.original(&tree)).height() - 1;
-        let list_size = if h >= MIN_HEIGHT {
-            h + 1 - MIN_HEIGHT
+        let list_size = if h >= min_height {
+            h + 1 - min_height
         } else {
             0
         };
@@ -649,6 +660,7 @@ where
             store,
             arena,
             max_height: h,
+            min_height,
             current_idx: if list_size == 0 { -1 } else { 0 },
             phantom: PhantomData,
         };
@@ -670,7 +682,7 @@ where
     }
 
     fn add_tree_aux(&mut self, tree: D::IdD, h: usize) {
-        if h >= MIN_HEIGHT {
+        if h >= self.min_height {
             let idx = self.idx(h);
             if self.trees[idx].is_none() {
                 self.trees[idx] = Some(vec![]);
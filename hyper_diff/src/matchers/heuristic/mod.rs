@@ -1 +1,2 @@
+pub mod cd;
 pub mod gt;
@@ -74,7 +74,7 @@ impl<T: PrimInt + Debug> VecStore<T> {
     }
 
     pub fn link_if_both_unmapped(&mut self, t1: T, t2: T) -> bool {
-        if self.is_src(&t1) && self.is_dst(&t2) {
+        if !self.is_src(&t1) && !self.is_dst(&t2) {
             self.link(t1, t2);
             true
         } else {
@@ -170,7 +170,7 @@ impl<T: PrimInt + Debug> MonoMappingStore for VecStore<T> {
     }
 
     fn link_if_both_unmapped(&mut self, t1: T, t2: T) -> bool {
-        if self.is_src(&t1) && self.is_dst(&t2) {
+        if !self.is_src(&t1) && !self.is_dst(&t2) {
             self.link(t1, t2);
             true
         } else {
@@ -489,7 +489,7 @@ impl<T: PrimInt + Debug + Hash> HashStore<T> {
     }
 
     pub fn link_if_both_unmapped(&mut self, t1: T, t2: T) -> bool {
-        if self.is_src(&t1) && self.is_dst(&t2) {
+        if !self.is_src(&t1) && !self.is_dst(&t2) {
             self.link(t1, t2);
             true
         } else {
@@ -564,7 +564,7 @@ impl<T: PrimInt + Debug + Hash> MonoMappingStore for HashStore<T> {
     }
 
     fn link_if_both_unmapped(&mut self, t1: T, t2: T) -> bool {
-        if self.is_src(&t1) && self.is_dst(&t2) {
+        if !self.is_src(&t1) && !self.is_dst(&t2) {
             self.link(t1, t2);
             true
         } else {
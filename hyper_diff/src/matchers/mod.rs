@@ -18,6 +18,76 @@ pub struct Mapper<'store, HAST, Dsrc, Ddst, M> {
     pub hyperast: &'store HAST,
     /// the decompressed subtrees coming from hyperAST and their mappings
     pub mapping: Mapping<Dsrc, Ddst, M>,
+    /// the thresholds used by the gumtree matchers
+    pub params: GumtreeParams,
+}
+
+/// Thresholds of the gumtree subtree and bottom-up matchers,
+/// given at runtime to tune them without recompiling.
+#[derive(Debug, Clone, Copy)]
+pub struct GumtreeParams {
+    /// minimum height of subtrees matched by the subtree matcher
+    pub min_height: usize,
+    /// maximum size of subtrees matched by the optimal matcher during last chance matching
+    pub size_threshold: usize,
+    /// minimum similarity for a bottom-up matcher to match containers,
+    /// if `None` the threshold decreases with the size of the compared subtrees, as upstream
+    pub sim_threshold: Option<f64>,
+}
+
+impl Default for GumtreeParams {
+    fn default() -> Self {
+        Self {
+            min_height: 1,
+            size_threshold: 1000,
+            sim_threshold: Some(0.5),
+        }
+    }
+}
+
+impl GumtreeParams {
+    /// Defaults of the simple variant of upstream GumTree.
+    pub fn simple() -> Self {
+        Self {
+            sim_threshold: None,
+            ..Default::default()
+        }
+    }
+
+    /// Defaults of the hybrid variant of upstream GumTree.
+    pub fn hybrid() -> Self {
+        Self {
+            size_threshold: 20,
+            sim_threshold: None,
+            ..Default::default()
+        }
+    }
+
+    /// Similarity threshold for containers with the given numbers of descendants,
+    /// falls back to the dynamic threshold of upstream when none is configured.
+    pub fn sim_threshold(&self, src_descendants: usize, dst_descendants: usize) -> f64 {
+        self.sim_threshold
+            .unwrap_or_else(|| 1. / (1. + ((src_descendants + dst_descendants) as f64).ln()))
+    }
+}
+
+// NOTE compares the bits of the threshold, so params can be used in cache keys
+impl PartialEq for GumtreeParams {
+    fn eq(&self, other: &Self) -> bool {
+        self.min_height == other.min_height
+            && self.size_threshold == other.size_threshold
+            && self.sim_threshold.map(f64::to_bits) == other.sim_threshold.map(f64::to_bits)
+    }
+}
+
+impl Eq for GumtreeParams {}
+
+impl std::hash::Hash for GumtreeParams {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.min_height.hash(state);
+        self.size_threshold.hash(state);
+        self.sim_threshold.map(f64::to_bits).hash(state);
+    }
 }
 // NOTE this is temporary, waiting for the refactoring of helpers
 // the refactoring is simple, do a spliting borrow, before accessing content
@@ -54,6 +124,7 @@ impl<'store, HAST, Dsrc, Ddst, M: MappingStore + Default> From<(&'store HAST, (D
                 dst_arena,
                 mappings,
             },
+            params: GumtreeParams::default(),
         }
     }
 }
@@ -70,6 +141,7 @@ impl<'a, HAST, Dsrc, Ddst, M: MappingStore> Mapper<'a, HAST, Dsrc, Ddst, M> {
         Mapper {
             hyperast: self.hyperast,
             mapping: self.mapping.map(f_src, f_dst),
+            params: self.params,
         }
     }
 }
@@ -1 +1,75 @@
+pub mod rted;
 pub mod zs;
+
+use std::fmt::Debug;
+
+use num_traits::PrimInt;
+
+use crate::decompressed_tree_store::PostOrderKeyRoots;
+use crate::matchers::mapping_store::MonoMappingStore;
+use hyper_ast::types::{HyperAST, NodeId, Tree};
+
+/// Optimal matcher used by bottom-up matchers on pairs of small subtrees,
+/// eg. to choose between [`Zs`] and [`Rted`] as a type parameter.
+pub trait OptimalMatcher {
+    fn match_with<'store: 'b, 'b, T, HAST, M, SD, DD>(
+        stores: &'store HAST,
+        src_arena: SD,
+        dst_arena: DD,
+    ) -> M
+    where
+        T::TreeId: Clone + NodeId<IdN = T::TreeId>,
+        M: MonoMappingStore + Default,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>;
+}
+
+/// see [`zs::ZsMatcher`]
+pub struct Zs;
+
+impl OptimalMatcher for Zs {
+    fn match_with<'store: 'b, 'b, T, HAST, M, SD, DD>(
+        stores: &'store HAST,
+        src_arena: SD,
+        dst_arena: DD,
+    ) -> M
+    where
+        T::TreeId: Clone + NodeId<IdN = T::TreeId>,
+        M: MonoMappingStore + Default,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        zs::ZsMatcher::<M, SD, DD>::match_with(stores, src_arena, dst_arena)
+    }
+}
+
+/// see [`rted::RtedMatcher`]
+pub struct Rted;
+
+impl OptimalMatcher for Rted {
+    fn match_with<'store: 'b, 'b, T, HAST, M, SD, DD>(
+        stores: &'store HAST,
+        src_arena: SD,
+        dst_arena: DD,
+    ) -> M
+    where
+        T::TreeId: Clone + NodeId<IdN = T::TreeId>,
+        M: MonoMappingStore + Default,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        rted::RtedMatcher::<M, SD, DD>::match_with(stores, src_arena, dst_arena)
+    }
+}
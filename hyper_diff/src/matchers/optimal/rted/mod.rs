@@ -0,0 +1,501 @@
+// Robust tree edit distance (RTED), Pawlik and Augsten 2011
+//
+// Same costs and mappings as Zhang and Shasha (see super::zs),
+// but each pair of subtrees is decomposed along the path that minimizes the number of subproblems,
+// instead of always along left paths.
+// Only left and right paths are considered (no heavy paths, as in APTED),
+// it is enough to avoid the worst cases of Zhang and Shasha on trees leaning to the right.
+
+use std::fmt::Debug;
+
+use num_traits::{cast, PrimInt, ToPrimitive};
+
+use super::zs::{MatcherImpl, ZsMatcherDist};
+use crate::decompressed_tree_store::{DecompressedTreeStore, PostOrderKeyRoots};
+use crate::matchers::mapping_store::MonoMappingStore;
+use hyper_ast::types::{DecompressedSubtree, HyperAST, NodeId, Tree};
+
+pub struct RtedMatcher<M, SD, DD = SD> {
+    pub mappings: M,
+    pub src_arena: SD,
+    pub dst_arena: DD,
+}
+
+impl<SD, DD, M: MonoMappingStore + Default> RtedMatcher<M, SD, DD> {
+    pub fn matchh<'store: 'b, 'b: 'c, 'c, T, HAST>(
+        stores: &'store HAST,
+        src: T::TreeId,
+        dst: T::TreeId,
+    ) -> Self
+    where
+        T::TreeId: Clone,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src> + DecompressedSubtree<'store, T, Out = SD>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst> + DecompressedSubtree<'store, T, Out = DD>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let src_arena = SD::decompress(stores.node_store(), &src);
+        let dst_arena = DD::decompress(stores.node_store(), &dst);
+        let mappings = {
+            let mut mappings = M::default();
+            mappings.topit(src_arena.len(), dst_arena.len());
+            let base = MatcherImpl::<'store, 'b, '_, SD, DD, T, HAST, M>::new(
+                stores, &src_arena, &dst_arena,
+            );
+            let mut dist = compute_dist(&base);
+            base.compute_mappings(&mut mappings, &mut dist);
+            mappings
+        };
+        Self {
+            src_arena,
+            dst_arena,
+            mappings,
+        }
+    }
+
+    pub fn match_with<'store: 'b, 'b, T, HAST>(
+        stores: &'store HAST,
+        src_arena: SD,
+        dst_arena: DD,
+    ) -> M
+    where
+        T::TreeId: Clone + NodeId<IdN = T::TreeId>,
+        M::Src: PrimInt + std::ops::SubAssign + Debug,
+        M::Dst: PrimInt + std::ops::SubAssign + Debug,
+        SD: 'b + PostOrderKeyRoots<'b, T, M::Src>,
+        DD: 'b + PostOrderKeyRoots<'b, T, M::Dst>,
+        T: 'store + Tree,
+        HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    {
+        let mut mappings = M::default();
+        mappings.topit(src_arena.len() + 1, dst_arena.len() + 1);
+        let base =
+            MatcherImpl::<'store, 'b, '_, _, _, T, _, M>::new(stores, &src_arena, &dst_arena);
+        let mut dist = compute_dist(&base);
+        base.compute_mappings(&mut mappings, &mut dist);
+        mappings
+    }
+}
+
+/// Computes the distances between all pairs of subtrees with RTED,
+/// then the forest distances of the roots, as left by [`MatcherImpl::compute_dist`],
+/// so that mappings can be extracted the same way.
+fn compute_dist<'store: 'b, 'b: 'c, 'c, SD, DD, T, HAST, M>(
+    base: &MatcherImpl<'store, 'b, 'c, SD, DD, T, HAST, M>,
+) -> ZsMatcherDist
+where
+    SD: 'c + DecompressedTreeStore<'b, T, M::Src> + PostOrderKeyRoots<'b, T, M::Src>,
+    DD: 'c + DecompressedTreeStore<'b, T, M::Dst> + PostOrderKeyRoots<'b, T, M::Dst>,
+    T: 'store + Tree,
+    T::TreeId: Clone,
+    HAST: HyperAST<'store, IdN = T::TreeId, T = T, Label = T::Label>,
+    M: MonoMappingStore,
+    M::Src: PrimInt + std::ops::SubAssign + Debug,
+    M::Dst: PrimInt + std::ops::SubAssign + Debug,
+{
+    let sa = base.src_arena;
+    let da = base.dst_arena;
+    debug_assert_eq!(sa.root().to_usize(), Some(sa.len() - 1));
+    debug_assert_eq!(da.root().to_usize(), Some(da.len() - 1));
+    let src_lld: Vec<usize> = (0..sa.len())
+        .map(|i| sa.lld(&cast(i).unwrap()).to_usize().unwrap())
+        .collect();
+    let dst_lld: Vec<usize> = (0..da.len())
+        .map(|j| da.lld(&cast(j).unwrap()).to_usize().unwrap())
+        .collect();
+    let src_trees: Vec<T::TreeId> = (0..sa.len()).map(|i| sa.tree(&cast(i).unwrap())).collect();
+    let dst_trees: Vec<T::TreeId> = (0..da.len()).map(|j| da.tree(&cast(j).unwrap())).collect();
+    let del = src_trees
+        .iter()
+        .map(|t| base.get_deletion_cost(t))
+        .collect();
+    let ins = dst_trees
+        .iter()
+        .map(|t| base.get_insertion_cost(t))
+        .collect();
+    let upd = src_trees
+        .iter()
+        .flat_map(|s| dst_trees.iter().map(|d| base.get_update_cost(s, d)))
+        .collect();
+    let td = Rted::new(Shape::new(&src_lld), Shape::new(&dst_lld), del, ins, upd).compute();
+    let m = da.len();
+    let mut tree = vec![vec![0.0; m + 1]; sa.len() + 1];
+    for (x, row) in td.chunks(m).enumerate() {
+        tree[x + 1][1..].copy_from_slice(row);
+    }
+    let mut dist = ZsMatcherDist::with_tree_dist(tree);
+    base.forest_dist(&mut dist, &sa.root(), &da.root());
+    dist
+}
+
+/// Decomposition path of a pair of subtrees,
+/// in the source or destination subtree, along leftmost or rightmost children.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Path {
+    SrcLeft,
+    SrcRight,
+    DstLeft,
+    DstRight,
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// A postorder of a tree, left-to-right or right-to-left.
+struct Order {
+    /// leftmost leaf descendant, in this order
+    ld: Vec<usize>,
+    /// id in the arena, ie. in left-to-right postorder
+    orig: Vec<usize>,
+    /// id in this order of a node of the arena
+    id: Vec<usize>,
+    /// keyroots, ie. root or not the first child of its parent in this order
+    kr: Vec<bool>,
+}
+
+/// Shape of a tree given by the leftmost leaf descendants of its nodes in postorder,
+/// indexed by ids in the arena.
+struct Shape {
+    children: Vec<Vec<usize>>,
+    size: Vec<usize>,
+    /// number of relevant subforests when decomposing along left (resp. right) paths,
+    /// ie. sum of the sizes of the keyroots
+    a_l: Vec<u64>,
+    a_r: Vec<u64>,
+    left: Order,
+    right: Order,
+}
+
+impl Shape {
+    fn new(lld: &[usize]) -> Self {
+        let n = lld.len();
+        let size: Vec<usize> = (0..n).map(|i| i - lld[i] + 1).collect();
+        let mut children = vec![vec![]; n];
+        for i in 0..n {
+            // descendants are contiguous, so the previous sibling ends just before a child's lld
+            let mut end = i;
+            while end > lld[i] {
+                let c = end - 1;
+                children[i].push(c);
+                end = lld[c];
+            }
+            children[i].reverse();
+        }
+        let mut kr_l = vec![false; n];
+        let mut kr_r = vec![false; n];
+        let mut a_l = vec![0; n];
+        let mut a_r = vec![0; n];
+        for i in 0..n {
+            let cs = &children[i];
+            a_l[i] = size[i] as u64;
+            a_r[i] = size[i] as u64;
+            for (k, &c) in cs.iter().enumerate() {
+                kr_l[c] = k != 0;
+                kr_r[c] = k != cs.len() - 1;
+                a_l[i] += a_l[c];
+                a_r[i] += a_r[c];
+            }
+            if let (Some(&lc), Some(&rc)) = (cs.first(), cs.last()) {
+                a_l[i] -= size[lc] as u64;
+                a_r[i] -= size[rc] as u64;
+            }
+        }
+        if n > 0 {
+            kr_l[n - 1] = true;
+            kr_r[n - 1] = true;
+        }
+        let left = Order {
+            ld: lld.to_vec(),
+            orig: (0..n).collect(),
+            id: (0..n).collect(),
+            kr: kr_l,
+        };
+        // right-to-left postorder is the reverse of the left-to-right preorder
+        let mut orig = Vec::with_capacity(n);
+        let mut stack: Vec<usize> = (n > 0).then(|| n - 1).into_iter().collect();
+        while let Some(x) = stack.pop() {
+            orig.push(x);
+            stack.extend(children[x].iter().rev());
+        }
+        orig.reverse();
+        let mut id = vec![0; n];
+        for (k, &x) in orig.iter().enumerate() {
+            id[x] = k;
+        }
+        let right = Order {
+            ld: orig
+                .iter()
+                .enumerate()
+                .map(|(k, &x)| k + 1 - size[x])
+                .collect(),
+            kr: orig.iter().map(|&x| kr_r[x]).collect(),
+            orig,
+            id,
+        };
+        Self {
+            children,
+            size,
+            a_l,
+            a_r,
+            left,
+            right,
+        }
+    }
+
+    fn order(&self, side: Side) -> &Order {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+}
+
+enum Step {
+    Decompose(usize, usize),
+    SinglePath(Path, usize, usize),
+}
+
+struct Rted {
+    src: Shape,
+    dst: Shape,
+    del: Vec<f64>,
+    ins: Vec<f64>,
+    /// by pairs of ids in the arenas
+    upd: Vec<f64>,
+    /// distances between subtrees, by pairs of ids in the arenas
+    td: Vec<f64>,
+    /// distances between forests, by pairs of ids of the current order, shifted by one
+    fd: Vec<f64>,
+}
+
+impl Rted {
+    fn new(src: Shape, dst: Shape, del: Vec<f64>, ins: Vec<f64>, upd: Vec<f64>) -> Self {
+        let n = src.size.len();
+        let m = dst.size.len();
+        Self {
+            src,
+            dst,
+            del,
+            ins,
+            upd,
+            td: vec![0.0; n * m],
+            fd: vec![0.0; (n + 1) * (m + 1)],
+        }
+    }
+
+    /// Distances between all pairs of subtrees, by pairs of ids in the arenas
+    fn compute(mut self) -> Vec<f64> {
+        let n = self.src.size.len();
+        let m = self.dst.size.len();
+        if n == 0 || m == 0 {
+            return self.td;
+        }
+        let strategy = self.strategy();
+        // iterative, trees can be too deep for the stack
+        let mut stack = vec![Step::Decompose(n - 1, m - 1)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Decompose(v, w) => {
+                    let path = strategy[v * m + w];
+                    // after the subtrees hanging off the path
+                    stack.push(Step::SinglePath(path, v, w));
+                    let (shape, mut x) = match path {
+                        Path::SrcLeft | Path::SrcRight => (&self.src, v),
+                        Path::DstLeft | Path::DstRight => (&self.dst, w),
+                    };
+                    loop {
+                        let cs = &shape.children[x];
+                        if cs.is_empty() {
+                            break;
+                        }
+                        let on_path = match path {
+                            Path::SrcLeft | Path::DstLeft => 0,
+                            Path::SrcRight | Path::DstRight => cs.len() - 1,
+                        };
+                        for (k, &c) in cs.iter().enumerate() {
+                            if k != on_path {
+                                stack.push(match path {
+                                    Path::SrcLeft | Path::SrcRight => Step::Decompose(c, w),
+                                    Path::DstLeft | Path::DstRight => Step::Decompose(v, c),
+                                });
+                            }
+                        }
+                        x = cs[on_path];
+                    }
+                }
+                Step::SinglePath(path, v, w) => self.single_path(path, v, w),
+            }
+        }
+        self.td
+    }
+
+    /// Chooses the path of each pair of subtrees minimizing the number of subproblems,
+    /// ie. the number of evaluations of the recurrence in [`Rted::forest_dist`].
+    fn strategy(&self) -> Vec<Path> {
+        let (f, g) = (&self.src, &self.dst);
+        let n = f.size.len();
+        let m = g.size.len();
+        let mut cost = vec![0u64; n * m];
+        // costs of the subtrees hanging off each kind of path
+        let mut s_l = vec![0u64; n * m];
+        let mut s_r = vec![0u64; n * m];
+        let mut t_l = vec![0u64; n * m];
+        let mut t_r = vec![0u64; n * m];
+        let mut strategy = vec![Path::SrcLeft; n * m];
+        for v in 0..n {
+            for w in 0..m {
+                let i = v * m + w;
+                let cv = &f.children[v];
+                if let (Some(&lc), Some(&rc)) = (cv.first(), cv.last()) {
+                    let sum: u64 = cv.iter().map(|c| cost[c * m + w]).sum();
+                    s_l[i] = s_l[lc * m + w] + sum - cost[lc * m + w];
+                    s_r[i] = s_r[rc * m + w] + sum - cost[rc * m + w];
+                }
+                let cw = &g.children[w];
+                if let (Some(&lc), Some(&rc)) = (cw.first(), cw.last()) {
+                    let sum: u64 = cw.iter().map(|c| cost[v * m + c]).sum();
+                    t_l[i] = t_l[v * m + lc] + sum - cost[v * m + lc];
+                    t_r[i] = t_r[v * m + rc] + sum - cost[v * m + rc];
+                }
+                let (size_v, size_w) = (f.size[v] as u64, g.size[w] as u64);
+                let candidates = [
+                    (Path::SrcLeft, size_v * g.a_l[w] + s_l[i]),
+                    (Path::SrcRight, size_v * g.a_r[w] + s_r[i]),
+                    (Path::DstLeft, size_w * f.a_l[v] + t_l[i]),
+                    (Path::DstRight, size_w * f.a_r[v] + t_r[i]),
+                ];
+                let (path, c) = candidates
+                    .into_iter()
+                    .reduce(|a, b| if b.1 < a.1 { b } else { a })
+                    .unwrap();
+                strategy[i] = path;
+                cost[i] = c;
+            }
+        }
+        strategy
+    }
+
+    /// Distances between the subtrees on the path and all the subtrees of the other side,
+    /// the distances of the subtrees hanging off the path must already be computed.
+    fn single_path(&mut self, path: Path, v: usize, w: usize) {
+        let side = match path {
+            Path::SrcLeft | Path::DstLeft => Side::Left,
+            Path::SrcRight | Path::DstRight => Side::Right,
+        };
+        let v = self.src.order(side).id[v];
+        let w = self.dst.order(side).id[w];
+        match path {
+            Path::SrcLeft | Path::SrcRight => {
+                let o = self.dst.order(side);
+                let krs: Vec<usize> = (o.ld[w]..=w).filter(|&k| o.kr[k] || k == w).collect();
+                for k in krs {
+                    self.forest_dist(side, v, k);
+                }
+            }
+            Path::DstLeft | Path::DstRight => {
+                let o = self.src.order(side);
+                let krs: Vec<usize> = (o.ld[v]..=v).filter(|&k| o.kr[k] || k == v).collect();
+                for k in krs {
+                    self.forest_dist(side, k, w);
+                }
+            }
+        }
+    }
+
+    /// Same recurrence as [`MatcherImpl::forest_dist`] on the given postorders.
+    fn forest_dist(&mut self, side: Side, i: usize, j: usize) {
+        let Self {
+            src,
+            dst,
+            del,
+            ins,
+            upd,
+            td,
+            fd,
+        } = self;
+        let f = src.order(side);
+        let g = dst.order(side);
+        let m = dst.size.len();
+        let w = m + 1;
+        let li = f.ld[i];
+        let lj = g.ld[j];
+        fd[li * w + lj] = 0.0;
+        for di in li..=i {
+            let x = f.orig[di];
+            let cost_del = del[x];
+            fd[(di + 1) * w + lj] = fd[di * w + lj] + cost_del;
+            for dj in lj..=j {
+                let y = g.orig[dj];
+                let cost_ins = ins[y];
+                fd[li * w + dj + 1] = fd[li * w + dj] + cost_ins;
+                let d = f64::min(
+                    fd[di * w + dj + 1] + cost_del,
+                    fd[(di + 1) * w + dj] + cost_ins,
+                );
+                fd[(di + 1) * w + dj + 1] = if f.ld[di] == li && g.ld[dj] == lj {
+                    let d = f64::min(d, fd[di * w + dj] + upd[x * m + y]);
+                    td[x * m + y] = d;
+                    d
+                } else {
+                    f64::min(d, fd[f.ld[di] * w + g.ld[dj]] + td[x * m + y])
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// lld of a tree given in postorder by the number of children of each node
+    fn lld(arities: &[usize]) -> Vec<usize> {
+        let mut lld = vec![];
+        let mut roots: Vec<usize> = vec![];
+        for (i, &a) in arities.iter().enumerate() {
+            let cs = roots.split_off(roots.len() - a);
+            lld.push(cs.first().map_or(i, |&c| lld[c]));
+            roots.push(i);
+        }
+        lld
+    }
+
+    #[test]
+    fn shape() {
+        // a(b, c(d, e), f)
+        let s = Shape::new(&lld(&[0, 0, 0, 2, 0, 3]));
+        assert_eq!(s.children[5], vec![0, 3, 4]);
+        assert_eq!(s.children[3], vec![1, 2]);
+        assert_eq!(s.left.kr, vec![false, false, true, true, true, true]);
+        assert_eq!(s.a_l[5], 6 + 3 + 1 + 1);
+        assert_eq!(s.a_r[5], 6 + 1 + 3 + 1);
+        // f, e, d, c, b, a
+        assert_eq!(s.right.orig, vec![4, 2, 1, 3, 0, 5]);
+        assert_eq!(s.right.ld, vec![0, 1, 2, 1, 4, 0]);
+        assert_eq!(s.right.kr, vec![false, false, true, true, true, true]);
+    }
+
+    #[test]
+    fn right_comb() {
+        // right combs are decomposed along right paths
+        let arities: Vec<usize> = [0; 11].into_iter().chain([2; 10]).collect();
+        let n = arities.len();
+        let shape = || Shape::new(&lld(&arities));
+        let rted = Rted::new(
+            shape(),
+            shape(),
+            vec![1.0; n],
+            vec![1.0; n],
+            vec![0.0; n * n],
+        );
+        assert_eq!(rted.strategy()[n * n - 1], Path::SrcRight);
+        let td = rted.compute();
+        assert_eq!(td[n * n - 1], 0.0);
+        assert_eq!(td[(n - 1) * n], (n - 1) as f64);
+    }
+}
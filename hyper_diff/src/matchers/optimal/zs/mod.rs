@@ -121,15 +121,24 @@ where
     M::Src: PrimInt + std::ops::SubAssign + Debug,
     M::Dst: PrimInt + std::ops::SubAssign + Debug,
 {
-    fn get_deletion_cost(&self, _di: &T::TreeId) -> f64 {
+    pub(crate) fn new(stores: &'store HAST, src_arena: &'c SD, dst_arena: &'c DD) -> Self {
+        Self {
+            stores,
+            src_arena,
+            dst_arena,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn get_deletion_cost(&self, _di: &T::TreeId) -> f64 {
         1.0
     }
 
-    fn get_insertion_cost(&self, _dj: &T::TreeId) -> f64 {
+    pub(crate) fn get_insertion_cost(&self, _dj: &T::TreeId) -> f64 {
         1.0
     }
 
-    fn get_update_cost(
+    pub(crate) fn get_update_cost(
         &self, //cache: &mut Cache<LS::I>,
         r1: &T::TreeId,
         r2: &T::TreeId,
@@ -197,6 +206,13 @@ pub struct ZsMatcherDist {
 
 // TODO make a fully typed interface to each dist
 impl ZsMatcherDist {
+    /// Given the distances between subtrees, eg. computed by [`super::rted`],
+    /// indexed by postorder ids shifted by one.
+    pub(crate) fn with_tree_dist(tree: Vec<Vec<f64>>) -> Self {
+        let forest = vec![vec![0.0; tree[0].len()]; tree.len()];
+        Self { tree, forest }
+    }
+
     fn f_dist<IdD1: PrimInt, IdD2: PrimInt>(&self, row: IdD1, col: IdD2) -> f64 {
         self.forest[row.to_usize().unwrap()][col.to_usize().unwrap()]
     }
@@ -0,0 +1,57 @@
+use std::marker::PhantomData;
+
+use crate::{
+    decompressed_tree_store::{CompletePostOrder, ShallowDecompressedTreeStore},
+    matchers::{
+        heuristic::cd::{bottom_up_matcher::BottomUpMatcher, leaves_matcher::LeavesMatcher},
+        mapping_store::{DefaultMappingStore, MappingStore},
+        Mapper, Mapping,
+    },
+    tests::examples::example_gt_java_code,
+    tree::{simple_tree::vpair_to_stores, TStore},
+};
+use hyper_ast::types::{HyperAST, SimpleHyperAST};
+
+#[test]
+fn test_with_custom_example() {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example_gt_java_code());
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let mapper: Mapper<
+        _,
+        CompletePostOrder<_, u16>,
+        CompletePostOrder<_, u16>,
+        DefaultMappingStore<u16>,
+    > = stores.decompress_pair(&src, &dst).into();
+    let mapper = LeavesMatcher::match_it(mapper);
+    // b, d and f, but not r1 and r2
+    assert_eq!(3, mapper.mappings().len());
+    let mapper = BottomUpMatcher::match_it(mapper);
+    let Mapper {
+        mapping:
+            Mapping {
+                src_arena,
+                dst_arena,
+                mappings,
+            },
+        ..
+    } = mapper;
+    let node_store = &stores.node_store;
+    let src = &src_arena.root();
+    let dst = &dst_arena.root();
+    assert_eq!(5, mappings.len());
+    assert!(mappings.has(src, &dst_arena.child(node_store, dst, &[0])));
+    assert!(mappings.has(
+        &src_arena.child(node_store, src, &[1]),
+        &dst_arena.child(node_store, dst, &[0, 1])
+    ));
+    assert!(mappings.has(
+        &src_arena.child(node_store, src, &[1, 2]),
+        &dst_arena.child(node_store, dst, &[0, 1, 2])
+    ));
+    assert!(!mappings.is_src(&src_arena.child(node_store, src, &[1, 3])));
+}
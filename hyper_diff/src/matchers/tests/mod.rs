@@ -1,5 +1,9 @@
+#[cfg(test)]
+pub mod changedistiller_tests;
 // TODO put it back
 // #[cfg(test)]
 // pub mod gumtree_tests;
 #[cfg(test)]
+pub mod simple_bottom_up_tests;
+#[cfg(test)]
 pub mod zs_tests;
@@ -0,0 +1,148 @@
+use std::marker::PhantomData;
+
+use crate::{
+    decompressed_tree_store::{CompletePostOrder, ShallowDecompressedTreeStore},
+    matchers::{
+        heuristic::gt::{
+            greedy_subtree_matcher::GreedySubtreeMatcher,
+            hybrid_bottom_up_matcher::HybridBottomUpMatcher,
+            simple_bottom_up_matcher::SimpleBottomUpMatcher,
+        },
+        mapping_store::{DefaultMappingStore, DefaultMultiMappingStore, MappingStore},
+        GumtreeParams, Mapper, Mapping,
+    },
+    tests::examples::example_gumtree,
+    tree::{simple_tree::vpair_to_stores, TStore},
+};
+use hyper_ast::types::{HyperAST, SimpleHyperAST};
+
+#[test]
+fn test_simple_with_gumtree_example() {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example_gumtree());
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let mut mapper: Mapper<
+        _,
+        CompletePostOrder<_, u16>,
+        CompletePostOrder<_, u16>,
+        DefaultMappingStore<u16>,
+    > = stores.decompress_pair(&src, &dst).into();
+    mapper.params = GumtreeParams::simple();
+    let mapper =
+        GreedySubtreeMatcher::<_, _, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    // b, c and d
+    assert_eq!(3, mapper.mappings().len());
+    let mapper = SimpleBottomUpMatcher::<_, _, _, _, _>::match_it(mapper);
+    let Mapper {
+        mapping:
+            Mapping {
+                src_arena,
+                dst_arena,
+                mappings,
+            },
+        ..
+    } = mapper;
+    let node_store = &stores.node_store;
+    let src = &src_arena.root();
+    let dst = &dst_arena.root();
+    let from_src = |path: &[u8]| src_arena.child(node_store, src, path);
+    let from_dst = |path: &[u8]| dst_arena.child(node_store, dst, path);
+    // roots, then g through the lcs of the last chance match,
+    // e has no candidate as none of its descendants are mapped
+    assert_eq!(5, mappings.len());
+    assert!(mappings.has(src, dst));
+    assert!(mappings.has(&from_src(&[1]), &from_dst(&[0])));
+    assert!(mappings.has(&from_src(&[2]), &from_dst(&[2])));
+    assert!(!mappings.is_src(&from_src(&[0])));
+    assert!(!mappings.is_src(&from_src(&[0, 0])));
+}
+
+#[test]
+fn test_hybrid_with_gumtree_example() {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example_gumtree());
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let mut mapper: Mapper<
+        _,
+        CompletePostOrder<_, u16>,
+        CompletePostOrder<_, u16>,
+        DefaultMappingStore<u16>,
+    > = stores.decompress_pair(&src, &dst).into();
+    mapper.params = GumtreeParams::hybrid();
+    let mapper =
+        GreedySubtreeMatcher::<_, _, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    let mapper = HybridBottomUpMatcher::<_, _, _, _, _>::match_it(mapper);
+    let Mapper {
+        mapping:
+            Mapping {
+                src_arena,
+                dst_arena,
+                mappings,
+            },
+        ..
+    } = mapper;
+    let node_store = &stores.node_store;
+    let src = &src_arena.root();
+    let dst = &dst_arena.root();
+    let from_src = |path: &[u8]| src_arena.child(node_store, src, path);
+    let from_dst = |path: &[u8]| dst_arena.child(node_store, dst, path);
+    // trees are smaller than the size threshold, so the optimal matcher completes the mappings,
+    // it maps g but not e, that would cross b
+    assert_eq!(5, mappings.len());
+    assert!(mappings.has(src, dst));
+    assert!(mappings.has(&from_src(&[1]), &from_dst(&[0])));
+    assert!(mappings.has(&from_src(&[1, 0]), &from_dst(&[0, 0])));
+    assert!(mappings.has(&from_src(&[1, 1]), &from_dst(&[0, 1])));
+    assert!(mappings.has(&from_src(&[2]), &from_dst(&[2])));
+    assert!(!mappings.is_src(&from_src(&[0])));
+    assert!(!mappings.is_src(&from_src(&[0, 0])));
+    assert!(!mappings.is_dst(&from_dst(&[1])));
+    assert!(!mappings.is_dst(&from_dst(&[1, 0])));
+    assert!(!mappings.is_dst(&from_dst(&[1, 0, 0])));
+}
+
+#[test]
+fn test_min_height_param() {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example_gumtree());
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let subtree_mappings = |min_height| {
+        let mut mapper: Mapper<
+            _,
+            CompletePostOrder<_, u16>,
+            CompletePostOrder<_, u16>,
+            DefaultMappingStore<u16>,
+        > = stores.decompress_pair(&src, &dst).into();
+        mapper.params = GumtreeParams {
+            min_height,
+            ..Default::default()
+        };
+        let mapper =
+            GreedySubtreeMatcher::<_, _, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+        let node_store = &stores.node_store;
+        let src_arena = &mapper.mapping.src_arena;
+        let dst_arena = &mapper.mapping.dst_arena;
+        let g_src = src_arena.child(node_store, &src_arena.root(), &[2]);
+        let g_dst = dst_arena.child(node_store, &dst_arena.root(), &[2]);
+        let g_mapped = mapper.mappings().has(&g_src, &g_dst);
+        (mapper.mappings().len(), g_mapped)
+    };
+    // leaves are also matched, so g is mapped along b, c and d
+    assert_eq!((4, true), subtree_mappings(0));
+    // only b, c and d
+    assert_eq!((3, false), subtree_mappings(1));
+    // b is not high enough
+    assert_eq!((0, false), subtree_mappings(2));
+}
@@ -4,9 +4,12 @@ use crate::{
     decompressed_tree_store::{ShallowDecompressedTreeStore, SimpleZsTree},
     matchers::{
         mapping_store::{DefaultMappingStore, MappingStore},
-        optimal::zs::ZsMatcher,
+        optimal::{rted::RtedMatcher, zs::ZsMatcher},
+    },
+    tests::examples::{
+        example_action, example_gt_java_code, example_gt_slides, example_gumtree, example_move,
+        example_simple, example_zs_paper,
     },
-    tests::examples::{example_gt_java_code, example_gt_slides, example_zs_paper},
     tree::{
         simple_tree::{vpair_to_stores, DisplayTree},
         TStore,
@@ -217,3 +220,63 @@ fn test_with_slide_example2() {
         &dst_arena.child(node_store, dst, &[2])
     ));
 }
+
+#[test]
+fn test_rted_with_custom_example() {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example_gt_java_code());
+
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let mapper =
+        RtedMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::matchh(&stores, src, dst);
+    let RtedMatcher {
+        src_arena,
+        dst_arena,
+        mappings,
+    } = mapper;
+    let node_store = &stores.node_store;
+    let src = &src_arena.root();
+    let dst = &dst_arena.root();
+    assert_eq!(6, mappings.src_to_dst.iter().filter(|x| **x != 0).count());
+    assert!(mappings.has(
+        &src_arena.child(node_store, src, &[0]),
+        &dst_arena.child(node_store, dst, &[0, 0])
+    ));
+    assert!(mappings.has(
+        &src_arena.child(node_store, src, &[1, 3]),
+        &dst_arena.child(node_store, dst, &[0, 1, 3])
+    ));
+}
+
+#[test]
+fn test_rted_same_as_zs() {
+    let examples = [
+        example_zs_paper,
+        example_gt_java_code,
+        example_gt_slides,
+        example_simple,
+        example_move,
+        example_gumtree,
+        example_action,
+    ];
+    for example in examples {
+        let (label_store, node_store, src, dst) = vpair_to_stores(example());
+        let stores = SimpleHyperAST {
+            type_store: TStore,
+            node_store,
+            label_store,
+            _phantom: PhantomData,
+        };
+        let zs =
+            ZsMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::matchh(&stores, src, dst);
+        let rted = RtedMatcher::<DefaultMappingStore<u16>, SimpleZsTree<_, u16>>::matchh(
+            &stores, src, dst,
+        );
+        assert_eq!(zs.mappings.src_to_dst, rted.mappings.src_to_dst);
+        assert_eq!(zs.mappings.dst_to_src, rted.mappings.dst_to_src);
+    }
+}
@@ -0,0 +1,548 @@
+//! Three-way structural merge of HyperAST trees.
+//!
+//! Both changed versions are mapped to their common ancestor with the gumtree matchers,
+//! then changes are merged node by node going down from the roots.
+//! Identical subtrees being shared in a HyperAST,
+//! a subtree left unchanged by a side is detected by comparing identifiers,
+//! so only the parts of the trees changed on both sides are visited.
+//!
+//! The result is a [`Merged`] tree referencing existing subtrees,
+//! it can be built in a node store with [`Merged::build`]
+//! or compared with an existing tree with [`Merged::same_as`],
+//! for example the tree of an actual merge commit.
+use std::{collections::HashSet, fmt::Debug};
+
+use hyper_ast::{
+    position::{compute_position, Position},
+    types::{
+        self, HyperAST, IterableChildren, Labeled, NodeId, NodeStore, NodeStoreExt, Typed,
+        WithChildren, WithSerialization,
+    },
+};
+
+use crate::{
+    decompressed_tree_store::{
+        CompletePostOrder, DecompressedWithParent, PostOrderIterable, ShallowDecompressedTreeStore,
+    },
+    matchers::{
+        heuristic::gt::{
+            greedy_bottom_up_matcher::GreedyBottomUpMatcher,
+            greedy_subtree_matcher::GreedySubtreeMatcher,
+        },
+        mapping_store::{DefaultMultiMappingStore, MonoMappingStore, VecStore},
+        Mapper, Mapping,
+    },
+};
+
+type CDS<T> = CompletePostOrder<T, u32>;
+
+/// A merged tree, sharing the subtrees of the merged versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Merged<IdN, L> {
+    /// an existing subtree
+    Kept(IdN),
+    /// a node with the type of `template` but with a new label or new children
+    Built {
+        template: IdN,
+        label: Option<L>,
+        children: Vec<Merged<IdN, L>>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+    /// both sides changed the label of a node differently, the left label is kept
+    UpdateUpdate,
+    /// a side deleted a node modified by the other side, the node is kept
+    DeleteModify,
+    /// both sides inserted nodes at the same place, right insertions come first
+    InsertInsert,
+    /// both sides reordered children of a node differently, the left order is kept
+    ReorderReorder,
+    /// both sides moved a node to different places, the first place is kept
+    MoveMove,
+}
+
+/// A conflict on a node, located by its path from the root of each version where it exists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict<Idx> {
+    pub kind: ConflictKind,
+    pub base: Option<Vec<Idx>>,
+    pub left: Option<Vec<Idx>>,
+    pub right: Option<Vec<Idx>>,
+}
+
+impl<Idx: Clone> Conflict<Idx> {
+    /// Positions of the conflicting node in each version,
+    /// the trees must be serializable to compute offsets
+    pub fn positions<'store, HAST>(
+        &self,
+        hyperast: &'store HAST,
+        base: &HAST::IdN,
+        left: &HAST::IdN,
+        right: &HAST::IdN,
+    ) -> [Option<Position>; 3]
+    where
+        HAST: HyperAST<'store, Idx = Idx>,
+        HAST::IdN: Clone,
+        HAST::T: WithSerialization,
+    {
+        let position = |root: &HAST::IdN, path: &Option<Vec<Idx>>| {
+            let path = path.as_ref()?;
+            Some(compute_position(root.clone(), &mut path.iter().cloned(), hyperast).0)
+        };
+        [
+            position(base, &self.base),
+            position(left, &self.left),
+            position(right, &self.right),
+        ]
+    }
+}
+
+pub struct MergeResult<IdN, L, Idx> {
+    pub merged: Merged<IdN, L>,
+    pub conflicts: Vec<Conflict<Idx>>,
+}
+
+/// Merges the changes from `base` to `left` and from `base` to `right`.
+///
+/// Conflicting changes are resolved in favor of `left`, see [`ConflictKind`],
+/// and reported in [`MergeResult::conflicts`].
+pub fn merge<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    base: &HAST::IdN,
+    left: &HAST::IdN,
+    right: &HAST::IdN,
+) -> MergeResult<HAST::IdN, HAST::Label, HAST::Idx>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::Label: Debug + Clone + Copy + Eq,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let left = map(hyperast, base, left);
+    let right = map(hyperast, base, right);
+    let mut merger = Merger {
+        hyperast,
+        left: &left,
+        right: &right,
+        placed: HashSet::new(),
+        visit_left: HashSet::new(),
+        visit_right: HashSet::new(),
+        conflicts: vec![],
+    };
+    merger.visit_left = merger.moved_and_modified(&left, &right);
+    merger.visit_right = merger.moved_and_modified(&right, &left);
+    let b = left.src_arena.root();
+    merger.placed.insert(b);
+    let merged = merger.merge_node(b, left.dst_arena.root(), right.dst_arena.root());
+    MergeResult {
+        merged,
+        conflicts: merger.conflicts,
+    }
+}
+
+/// Maps `base` to `other` like [`crate::algorithms::gumtree::diff`]
+fn map<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    base: &HAST::IdN,
+    other: &HAST::IdN,
+) -> Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::Label: Debug + Clone + Copy,
+    HAST::T: 'store + types::WithHashs + types::WithStats,
+{
+    let mapper: Mapper<_, CDS<HAST::T>, CDS<HAST::T>, VecStore<_>> =
+        hyperast.decompress_pair(base, other).into();
+    let mapper =
+        GreedySubtreeMatcher::<_, _, _, _, _>::match_it::<DefaultMultiMappingStore<_>>(mapper);
+    let mapper = GreedyBottomUpMatcher::<_, _, _, _, _>::match_it(mapper);
+    mapper.mapping
+}
+
+/// A child in a merged list of children
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    /// a node of base, possibly modified by a side
+    Base(u32),
+    /// a node inserted by left
+    Left(u32),
+    /// a node inserted by right
+    Right(u32),
+}
+
+impl Key {
+    fn base(&self) -> Option<u32> {
+        match self {
+            Key::Base(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// key of the node `x` of a side, `side` being used if `x` is not mapped to base
+    fn of<T>(m: &Mapping<T, T, VecStore<u32>>, side: fn(u32) -> Key, x: u32) -> Key {
+        m.mappings.get_src(&x).map_or(side(x), Key::Base)
+    }
+}
+
+struct Merger<'a, 'store, HAST: HyperAST<'store>> {
+    hyperast: &'store HAST,
+    /// base is the src of both mappings
+    left: &'a Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    right: &'a Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    /// nodes of base already put in the merged tree
+    placed: HashSet<u32>,
+    /// nodes of left, and their ancestors, moved by left and modified by right,
+    /// they cannot be kept as is without losing the changes of right
+    visit_left: HashSet<u32>,
+    /// same as `visit_left` for nodes moved by right
+    visit_right: HashSet<u32>,
+    conflicts: Vec<Conflict<HAST::Idx>>,
+}
+
+impl<'a, 'store, HAST: HyperAST<'store>> Merger<'a, 'store, HAST>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Idx: hyper_ast::PrimInt,
+    HAST::Label: Debug + Clone + Copy + Eq,
+    HAST::T: 'store,
+{
+    fn merge_node(&mut self, b: u32, l: u32, r: u32) -> Merged<HAST::IdN, HAST::Label> {
+        let base = self.left.src_arena.original(&b);
+        let left = self.left.dst_arena.original(&l);
+        let right = self.right.dst_arena.original(&r);
+        if left == right || (right == base && !self.visit_left.contains(&l)) {
+            return Merged::Kept(left);
+        } else if left == base && !self.visit_right.contains(&r) {
+            return Merged::Kept(right);
+        }
+        let label = self.merge_label(b, &base, &left, &right);
+        let children = self.merge_children(b, l, r);
+        if let Some(same) = [left.clone(), right]
+            .into_iter()
+            .find(|x| self.label(x) == label && self.reuses(x, &children))
+        {
+            return Merged::Kept(same);
+        }
+        Merged::Built {
+            template: left,
+            label,
+            children,
+        }
+    }
+
+    fn label(&self, id: &HAST::IdN) -> Option<HAST::Label> {
+        self.hyperast
+            .node_store()
+            .resolve(id)
+            .try_get_label()
+            .copied()
+    }
+
+    /// true if `children` are exactly the children of `id`
+    fn reuses(&self, id: &HAST::IdN, children: &[Merged<HAST::IdN, HAST::Label>]) -> bool {
+        let node = self.hyperast.node_store().resolve(id);
+        let Some(cs) = node.children() else {
+            return children.is_empty();
+        };
+        let cs: Vec<_> = cs.iter_children().collect();
+        cs.len() == children.len()
+            && cs
+                .into_iter()
+                .zip(children)
+                .all(|(c, x)| matches!(x, Merged::Kept(x) if x == c))
+    }
+
+    fn merge_label(
+        &mut self,
+        b: u32,
+        base: &HAST::IdN,
+        left: &HAST::IdN,
+        right: &HAST::IdN,
+    ) -> Option<HAST::Label> {
+        let base = self.label(base);
+        let left = self.label(left);
+        let right = self.label(right);
+        if left == base {
+            right
+        } else if right == base || right == left {
+            left
+        } else {
+            self.conflict(ConflictKind::UpdateUpdate, b);
+            left
+        }
+    }
+
+    fn merge_children(&mut self, b: u32, l: u32, r: u32) -> Vec<Merged<HAST::IdN, HAST::Label>> {
+        let node_store = self.hyperast.node_store();
+        let base: Vec<Key> = self
+            .left
+            .src_arena
+            .children(node_store, &b)
+            .into_iter()
+            .map(Key::Base)
+            .collect();
+        let left: Vec<Key> = (self.left.dst_arena.children(node_store, &l).into_iter())
+            .map(|x| Key::of(self.left, Key::Left, x))
+            .collect();
+        let right: Vec<Key> = (self.right.dst_arena.children(node_store, &r).into_iter())
+            .map(|x| Key::of(self.right, Key::Right, x))
+            .collect();
+        let keys = if left == base && !self.deletes_modified(&left, &right, self.left, self.right) {
+            right
+        } else if right == base && !self.deletes_modified(&right, &left, self.right, self.left) {
+            left
+        } else {
+            self.merge_keys(b, &base, &left, &right)
+        };
+        keys.into_iter()
+            .filter_map(|k| self.merge_child(k))
+            .collect()
+    }
+
+    /// true if a child in `kept` is modified by `kept_by`
+    /// while being deleted in `other` by `deleted_by`
+    fn deletes_modified(
+        &self,
+        kept: &[Key],
+        other: &[Key],
+        kept_by: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+        deleted_by: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    ) -> bool {
+        kept.iter().filter_map(Key::base).any(|x| {
+            !other.contains(&Key::Base(x))
+                && !deleted_by.mappings.is_src(&x)
+                && self.modified(kept_by, x)
+        })
+    }
+
+    /// Merges lists of children, starting from left then adding what was changed by right
+    fn merge_keys(&mut self, b: u32, base: &[Key], left: &[Key], right: &[Key]) -> Vec<Key> {
+        let in_base: HashSet<u32> = base.iter().filter_map(Key::base).collect();
+        let in_left: HashSet<u32> = left.iter().filter_map(Key::base).collect();
+        let in_right: HashSet<u32> = right.iter().filter_map(Key::base).collect();
+
+        let common = |keys: &[Key]| -> Vec<u32> {
+            keys.iter()
+                .filter_map(Key::base)
+                .filter(|x| in_base.contains(x) && in_left.contains(x) && in_right.contains(x))
+                .collect()
+        };
+        let (common_b, common_l, common_r) = (common(base), common(left), common(right));
+        if common_l != common_b && common_r != common_b && common_l != common_r {
+            self.conflict(ConflictKind::ReorderReorder, b);
+        }
+
+        let mut result = vec![];
+        for k in left {
+            if let Key::Base(x) = k {
+                if in_base.contains(x) && !in_right.contains(x) {
+                    // removed by right, either deleted or moved elsewhere
+                    if self.right.mappings.is_src(x) || !self.modified(self.left, *x) {
+                        continue;
+                    }
+                    self.conflict(ConflictKind::DeleteModify, *x);
+                }
+            }
+            result.push(*k);
+        }
+
+        let is_left_insertion = |k: &Key| match k {
+            Key::Left(_) => true,
+            Key::Base(x) => !in_base.contains(x),
+            Key::Right(_) => false,
+        };
+        let mut anchor: Option<Key> = None;
+        let mut reported = false;
+        for k in right {
+            let insert = match k {
+                Key::Base(x) if in_left.contains(x) => false,
+                Key::Base(x) if in_base.contains(x) => {
+                    // removed by left, either deleted or moved elsewhere
+                    let deleted = !self.left.mappings.is_src(x);
+                    if deleted && self.modified(self.right, *x) {
+                        self.conflict(ConflictKind::DeleteModify, *x);
+                        true
+                    } else {
+                        false
+                    }
+                }
+                _ => true,
+            };
+            if insert {
+                let at = anchor
+                    .and_then(|a| result.iter().position(|x| *x == a))
+                    .map_or(0, |i| i + 1);
+                let after_right = anchor.map_or(false, |a| !left.contains(&a));
+                if !reported && !after_right && result.get(at).map_or(false, is_left_insertion) {
+                    reported = true;
+                    self.conflict(ConflictKind::InsertInsert, b);
+                }
+                result.insert(at, *k);
+            }
+            if result.contains(k) {
+                anchor = Some(*k);
+            }
+        }
+        result
+    }
+
+    fn merge_child(&mut self, k: Key) -> Option<Merged<HAST::IdN, HAST::Label>> {
+        let x = match k {
+            Key::Left(x) if self.visit_left.contains(&x) => {
+                return Some(self.merge_inserted(self.left, Key::Left, x))
+            }
+            Key::Right(x) if self.visit_right.contains(&x) => {
+                return Some(self.merge_inserted(self.right, Key::Right, x))
+            }
+            Key::Left(x) => return Some(Merged::Kept(self.left.dst_arena.original(&x))),
+            Key::Right(x) => return Some(Merged::Kept(self.right.dst_arena.original(&x))),
+            Key::Base(x) => x,
+        };
+        if !self.placed.insert(x) {
+            self.conflict(ConflictKind::MoveMove, x);
+            return None;
+        }
+        let l = self.left.mappings.get_dst(&x);
+        let r = self.right.mappings.get_dst(&x);
+        match (l, r) {
+            (Some(l), Some(r)) => Some(self.merge_node(x, l, r)),
+            (Some(l), None) if self.visit_left.contains(&l) => {
+                Some(self.merge_inserted(self.left, Key::Left, l))
+            }
+            (None, Some(r)) if self.visit_right.contains(&r) => {
+                Some(self.merge_inserted(self.right, Key::Right, r))
+            }
+            (Some(l), None) => Some(Merged::Kept(self.left.dst_arena.original(&l))),
+            (None, Some(r)) => Some(Merged::Kept(self.right.dst_arena.original(&r))),
+            (None, None) => None,
+        }
+    }
+
+    /// Merges the node `x` only present in the side of `m`,
+    /// going through its children to reach the nodes it contains that the other side modified
+    fn merge_inserted(
+        &mut self,
+        m: &'a Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+        side: fn(u32) -> Key,
+        x: u32,
+    ) -> Merged<HAST::IdN, HAST::Label> {
+        let id = m.dst_arena.original(&x);
+        let children: Vec<_> = (m.dst_arena.children(self.hyperast.node_store(), &x))
+            .into_iter()
+            .filter_map(|c| self.merge_child(Key::of(m, side, c)))
+            .collect();
+        if self.reuses(&id, &children) {
+            return Merged::Kept(id);
+        }
+        Merged::Built {
+            label: self.label(&id),
+            template: id,
+            children,
+        }
+    }
+
+    /// Nodes of the dst of `moving`, with their ancestors,
+    /// that are moved by `moving` while being modified by `other`
+    fn moved_and_modified(
+        &self,
+        moving: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+        other: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    ) -> HashSet<u32> {
+        let mut result = HashSet::new();
+        for b in moving.src_arena.iter_df_post::<true>() {
+            let (Some(p), Some(d)) = (moving.src_arena.parent(&b), moving.mappings.get_dst(&b))
+            else {
+                continue;
+            };
+            let moved = moving.mappings.get_dst(&p) != moving.dst_arena.parent(&d);
+            if moved && self.modified(other, b) {
+                result.insert(d);
+                result.extend(moving.dst_arena.parents(d));
+            }
+        }
+        result
+    }
+
+    /// true if the subtree of `b` in base is not the same in the other version of `mapping`
+    fn modified(
+        &self,
+        mapping: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+        b: u32,
+    ) -> bool {
+        mapping.mappings.get_dst(&b).map_or(false, |x| {
+            mapping.dst_arena.original(&x) != mapping.src_arena.original(&b)
+        })
+    }
+
+    fn conflict(&mut self, kind: ConflictKind, b: u32) {
+        let path = |m: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>| {
+            m.mappings.get_dst(&b).map(|x| m.dst_arena.path_rooted(&x))
+        };
+        self.conflicts.push(Conflict {
+            kind,
+            base: Some(self.left.src_arena.path_rooted(&b)),
+            left: path(self.left),
+            right: path(self.right),
+        });
+    }
+}
+
+impl<IdN: Clone + Eq, L: Clone + Eq> Merged<IdN, L> {
+    /// Builds the merged tree in `store`,
+    /// it requires the same capabilities as [`crate::actions::action_vec::apply_actions`]
+    pub fn build<T, S>(&self, store: &mut S) -> IdN
+    where
+        T: types::TypedTree<TreeId = IdN, Label = L>,
+        IdN: NodeId<IdN = IdN>,
+        S: NodeStoreExt<T> + NodeStore<IdN>,
+        for<'d> S::R<'d>: types::TypedTree<TreeId = IdN, Type = T::Type, Label = L>,
+    {
+        match self {
+            Merged::Kept(x) => x.clone(),
+            Merged::Built {
+                template,
+                label,
+                children,
+            } => {
+                let children = children.iter().map(|x| x.build(store)).collect();
+                let t = store.resolve(template).get_type();
+                store.build_then_insert(template.clone(), t, label.clone(), children)
+            }
+        }
+    }
+
+    /// true if the merged tree is the same as the existing tree `other`,
+    /// without building it
+    pub fn same_as<'store, HAST>(&self, hyperast: &'store HAST, other: &IdN) -> bool
+    where
+        HAST: HyperAST<'store, IdN = IdN, Label = L>,
+    {
+        match self {
+            Merged::Kept(x) => x == other,
+            Merged::Built {
+                template,
+                label,
+                children,
+            } => {
+                if hyperast.resolve_type(template) != hyperast.resolve_type(other) {
+                    return false;
+                }
+                let node = hyperast.node_store().resolve(other);
+                if node.try_get_label() != label.as_ref() {
+                    return false;
+                }
+                let cs: Vec<IdN> = node
+                    .children()
+                    .map_or(vec![], |cs| cs.iter_children().cloned().collect());
+                cs.len() == children.len()
+                    && children
+                        .iter()
+                        .zip(cs.iter())
+                        .all(|(x, c)| x.same_as(hyperast, c))
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Groups the atomic actions of an edit script on java code into higher level changes,
+//! such as renaming a method or extracting a method.
+//!
+//! Refactorings are recognised using the mappings that produced the edit script,
+//! then the actions located in the concerned subtrees are attached to them.
+use std::fmt::Debug;
+
+use hyper_ast::types::{self, HyperAST, HyperType, Labeled, NodeStore, WithChildren};
+use hyper_ast_gen_ts_java::types::Type;
+
+use crate::{
+    actions::{
+        action_vec::ActionsVec,
+        script_generator2::{Act, SimpleAction},
+    },
+    decompressed_tree_store::{
+        CompletePostOrder, ContiguousDescendants, DecompressedWithParent, PostOrderIterable,
+        ShallowDecompressedTreeStore,
+    },
+    matchers::{
+        mapping_store::{MonoMappingStore, VecStore},
+        Mapping,
+    },
+    tree::tree_path::CompressedTreePath,
+};
+
+type CDS<T> = CompletePostOrder<T, u32>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    RenameMethod,
+    RenameClass,
+    RenameVariable,
+    /// `src` is the method from which code was extracted, `dst` is the new method
+    ExtractMethod,
+    /// the method is mapped, but not their enclosing type declarations
+    MoveMethod,
+    ChangeParameterList,
+    AddImport,
+    RemoveImport,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refactoring {
+    pub kind: Kind,
+    /// concerned node in the src arena
+    pub src: Option<u32>,
+    /// concerned node in the dst arena
+    pub dst: Option<u32>,
+    /// indexes of the underlying atomic actions in the edit script
+    pub actions: Vec<usize>,
+}
+
+impl Refactoring {
+    pub fn actions<'a, A>(
+        &'a self,
+        actions: &'a ActionsVec<A>,
+    ) -> impl Iterator<Item = &'a A> + 'a {
+        self.actions.iter().map(|i| &actions.0[*i])
+    }
+}
+
+/// Recognises refactorings from the `mapping` and `actions` given by a diff,
+/// e.g. [`crate::algorithms::gumtree::diff`].
+pub fn group<'store, HAST: HyperAST<'store>>(
+    hyperast: &'store HAST,
+    mapping: &Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    actions: &ActionsVec<
+        SimpleAction<
+            HAST::Label,
+            CompressedTreePath<<HAST::T as WithChildren>::ChildIdx>,
+            HAST::IdN,
+        >,
+    >,
+) -> Vec<Refactoring>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq,
+    HAST::T: 'store + types::WithStats,
+{
+    let grouper = Grouper {
+        hyperast,
+        mapping,
+        touched: actions.iter().map(touched).collect(),
+    };
+    let mut r = vec![];
+    grouper.from_src(&mut r);
+    grouper.from_dst(&mut r);
+    r
+}
+
+/// Paths, from the root, of the src and dst nodes touched by an action.
+///
+/// The original path of an update can be in src or dst, thus it is considered in both.
+fn touched<L, Idx: hyper_ast::PrimInt, I>(
+    action: &SimpleAction<L, CompressedTreePath<Idx>, I>,
+) -> (Vec<Vec<Idx>>, Vec<Vec<Idx>>) {
+    let ori = || action.path.ori.iter().collect::<Vec<_>>();
+    match &action.action {
+        Act::Delete {} => (vec![ori()], vec![]),
+        Act::Insert { .. } => (vec![], vec![ori()]),
+        Act::Update { .. } => (vec![ori()], vec![ori()]),
+        Act::Move { from } | Act::MovUpd { from, .. } => {
+            (vec![from.ori.iter().collect()], vec![ori()])
+        }
+    }
+}
+
+struct Grouper<'a, 'store, HAST: HyperAST<'store>> {
+    hyperast: &'store HAST,
+    mapping: &'a Mapping<CDS<HAST::T>, CDS<HAST::T>, VecStore<u32>>,
+    touched: Vec<(
+        Vec<Vec<<HAST::T as WithChildren>::ChildIdx>>,
+        Vec<Vec<<HAST::T as WithChildren>::ChildIdx>>,
+    )>,
+}
+
+impl<'a, 'store, HAST: HyperAST<'store>> Grouper<'a, 'store, HAST>
+where
+    HAST::IdN: Clone + Debug + Eq,
+    HAST::Label: Clone + Copy + Eq,
+    HAST::T: 'store + types::WithStats,
+{
+    fn from_src(&self, r: &mut Vec<Refactoring>) {
+        let src_arena = &self.mapping.src_arena;
+        let dst_arena = &self.mapping.dst_arena;
+        let mappings = &self.mapping.mappings;
+        for s in src_arena.iter_df_post::<true>() {
+            let Some(t) = self.ty(src_arena, s) else {
+                continue;
+            };
+            let Some(d) = mappings.get_dst(&s) else {
+                if t == Type::ImportDeclaration {
+                    r.push(self.make(Kind::RemoveImport, Some(s), None));
+                }
+                continue;
+            };
+            if self.ty(dst_arena, d) != Some(t) {
+                continue;
+            }
+            if let Some(kind) = rename_kind(t) {
+                let src_name = self.name(src_arena, s);
+                let dst_name = self.name(dst_arena, d);
+                if self.label(src_arena, src_name) != self.label(dst_arena, dst_name) {
+                    r.push(self.make(kind, src_name, dst_name));
+                }
+            }
+            if !is_method(t) {
+                continue;
+            }
+            let src_params = self.child(src_arena, s, Type::FormalParameters);
+            let dst_params = self.child(dst_arena, d, Type::FormalParameters);
+            let params = self.make(Kind::ChangeParameterList, src_params, dst_params);
+            if !params.actions.is_empty() {
+                r.push(Refactoring {
+                    src: Some(s),
+                    dst: Some(d),
+                    ..params
+                });
+            }
+            let src_decl = src_arena
+                .parents(s)
+                .find(|x| self.is_type_decl(src_arena, *x));
+            let dst_decl = dst_arena
+                .parents(d)
+                .find(|x| self.is_type_decl(dst_arena, *x));
+            if src_decl.and_then(|x| mappings.get_dst(&x)) != dst_decl {
+                r.push(self.make(Kind::MoveMethod, Some(s), Some(d)));
+            }
+        }
+    }
+
+    fn from_dst(&self, r: &mut Vec<Refactoring>) {
+        let dst_arena = &self.mapping.dst_arena;
+        let mappings = &self.mapping.mappings;
+        for d in dst_arena.iter_df_post::<true>() {
+            if mappings.is_dst(&d) {
+                continue;
+            }
+            match self.ty(dst_arena, d) {
+                Some(Type::ImportDeclaration) => {
+                    r.push(self.make(Kind::AddImport, None, Some(d)));
+                }
+                Some(Type::MethodDeclaration) => {
+                    if let Some(s) = self.extracted_from(d) {
+                        r.push(self.make(Kind::ExtractMethod, Some(s), Some(d)));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Finds the src method from which the unmapped dst method `d` was extracted,
+    /// ie. descendants of `d` are mapped in a method still calling `d` in dst.
+    fn extracted_from(&self, d: u32) -> Option<u32> {
+        let src_arena = &self.mapping.src_arena;
+        let dst_arena = &self.mapping.dst_arena;
+        let mappings = &self.mapping.mappings;
+        let name = self.label(dst_arena, self.name(dst_arena, d))?;
+        let mut candidates: Vec<u32> = dst_arena
+            .descendants_range(&d)
+            .filter_map(|x| mappings.get_src(&x))
+            .filter_map(|x| src_arena.parents(x).find(|x| self.is_method(src_arena, *x)))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates.into_iter().find(|s| {
+            let Some(old) = mappings.get_dst(s) else {
+                return false;
+            };
+            dst_arena.descendants_range(&old).any(|x| {
+                self.ty(dst_arena, x) == Some(Type::MethodInvocation)
+                    && self.label(dst_arena, self.last_child(dst_arena, x, Type::Identifier))
+                        == Some(name)
+            })
+        })
+    }
+
+    /// Attaches the actions touching the subtrees of `src` and `dst`
+    fn make(&self, kind: Kind, src: Option<u32>, dst: Option<u32>) -> Refactoring {
+        let src_path = src.map(|x| self.mapping.src_arena.path_rooted(&x));
+        let dst_path = dst.map(|x| self.mapping.dst_arena.path_rooted(&x));
+        let within = |scope: &Option<Vec<_>>, paths: &Vec<Vec<_>>| {
+            scope
+                .as_ref()
+                .map_or(false, |scope| paths.iter().any(|p| p.starts_with(scope)))
+        };
+        let actions = self
+            .touched
+            .iter()
+            .enumerate()
+            .filter(|(_, (s, d))| within(&src_path, s) || within(&dst_path, d))
+            .map(|(i, _)| i)
+            .collect();
+        Refactoring {
+            kind,
+            src,
+            dst,
+            actions,
+        }
+    }
+
+    fn ty(&self, arena: &CDS<HAST::T>, x: u32) -> Option<Type> {
+        let t = self.hyperast.resolve_type(&arena.original(&x));
+        t.as_any().downcast_ref::<Type>().copied()
+    }
+
+    fn label(&self, arena: &CDS<HAST::T>, x: Option<u32>) -> Option<HAST::Label> {
+        let n = self.hyperast.node_store().resolve(&arena.original(&x?));
+        n.try_get_label().copied()
+    }
+
+    fn is_method(&self, arena: &CDS<HAST::T>, x: u32) -> bool {
+        self.ty(arena, x).map_or(false, is_method)
+    }
+
+    fn is_type_decl(&self, arena: &CDS<HAST::T>, x: u32) -> bool {
+        self.ty(arena, x)
+            .map_or(false, |t| rename_kind(t) == Some(Kind::RenameClass))
+    }
+
+    fn child(&self, arena: &CDS<HAST::T>, x: u32, t: Type) -> Option<u32> {
+        let cs = arena.children(self.hyperast.node_store(), &x);
+        cs.into_iter().find(|x| self.ty(arena, *x) == Some(t))
+    }
+
+    fn last_child(&self, arena: &CDS<HAST::T>, x: u32, t: Type) -> Option<u32> {
+        let cs = arena.children(self.hyperast.node_store(), &x);
+        cs.into_iter().rev().find(|x| self.ty(arena, *x) == Some(t))
+    }
+
+    /// the name of a declaration is its first identifier,
+    /// as the type of a member or a variable is never a plain identifier
+    fn name(&self, arena: &CDS<HAST::T>, x: u32) -> Option<u32> {
+        self.child(arena, x, Type::Identifier)
+    }
+}
+
+/// Kind of a change of name of a declaration of type `t`,
+/// constructors are not considered as they are renamed with their class
+fn rename_kind(t: Type) -> Option<Kind> {
+    match t {
+        Type::MethodDeclaration => Some(Kind::RenameMethod),
+        Type::ClassDeclaration
+        | Type::InterfaceDeclaration
+        | Type::EnumDeclaration
+        | Type::RecordDeclaration
+        | Type::AnnotationTypeDeclaration => Some(Kind::RenameClass),
+        Type::VariableDeclarator | Type::FormalParameter | Type::CatchFormalParameter => {
+            Some(Kind::RenameVariable)
+        }
+        _ => None,
+    }
+}
+
+fn is_method(t: Type) -> bool {
+    t == Type::MethodDeclaration || t == Type::ConstructorDeclaration
+}
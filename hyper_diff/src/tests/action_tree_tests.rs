@@ -0,0 +1,47 @@
+use crate::{
+    actions::{action_tree::ActionsTree, action_vec::ActionsVec, Actions},
+    tests::action_generator2_tests::{make_delete, make_insert, make_move, make_update},
+    tree::{simple_tree::Tree, tree_path::CompressedTreePath},
+};
+
+type P = CompressedTreePath<u8>;
+
+#[test]
+fn test_dependencies() {
+    let actions = ActionsVec(vec![
+        make_update::<Tree, P>(42, (&[0, 0, 0], &[0, 0, 0])),
+        make_delete::<Tree, P>((&[0, 1, 2], &[0, 1, 2])),
+        // shifts the previously deleted node
+        make_insert::<Tree, P>(3, (&[0, 1, 0], &[0, 1, 0])),
+        make_move::<Tree, P>((&[0, 0, 1], &[0, 0, 1]), (&[0, 2], &[0, 2])),
+        // parent of the updated node and of the source of the move
+        make_update::<Tree, P>(43, (&[0, 0], &[0, 0])),
+    ]);
+    let tree: ActionsTree<_> = actions.into();
+    assert_eq!(5, tree.len());
+    assert_eq!(vec![0, 1, 3], tree.roots().collect::<Vec<_>>());
+    assert_eq!(&[1], tree.dependencies(2));
+    assert_eq!(&[0, 3], tree.dependencies(4));
+    assert_eq!(&[4], tree.dependents(0));
+    assert_eq!(vec![vec![0, 1, 3], vec![2, 4]], tree.layers());
+    assert_eq!(vec![1, 2], tree.closure([2]));
+    assert_eq!(vec![0, 3, 4], tree.closure([4]));
+    assert!(tree.independent(0, 2));
+    assert!(tree.independent(2, 3));
+    assert!(!tree.independent(2, 1));
+    assert!(!tree.independent(4, 0));
+}
+
+#[test]
+fn test_shifted_sibling() {
+    let actions = ActionsVec(vec![
+        make_delete::<Tree, P>((&[0, 3], &[0, 3])),
+        // after the deleted node, not shifted
+        make_update::<Tree, P>(42, (&[0, 2, 1], &[0, 2, 1])),
+        // before the deleted node, shifts it
+        make_insert::<Tree, P>(3, (&[0, 1], &[0, 1])),
+    ]);
+    let tree: ActionsTree<_> = actions.into();
+    assert!(tree.independent(0, 1));
+    assert_eq!(&[0, 1], tree.dependencies(2));
+}
@@ -0,0 +1,355 @@
+use std::marker::PhantomData;
+
+use hyper_ast::{
+    filter::BloomSize,
+    hashed::{self, IndexingHashBuilder, MetaDataHashsBuilder, SyntaxNodeHashs},
+    nodes::HashSize,
+    store::{
+        labels::LabelStore,
+        nodes::legion::{compo, HashedNodeRef, NodeIdentifier, NodeStore, TypedNodeStoreMut},
+        SimpleStores,
+    },
+    tree_gen::SubTreeMetrics,
+    types::{
+        AnyType, HyperType, LabelStore as _, LangRef, LangWrapper, NodeId, Shared, SimpleHyperAST,
+        TypeIndex, TypeStore, TypedNodeId,
+    },
+};
+
+use crate::{
+    actions::action_vec::apply_actions,
+    algorithms,
+    tests::{examples::*, simple_examples::*},
+    tree::{
+        simple_tree::{vpair_to_stores, SimpleTree, NS},
+        TStore,
+    },
+};
+
+/// Applies the edit script of a gumtree diff on src,
+/// the result must be the same node as dst.
+fn check_apply(example: (SimpleTree<u8>, SimpleTree<u8>)) {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example);
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let actions = algorithms::gumtree::diff(&stores, &src, &dst)
+        .actions
+        .unwrap();
+    let mut node_store = stores.node_store;
+    let mut root = vec![src];
+    let then = apply_actions::<_, NS<_>, _>(actions, &mut root, &mut node_store);
+    assert_eq!(then, dst);
+}
+
+#[test]
+fn test_apply_on_examples() {
+    check_apply(example_single());
+    check_apply(example_simple());
+    check_apply(example_simple1());
+    check_apply(example_move());
+    check_apply(example_move1());
+    check_apply(example_move2());
+    check_apply(example_move3());
+    check_apply(example_zs_paper());
+    check_apply(example_gt_java_code());
+    check_apply(example_gt_slides());
+    check_apply(example_gumtree());
+    check_apply(example_gumtree_ambiguous());
+    check_apply(example_bottom_up());
+    check_apply(example_action());
+    check_apply(example_action2());
+    check_apply(example_eq_simple_class_rename());
+    check_apply(example_very_simple_post_order());
+}
+
+#[test]
+fn test_apply_on_simple_examples() {
+    check_apply(example_delete_action());
+    check_apply(example_rename_action());
+    check_apply(example_move_action());
+}
+
+/// Node types of the examples once in a legion store
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+struct Ty(u8);
+
+static TYPES: [Ty; 16] = {
+    let mut types = [Ty(0); 16];
+    let mut i = 0;
+    while i < types.len() {
+        types[i] = Ty(i as u8);
+        i += 1;
+    }
+    types
+};
+
+impl std::fmt::Display for Ty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl HyperType for Ty {
+    fn as_shared(&self) -> Shared {
+        Shared::Other
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_static(&self) -> &'static dyn HyperType {
+        &TYPES[self.0 as usize]
+    }
+
+    fn generic_eq(&self, other: &dyn HyperType) -> bool
+    where
+        Self: 'static + Sized,
+    {
+        other
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |a| self == a)
+    }
+
+    fn is_file(&self) -> bool {
+        false
+    }
+
+    fn is_directory(&self) -> bool {
+        false
+    }
+
+    fn is_spaces(&self) -> bool {
+        false
+    }
+
+    fn is_syntax(&self) -> bool {
+        false
+    }
+
+    fn get_lang(&self) -> LangWrapper<Self>
+    where
+        Self: Sized,
+    {
+        From::<&'static dyn LangRef<Ty>>::from(&Examples)
+    }
+}
+
+/// The "language" of the examples
+struct Examples;
+
+impl LangRef<Ty> for Examples {
+    fn name(&self) -> &'static str {
+        "examples"
+    }
+
+    fn make(&self, t: u16) -> &'static Ty {
+        &TYPES[t as usize]
+    }
+
+    fn to_u16(&self, t: Ty) -> u16 {
+        t.0 as u16
+    }
+}
+
+impl LangRef<AnyType> for Examples {
+    fn name(&self) -> &'static str {
+        "examples"
+    }
+
+    fn make(&self, t: u16) -> &'static AnyType {
+        static ANY_TYPES: std::sync::OnceLock<Vec<AnyType>> = std::sync::OnceLock::new();
+        let types = ANY_TYPES.get_or_init(|| TYPES.iter().map(|t| t.as_static().into()).collect());
+        &types[t as usize]
+    }
+
+    fn to_u16(&self, t: AnyType) -> u16 {
+        t.as_any().downcast_ref::<Ty>().unwrap().0 as u16
+    }
+}
+
+/// Identifies nodes typed with [`Ty`], like generators do with the types of their language
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
+struct TIdN(NodeIdentifier);
+
+impl NodeId for TIdN {
+    type IdN = NodeIdentifier;
+
+    fn as_id(&self) -> &Self::IdN {
+        &self.0
+    }
+
+    unsafe fn from_id(id: Self::IdN) -> Self {
+        Self(id)
+    }
+
+    unsafe fn from_ref_id(id: &Self::IdN) -> &Self {
+        std::mem::transmute(id)
+    }
+}
+
+impl TypedNodeId for TIdN {
+    type Ty = Ty;
+}
+
+struct TStoreExamples;
+
+impl<'a> TypeStore<HashedNodeRef<'a, NodeIdentifier>> for TStoreExamples {
+    type Ty = AnyType;
+
+    const MASK: u16 = 0b1000_0000_0000_0000;
+
+    type Marshaled = TypeIndex;
+
+    fn marshal_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Marshaled {
+        TypeIndex {
+            lang: LangRef::<Ty>::name(&Examples),
+            ty: n.get_component::<Ty>().unwrap().0 as u16,
+        }
+    }
+
+    fn resolve_type(&self, n: &HashedNodeRef<'a, NodeIdentifier>) -> Self::Ty {
+        n.get_component::<Ty>().unwrap().as_static().into()
+    }
+
+    fn resolve_lang(&self, _n: &HashedNodeRef<'a, NodeIdentifier>) -> LangWrapper<Self::Ty> {
+        From::<&'static dyn LangRef<AnyType>>::from(&Examples)
+    }
+
+    fn type_eq(
+        &self,
+        n: &HashedNodeRef<'a, NodeIdentifier>,
+        m: &HashedNodeRef<'a, NodeIdentifier>,
+    ) -> bool {
+        n.get_component::<Ty>().ok() == m.get_component::<Ty>().ok()
+    }
+}
+
+/// Inserts a node the way generators do,
+/// ie. with metrics accumulated from the children while folding instead of read from the store.
+fn insert_like_generator(
+    stores: &mut SimpleStores<TStoreExamples>,
+    t: &u8,
+    l: Option<&str>,
+    cs: Vec<(NodeIdentifier, SubTreeMetrics<SyntaxNodeHashs<HashSize>>)>,
+) -> (NodeIdentifier, SubTreeMetrics<SyntaxNodeHashs<HashSize>>) {
+    let t = TYPES[*t as usize];
+    let mut acc = SubTreeMetrics::<SyntaxNodeHashs<HashSize>>::default();
+    for (_, m) in &cs {
+        acc.acc(*m);
+    }
+    let cs: Vec<_> = cs.into_iter().map(|(c, _)| c).collect();
+    let label = l.map(|l| l.to_string());
+    let label_id = l.map(|l| stores.label_store.get_or_insert(l));
+    let metrics = SubTreeMetrics {
+        size: acc.size + 1,
+        height: acc.height + 1,
+        size_no_spaces: acc.size_no_spaces + 1,
+        hashs: Default::default(),
+    };
+    let hbuilder = hashed::Builder::new(acc.hashs, &t, &label, metrics.size_no_spaces);
+    let hashable = hbuilder.most_discriminating();
+    let eq = |x: hyper_ast::store::nodes::legion::EntryRef| {
+        x.get_component::<Ty>() == Ok(&t)
+            && x.get_component::<hyper_ast::store::defaults::LabelIdentifier>()
+                .ok()
+                == label_id.as_ref()
+            && match x.get_component::<compo::CS<NodeIdentifier>>() {
+                Ok(compo::CS(x)) => x.as_ref() == cs.as_slice(),
+                Err(_) => cs.is_empty(),
+            }
+    };
+    let insertion = stores.node_store.prepare_insertion(&hashable, eq);
+    let hashs = hbuilder.build();
+    let metrics = SubTreeMetrics { hashs, ..metrics };
+    if let Some(id) = insertion.occupied_id() {
+        return (id, metrics);
+    }
+    let vacant = insertion.vacant();
+    let size = compo::Size(metrics.size);
+    let size_no_spaces = compo::SizeNoSpaces(metrics.size_no_spaces);
+    let height = compo::Height(metrics.height);
+    let id = match (label_id, cs.is_empty()) {
+        (None, true) => NodeStore::insert_after_prepare(vacant, (t, hashs, BloomSize::None)),
+        (Some(l), true) => NodeStore::insert_after_prepare(vacant, (t, hashs, BloomSize::None, l)),
+        (None, false) => {
+            let cs = compo::CS(cs.into_boxed_slice());
+            let c = (t, hashs, BloomSize::None, size, size_no_spaces, height, cs);
+            NodeStore::insert_after_prepare(vacant, c)
+        }
+        (Some(l), false) => {
+            let cs = compo::CS(cs.into_boxed_slice());
+            let c = (
+                t,
+                hashs,
+                BloomSize::None,
+                l,
+                size,
+                size_no_spaces,
+                height,
+                cs,
+            );
+            NodeStore::insert_after_prepare(vacant, c)
+        }
+    };
+    (id, metrics)
+}
+
+/// Same as [`check_apply`] but on a legion store, as used for actual code.
+///
+/// src and dst are inserted like generators do, and the patched root is rebuilt by [`apply_actions`],
+/// so it must be deduplicated with the independently built dst.
+fn check_apply_legion((src, dst): (SimpleTree<u8>, SimpleTree<u8>)) {
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStoreExamples,
+        node_store: NodeStore::new(),
+    };
+    let mut insert = |t: &u8, l: Option<&str>, cs| insert_like_generator(&mut stores, t, l, cs);
+    let (src, _) = src.fold(&mut insert);
+    let (dst, _) = dst.fold(&mut insert);
+    let actions = algorithms::gumtree::diff(&stores, &src, &dst)
+        .actions
+        .unwrap();
+    let mut node_store =
+        TypedNodeStoreMut::<TIdN>::new(&mut stores.node_store, &stores.label_store);
+    let mut root = vec![src];
+    let then =
+        apply_actions::<HashedNodeRef<'static, TIdN>, _, _>(actions, &mut root, &mut node_store);
+    assert_eq!(then, dst);
+}
+
+#[test]
+fn test_apply_on_legion_store() {
+    for example in [
+        example_single(),
+        example_simple(),
+        example_simple1(),
+        example_move(),
+        example_move1(),
+        example_move2(),
+        example_move3(),
+        example_zs_paper(),
+        example_gt_java_code(),
+        example_gt_slides(),
+        example_gumtree(),
+        example_gumtree_ambiguous(),
+        example_bottom_up(),
+        example_action(),
+        example_action2(),
+        example_eq_simple_class_rename(),
+        example_very_simple_post_order(),
+        example_move_across_files(),
+        example_delete_action(),
+        example_rename_action(),
+        example_move_action(),
+    ] {
+        check_apply_legion(example);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::diff_output::{unified_diff, write_json, write_xml, Act, Kind, Match, Tree, F};
+
+fn tree(r#type: &str, label: Option<&str>, start: usize, end: usize) -> Tree {
+    Tree {
+        r#type: r#type.to_string(),
+        label: label.map(str::to_string),
+        file: "A.java".to_string(),
+        start,
+        end,
+    }
+}
+
+fn update(tree: Tree, label: &str) -> Act<Tree> {
+    Act {
+        action: Kind::Upd,
+        tree,
+        parent: None,
+        at: None,
+        label: Some(label.to_string()),
+    }
+}
+
+/// a diff with a match and an action of each kind
+fn example() -> F<Tree> {
+    let block = tree("block", None, 10, 30);
+    F {
+        times: vec![],
+        matches: vec![Match {
+            src: block.clone(),
+            dest: tree("block", None, 10, 34),
+        }],
+        actions: Some(vec![
+            update(tree("identifier", Some("a<b"), 12, 15), "c"),
+            Act {
+                action: Kind::Move,
+                tree: tree("expression_statement", None, 16, 20),
+                parent: Some(block.clone()),
+                at: Some(0),
+                label: None,
+            },
+            Act {
+                action: Kind::Ins,
+                tree: tree("expression_statement", None, 20, 24),
+                parent: Some(block),
+                at: Some(2),
+                label: None,
+            },
+            Act {
+                action: Kind::Del,
+                tree: tree("string_literal", Some("\"s\""), 22, 25),
+                parent: None,
+                at: None,
+                label: None,
+            },
+        ]),
+    }
+}
+
+#[test]
+fn test_write_json() {
+    let diff = example();
+    let mut out = vec![];
+    write_json(&mut out, &diff).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let update = &json["actions"][0];
+    assert_eq!(update["action"], "update-node");
+    assert_eq!(update["tree"]["type"], "identifier");
+    assert_eq!(update["tree"]["label"], "a<b");
+    assert_eq!(update["label"], "c");
+    assert!(update.get("parent").is_none());
+    assert!(update.get("at").is_none());
+    assert_eq!(json["actions"][1]["action"], "move-tree");
+    assert_eq!(json["actions"][1]["at"], 0);
+    assert!(json["matches"][0]["src"].get("label").is_none());
+
+    let read: F<Tree> = serde_json::from_slice(&out).unwrap();
+    assert_eq!(read.matches, diff.matches);
+    assert_eq!(read.actions, diff.actions);
+}
+
+#[test]
+fn test_write_xml() {
+    let mut out = vec![];
+    write_xml(&mut out, &example()).unwrap();
+    let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<diff>
+  <matches>
+    <match src="block [10,30]" dest="block [10,34]"/>
+  </matches>
+  <actions>
+    <action type="update-node" tree="identifier: a&lt;b [12,15]" label="c"/>
+    <action type="move-tree" tree="expression_statement [16,20]" parent="block [10,30]" at="0"/>
+    <action type="insert-node" tree="expression_statement [20,24]" parent="block [10,30]" at="2"/>
+    <action type="delete-node" tree="string_literal: &quot;s&quot; [22,25]"/>
+  </actions>
+</diff>
+"#;
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn test_unified_diff() {
+    let src = "a\nb\nc\n";
+    let dst = "a\nd\nc\n";
+    let diff = F {
+        times: vec![],
+        matches: vec![],
+        actions: Some(vec![update(tree("identifier", Some("b"), 2, 3), "d")]),
+    };
+    let expected = "\
+--- a/A.java
++++ b/A.java
+@@ -1,3 +1,3 @@
+# update-node identifier: b [2,3] to d
+ a
+-b
++d
+ c
+";
+    assert_eq!(unified_diff("A.java", src, dst, &diff, 1), expected);
+}
+
+#[test]
+fn test_unified_diff_hunks() {
+    let src = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let dst = "a\nB\nc\nd\ne\nf\ng\nh\nI\nj";
+    let diff = F {
+        times: vec![],
+        matches: vec![],
+        actions: Some(vec![update(tree("identifier", Some("i"), 16, 17), "I")]),
+    };
+    let expected = "\
+--- a/A.java
++++ b/A.java
+@@ -1,3 +1,3 @@
+ a
+-b
++B
+ c
+@@ -8,3 +8,3 @@
+# update-node identifier: i [16,17] to I
+ h
+-i
+-j
++I
++j
+\\ No newline at end of file
+";
+    assert_eq!(unified_diff("A.java", src, dst, &diff, 1), expected);
+}
+
+#[test]
+fn test_unified_diff_moved_lines() {
+    // the longest common subsequence is kept, other lines are deleted then inserted
+    let src = "a\nb\nc\nd\ne\n";
+    let dst = "c\nd\ne\na\nb\n";
+    let diff = F {
+        times: vec![],
+        matches: vec![],
+        actions: Some(vec![]),
+    };
+    let expected = "\
+--- a/A.java
++++ b/A.java
+@@ -1,5 +1,5 @@
+-a
+-b
+ c
+ d
+ e
++a
++b
+";
+    assert_eq!(unified_diff("A.java", src, dst, &diff, 3), expected);
+}
+
+#[cfg(feature = "java")]
+#[test]
+fn test_format_diff() {
+    use hyper_ast::store::{labels::LabelStore, nodes::legion::NodeStore, SimpleStores};
+    use hyper_ast_gen_ts_java::{legion_with_refs::JavaTreeGen, types::TStore};
+
+    use crate::{algorithms, diff_output::format_diff, tests::refactorings_tests::parse};
+
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: NodeStore::new(),
+    };
+    let mut md_cache = Default::default();
+    let mut java_tree_gen = JavaTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+        md_cache: &mut md_cache,
+    };
+    let src = parse(&mut java_tree_gen, "class A { int f() { return 1; } }");
+    let dst = parse(&mut java_tree_gen, "class A { int f() { return 2; } }");
+    let diff = algorithms::gumtree::diff(&stores, &src, &dst);
+    let formatted = format_diff(&diff.mapper, &diff.actions.unwrap());
+
+    let actions = formatted.actions.as_ref().unwrap();
+    assert_eq!(actions.len(), 1);
+    let update = &actions[0];
+    assert_eq!(update.action, Kind::Upd);
+    assert_eq!(update.tree.r#type, "decimal_integer_literal");
+    assert_eq!(update.tree.label.as_deref(), Some("1"));
+    assert_eq!((update.tree.start, update.tree.end), (27, 28));
+    assert_eq!(update.label.as_deref(), Some("2"));
+    let literal = formatted.matches.iter().find(|m| m.src == update.tree);
+    assert_eq!(literal.unwrap().dest.label.as_deref(), Some("2"));
+    assert_eq!(literal.unwrap().dest.start, 27);
+
+    // what is written can be read back like the outputs of GumTree
+    let mut out = vec![];
+    write_json(&mut out, &formatted).unwrap();
+    let read: F<Tree> = serde_json::from_slice(&out).unwrap();
+    assert_eq!(read.matches, formatted.matches);
+    assert_eq!(read.actions, formatted.actions);
+}
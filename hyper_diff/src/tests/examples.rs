@@ -430,3 +430,39 @@ pub(crate) fn example_very_simple_post_order() -> (ST<u8>, ST<u8>) {
     ]);
     (src, dst)
 }
+
+/// Type of directories in [`example_move_across_files`]
+pub(crate) const DIRECTORY: u8 = 9;
+
+/// A method moved from a file to another one, in another directory
+pub(crate) fn example_move_across_files() -> (ST<u8>, ST<u8>) {
+    let src = tree!(
+        DIRECTORY, "root"; [
+            tree!(DIRECTORY, "a"; [
+                tree!(1, "A.java"; [
+                    tree!(2, "m1"; [tree!(3, "x1"), tree!(4, "y1")]),
+                    tree!(2, "m2"; [tree!(3, "x2"), tree!(4, "y2")]),
+                ]),
+            ]),
+            tree!(DIRECTORY, "b"; [
+                tree!(1, "B.java"; [
+                    tree!(2, "m3"; [tree!(3, "x3"), tree!(4, "y3")]),
+                ]),
+            ]),
+    ]);
+    let dst = tree!(
+        DIRECTORY, "root"; [
+            tree!(DIRECTORY, "a"; [
+                tree!(1, "A.java"; [
+                    tree!(2, "m1"; [tree!(3, "x1"), tree!(4, "y1")]),
+                ]),
+            ]),
+            tree!(DIRECTORY, "b"; [
+                tree!(1, "B.java"; [
+                    tree!(2, "m3"; [tree!(3, "x3"), tree!(4, "y3")]),
+                    tree!(2, "m2"; [tree!(3, "x2"), tree!(4, "y2")]),
+                ]),
+            ]),
+    ]);
+    (src, dst)
+}
@@ -0,0 +1,393 @@
+use std::marker::PhantomData;
+
+use hyper_ast::types::SimpleHyperAST;
+
+use crate::{
+    merge::{merge, ConflictKind},
+    tree::{
+        simple_tree::{tree, vec_to_stores, SimpleTree, NS},
+        TStore,
+    },
+};
+
+type ST<K> = SimpleTree<K>;
+
+fn base() -> ST<u8> {
+    tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ])
+}
+
+/// Merges left and right, then checks that building the result gives expected
+fn check_merge(
+    base: ST<u8>,
+    left: ST<u8>,
+    right: ST<u8>,
+    expected: ST<u8>,
+    conflicts: &[(ConflictKind, Vec<u16>)],
+) {
+    let (label_store, node_store, roots) = vec_to_stores(vec![base, left, right, expected]);
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let result = merge(&stores, &roots[0], &roots[1], &roots[2]);
+    let actual: Vec<_> = result
+        .conflicts
+        .iter()
+        .map(|x| (x.kind, x.base.clone().unwrap()))
+        .collect();
+    assert_eq!(actual, conflicts);
+    assert!(result.merged.same_as(&stores, &roots[3]));
+    let mut node_store = stores.node_store;
+    let merged = result.merged.build::<_, NS<_>>(&mut node_store);
+    assert_eq!(merged, roots[3]);
+}
+
+#[test]
+fn test_merge_without_conflict() {
+    // f renamed to g on the left, d deleted on the right
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "g")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "g")]),
+            tree!(0, "b"; [
+                tree!(0, "c")]),
+    ]);
+    check_merge(base(), left, right, expected, &[]);
+}
+
+#[test]
+fn test_merge_update_update() {
+    // f renamed differently on both sides, the left label is kept
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "g")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "h")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d"),
+                tree!(0, "i")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "g")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d"),
+                tree!(0, "i")]),
+    ]);
+    check_merge(
+        base(),
+        left,
+        right,
+        expected,
+        &[(ConflictKind::UpdateUpdate, vec![0, 0])],
+    );
+}
+
+#[test]
+fn test_merge_move() {
+    // d moved to e on the left, i inserted after d on the right
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "d")]),
+            tree!(0, "b"; [
+                tree!(0, "c")]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d"),
+                tree!(0, "i")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "d")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "i")]),
+    ]);
+    check_merge(base(), left, right, expected, &[]);
+}
+
+#[test]
+fn test_merge_moved_and_modified() {
+    // d moved to e on the right, n renamed to o inside d on the left,
+    // the rename must be applied to the moved d
+    let base = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "g"),
+                tree!(0, "h")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "p"),
+                tree!(0, "q"),
+                tree!(0, "d"; [
+                    tree!(0, "m"),
+                    tree!(0, "r"),
+                    tree!(0, "s"),
+                    tree!(0, "n")])]),
+    ]);
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "g"),
+                tree!(0, "h")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "p"),
+                tree!(0, "q"),
+                tree!(0, "d"; [
+                    tree!(0, "m"),
+                    tree!(0, "r"),
+                    tree!(0, "s"),
+                    tree!(0, "o")])]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "g"),
+                tree!(0, "h"),
+                tree!(0, "d"; [
+                    tree!(0, "m"),
+                    tree!(0, "r"),
+                    tree!(0, "s"),
+                    tree!(0, "n")])]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "p"),
+                tree!(0, "q")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "g"),
+                tree!(0, "h"),
+                tree!(0, "d"; [
+                    tree!(0, "m"),
+                    tree!(0, "r"),
+                    tree!(0, "s"),
+                    tree!(0, "o")])]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "p"),
+                tree!(0, "q")]),
+    ]);
+    check_merge(base, left, right, expected, &[]);
+}
+
+#[test]
+fn test_merge_insert_insert() {
+    // x and y inserted after c on each side, the right insertion comes first
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "x"),
+                tree!(0, "d")]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "y"),
+                tree!(0, "d")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "y"),
+                tree!(0, "x"),
+                tree!(0, "d")]),
+    ]);
+    check_merge(
+        base(),
+        left,
+        right,
+        expected,
+        &[(ConflictKind::InsertInsert, vec![1])],
+    );
+}
+
+#[test]
+fn test_merge_delete_modify() {
+    // e deleted on the left while f is renamed to g inside e on the right, e is kept
+    let base = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f"),
+                tree!(0, "h"),
+                tree!(0, "j")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ]);
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "g"),
+                tree!(0, "h"),
+                tree!(0, "j")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "g"),
+                tree!(0, "h"),
+                tree!(0, "j")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d")]),
+    ]);
+    check_merge(
+        base,
+        left,
+        right,
+        expected,
+        &[(ConflictKind::DeleteModify, vec![0])],
+    );
+}
+
+#[test]
+fn test_merge_reorder_reorder() {
+    // children of b reordered differently on both sides, the left order is kept
+    let base = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "d"),
+                tree!(0, "i")]),
+    ]);
+    let left = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "d"),
+                tree!(0, "c"),
+                tree!(0, "i")]),
+    ]);
+    let right = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "c"),
+                tree!(0, "i"),
+                tree!(0, "d")]),
+    ]);
+    let expected = tree!(
+        0,"a"; [
+            tree!(0, "e"; [
+                tree!(0, "f")]),
+            tree!(0, "b"; [
+                tree!(0, "d"),
+                tree!(0, "c"),
+                tree!(0, "i")]),
+    ]);
+    check_merge(
+        base,
+        left,
+        right,
+        expected,
+        &[(ConflictKind::ReorderReorder, vec![1])],
+    );
+}
+
+#[cfg(feature = "java")]
+#[test]
+fn test_conflict_positions() {
+    use hyper_ast::store::{labels::LabelStore, nodes::legion::NodeStore, SimpleStores};
+    use hyper_ast_gen_ts_java::{legion_with_refs::JavaTreeGen, types::TStore};
+
+    use crate::tests::refactorings_tests::parse;
+
+    let base = "class A { int f() { return 1; } }";
+    let left = "class A { int f() { return 22; } }";
+    let right = "class A { int f() { return 333; } }";
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: NodeStore::new(),
+    };
+    let mut md_cache = Default::default();
+    let mut java_tree_gen = JavaTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+        md_cache: &mut md_cache,
+    };
+    let base = parse(&mut java_tree_gen, base);
+    let left = parse(&mut java_tree_gen, left);
+    let right = parse(&mut java_tree_gen, right);
+    let result = merge(&stores, &base, &left, &right);
+    assert_eq!(result.conflicts.len(), 1);
+    let conflict = &result.conflicts[0];
+    assert_eq!(conflict.kind, ConflictKind::UpdateUpdate);
+    let [b, l, r] = conflict.positions(&stores, &base, &left, &right);
+    let at = "class A { int f() { return ".len();
+    assert_eq!(b.unwrap().range(), at..at + 1);
+    assert_eq!(l.unwrap().range(), at..at + 2);
+    assert_eq!(r.unwrap().range(), at..at + 3);
+}
@@ -2,9 +2,21 @@ pub mod action_generator2_simple_tests;
 pub mod action_generator2_tests;
 pub mod action_generator_tests;
 #[cfg(test)]
+pub mod action_tree_tests;
+#[cfg(test)]
+pub mod apply_actions_tests;
+#[cfg(test)]
+pub mod diff_output_tests;
+#[cfg(test)]
 pub mod examples;
 pub mod hungarian_tests;
 #[cfg(test)]
 pub mod lazy_decompression_tests;
+#[cfg(test)]
+pub mod merge_tests;
 pub mod pair_tests;
+#[cfg(test)]
+pub mod partitioned_lazy_tests;
+#[cfg(feature = "java")]
+pub mod refactorings_tests;
 pub mod simple_examples;
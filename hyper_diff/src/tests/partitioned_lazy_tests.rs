@@ -0,0 +1,54 @@
+use std::marker::PhantomData;
+
+use hyper_ast::types::{HyperAST, NodeStore, SimpleHyperAST, Typed};
+
+use crate::{
+    algorithms::{gumtree_lazy, gumtree_partitioned_lazy},
+    decompressed_tree_store::lazy_post_order::LazyPostOrder,
+    matchers::{
+        mapping_store::{MappingStore, VecStore},
+        Mapper,
+    },
+    tests::examples::{example_move_across_files, DIRECTORY},
+    tree::{simple_tree::vpair_to_stores, TStore},
+};
+
+#[test]
+fn test_same_as_whole_trees() {
+    let (label_store, node_store, src, dst) = vpair_to_stores(example_move_across_files());
+    let stores = SimpleHyperAST {
+        type_store: TStore,
+        node_store,
+        label_store,
+        _phantom: PhantomData,
+    };
+    let is_directory = |id: &u16| stores.node_store.resolve(id).get_type() == DIRECTORY;
+    let partitioned = |parallel| {
+        let mut mapper: Mapper<_, LazyPostOrder<_, u32>, LazyPostOrder<_, u32>, VecStore<u32>> =
+            stores.decompress_pair(&src, &dst).into();
+        let partitions = gumtree_partitioned_lazy::partition_by(
+            &stores,
+            &mut mapper.mapping.src_arena,
+            &mut mapper.mapping.dst_arena,
+            is_directory,
+        );
+        // A.java and B.java, in post-order
+        assert_eq!(vec![(6, 3), (11, 11)], partitions);
+        gumtree_partitioned_lazy::match_with_partitions(mapper, &partitions, parallel)
+    };
+    let sequential = partitioned(false);
+    let parallel = partitioned(true);
+    assert_eq!(
+        sequential.mappings().iter().collect::<Vec<_>>(),
+        parallel.mappings().iter().collect::<Vec<_>>()
+    );
+
+    // m2 moved from A.java to B.java
+    assert!(parallel.mappings().has(&5, &10));
+
+    let whole = gumtree_lazy::diff(&stores, &src, &dst);
+    assert_eq!(
+        whole.mapper.mappings().iter().collect::<Vec<_>>(),
+        parallel.mappings().iter().collect::<Vec<_>>()
+    );
+}
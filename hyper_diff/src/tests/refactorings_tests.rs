@@ -0,0 +1,191 @@
+use hyper_ast::store::{labels::LabelStore, nodes::legion::NodeStore, SimpleStores};
+use hyper_ast_gen_ts_java::{
+    legion_with_refs::{self, JavaTreeGen, NodeIdentifier},
+    types::TStore,
+};
+
+use crate::{
+    actions::script_generator2::Act,
+    algorithms,
+    refactorings::{group, Kind},
+};
+
+pub(crate) fn parse<'a>(
+    java_tree_gen: &mut JavaTreeGen<'a, '_, TStore>,
+    text: &'a str,
+) -> NodeIdentifier {
+    let tree = match legion_with_refs::tree_sitter_parse(text.as_bytes()) {
+        Ok(t) => t,
+        Err(t) => t,
+    };
+    let full_node = java_tree_gen.generate_file(b"", text.as_bytes(), tree.walk());
+    full_node.local.compressed_node
+}
+
+/// Refactorings found between `buggy` and `fixed`, with the kinds of their actions
+fn refactorings(buggy: &str, fixed: &str) -> Vec<(Kind, Vec<&'static str>)> {
+    let mut stores = SimpleStores {
+        label_store: LabelStore::new(),
+        type_store: TStore::default(),
+        node_store: NodeStore::new(),
+    };
+    let mut md_cache = Default::default();
+    let mut java_tree_gen = JavaTreeGen {
+        line_break: "\n".as_bytes().to_vec(),
+        stores: &mut stores,
+        md_cache: &mut md_cache,
+    };
+    let src = parse(&mut java_tree_gen, buggy);
+    let dst = parse(&mut java_tree_gen, fixed);
+    let diff = algorithms::gumtree::diff(&stores, &src, &dst);
+    let actions = diff.actions.unwrap();
+    group(&stores, &diff.mapper.mapping, &actions)
+        .into_iter()
+        .map(|x| {
+            let acts = x.actions(&actions).map(|a| match a.action {
+                Act::Delete {} => "delete",
+                Act::Insert { .. } => "insert",
+                Act::Update { .. } => "update",
+                Act::Move { .. } => "move",
+                Act::MovUpd { .. } => "movupd",
+            });
+            (x.kind, acts.collect())
+        })
+        .collect()
+}
+
+fn kinds(refactorings: &[(Kind, Vec<&'static str>)]) -> Vec<Kind> {
+    refactorings.iter().map(|x| x.0).collect()
+}
+
+#[test]
+fn test_rename_method() {
+    let buggy = r#"import a.B;
+class A {
+    int f(int x) { return x + 1; }
+    void g() { int y = 0; f(y); }
+}"#;
+    let fixed = r#"import a.B;
+class A {
+    int h(int x) { return x + 1; }
+    void g() { int y = 0; h(y); }
+}"#;
+    let refactorings = refactorings(buggy, fixed);
+    let kinds = kinds(&refactorings);
+    assert!(kinds.contains(&Kind::RenameMethod));
+    assert!(!kinds.contains(&Kind::RenameVariable));
+    assert!(!kinds.contains(&Kind::MoveMethod));
+    let (_, rename) = refactorings
+        .iter()
+        .find(|x| x.0 == Kind::RenameMethod)
+        .unwrap();
+    assert!(rename.contains(&"update"));
+}
+
+#[test]
+fn test_extract_method() {
+    let buggy = r#"class A {
+    void g() {
+        int y = 0;
+        y = y * 2 + 1;
+        System.out.println(y);
+    }
+}"#;
+    let fixed = r#"class A {
+    void g() {
+        int y = 0;
+        y = next(y);
+        System.out.println(y);
+    }
+    int next(int y) {
+        return y * 2 + 1;
+    }
+}"#;
+    let refactorings = refactorings(buggy, fixed);
+    let kinds = kinds(&refactorings);
+    assert!(kinds.contains(&Kind::ExtractMethod));
+    assert!(!kinds.contains(&Kind::RenameMethod));
+    assert!(!kinds.contains(&Kind::MoveMethod));
+    let (_, extract) = refactorings
+        .iter()
+        .find(|x| x.0 == Kind::ExtractMethod)
+        .unwrap();
+    assert!(!extract.is_empty());
+}
+
+#[test]
+fn test_move_method() {
+    let buggy = r#"class A {
+    int a1() { return 1; }
+    int a2() { return 2; }
+    int f(int x) { return x + 3; }
+}
+class B {
+    int b1() { return 4; }
+    int b2() { return 5; }
+}"#;
+    let fixed = r#"class A {
+    int a1() { return 1; }
+    int a2() { return 2; }
+}
+class B {
+    int b1() { return 4; }
+    int b2() { return 5; }
+    int f(int x) { return x + 3; }
+}"#;
+    let refactorings = refactorings(buggy, fixed);
+    let kinds = kinds(&refactorings);
+    assert!(kinds.contains(&Kind::MoveMethod));
+    assert!(!kinds.contains(&Kind::RenameClass));
+    assert!(!kinds.contains(&Kind::ChangeParameterList));
+    let (_, moved) = refactorings
+        .iter()
+        .find(|x| x.0 == Kind::MoveMethod)
+        .unwrap();
+    assert!(moved.contains(&"move"));
+}
+
+#[test]
+fn test_change_parameter_list() {
+    let buggy = r#"class A {
+    int f(int x) { return x + 1; }
+}"#;
+    let fixed = r#"class A {
+    int f(int x, int y) { return x + 1; }
+}"#;
+    let refactorings = refactorings(buggy, fixed);
+    let kinds = kinds(&refactorings);
+    assert!(kinds.contains(&Kind::ChangeParameterList));
+    assert!(!kinds.contains(&Kind::RenameVariable));
+    assert!(!kinds.contains(&Kind::MoveMethod));
+    let (_, params) = refactorings
+        .iter()
+        .find(|x| x.0 == Kind::ChangeParameterList)
+        .unwrap();
+    assert!(params.contains(&"insert"));
+}
+
+const WITHOUT_IMPORT: &str = r#"import a.B;
+class A {
+    void f() { }
+}"#;
+
+const WITH_IMPORT: &str = r#"import a.B;
+import c.D;
+class A {
+    void f() { }
+}"#;
+
+#[test]
+fn test_add_import() {
+    let refactorings = refactorings(WITHOUT_IMPORT, WITH_IMPORT);
+    assert_eq!(vec![Kind::AddImport], kinds(&refactorings));
+    assert!(refactorings[0].1.contains(&"insert"));
+}
+
+#[test]
+fn test_remove_import() {
+    let refactorings = refactorings(WITH_IMPORT, WITHOUT_IMPORT);
+    assert_eq!(vec![Kind::RemoveImport], kinds(&refactorings));
+    assert!(refactorings[0].1.contains(&"delete"));
+}
@@ -28,6 +28,15 @@ impl<K> SimpleTree<K> {
     }
 }
 
+#[cfg(test)]
+impl<K> SimpleTree<K> {
+    /// Builds bottom-up another representation of the tree, eg. in another node store
+    pub(crate) fn fold<R>(&self, f: &mut impl FnMut(&K, Option<&str>, Vec<R>) -> R) -> R {
+        let cs = self.children.iter().map(|c| c.fold(f)).collect();
+        f(&self.kind, self.label.as_deref(), cs)
+    }
+}
+
 #[cfg(test)]
 fn store<'a>(ls: &mut LS<u16>, ns: &mut NS<Tree>, node: &SimpleTree<u8>) -> u16 {
     fn store_aux<'a>(ls: &mut LS<u16>, ns: &mut NS<Tree>, node: &SimpleTree<u8>) -> Tree {
@@ -71,6 +80,16 @@ pub(crate) fn vpair_to_stores<'a>(
     (label_store, compressed_node_store, src, dst)
 }
 
+#[cfg(test)]
+pub(crate) fn vec_to_stores(trees: Vec<SimpleTree<u8>>) -> (LS<u16>, NS<Tree>, Vec<u16>) {
+    let (mut label_store, mut compressed_node_store) = make_stores();
+    let roots = trees
+        .iter()
+        .map(|x| store(&mut label_store, &mut compressed_node_store, x))
+        .collect();
+    (label_store, compressed_node_store, roots)
+}
+
 impl AsRef<Tree> for &Tree {
     fn as_ref(&self) -> &Tree {
         self
@@ -215,15 +234,20 @@ impl hyper_ast::types::NodeStoreExt<Tree> for NS<Tree> {
         &mut self,
         _i: <Tree as hyper_ast::types::Stored>::TreeId,
         t: <Tree as hyper_ast::types::Typed>::Type,
-        _l: Option<<Tree as hyper_ast::types::Labeled>::Label>,
+        l: Option<<Tree as hyper_ast::types::Labeled>::Label>,
         cs: Vec<<Tree as Stored>::TreeId>,
     ) -> <Tree as Stored>::TreeId {
+        // same stats as when storing a SimpleTree, so identical trees are deduplicated
+        let (size, height) = cs.iter().fold((1, 0), |(size, height), x| {
+            let x = &self.v[x.to_usize().unwrap()];
+            (size + x.size, height.max(x.height))
+        });
         let node = Tree {
             t,
-            label: 0,
+            label: l.unwrap_or(0),
             children: cs,
-            size: 0,
-            height: 0,
+            size,
+            height: height + 1,
         };
         self.get_or_insert(node)
     }
@@ -313,7 +313,8 @@ fn main() {
             evaluated,
             ..
         } => {
-            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources/repo");
+            let repo =
+                fetch_repository(repository.clone(), "/tmp/hyperastgitresources/repo").unwrap();
             let bl_rs = handle_file(File::open(baseline).expect("should be a file")).unwrap();
             let t_rs = handle_file(File::open(evaluated).expect("should be a file")).unwrap();
             let mut per_module: HashMap<String, (_, _)> = Default::default();
@@ -394,7 +395,7 @@ fn main() {
             only_misses,
             ..
         } => {
-            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources");
+            let repo = fetch_repository(repository.clone(), "/tmp/hyperastgitresources").unwrap();
             let bl_rs = handle_file(File::open(baseline).expect("should be a file")).unwrap();
             let t_rs = handle_file(File::open(test).expect("should be a file")).unwrap();
             let mut per_module: HashMap<String, (_, _)> = Default::default();